@@ -0,0 +1,747 @@
+//! Aggregation request specs and response flattening.
+//!
+//! Shared by the logs and spans aggregate commands. Computes and group-bys are
+//! parsed from compact CLI specs (e.g. `p99:@duration`, `service:10`), and the
+//! bucketed API responses are flattened into one JSON object per bucket (or per
+//! bucket and timestamp for timeseries computes).
+//!
+//! Both analytics APIs take computes and group-bys of the same shape, so the
+//! request is described once here and converted into each API's model.
+//!
+//! Datadog names computes positionally in responses (`c0`, `c1`, ...), so each
+//! [`Compute`] also provides a human-readable label used as the output key.
+
+use serde_json::{Map, Value, json};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Filter that selects failed logs or spans when computing error rates.
+const ERROR_FILTER: &str = "status:error";
+
+/// Bucket limit per facet when counting errors, so every group returned by the
+/// main request finds its error count.
+const ERROR_GROUP_LIMIT: i64 = 10_000;
+
+/// Aggregation functions supported by the logs and spans analytics endpoints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AggregationFunction {
+    Count,
+    Cardinality,
+    Sum,
+    Min,
+    Max,
+    Avg,
+    Median,
+    Pc75,
+    Pc90,
+    Pc95,
+    Pc98,
+    Pc99,
+    /// Share of matching events with `status:error`, from 0 to 1
+    ErrorRate,
+}
+
+impl AggregationFunction {
+    /// Short name used in CLI specs and output labels (e.g. "avg", "p99").
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AggregationFunction::Count => "count",
+            AggregationFunction::Cardinality => "cardinality",
+            AggregationFunction::Sum => "sum",
+            AggregationFunction::Min => "min",
+            AggregationFunction::Max => "max",
+            AggregationFunction::Avg => "avg",
+            AggregationFunction::Median => "median",
+            AggregationFunction::Pc75 => "p75",
+            AggregationFunction::Pc90 => "p90",
+            AggregationFunction::Pc95 => "p95",
+            AggregationFunction::Pc98 => "p98",
+            AggregationFunction::Pc99 => "p99",
+            AggregationFunction::ErrorRate => "error_rate",
+        }
+    }
+
+    /// Name of the function in analytics API requests.
+    ///
+    /// Error rates are computed from a count, divided by the error count later.
+    fn api_name(&self) -> &'static str {
+        match self {
+            AggregationFunction::Count | AggregationFunction::ErrorRate => "count",
+            AggregationFunction::Pc75 => "pc75",
+            AggregationFunction::Pc90 => "pc90",
+            AggregationFunction::Pc95 => "pc95",
+            AggregationFunction::Pc98 => "pc98",
+            AggregationFunction::Pc99 => "pc99",
+            other => other.as_str(),
+        }
+    }
+
+    /// Whether the function operates on a measure and therefore needs a metric.
+    pub fn requires_metric(&self) -> bool {
+        !matches!(
+            self,
+            AggregationFunction::Count | AggregationFunction::ErrorRate
+        )
+    }
+}
+
+impl FromStr for AggregationFunction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "count" => Ok(AggregationFunction::Count),
+            "cardinality" => Ok(AggregationFunction::Cardinality),
+            "sum" => Ok(AggregationFunction::Sum),
+            "min" => Ok(AggregationFunction::Min),
+            "max" => Ok(AggregationFunction::Max),
+            "avg" => Ok(AggregationFunction::Avg),
+            "median" | "p50" => Ok(AggregationFunction::Median),
+            "p75" | "pc75" => Ok(AggregationFunction::Pc75),
+            "p90" | "pc90" => Ok(AggregationFunction::Pc90),
+            "p95" | "pc95" => Ok(AggregationFunction::Pc95),
+            "p98" | "pc98" => Ok(AggregationFunction::Pc98),
+            "p99" | "pc99" => Ok(AggregationFunction::Pc99),
+            "error_rate" => Ok(AggregationFunction::ErrorRate),
+            other => Err(format!(
+                "Unknown aggregation '{}'. Expected one of: count, cardinality, sum, min, max, avg, median, p50, p75, p90, p95, p98, p99, error_rate",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for AggregationFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A single compute: an aggregation function and the measure it applies to.
+///
+/// Parsed from `FUNCTION[:METRIC]`, e.g. `count`, `avg:@duration`, `cardinality:@usr.id`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Compute {
+    pub function: AggregationFunction,
+    pub metric: Option<String>,
+}
+
+impl Compute {
+    /// Output key for this compute (e.g. "count", "p99_duration", "cardinality_usr_id").
+    pub fn label(&self) -> String {
+        match &self.metric {
+            Some(metric) => format!("{}_{}", self.function, label_segment(metric)),
+            None => self.function.to_string(),
+        }
+    }
+}
+
+impl FromStr for Compute {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (function, metric) = match s.split_once(':') {
+            Some((function, metric)) => (function, Some(metric.trim())),
+            None => (s, None),
+        };
+        let function: AggregationFunction = function.trim().parse()?;
+
+        match metric {
+            Some("") => Err(format!("Missing metric in compute '{}'", s)),
+            Some(_) if function == AggregationFunction::ErrorRate => {
+                Err(format!("Aggregation '{}' takes no metric", function))
+            }
+            Some(metric) => Ok(Compute {
+                function,
+                metric: Some(metric.to_string()),
+            }),
+            None if function.requires_metric() => Err(format!(
+                "Aggregation '{}' requires a metric, e.g. '{}:@duration'",
+                function, function
+            )),
+            None => Ok(Compute {
+                function,
+                metric: None,
+            }),
+        }
+    }
+}
+
+/// A group-by facet with an optional bucket limit.
+///
+/// Parsed from `FACET[:LIMIT]`, e.g. `service`, `@http.status_code:20`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupBy {
+    pub facet: String,
+    pub limit: Option<i64>,
+}
+
+impl FromStr for GroupBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Facets may contain colons only in exotic cases; treat a trailing
+        // numeric segment as the limit and keep everything else as the facet.
+        if let Some((facet, limit)) = s.rsplit_once(':')
+            && let Ok(limit) = limit.parse::<i64>()
+        {
+            if limit <= 0 {
+                return Err(format!("Group-by limit must be positive in '{}'", s));
+            }
+            return Ok(GroupBy {
+                facet: facet.to_string(),
+                limit: Some(limit),
+            });
+        }
+
+        if s.is_empty() {
+            return Err("Group-by facet cannot be empty".to_string());
+        }
+
+        Ok(GroupBy {
+            facet: s.to_string(),
+            limit: None,
+        })
+    }
+}
+
+/// How group-by buckets are ordered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GroupSort {
+    /// Descending by the first compute (largest buckets first)
+    #[default]
+    Desc,
+    /// Ascending by the first compute
+    Asc,
+    /// Alphabetically by facet value
+    Alpha,
+}
+
+impl FromStr for GroupSort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "desc" => Ok(GroupSort::Desc),
+            "asc" => Ok(GroupSort::Asc),
+            "alpha" => Ok(GroupSort::Alpha),
+            other => Err(format!(
+                "Unknown group sort '{}'. Expected one of: desc, asc, alpha",
+                other
+            )),
+        }
+    }
+}
+
+/// Checks a timeseries bucket width (e.g. "1m", "5m", "1h"), returning it as
+/// sent to the API.
+///
+/// Takes the same widths as metric rollup intervals; plain numbers are seconds.
+pub fn parse_interval(s: &str) -> Result<String, String> {
+    crate::timeseries::parse_interval(s)?;
+    let s = s.trim();
+    Ok(if s.bytes().all(|b| b.is_ascii_digit()) {
+        format!("{}s", s)
+    } else {
+        s.to_string()
+    })
+}
+
+/// A complete aggregation request, independent of the backing API.
+#[derive(Debug, Clone)]
+pub struct AggregateRequest {
+    pub computes: Vec<Compute>,
+    pub group_by: Vec<GroupBy>,
+    pub group_sort: GroupSort,
+    /// Timeseries bucket width (e.g. "5m"); `None` returns a single total per group
+    pub interval: Option<String>,
+}
+
+impl AggregateRequest {
+    /// Returns the computes to send, defaulting to a single `count`.
+    pub fn effective_computes(&self) -> Vec<Compute> {
+        if self.computes.is_empty() {
+            vec![Compute {
+                function: AggregationFunction::Count,
+                metric: None,
+            }]
+        } else {
+            self.computes.clone()
+        }
+    }
+
+    /// Computes in the request format of the logs and spans analytics APIs.
+    pub fn api_computes(&self) -> Value {
+        let computes = self.effective_computes();
+        let computes = computes.iter().map(|compute| {
+            let mut api_compute = json!({ "aggregation": compute.function.api_name() });
+            if let Some(metric) = &compute.metric {
+                api_compute["metric"] = json!(metric);
+            }
+            if let Some(interval) = &self.interval {
+                api_compute["type"] = json!("timeseries");
+                api_compute["interval"] = json!(interval);
+            }
+            api_compute
+        });
+        Value::Array(computes.collect())
+    }
+
+    /// Group-bys in the request format of the logs and spans analytics APIs.
+    ///
+    /// Buckets are sorted by the first compute unless alphabetical order is requested.
+    pub fn api_group_by(&self) -> Value {
+        let computes = self.effective_computes();
+        let primary = &computes[0];
+        let sort = match self.group_sort {
+            GroupSort::Alpha => json!({ "type": "alphabetical", "order": "asc" }),
+            order => {
+                let mut sort = json!({
+                    "type": "measure",
+                    "aggregation": primary.function.api_name(),
+                    "order": if order == GroupSort::Asc { "asc" } else { "desc" },
+                });
+                if let Some(metric) = &primary.metric {
+                    sort["metric"] = json!(metric);
+                }
+                sort
+            }
+        };
+
+        let group_by = self.group_by.iter().map(|group| {
+            let mut api_group = json!({ "facet": group.facet, "sort": sort });
+            if let Some(limit) = group.limit {
+                api_group["limit"] = json!(limit);
+            }
+            api_group
+        });
+        Value::Array(group_by.collect())
+    }
+
+    /// Returns the request counting errors per group, if any compute is an error rate.
+    ///
+    /// It is sent with [`error_query`] and its rows passed to [`Self::apply_error_rates`].
+    pub fn error_request(&self) -> Option<AggregateRequest> {
+        let wanted = self
+            .computes
+            .iter()
+            .any(|c| c.function == AggregationFunction::ErrorRate);
+        wanted.then(|| AggregateRequest {
+            computes: Vec::new(),
+            group_by: self
+                .group_by
+                .iter()
+                .map(|group| GroupBy {
+                    facet: group.facet.clone(),
+                    limit: Some(ERROR_GROUP_LIMIT),
+                })
+                .collect(),
+            group_sort: GroupSort::Desc,
+            interval: self.interval.clone(),
+        })
+    }
+
+    /// Replaces the counts sent for error rate computes with errors divided by that count.
+    ///
+    /// `errors` are the rows returned for [`Self::error_request`]; groups and
+    /// timestamps missing from them had no errors. Rates of empty buckets are null.
+    pub fn apply_error_rates(&self, rows: &mut [AggregateRow], errors: &[AggregateRow]) {
+        let labels: Vec<String> = self
+            .computes
+            .iter()
+            .filter(|c| c.function == AggregationFunction::ErrorRate)
+            .map(|c| c.label())
+            .collect();
+        let error_counts: BTreeMap<String, f64> = errors
+            .iter()
+            .map(|row| {
+                let count = row.get("count").and_then(Value::as_f64).unwrap_or(0.0);
+                (self.bucket_key(row), count)
+            })
+            .collect();
+
+        for row in rows {
+            let errors = error_counts
+                .get(&self.bucket_key(row))
+                .copied()
+                .unwrap_or(0.0);
+            for label in &labels {
+                let total = row.get(label).and_then(Value::as_f64).unwrap_or(0.0);
+                let rate = if total > 0.0 {
+                    json!(errors / total)
+                } else {
+                    Value::Null
+                };
+                row.insert(label.clone(), rate);
+            }
+        }
+    }
+
+    /// Identifies a row by its group-by values and timestamp.
+    fn bucket_key(&self, row: &AggregateRow) -> String {
+        let mut key: Vec<&Value> = self
+            .group_by
+            .iter()
+            .map(|group| {
+                row.get(group.facet.trim_start_matches('@'))
+                    .unwrap_or(&Value::Null)
+            })
+            .collect();
+        key.push(row.get("timestamp").unwrap_or(&Value::Null));
+        serde_json::to_string(&key).unwrap_or_default()
+    }
+}
+
+/// Returns `query` narrowed to failed logs or spans, for [`AggregateRequest::error_request`].
+pub fn error_query(query: &str) -> String {
+    format!("({}) {}", query, ERROR_FILTER)
+}
+
+/// A flattened aggregation row, ready for NDJSON output.
+pub type AggregateRow = Map<String, Value>;
+
+/// Flattens one response bucket, as serialized from either API's model, into output rows.
+///
+/// Logs buckets hold `by` and `computes` directly; spans buckets nest them
+/// under `attributes`.
+pub fn bucket_rows(bucket: &Value, labels: &[String]) -> Vec<AggregateRow> {
+    let fields = bucket.get("attributes").unwrap_or(bucket);
+    flatten_bucket(fields.get("by"), fields.get("computes"), labels)
+}
+
+/// Flattens one response bucket into output rows.
+///
+/// `by` holds the group-by facet values and `computes` the positional compute
+/// results (`c0`, `c1`, ...). Scalar computes produce a single row; timeseries
+/// computes produce one row per timestamp with a `timestamp` field.
+pub fn flatten_bucket(
+    by: Option<&Value>,
+    computes: Option<&Value>,
+    labels: &[String],
+) -> Vec<AggregateRow> {
+    let mut base = Map::new();
+    if let Some(Value::Object(by)) = by {
+        for (facet, value) in by {
+            base.insert(facet.trim_start_matches('@').to_string(), value.clone());
+        }
+    }
+
+    let mut scalars = Map::new();
+    let mut series: BTreeMap<String, Map<String, Value>> = BTreeMap::new();
+
+    if let Some(Value::Object(computes)) = computes {
+        for (index, label) in labels.iter().enumerate() {
+            match computes.get(&format!("c{}", index)) {
+                Some(Value::Array(points)) => {
+                    for point in points {
+                        let Some(time) = point.get("time") else {
+                            continue;
+                        };
+                        let key = match time {
+                            Value::String(s) => s.clone(),
+                            other => other.to_string(),
+                        };
+                        let row = series.entry(key).or_insert_with(|| {
+                            let mut row = Map::new();
+                            row.insert("timestamp".to_string(), time.clone());
+                            row
+                        });
+                        row.insert(
+                            label.clone(),
+                            point.get("value").cloned().unwrap_or(Value::Null),
+                        );
+                    }
+                }
+                Some(value) => {
+                    scalars.insert(label.clone(), value.clone());
+                }
+                None => {}
+            }
+        }
+    }
+
+    if series.is_empty() {
+        let mut row = base;
+        row.extend(scalars);
+        return vec![row];
+    }
+
+    series
+        .into_values()
+        .map(|point| {
+            let mut row = base.clone();
+            row.extend(scalars.clone());
+            row.extend(point);
+            row
+        })
+        .collect()
+}
+
+/// Converts a facet or measure path into a label segment ("@usr.id" -> "usr_id").
+fn label_segment(path: &str) -> String {
+    path.trim_start_matches('@')
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_compute_count() {
+        let compute: Compute = "count".parse().unwrap();
+        assert_eq!(compute.function, AggregationFunction::Count);
+        assert_eq!(compute.metric, None);
+        assert_eq!(compute.label(), "count");
+    }
+
+    #[test]
+    fn test_parse_compute_with_metric() {
+        let compute: Compute = "p99:@duration".parse().unwrap();
+        assert_eq!(compute.function, AggregationFunction::Pc99);
+        assert_eq!(compute.metric.as_deref(), Some("@duration"));
+        assert_eq!(compute.label(), "p99_duration");
+
+        let compute: Compute = "cardinality:@usr.id".parse().unwrap();
+        assert_eq!(compute.label(), "cardinality_usr_id");
+    }
+
+    #[test]
+    fn test_parse_compute_invalid() {
+        assert!("avg".parse::<Compute>().is_err());
+        assert!("avg:".parse::<Compute>().is_err());
+        assert!("p42:@duration".parse::<Compute>().is_err());
+        assert!("bogus".parse::<Compute>().is_err());
+        assert!("error_rate:@duration".parse::<Compute>().is_err());
+    }
+
+    #[test]
+    fn test_parse_compute_error_rate() {
+        let compute: Compute = "error_rate".parse().unwrap();
+        assert_eq!(compute.function, AggregationFunction::ErrorRate);
+        assert_eq!(compute.label(), "error_rate");
+    }
+
+    #[test]
+    fn test_parse_group_by() {
+        let group: GroupBy = "service".parse().unwrap();
+        assert_eq!(group.facet, "service");
+        assert_eq!(group.limit, None);
+
+        let group: GroupBy = "@http.status_code:20".parse().unwrap();
+        assert_eq!(group.facet, "@http.status_code");
+        assert_eq!(group.limit, Some(20));
+
+        assert!("service:0".parse::<GroupBy>().is_err());
+        assert!("".parse::<GroupBy>().is_err());
+    }
+
+    #[test]
+    fn test_parse_interval() {
+        assert_eq!(parse_interval("5m").unwrap(), "5m");
+        assert_eq!(parse_interval("90").unwrap(), "90s");
+        assert!(parse_interval("5 minutes").is_err());
+        assert!(parse_interval("0m").is_err());
+        assert!(parse_interval("999999999999999d").is_err());
+    }
+
+    #[test]
+    fn test_parse_group_sort() {
+        assert_eq!("desc".parse::<GroupSort>().unwrap(), GroupSort::Desc);
+        assert_eq!("ASC".parse::<GroupSort>().unwrap(), GroupSort::Asc);
+        assert_eq!("alpha".parse::<GroupSort>().unwrap(), GroupSort::Alpha);
+        assert!("random".parse::<GroupSort>().is_err());
+    }
+
+    #[test]
+    fn test_effective_computes_defaults_to_count() {
+        let request = AggregateRequest {
+            computes: vec![],
+            group_by: vec![],
+            group_sort: GroupSort::Desc,
+            interval: None,
+        };
+        let computes = request.effective_computes();
+        assert_eq!(computes.len(), 1);
+        assert_eq!(computes[0].function, AggregationFunction::Count);
+    }
+
+    fn request(computes: &[&str], group_by: &[&str], interval: Option<&str>) -> AggregateRequest {
+        AggregateRequest {
+            computes: computes.iter().map(|c| c.parse().unwrap()).collect(),
+            group_by: group_by.iter().map(|g| g.parse().unwrap()).collect(),
+            group_sort: GroupSort::Desc,
+            interval: interval.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_api_computes_and_group_by() {
+        let request = request(&["p99:@duration", "error_rate"], &["service:5"], Some("5m"));
+        assert_eq!(
+            request.api_computes(),
+            json!([
+                {"aggregation": "pc99", "metric": "@duration", "type": "timeseries", "interval": "5m"},
+                {"aggregation": "count", "type": "timeseries", "interval": "5m"}
+            ])
+        );
+        assert_eq!(
+            request.api_group_by(),
+            json!([{
+                "facet": "service",
+                "limit": 5,
+                "sort": {"type": "measure", "aggregation": "pc99", "metric": "@duration", "order": "desc"}
+            }])
+        );
+    }
+
+    #[test]
+    fn test_api_request_converts_to_both_models() {
+        use datadog_api_client::datadogV2::model::{
+            LogsCompute, LogsGroupBy, SpansCompute, SpansGroupBy,
+        };
+
+        fn roundtrip<T: serde::de::DeserializeOwned + serde::Serialize>(value: &Value) -> Value {
+            let model: Vec<T> = serde_json::from_value(value.clone()).unwrap();
+            serde_json::to_value(model).unwrap()
+        }
+
+        let mut request = request(
+            &["p95:@duration", "cardinality:@usr.id"],
+            &["service"],
+            None,
+        );
+        request.group_sort = GroupSort::Alpha;
+        let (computes, group_by) = (request.api_computes(), request.api_group_by());
+        assert_eq!(roundtrip::<LogsCompute>(&computes), computes);
+        assert_eq!(roundtrip::<SpansCompute>(&computes), computes);
+        assert_eq!(roundtrip::<LogsGroupBy>(&group_by), group_by);
+        assert_eq!(roundtrip::<SpansGroupBy>(&group_by), group_by);
+    }
+
+    #[test]
+    fn test_error_request_counts_errors_per_group() {
+        assert!(
+            request(&["count"], &["service"], None)
+                .error_request()
+                .is_none()
+        );
+
+        let errors = request(&["p99:@duration", "error_rate"], &["service:5"], Some("1m"))
+            .error_request()
+            .unwrap();
+        assert_eq!(errors.effective_computes()[0].label(), "count");
+        assert_eq!(errors.group_by[0].facet, "service");
+        assert_eq!(errors.group_by[0].limit, Some(ERROR_GROUP_LIMIT));
+        assert_eq!(errors.interval.as_deref(), Some("1m"));
+        assert_eq!(error_query("service:api"), "(service:api) status:error");
+    }
+
+    #[test]
+    fn test_apply_error_rates() {
+        let request = request(&["error_rate"], &["@http.method"], None);
+        let row = |method: &str, count: u64| {
+            bucket_rows(
+                &json!({"by": {"@http.method": method}, "computes": {"c0": count}}),
+                &["error_rate".to_string()],
+            )
+            .remove(0)
+        };
+        let mut rows = vec![row("GET", 200), row("POST", 10), row("PUT", 0)];
+        let errors = vec![
+            bucket_rows(
+                &json!({"by": {"@http.method": "GET"}, "computes": {"c0": 50}}),
+                &["count".to_string()],
+            )
+            .remove(0),
+        ];
+
+        request.apply_error_rates(&mut rows, &errors);
+        assert_eq!(rows[0]["error_rate"], 0.25);
+        assert_eq!(rows[1]["error_rate"], 0.0);
+        assert_eq!(rows[2]["error_rate"], Value::Null);
+    }
+
+    #[test]
+    fn test_spans_bucket_rows() {
+        let bucket = json!({
+            "id": "bucket-1",
+            "type": "bucket",
+            "attributes": {
+                "by": {"service": "api", "resource_name": "/users"},
+                "computes": {"c0": 812000000.0, "c1": 1200}
+            }
+        });
+        let labels = vec!["p99_duration".to_string(), "count".to_string()];
+
+        let rows = bucket_rows(&bucket, &labels);
+        assert_eq!(
+            Value::Object(rows[0].clone()),
+            json!({"service": "api", "resource_name": "/users", "p99_duration": 812000000.0, "count": 1200})
+        );
+    }
+
+    #[test]
+    fn test_spans_timeseries_bucket_rows() {
+        let bucket = json!({
+            "attributes": {
+                "by": {"resource_name": "/users"},
+                "computes": {"c0": [
+                    {"time": "2024-01-15T10:00:00Z", "value": 700000000.0},
+                    {"time": "2024-01-15T10:01:00Z", "value": 650000000.0}
+                ]}
+            }
+        });
+
+        let rows = bucket_rows(&bucket, &["p95_duration".to_string()]);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1]["resource_name"], "/users");
+        assert_eq!(rows[1]["timestamp"], "2024-01-15T10:01:00Z");
+        assert_eq!(rows[1]["p95_duration"], 650000000.0);
+    }
+
+    #[test]
+    fn test_flatten_scalar_bucket() {
+        let by = json!({"service": "api", "@http.status_code": "500"});
+        let computes = json!({"c0": 42, "c1": 1.5});
+        let labels = vec!["count".to_string(), "avg_duration".to_string()];
+
+        let rows = flatten_bucket(Some(&by), Some(&computes), &labels);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0]["service"], "api");
+        assert_eq!(rows[0]["http.status_code"], "500");
+        assert_eq!(rows[0]["count"], 42);
+        assert_eq!(rows[0]["avg_duration"], 1.5);
+    }
+
+    #[test]
+    fn test_flatten_timeseries_bucket() {
+        let by = json!({"service": "api"});
+        let computes = json!({
+            "c0": [
+                {"time": "2024-01-15T10:00:00Z", "value": 3},
+                {"time": "2024-01-15T10:05:00Z", "value": 5}
+            ]
+        });
+        let labels = vec!["count".to_string()];
+
+        let rows = flatten_bucket(Some(&by), Some(&computes), &labels);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0]["timestamp"], "2024-01-15T10:00:00Z");
+        assert_eq!(rows[0]["count"], 3);
+        assert_eq!(rows[1]["service"], "api");
+        assert_eq!(rows[1]["count"], 5);
+    }
+
+    #[test]
+    fn test_flatten_bucket_without_group_by() {
+        let computes = json!({"c0": 7});
+        let rows = flatten_bucket(None, Some(&computes), &["count".to_string()]);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0]["count"], 7);
+    }
+}
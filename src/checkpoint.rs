@@ -0,0 +1,232 @@
+//! Checkpoint files for resumable search exports.
+//!
+//! A checkpoint records what a search exports (command, query, indexes,
+//! resolved time bounds, sort order and limit) and how far it got: the pagination cursor
+//! of the page in progress, how many of that page's records were written and
+//! the total written so far. Checkpoints are stored as JSON and replaced
+//! atomically, so an interrupted save never leaves a truncated file.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::client::SortOrder;
+use crate::error::AppError;
+use crate::time::ResolvedRange;
+
+/// Progress of a search export.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// Command that started the export (e.g. "logs search")
+    pub command: String,
+    /// Search query
+    pub query: String,
+    /// Log indexes searched (empty for spans)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub indexes: Vec<String>,
+    /// Start of the time range (Unix ms), fixed when the export started
+    pub from_ms: i64,
+    /// End of the time range (Unix ms), fixed when the export started
+    pub to_ms: i64,
+    /// Total number of records wanted (0 for unlimited)
+    pub limit: u64,
+    /// Timestamp order of the search; the cursor is only valid for this order
+    #[serde(default)]
+    pub sort: SortOrder,
+    /// Cursor of the page in progress, or `None` for the first page
+    pub cursor: Option<String>,
+    /// Records of the page in progress already written
+    pub page_offset: usize,
+    /// Records written across all runs
+    pub records_written: u64,
+    /// Whether every record has been written
+    pub complete: bool,
+}
+
+impl Checkpoint {
+    /// Creates a checkpoint for an export that has not fetched anything yet.
+    pub fn new(
+        command: &str,
+        query: &str,
+        indexes: Vec<String>,
+        range: &ResolvedRange,
+        sort: SortOrder,
+        limit: u64,
+    ) -> Self {
+        Self {
+            command: command.to_string(),
+            query: query.to_string(),
+            indexes,
+            from_ms: range.from_ms,
+            to_ms: range.to_ms,
+            limit,
+            sort,
+            cursor: None,
+            page_offset: 0,
+            records_written: 0,
+            complete: false,
+        }
+    }
+
+    /// The time range the export covers.
+    pub fn range(&self) -> ResolvedRange {
+        ResolvedRange::new(self.from_ms, self.to_ms)
+    }
+
+    /// Number of records still wanted, or `None` when the export is unlimited.
+    pub fn remaining(&self) -> Option<u64> {
+        (self.limit > 0).then(|| self.limit.saturating_sub(self.records_written))
+    }
+
+    /// Records that `count` more records were written from the page in progress.
+    pub fn advance(&mut self, count: usize) {
+        self.page_offset += count;
+        self.records_written += count as u64;
+    }
+
+    /// Moves on to the page after the one in progress, or marks the export complete.
+    pub fn next_page(&mut self, cursor: Option<String>) {
+        self.complete = cursor.is_none();
+        self.cursor = cursor;
+        self.page_offset = 0;
+    }
+
+    /// Checks that a resumed export searches for the same records.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Config` if the command, query or indexes differ.
+    pub fn ensure_matches(
+        &self,
+        command: &str,
+        query: &str,
+        indexes: &[String],
+    ) -> Result<(), AppError> {
+        if self.command != command || self.query != query || self.indexes != indexes {
+            let indexes = if self.indexes.is_empty() {
+                String::new()
+            } else {
+                format!(" --indexes {}", self.indexes.join(","))
+            };
+            return Err(AppError::Config(format!(
+                "Checkpoint was written by `ddog {} '{}'{}`; resume with the same query and indexes",
+                self.command, self.query, indexes
+            )));
+        }
+        Ok(())
+    }
+
+    /// Reads a checkpoint file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Config` if the file cannot be read or parsed.
+    pub fn load(path: &Path) -> Result<Self, AppError> {
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            AppError::Config(format!(
+                "Failed to read checkpoint {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        serde_json::from_str(&contents)
+            .map_err(|e| AppError::Config(format!("Invalid checkpoint {}: {}", path.display(), e)))
+    }
+
+    /// Writes the checkpoint file, replacing any previous version atomically.
+    pub fn save(&self, path: &Path) -> Result<(), AppError> {
+        let mut temp = PathBuf::from(path).into_os_string();
+        temp.push(".tmp");
+        std::fs::write(&temp, serde_json::to_vec_pretty(self)?)?;
+        std::fs::rename(&temp, path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Checkpoint {
+        Checkpoint::new(
+            "logs search",
+            "service:api",
+            vec!["main".to_string()],
+            &ResolvedRange::new(1000, 2000),
+            SortOrder::Asc,
+            0,
+        )
+    }
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let path =
+            std::env::temp_dir().join(format!("ddog-checkpoint-{}.json", std::process::id()));
+        let mut checkpoint = sample();
+        checkpoint.advance(1000);
+        checkpoint.next_page(Some("cursor-1".to_string()));
+        checkpoint.advance(3);
+
+        checkpoint.save(&path).unwrap();
+        let loaded = Checkpoint::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded, checkpoint);
+        assert_eq!(loaded.range(), ResolvedRange::new(1000, 2000));
+        assert_eq!(loaded.page_offset, 3);
+        assert_eq!(loaded.records_written, 1003);
+    }
+
+    #[test]
+    fn test_next_page_without_cursor_completes() {
+        let mut checkpoint = sample();
+        checkpoint.advance(5);
+        checkpoint.next_page(None);
+        assert!(checkpoint.complete);
+        assert_eq!(checkpoint.page_offset, 0);
+    }
+
+    #[test]
+    fn test_remaining() {
+        let mut checkpoint = sample();
+        assert_eq!(checkpoint.remaining(), None);
+        checkpoint.limit = 10;
+        checkpoint.advance(4);
+        assert_eq!(checkpoint.remaining(), Some(6));
+    }
+
+    #[test]
+    fn test_ensure_matches() {
+        let checkpoint = sample();
+        let main = vec!["main".to_string()];
+        assert!(
+            checkpoint
+                .ensure_matches("logs search", "service:api", &main)
+                .is_ok()
+        );
+        assert!(
+            checkpoint
+                .ensure_matches("logs search", "service:web", &main)
+                .is_err()
+        );
+        assert!(
+            checkpoint
+                .ensure_matches("logs search", "service:api", &[])
+                .is_err()
+        );
+        assert!(
+            checkpoint
+                .ensure_matches("spans search", "service:api", &main)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_load_invalid_file() {
+        let path =
+            std::env::temp_dir().join(format!("ddog-checkpoint-bad-{}.json", std::process::id()));
+        std::fs::write(&path, "not json").unwrap();
+        let error = Checkpoint::load(&path).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(error, AppError::Config(_)));
+    }
+}
@@ -1,26 +1,59 @@
 //! Main CLI argument definitions.
 
 use clap::{Parser, Subcommand};
+use std::path::PathBuf;
 
+use crate::output::{Compression, ErrorFormat, OutputFormat, Rotation};
+use ddog::client::{DEFAULT_MAX_RETRIES, DEFAULT_RETRY_TIMEOUT};
+
+use super::config::ConfigAction;
+use super::dashboards::DashboardsAction;
+use super::events::EventsAction;
 use super::logs::LogsAction;
 use super::metrics::MetricsAction;
+use super::monitors::MonitorsAction;
+use super::slo::SloAction;
 use super::spans::SpansAction;
 
 /// Main CLI application structure.
 #[derive(Parser, Debug)]
 #[command(name = "ddog")]
-#[command(about = "Query Datadog logs, APM spans, and metrics from the command line")]
 #[command(
-    long_about = "Query Datadog logs, APM spans, and metrics from the command line.
+    about = "Query Datadog logs, APM spans, metrics, events, monitors, SLOs and dashboards from the command line"
+)]
+#[command(
+    long_about = "Query Datadog logs, APM spans, metrics, events, monitors, SLOs and dashboards from the command line.
 
-Environment Variables (Required):
+Environment Variables (Required unless a profile provides them):
   DD_API_KEY     Your Datadog API key
   DD_APP_KEY     Your Datadog application key
   DD_SITE        Datadog site (optional, default: datadoghq.com)
 
+Profiles:
+  Named profiles in ~/.config/ddog/config.toml hold the site, key references
+  and default indexes for each Datadog organization. Select one with --profile
+  or DDOG_PROFILE. Precedence: --profile > environment > default profile > defaults.
+  See: ddog config --help
+
+Retries:
+  Rate-limited (429) requests wait for the time given by Datadog's rate-limit
+  headers; 5xx responses and connection errors back off exponentially.
+  Paginated commands resume from the failed page. Tune with --max-retries
+  and --retry-timeout; use --verbose to see each retry.
+
+Errors:
+  Failures are printed to stderr as \"Error: ...\" with a non-zero exit code.
+  With --error-format json, a single JSON object is printed instead, with
+  kind, exit_code, http_status, message, request_id and command fields.
+
 Output Format:
-  All commands output newline-delimited JSON (NDJSON), one record per line.
-  Perfect for piping to jq, grep, or other line-oriented tools.
+  All commands output newline-delimited JSON (NDJSON) by default, one record
+  per line. Perfect for piping to jq, grep, or other line-oriented tools.
+  Use --output to choose json, csv, tsv, table, yaml or logfmt instead.
+  Tabular formats flatten nested objects into dotted columns; pick columns
+  with --fields (e.g. --fields attributes.service,attributes.status).
+  Write to files with --out, optionally with --compress gzip|zstd and
+  --rotate-every N|SIZE to split large exports into numbered parts.
 
 Examples:
   # Search logs for errors
@@ -32,6 +65,24 @@ Examples:
   # Search spans with time range
   ddog spans search \"service:web\" --from now-1h --to now
 
+  # Mute a monitor for the next two hours
+  ddog monitors mute 12345 --end now+2h
+
+  # Fail when an SLO burns its error budget too fast
+  ddog slo burn-rate abc123 --windows 1h:14.4,6h:6
+
+  # Mark a deploy from CI
+  ddog events post --title \"Deployed api v1.2.3\" --text \"Commit abc123\" --tags service:api,deploy
+
+  # Back up every dashboard to JSON files
+  ddog dashboards export dashboards/
+
+  # Archive a day of logs as gzipped parts of 100000 records
+  ddog logs search \"service:api\" --last 1d --limit 0 --out api.ndjson --compress gzip --rotate-every 100000
+
+  # Show recent errors as a table
+  ddog logs search \"status:error\" -o table --fields attributes.service,attributes.message
+
 Documentation:
   https://github.com/tmcinerney/ddog"
 )]
@@ -41,6 +92,46 @@ pub struct Cli {
     #[arg(short, long, global = true)]
     pub verbose: bool,
 
+    /// Configuration profile to use (overrides DD_* environment variables)
+    #[arg(short = 'p', long, global = true)]
+    pub profile: Option<String>,
+
+    /// Output format [default: ndjson]
+    #[arg(short, long, global = true, value_enum)]
+    pub output: Option<OutputFormat>,
+
+    /// Comma-separated dotted paths to output; columns in tabular formats (e.g. attributes.service,attributes.status)
+    #[arg(long, global = true, value_delimiter = ',')]
+    pub fields: Vec<String>,
+
+    /// Write output to a file instead of stdout
+    #[arg(long, global = true, value_name = "PATH")]
+    pub out: Option<PathBuf>,
+
+    /// Compress the --out file (adds .gz or .zst to its name)
+    #[arg(long, global = true, value_enum, requires = "out")]
+    pub compress: Option<Compression>,
+
+    /// Start a new --out file every N records or SIZE bytes (e.g. 100000, 256MB)
+    #[arg(long, global = true, value_name = "N|SIZE", requires = "out")]
+    pub rotate_every: Option<Rotation>,
+
+    /// Flush output every N records [default: every record on stdout, when buffers fill with --out]
+    #[arg(long, global = true, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    pub flush_every: Option<u64>,
+
+    /// How to report errors on stderr
+    #[arg(long, global = true, value_enum, default_value_t = ErrorFormat::Text)]
+    pub error_format: ErrorFormat,
+
+    /// Maximum retries per API request on rate limits, 5xx and connection errors (0 to disable)
+    #[arg(long, global = true, default_value_t = DEFAULT_MAX_RETRIES)]
+    pub max_retries: u32,
+
+    /// Total seconds to keep retrying a single API request
+    #[arg(long, global = true, default_value_t = DEFAULT_RETRY_TIMEOUT.as_secs())]
+    pub retry_timeout: u64,
+
     #[command(subcommand)]
     pub domain: Domain,
 }
@@ -48,6 +139,20 @@ pub struct Cli {
 /// Available domains for querying Datadog.
 #[derive(Subcommand, Debug)]
 pub enum Domain {
+    // Domains calling the API, listed first in help
+    #[command(flatten)]
+    Api(Box<ApiDomain>),
+
+    /// Config domain - manage configuration profiles
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+}
+
+/// Domains that call the Datadog API, and so need credentials.
+#[derive(Subcommand, Debug)]
+pub enum ApiDomain {
     /// Logs domain - search and analyze logs
     Logs {
         #[command(subcommand)]
@@ -65,4 +170,28 @@ pub enum Domain {
         #[command(subcommand)]
         action: MetricsAction,
     },
+
+    /// Monitors domain - list, search, mute and inspect monitors
+    Monitors {
+        #[command(subcommand)]
+        action: MonitorsAction,
+    },
+
+    /// SLO domain - list SLOs, their history and error-budget burn rates
+    Slo {
+        #[command(subcommand)]
+        action: SloAction,
+    },
+
+    /// Events domain - search events and post deploy markers
+    Events {
+        #[command(subcommand)]
+        action: EventsAction,
+    },
+
+    /// Dashboards domain - back up, restore and compare dashboards
+    Dashboards {
+        #[command(subcommand)]
+        action: DashboardsAction,
+    },
 }
@@ -0,0 +1,68 @@
+//! Config domain command actions.
+
+use clap::Subcommand;
+
+/// Available actions for the config domain.
+#[derive(Subcommand, Debug)]
+pub enum ConfigAction {
+    /// List configured profiles
+    #[command(long_about = "List the profiles defined in the config file.
+
+The config file is read from $DDOG_CONFIG, $XDG_CONFIG_HOME/ddog/config.toml
+or ~/.config/ddog/config.toml, in that order.
+
+Output Format:
+  Each line contains a JSON object with the profile name, site, whether it is
+  the default profile and whether it is active for this invocation.
+  Keys are never printed.
+
+Example config file:
+  default_profile = \"us1-prod\"
+
+  [profiles.us1-prod]
+  site = \"datadoghq.com\"
+  api_key_env = \"DD_US1_API_KEY\"
+  app_key_env = \"DD_US1_APP_KEY\"
+  default_indexes = [\"main\"]
+
+  [profiles.eu]
+  site = \"datadoghq.eu\"
+  api_key_env = \"DD_EU_API_KEY\"
+  app_key_env = \"DD_EU_APP_KEY\"
+
+Examples:
+  ddog config list
+  ddog config list | jq -r '.name'")]
+    List,
+
+    /// Show the resolved settings for a profile, with keys redacted
+    #[command(long_about = "Show the settings ddog would use, with keys redacted.
+
+Without a name, shows the active settings (after applying --profile,
+environment variables and the default profile). With a name, shows that
+profile as if it were selected with --profile.
+
+Each setting is reported with its source: flag, env, profile or default.
+
+Examples:
+  ddog config show
+  ddog config show eu
+  ddog --profile staging config show")]
+    Show {
+        /// Profile name (default: the active profile)
+        name: Option<String>,
+    },
+
+    /// Set the default profile
+    #[command(long_about = "Set the default profile in the config file.
+
+The default profile is used when neither --profile nor DDOG_PROFILE is set.
+Note: rewriting the file does not preserve comments.
+
+Examples:
+  ddog config use eu")]
+    Use {
+        /// Profile name to make the default
+        name: String,
+    },
+}
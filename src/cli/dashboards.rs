@@ -0,0 +1,104 @@
+//! Dashboards domain command actions.
+
+use clap::Subcommand;
+use std::path::PathBuf;
+
+/// Available actions for the dashboards domain.
+#[derive(Subcommand, Debug)]
+pub enum DashboardsAction {
+    /// List dashboards
+    #[command(long_about = "List dashboards.
+
+Output Format:
+  One JSON object per dashboard with its ID, title, layout, author and URL.
+
+Examples:
+  ddog dashboards list
+  ddog dashboards list | jq -r '[.id, .title] | @tsv'")]
+    List,
+
+    /// Show a single dashboard by ID
+    Get {
+        /// Dashboard ID (e.g. abc-def-ghi)
+        id: String,
+    },
+
+    /// Back up dashboards to JSON files, one per dashboard
+    #[command(long_about = "Back up dashboards to JSON files, one per dashboard.
+
+Each dashboard is written to <dir>/<id>.json without the fields Datadog manages
+itself (id, author, timestamps, url) and without widget IDs, so it can be
+imported into another org. Keys are sorted and widgets are ordered by their
+position, so exporting an unchanged dashboard gives the same file and git
+diffs only show real changes. Existing files are overwritten.
+
+Output Format:
+  One JSON object per file written, with the dashboard ID, title and path.
+
+Examples:
+  # Back up every dashboard
+  ddog dashboards export dashboards/
+
+  # Back up two dashboards
+  ddog dashboards export dashboards/ --id abc-def-ghi --id jkl-mno-pqr")]
+    Export {
+        /// Directory to write the dashboards to (created if missing)
+        dir: PathBuf,
+
+        /// Only these dashboards (repeatable); every dashboard by default
+        #[arg(long = "id")]
+        ids: Vec<String>,
+    },
+
+    /// Create dashboards from JSON files
+    #[command(long_about = "Create dashboards from JSON files.
+
+Each file is checked against the dashboard model and created as a new
+dashboard. Files may be backups from export or dashboards as returned by get.
+Use the global --profile option to import into another org. With --replace,
+the single file given replaces an existing dashboard instead.
+
+Output Format:
+  One JSON object per dashboard, with its ID, title, URL and file.
+
+Examples:
+  # Restore backups into the staging org
+  ddog --profile staging dashboards import dashboards/*.json
+
+  # Overwrite a dashboard with its edited backup
+  ddog dashboards import dashboards/abc-def-ghi.json --replace abc-def-ghi")]
+    Import {
+        /// Dashboard files
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+
+        /// Replace this dashboard instead of creating a new one
+        #[arg(long, value_name = "ID")]
+        replace: Option<String>,
+    },
+
+    /// Compare a dashboard file with the live dashboard, widget by widget
+    #[command(
+        long_about = "Compare a dashboard file with the live dashboard, widget by widget.
+
+Both sides are normalized as by export. Widgets are matched by type and title.
+Changes go from the live dashboard to the file, i.e. what importing the file
+with --replace would change.
+
+Output Format:
+  One JSON object per changed widget, or \"dashboard\" for the dashboard's own
+  fields, with its status (added, removed, changed) and the changed fields
+  with their live (from) and file (to) values.
+
+Examples:
+  ddog dashboards diff abc-def-ghi dashboards/abc-def-ghi.json
+  ddog dashboards diff abc-def-ghi dashboards/abc-def-ghi.json | jq -r .target"
+    )]
+    Diff {
+        /// Dashboard ID
+        id: String,
+
+        /// Dashboard file to compare with
+        file: PathBuf,
+    },
+}
@@ -0,0 +1,78 @@
+//! Events domain command actions.
+
+use clap::Subcommand;
+
+use super::shared::{Pagination, TimeRange};
+use ddog::client::AlertType;
+
+/// Available actions for the events domain.
+#[derive(Subcommand, Debug)]
+pub enum EventsAction {
+    /// Search events using the event search syntax
+    #[command(long_about = "Search events using the event search syntax.
+
+Query Syntax:
+  • Free text matches titles and messages: deploy
+  • Source: source:github, source:my_apps
+  • Tags: tags:env:prod, service:api
+  • Status: status:error
+  • Boolean: source:github AND tags:env:prod
+  • Negation: -status:info
+
+Output Format:
+  Each line contains a JSON object with the event's attributes and metadata.
+
+Examples:
+  # Deploy markers from the last day
+  ddog events search \"tags:deploy service:api\" --from now-1d
+
+  # The latest 20 error events
+  ddog events search \"status:error\" --sort desc --limit 20
+
+Documentation:
+  https://docs.datadoghq.com/service_management/events/explorer/searching/")]
+    Search {
+        /// Event search query (e.g., "source:github tags:env:prod")
+        query: String,
+
+        #[command(flatten)]
+        time_range: TimeRange,
+
+        #[command(flatten)]
+        pagination: Pagination,
+    },
+
+    /// Post an event, e.g. a deploy marker from CI
+    #[command(long_about = "Post an event, e.g. a deploy marker from CI.
+
+Events appear in the event explorer and can be overlaid on dashboards to
+correlate incidents with deploys and configuration changes.
+
+Output Format:
+  The posted event as a JSON object, with its ID and URL.
+
+Examples:
+  # Mark a deploy
+  ddog events post --title \"Deployed api v1.2.3\" --text \"Commit abc123\" \\
+    --tags service:api,env:prod,deploy
+
+  # Report a failed rollout
+  ddog events post --title \"Rollout failed\" --text \"$CI_JOB_URL\" --alert-type error")]
+    Post {
+        /// Event title
+        #[arg(long)]
+        title: String,
+
+        /// Event body; Markdown when wrapped in "%%% \n" and "\n %%%"
+        #[arg(long)]
+        text: String,
+
+        /// Tags to attach (e.g. service:api,env:prod)
+        #[arg(long, value_delimiter = ',')]
+        tags: Vec<String>,
+
+        /// Severity: error, warning, info or success
+        #[arg(long = "alert-type", default_value = "info")]
+        alert_type: AlertType,
+    },
+}
@@ -2,7 +2,7 @@
 
 use clap::Subcommand;
 
-use super::shared::{Pagination, TimeRange};
+use super::shared::{Aggregation, Checkpointing, Follow, Pagination, Parallel, TimeRange};
 
 /// Available actions for the logs domain.
 #[derive(Subcommand, Debug)]
@@ -36,6 +36,9 @@ Examples:
   # Complex query with filtering
   ddog logs search \"service:api\" | jq 'select(.attributes.duration > 1000)'
 
+  # Live tail errors during a deploy
+  ddog logs search \"service:api status:error\" --follow --poll-interval 2
+
 Documentation:
   https://docs.datadoghq.com/logs/explorer/search_syntax/")]
     Search {
@@ -63,19 +66,72 @@ Examples:
         #[command(flatten)]
         pagination: Pagination,
 
-        /// Log indexes to search (comma-separated, default: all)
+        /// Log indexes to search (comma-separated, default: profile's default_indexes or all)
         #[arg(
             short,
             long,
             value_delimiter = ',',
-            default_value = "*",
             long_help = "Log indexes to search. Specify multiple indexes separated by commas.
 
+When omitted, uses the active profile's default_indexes, or all indexes.
+
 Examples:
   --indexes main           # Search only the 'main' index
   --indexes main,web       # Search both 'main' and 'web' indexes
   --indexes \"*\"            # Search all indexes (default)"
         )]
-        indexes: Vec<String>,
+        indexes: Option<Vec<String>>,
+
+        #[command(flatten)]
+        follow: Follow,
+
+        #[command(flatten)]
+        parallel: Parallel,
+
+        #[command(flatten)]
+        checkpointing: Checkpointing,
+    },
+
+    /// Aggregate logs server-side: counts, group-bys and computes
+    #[command(
+        long_about = "Aggregate logs server-side using Datadog's analytics endpoint.
+
+Instead of streaming raw events, Datadog computes counts and statistics per
+group and returns one bucket per facet combination. Much faster than piping
+search results into jq for \"how many X per Y\" questions.
+
+Output Format:
+  Each line contains a JSON object for one bucket, with a field per group-by
+  facet and per compute. With --interval, one line per bucket and timestamp.
+
+Examples:
+  # Count 5xx errors per service in the last hour
+  ddog logs aggregate \"@http.status_code:>=500\" --group-by service
+
+  # Top 10 services by p99 duration
+  ddog logs aggregate \"env:prod\" --group-by service:10 --compute p99:@duration
+
+  # Unique users per status, plus total count
+  ddog logs aggregate \"service:api\" --group-by status --compute count,cardinality:@usr.id
+
+  # Error count per service in 5 minute buckets
+  ddog logs aggregate \"status:error\" --group-by service --interval 5m --from now-6h
+
+Documentation:
+  https://docs.datadoghq.com/logs/explorer/analytics/"
+    )]
+    Aggregate {
+        /// Datadog query string (e.g., "service:api AND @http.status_code:500")
+        query: String,
+
+        #[command(flatten)]
+        time_range: TimeRange,
+
+        #[command(flatten)]
+        aggregation: Aggregation,
+
+        /// Log indexes to search (comma-separated, default: profile's default_indexes or all)
+        #[arg(short, long, value_delimiter = ',')]
+        indexes: Option<Vec<String>>,
     },
 }
@@ -2,7 +2,8 @@
 
 use clap::Subcommand;
 
-use super::shared::{TimeFrom, TimeRangeRelativeOnly};
+use super::shared::{TimeFrom, TimeRange};
+use ddog::timeseries::{NamedQuery, ScalarAggregator};
 
 /// Available actions for the metrics domain.
 #[derive(Subcommand, Debug)]
@@ -11,10 +12,6 @@ pub enum MetricsAction {
     #[command(
         long_about = "Query metrics timeseries data using Datadog's metric query syntax.
 
-⚠️  Time Format Limitation:
-  Metrics queries do NOT support ISO8601 timestamps.
-  Use relative times (now-1h) or Unix timestamps only.
-
 Query Syntax:
   • Basic: avg:system.cpu.user{*}
   • Aggregation: sum, avg, min, max, count
@@ -69,7 +66,7 @@ Examples:
         query: String,
 
         #[command(flatten)]
-        time_range: TimeRangeRelativeOnly,
+        time_range: TimeRange,
 
         /// Maximum number of data points to return (use 0 for unlimited)
         #[arg(
@@ -89,6 +86,101 @@ Examples:
         limit: u64,
     },
 
+    /// Reduce a metric query to one number per group
+    #[command(
+        long_about = "Reduce a metric query to one number per group over the time range.
+
+Uses Datadog's v2 scalar query API, so the reduction happens server-side:
+no need to pipe timeseries points through jq to average them.
+
+Aggregators:
+  avg, sum, min, max   Statistic over all points in the time range
+  last                 Most recent value
+  percentile           Percentile of a distribution metric (e.g. p95:latency{*})
+
+Output Format:
+  One JSON object per group, with the group-by tags and the reduced value.
+
+Examples:
+  # Average CPU per host over the last hour
+  ddog metrics scalar \"avg:system.cpu.user{env:prod}\" --group-by host
+
+  # Total requests per service today
+  ddog metrics scalar \"sum:trace.http.request.hits{*}.as_count()\" -a sum -g service --from now-1d
+
+  # Current queue depth
+  ddog metrics scalar \"max:rabbitmq.queue.messages{queue:jobs}\" -a last"
+    )]
+    Scalar {
+        /// Datadog metric query (e.g., "avg:system.cpu.user{*}")
+        query: String,
+
+        #[command(flatten)]
+        time_range: TimeRange,
+
+        /// How to reduce each series: avg, sum, min, max, last or percentile
+        #[arg(short, long, default_value = "avg")]
+        aggregator: ScalarAggregator,
+
+        /// Tags to group by, one output row per group (e.g. host,service)
+        #[arg(short, long = "group-by", value_delimiter = ',')]
+        group_by: Vec<String>,
+    },
+
+    /// Query timeseries with named queries and formulas (v2 API)
+    #[command(
+        long_about = "Query metrics timeseries with named queries and formulas.
+
+Uses Datadog's v2 formula-and-function API: define one or more named queries
+with --query and combine them with --formula, like a dashboard widget.
+
+Queries:
+  NAME=QUERY                   Metrics query, e.g. a=sum:trace.http.request.hits{*}
+  NAME=cloud_cost:QUERY        Cloud cost query, e.g. c=cloud_cost:sum:aws.cost.amortized{*}
+
+Formulas:
+  Arithmetic over query names, plus Datadog functions (e.g. a / b * 100, abs(a), per_minute(a)).
+  Without --formula, each query is returned as its own series.
+
+Output Format:
+  One JSON object per series and timestamp, with the formula, group tags,
+  timestamp (Unix seconds) and value.
+
+Examples:
+  # Error rate per service
+  ddog metrics timeseries \\
+    --query \"errors=sum:trace.http.request.errors{env:prod} by {service}.as_count()\" \\
+    --query \"hits=sum:trace.http.request.hits{env:prod} by {service}.as_count()\" \\
+    --formula \"errors / hits * 100\" --from now-4h --interval 5m
+
+  # Several formulas over the same queries
+  ddog metrics timeseries -q \"a=avg:system.cpu.user{*}\" -q \"b=avg:system.cpu.system{*}\" \\
+    --formula \"a + b\" --formula \"a / (a + b)\"
+
+Documentation:
+  https://docs.datadoghq.com/dashboards/functions/"
+    )]
+    Timeseries {
+        /// Named query as NAME=QUERY (repeatable)
+        #[arg(short, long = "query", required = true)]
+        queries: Vec<NamedQuery>,
+
+        /// Formula over query names, e.g. "a / b * 100" (repeatable)
+        #[arg(long = "formula")]
+        formulas: Vec<String>,
+
+        #[command(flatten)]
+        time_range: TimeRange,
+
+        /// Rollup interval (e.g. 30s, 5m, 1h); omit to let Datadog choose
+        #[arg(long, value_parser = ddog::timeseries::parse_interval)]
+        interval: Option<i64>,
+
+        /// Maximum number of data points to return (use 0 for unlimited)
+        #[arg(short, long, default_value = "1000")]
+        limit: u64,
+    },
+
     /// List active metrics within a time window
     #[command(long_about = "List active metrics within a time window.
 
@@ -96,10 +188,6 @@ Lists all metrics that were actively reporting data during the specified
 time range. Useful for discovering available metrics or finding metrics
 by pattern.
 
-⚠️  Time Format Limitation:
-  Metrics commands do NOT support ISO8601 timestamps.
-  Use relative times (now-1h) or Unix timestamps only.
-
 Output Format:
   Each line contains a JSON object with metric name and metadata.
   Use grep to filter: ddog metrics list | grep \"system.cpu\"
@@ -3,13 +3,23 @@
 //! Defines the CLI structure with domain-based subcommands for querying Datadog.
 
 mod args;
+mod config;
+mod dashboards;
+mod events;
 mod logs;
 mod metrics;
+mod monitors;
 mod shared;
+mod slo;
 mod spans;
 
-pub use args::{Cli, Domain};
+pub use args::{ApiDomain, Cli, Domain};
+pub use config::ConfigAction;
+pub use dashboards::DashboardsAction;
+pub use events::EventsAction;
 pub use logs::LogsAction;
 pub use metrics::MetricsAction;
-pub use shared::{Pagination, TimeFrom, TimeRange, TimeRangeRelativeOnly};
+pub use monitors::MonitorsAction;
+pub use shared::{Aggregation, Checkpointing, Follow, Pagination, Parallel};
+pub use slo::SloAction;
 pub use spans::SpansAction;
@@ -0,0 +1,259 @@
+//! Monitors domain command actions.
+
+use clap::{Args, Subcommand};
+use std::path::PathBuf;
+
+use ddog::monitors::definitions::DefinitionFormat;
+use ddog::monitors::{MonitorFilter, MonitorStatus};
+use ddog::time::{TimeSpec, Zone};
+
+/// Name and tag filters selecting which monitors a command works on.
+#[derive(Args, Debug, Clone)]
+pub struct MonitorSelection {
+    /// Only monitors whose name contains this text
+    #[arg(long)]
+    pub name: Option<String>,
+
+    /// Only monitors scoped to these tags (e.g. host:web-1,env:prod)
+    #[arg(long, value_delimiter = ',')]
+    pub tags: Vec<String>,
+
+    /// Only monitors tagged with these tags (e.g. team:payments)
+    #[arg(long = "monitor-tags", value_delimiter = ',')]
+    pub monitor_tags: Vec<String>,
+}
+
+impl MonitorSelection {
+    /// Builds the list filter for this selection, keeping monitors in `statuses`.
+    pub fn filter(self, statuses: Vec<MonitorStatus>) -> MonitorFilter {
+        MonitorFilter {
+            name: self.name,
+            tags: self.tags,
+            monitor_tags: self.monitor_tags,
+            statuses,
+        }
+    }
+}
+
+/// Available actions for the monitors domain.
+#[derive(Subcommand, Debug)]
+pub enum MonitorsAction {
+    /// List monitors, filtered by name, tags and status
+    #[command(long_about = "List monitors, filtered by name, tags and status.
+
+Name and tag filters are applied by Datadog; --status is matched against each
+monitor's overall state.
+
+Output Format:
+  One JSON object per monitor, as returned by the Datadog API.
+
+Examples:
+  # Every monitor
+  ddog monitors list
+
+  # Alerting or warning monitors owned by a team
+  ddog monitors list --monitor-tags team:payments --status alert,warn
+
+  # Monitors whose name contains \"cpu\"
+  ddog monitors list --name cpu | jq -r '.name'")]
+    List {
+        #[command(flatten)]
+        selection: MonitorSelection,
+
+        /// Only monitors in these states: alert, warn, no-data, ok, ignored, skipped, unknown
+        #[arg(long, value_delimiter = ',')]
+        status: Vec<MonitorStatus>,
+    },
+
+    /// Show a single monitor by ID
+    Get {
+        /// Monitor ID
+        id: i64,
+    },
+
+    /// Search monitors using the monitor search syntax
+    #[command(long_about = "Search monitors using the monitor search syntax.
+
+Query Syntax:
+  • Free text matches monitor names: cpu
+  • Status: status:alert, status:warn, status:\"no data\"
+  • Type: type:metric, type:log, type:apm
+  • Tags: tag:team:payments, scope:host:web-1
+  • Muted: muted:true
+  • Combine terms with spaces (AND)
+
+Output Format:
+  One JSON object per monitor, with its status, query, tags and last trigger time.
+
+Examples:
+  ddog monitors search \"status:alert tag:env:prod\"
+  ddog monitors search \"type:log muted:true\"
+
+Documentation:
+  https://docs.datadoghq.com/monitors/manage/search/")]
+    Search {
+        /// Monitor search query (e.g. "status:alert tag:team:payments")
+        query: String,
+    },
+
+    /// Mute a monitor, or some of its groups, until an end time
+    #[command(
+        long_about = "Mute a monitor, or some of its groups, until an end time.
+
+Creates a downtime for the monitor that starts now. Without --end the monitor
+stays muted until it is unmuted.
+
+Output Format:
+  The created downtime as a JSON object.
+
+Examples:
+  # Mute the whole monitor for two hours
+  ddog monitors mute 12345 --end now+2h
+
+  # Mute a single host until 18:00 Paris time today
+  ddog monitors mute 12345 --scope host:web-1 --end today+18h --tz Europe/Paris
+
+  # Mute during a deploy, with a message
+  ddog monitors mute 12345 --end now+30m --message \"Deploying v2.3\""
+    )]
+    Mute {
+        /// Monitor ID
+        id: i64,
+
+        /// Groups to mute (e.g. host:web-1); * mutes the whole monitor
+        #[arg(long, default_value = "*")]
+        scope: String,
+
+        /// When to unmute - relative (now+2h, today+18h), ISO8601 or Unix ms
+        #[arg(long)]
+        end: Option<TimeSpec>,
+
+        /// Time zone for day names in --end (UTC, local, +02:00, Europe/Paris)
+        #[arg(long, default_value = "UTC")]
+        tz: Zone,
+
+        /// Message included in the downtime's notifications
+        #[arg(long)]
+        message: Option<String>,
+    },
+
+    /// Unmute a monitor by cancelling its active downtimes
+    #[command(long_about = "Unmute a monitor by cancelling its active downtimes.
+
+Cancels the active downtimes set on this monitor, or only those with the
+given --scope. Downtimes that also mute other monitors, because they target
+monitor tags or another monitor, are skipped with a warning unless
+--include-shared is given, since cancelling them unmutes those monitors too.
+
+Output Format:
+  One JSON object per cancelled downtime.
+
+Examples:
+  ddog monitors unmute 12345
+  ddog monitors unmute 12345 --scope host:web-1
+  ddog monitors unmute 12345 --include-shared")]
+    Unmute {
+        /// Monitor ID
+        id: i64,
+
+        /// Only cancel downtimes with exactly this scope (e.g. host:web-1)
+        #[arg(long)]
+        scope: Option<String>,
+
+        /// Also cancel downtimes shared with other monitors, unmuting them too
+        #[arg(long)]
+        include_shared: bool,
+    },
+
+    /// Show the state of each group of a monitor
+    #[command(long_about = "Show the state of each group of a monitor.
+
+Multi-alert monitors report one state per group (e.g. per host). Monitors
+without groups are shown as a single \"*\" group with the overall state.
+
+Output Format:
+  One JSON object per group with its status and last trigger, resolve,
+  notification and no-data times (Unix seconds).
+
+Examples:
+  ddog monitors status 12345
+  ddog monitors status 12345 --status alert,no-data -o table")]
+    Status {
+        /// Monitor ID
+        id: i64,
+
+        /// Only groups in these states: alert, warn, no-data, ok, ignored, skipped, unknown
+        #[arg(long, value_delimiter = ',')]
+        status: Vec<MonitorStatus>,
+    },
+
+    /// Write monitor definitions to files, one per monitor
+    #[command(long_about = "Write monitor definitions to files, one per monitor.
+
+Each monitor is written as it is defined, without the fields Datadog manages
+itself (id, created, modified, creator, overall_state, state, ...), so the
+files can be kept in git and edited. File names come from the monitor names.
+Existing files with the same names are overwritten.
+
+Output Format:
+  One JSON object per file written, with the monitor ID, name and path.
+
+Examples:
+  # Export a team's monitors as YAML
+  ddog monitors export monitors/ --monitor-tags team:payments --format yaml
+
+  # Export every monitor
+  ddog monitors export monitors/")]
+    Export {
+        /// Directory to write the definitions to (created if missing)
+        dir: PathBuf,
+
+        /// File format: json or yaml
+        #[arg(long, default_value = "json")]
+        format: DefinitionFormat,
+
+        #[command(flatten)]
+        selection: MonitorSelection,
+    },
+
+    /// Create, update and delete monitors to match a directory of definitions
+    #[command(
+        long_about = "Create, update and delete monitors to match a directory of definitions.
+
+Reads every .json, .yaml and .yml file in the directory and matches each to the
+monitor with the same name among the selected monitors (--name, --tags and
+--monitor-tags; every monitor by default). Definitions without a monitor are
+created; monitors whose fields differ are updated. Fields left out of a file
+keep their current value. With --prune, selected monitors that no file names
+are deleted.
+
+Nothing is changed without --yes: the plan is printed and the command stops.
+
+Output Format:
+  One JSON object per planned step, with the action (create, update, delete),
+  the monitor name and ID, the file, and the changed fields with their current
+  (from) and new (to) values.
+
+Examples:
+  # Review the plan for a team's monitors
+  ddog monitors apply monitors/ --monitor-tags team:payments
+
+  # Apply it, deleting the team's monitors that were removed from git
+  ddog monitors apply monitors/ --monitor-tags team:payments --prune --yes"
+    )]
+    Apply {
+        /// Directory of monitor definitions
+        dir: PathBuf,
+
+        #[command(flatten)]
+        selection: MonitorSelection,
+
+        /// Delete selected monitors that have no definition
+        #[arg(long)]
+        prune: bool,
+
+        /// Apply the plan instead of only printing it
+        #[arg(long)]
+        yes: bool,
+    },
+}
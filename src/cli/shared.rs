@@ -1,11 +1,21 @@
 //! Shared CLI argument structures used across multiple commands.
 
+use chrono::{DateTime, Utc};
 use clap::Args;
+use std::path::PathBuf;
 
-/// Time range arguments for logs and spans (supports ISO8601, relative, and Unix timestamps).
+use ddog::aggregate::{Compute, GroupBy, GroupSort};
+use ddog::client::SortOrder;
+use ddog::error::AppError;
+use ddog::time::{ResolvedRange, Span, TimeSpec, Zone};
+
+/// Time range arguments (supports relative date math, ISO8601, wall-clock times and Unix timestamps).
+///
+/// The range is given by `--from`/`--since` and `--to`, by `--last` (a duration ending at
+/// `--to`), or by `--around` and `--window`.
 #[derive(Args, Debug, Clone)]
 pub struct TimeRange {
-    /// Start time - supports relative (now-1h), ISO8601 (2024-01-15T10:00:00Z), or Unix ms (1705315200000)
+    /// Start time - supports relative (now-1h, now-1d/d), ISO8601 (2024-01-15T10:00:00Z), or Unix ms (1705315200000)
     #[arg(
         short,
         long,
@@ -15,18 +25,29 @@ pub struct TimeRange {
 Supported formats:
   • Relative: now, now-15m, now-1h, now-2d, now-1w, now-3mo, now-1y
     Units: s (seconds), m (minutes), h (hours), d (days), w (weeks), mo (months), y (years)
-  • ISO8601: 2024-01-15T10:00:00Z, 2024-01-15T10:00:00+00:00
-  • Unix timestamp: 1705315200000 (milliseconds since epoch)
+    Chain offsets with + and -: now-1d+2h
+    Round down with /unit (weeks start Monday): now/d, now-1d/h
+  • Days: today, yesterday, optionally with offsets: yesterday+9h
+  • ISO8601: 2024-01-15T10:00:00Z, 2024-01-15T10:00:00+02:00, 2024-01-15T10:00:00.123Z
+  • Wall clock: 14:32 (most recent occurrence), 2024-06-01 09:00, 2024-01-15
+  • Unix timestamp: 1705315200000 (milliseconds) or 1705315200 (seconds)
+
+Days, wall-clock times and rounding use --tz (default UTC).
 
 Examples:
   --from now-30m     # 30 minutes ago
   --from now-1d      # 1 day ago
+  --from now/d       # Start of today (UTC)
   --from 2024-01-15T10:00:00Z
   --from 1705315200000"
     )]
-    pub from: String,
+    pub from: TimeSpec,
 
-    /// End time - supports relative (now), ISO8601 (2024-01-15T10:00:00Z), or Unix ms (1705315200000)
+    /// Start time, same as --from (e.g. --since yesterday, --since 09:00)
+    #[arg(long, conflicts_with = "from")]
+    pub since: Option<TimeSpec>,
+
+    /// End time - supports relative (now, now/d), ISO8601 (2024-01-15T10:00:00Z), or Unix ms (1705315200000)
     #[arg(
         short,
         long,
@@ -36,99 +57,119 @@ Examples:
 Supported formats:
   • Relative: now, now-15m, now-1h, now-2d, now-1w, now-3mo, now-1y
     Units: s (seconds), m (minutes), h (hours), d (days), w (weeks), mo (months), y (years)
-  • ISO8601: 2024-01-15T10:00:00Z, 2024-01-15T10:00:00+00:00
-  • Unix timestamp: 1705315200000 (milliseconds since epoch)
+    Chain offsets with + and -: now-1d+2h
+    Round down with /unit (weeks start Monday): now/d, now-1d/h
+  • Days: today, yesterday, optionally with offsets: yesterday+9h
+  • ISO8601: 2024-01-15T10:00:00Z, 2024-01-15T10:00:00+02:00, 2024-01-15T10:00:00.123Z
+  • Wall clock: 14:32 (most recent occurrence), 2024-06-01 09:00, 2024-01-15
+  • Unix timestamp: 1705315200000 (milliseconds) or 1705315200 (seconds)
+
+Days, wall-clock times and rounding use --tz (default UTC).
 
 Examples:
   --to now           # Current time (default)
   --to now-5m        # 5 minutes ago
+  --to now/d         # Midnight UTC, i.e. up to the end of yesterday
   --to 2024-01-15T11:00:00Z
   --to 1705318800000"
     )]
-    pub to: String,
-}
+    pub to: TimeSpec,
 
-/// Time range arguments for metrics (supports only relative and Unix timestamps, no ISO8601).
-#[derive(Args, Debug, Clone)]
-pub struct TimeRangeRelativeOnly {
-    /// Start time - supports relative (now-1h) or Unix ms (1705315200000). ISO8601 NOT supported for metrics.
+    /// Duration ending at --to (e.g. 15m, 2h, 1d)
     #[arg(
-        short,
         long,
-        default_value = "now-1h",
-        long_help = "Start time for the query.
-
-⚠️  Note: Metrics commands do NOT support ISO8601 format.
+        conflicts_with_all = ["from", "since"],
+        long_help = "Query the given duration, ending at --to (default: now).
 
-Supported formats:
-  • Relative: now, now-15m, now-1h, now-2d, now-1w, now-3mo, now-1y
-    Units: s (seconds), m (minutes), h (hours), d (days), w (weeks), mo (months), y (years)
-  • Unix timestamp: 1705315200000 (milliseconds since epoch)
+Durations use the date math units: s, m, h, d, w, mo, y.
 
 Examples:
-  --from now-30m     # 30 minutes ago
-  --from now-1d      # 1 day ago
-  --from 1705315200000
-
-Not supported:
-  --from 2024-01-15T10:00:00Z  ❌ ISO8601 format not available for metrics"
+  --last 2h                 # The last two hours
+  --last 1d --to now/d      # All of yesterday"
     )]
-    pub from: String,
+    pub last: Option<Span>,
 
-    /// End time - supports relative (now) or Unix ms (1705315200000). ISO8601 NOT supported for metrics.
+    /// Time to center the range on, see --window (e.g. 14:32, 2024-06-01T14:32:00Z)
     #[arg(
-        short,
         long,
-        default_value = "now",
-        long_help = "End time for the query.
+        conflicts_with_all = ["from", "to", "since", "last"],
+        long_help = "Query a window around a point in time, e.g. when an incident started.
 
-⚠️  Note: Metrics commands do NOT support ISO8601 format.
-
-Supported formats:
-  • Relative: now, now-15m, now-1h, now-2d, now-1w, now-3mo, now-1y
-    Units: s (seconds), m (minutes), h (hours), d (days), w (weeks), mo (months), y (years)
-  • Unix timestamp: 1705315200000 (milliseconds since epoch)
+Accepts every --from format. The range reaches --window before and after it.
 
 Examples:
-  --to now           # Current time (default)
-  --to now-5m        # 5 minutes ago
-  --to 1705318800000
-
-Not supported:
-  --to 2024-01-15T11:00:00Z  ❌ ISO8601 format not available for metrics"
+  --around 14:32                        # 14:17 to 14:47 UTC, most recent 14:32
+  --around 14:32 --tz Europe/Paris      # Same, in Paris time
+  --around 2024-06-01T14:32:00Z --window 1h"
     )]
-    pub to: String,
+    pub around: Option<TimeSpec>,
+
+    /// How far --around reaches on each side
+    #[arg(long, default_value = "15m", requires = "around")]
+    pub window: Span,
+
+    /// Time zone for wall-clock times, day names and rounding (UTC, local, +02:00, Europe/Paris)
+    #[arg(long, default_value = "UTC")]
+    pub tz: Zone,
 }
 
 /// Single time argument for commands that only need a start time (e.g., metrics list).
 #[derive(Args, Debug, Clone)]
 pub struct TimeFrom {
-    /// Start time - supports relative (now-1h) or Unix ms (1705315200000). ISO8601 NOT supported for metrics.
+    /// Start time - supports relative (now-1h, now-1d/d), ISO8601 (2024-01-15T10:00:00Z), or Unix ms (1705315200000)
     #[arg(
         short,
         long,
         default_value = "now-1h",
+        visible_alias = "since",
         long_help = "Start time for the query. Metrics active after this time will be listed.
 
-⚠️  Note: Metrics commands do NOT support ISO8601 format.
-
 Supported formats:
   • Relative: now, now-15m, now-1h, now-2d, now-1w, now-3mo, now-1y
     Units: s (seconds), m (minutes), h (hours), d (days), w (weeks), mo (months), y (years)
-  • Unix timestamp: 1705315200000 (milliseconds since epoch)
+    Chain offsets with + and -, round down with /unit: now-1d+2h, now/d
+  • Days: today, yesterday, yesterday+9h
+  • ISO8601: 2024-01-15T10:00:00Z, 2024-01-15T10:00:00+02:00, 2024-01-15
+  • Wall clock: 14:32, 2024-06-01 09:00 (in --tz, default UTC)
+  • Unix timestamp: 1705315200000 (milliseconds) or 1705315200 (seconds)
 
 Examples:
   --from now-30m     # 30 minutes ago
   --from now-1d      # 1 day ago
-  --from 1705315200000
-
-Not supported:
-  --from 2024-01-15T10:00:00Z  ❌ ISO8601 format not available for metrics"
+  --since yesterday  # Start of yesterday (UTC)
+  --from 2024-01-15T10:00:00Z
+  --from 1705315200000"
     )]
-    pub from: String,
+    pub from: TimeSpec,
+
+    /// Time zone for wall-clock times, day names and rounding (UTC, local, +02:00, Europe/Paris)
+    #[arg(long, default_value = "UTC")]
+    pub tz: Zone,
 }
 
-/// Pagination arguments for limiting query results.
+impl TimeRange {
+    /// Resolves the range against `now`, whichever way it was given.
+    pub fn resolve(&self, now: DateTime<Utc>) -> Result<ResolvedRange, AppError> {
+        if let Some(center) = &self.around {
+            ResolvedRange::around(center, &self.window, now, &self.tz)
+        } else if let Some(span) = &self.last {
+            ResolvedRange::last(span, &self.to, now, &self.tz)
+        } else {
+            let from = self.since.as_ref().unwrap_or(&self.from);
+            ResolvedRange::resolve(from, &self.to, now, &self.tz)
+        }
+    }
+}
+
+impl TimeFrom {
+    /// Resolves the start time against `now`, which also ends the range.
+    pub fn resolve(&self, now: DateTime<Utc>) -> Result<ResolvedRange, AppError> {
+        let to = "now".parse().expect("valid time");
+        ResolvedRange::resolve(&self.from, &to, now, &self.tz)
+    }
+}
+
+/// Pagination arguments for limiting and ordering search results.
 #[derive(Args, Debug, Clone)]
 pub struct Pagination {
     /// Maximum number of results to return (use 0 for unlimited)
@@ -146,4 +187,169 @@ Examples:
   --limit 0          # Return all matching results (unlimited)"
     )]
     pub limit: u64,
+
+    /// Timestamp order of results: asc (oldest first) or desc (newest first)
+    #[arg(
+        long,
+        default_value = "asc",
+        long_help = "Timestamp order of results: asc (oldest first) or desc (newest first).
+
+Combined with --limit, desc returns the most recent matches.
+
+Examples:
+  --sort desc --limit 20     # The latest 20 matches"
+    )]
+    pub sort: SortOrder,
+}
+
+/// Aggregation arguments shared by the logs and spans aggregate commands.
+#[derive(Args, Debug, Clone)]
+pub struct Aggregation {
+    /// Facets to group by, optionally with a bucket limit (e.g. service, @http.status_code:10)
+    #[arg(
+        short,
+        long = "group-by",
+        value_delimiter = ',',
+        long_help = "Facets to group results by. Repeat the flag or separate facets with commas.
+
+Append :N to limit the number of buckets returned for a facet.
+
+Examples:
+  --group-by service                 # One row per service
+  --group-by service,@http.status_code
+  --group-by service:5               # Top 5 services only"
+    )]
+    pub group_by: Vec<GroupBy>,
+
+    /// Computes to calculate per bucket (default: count)
+    #[arg(
+        short,
+        long,
+        value_delimiter = ',',
+        long_help = "Computes to calculate for each bucket, as FUNCTION[:MEASURE].
+
+Functions:
+  count                      Number of matching events (no measure needed)
+  cardinality:<facet>        Number of unique values
+  sum, min, max, avg, median Statistics over a measure (p50 is an alias for median)
+  p75, p90, p95, p98, p99    Percentiles over a measure
+  error_rate                 Share of events with status:error, from 0 to 1
+                             (sent as a second request counting errors)
+
+Each compute becomes an output field named after the function and measure,
+e.g. p99:@duration is emitted as \"p99_duration\".
+
+Examples:
+  --compute count
+  --compute avg:@duration,p99:@duration
+  --compute cardinality:@usr.id
+  --compute count,error_rate"
+    )]
+    pub compute: Vec<Compute>,
+
+    /// Timeseries bucket width (e.g. 1m, 5m, 1h); omit for a single total per group
+    #[arg(
+        long,
+        value_parser = ddog::aggregate::parse_interval,
+        long_help = "Split results into timeseries buckets of the given width.
+
+When set, one row is emitted per group and time bucket, with a \"timestamp\" field.
+When omitted, one row is emitted per group covering the whole time range.
+
+Examples:
+  --interval 1m
+  --interval 1h"
+    )]
+    pub interval: Option<String>,
+
+    /// Order of group-by buckets: desc, asc (by the first compute) or alpha
+    #[arg(long, default_value = "desc")]
+    pub group_sort: GroupSort,
+}
+
+/// Arguments for splitting a search into concurrent time slices.
+#[derive(Args, Debug, Clone)]
+pub struct Parallel {
+    /// Number of time slices to fetch concurrently (1 to 16)
+    #[arg(
+        long,
+        default_value = "1",
+        value_parser = clap::value_parser!(u16).range(1..=16),
+        long_help = "Split the time range into slices and fetch up to N of them concurrently.
+
+Speeds up large exports (e.g. --limit 0 over a day) that would otherwise page
+through a single cursor. Output stays in timestamp order unless --unordered is
+given, and --limit still caps the total. Rate-limited requests are retried as
+usual; lower N if retries become frequent.
+
+Examples:
+  --parallel 8 --limit 0                 # Export everything, 8 slices at a time
+  --parallel 8 --limit 0 --unordered     # Same, emitting records as they arrive"
+    )]
+    pub parallel: u16,
+
+    /// With --parallel, emit records as they arrive instead of in timestamp order
+    #[arg(long, requires = "parallel")]
+    pub unordered: bool,
+}
+
+/// Arguments for resumable exports.
+#[derive(Args, Debug, Clone)]
+pub struct Checkpointing {
+    /// Save export progress to FILE after every page
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with = "parallel",
+        long_help = "Save export progress to FILE after every page of results.
+
+The checkpoint holds the query, the resolved time bounds, the limit, the
+pagination cursor and the number of records written. It is also saved within
+a page whenever written records are flushed (every record on stdout). If the export dies (VPN
+drop, laptop sleep, rate limits), rerun the same command with --resume to
+continue where it stopped, appending to the same output.
+
+Refuses to overwrite an existing checkpoint unless --resume is given. Needs a
+streaming output format (not json or table).
+
+Examples:
+  ddog logs search \"service:api\" --limit 0 --checkpoint api.ckpt > api.ndjson
+  ddog logs search \"service:api\" --limit 0 --checkpoint api.ckpt --resume >> api.ndjson"
+    )]
+    pub checkpoint: Option<PathBuf>,
+
+    /// Continue the export saved in --checkpoint instead of starting over
+    #[arg(long, requires = "checkpoint")]
+    pub resume: bool,
+}
+
+/// Live tail arguments for continuously polling for new results.
+#[derive(Args, Debug, Clone)]
+pub struct Follow {
+    /// Keep polling for new results until interrupted (like tail -f)
+    #[arg(
+        short = 'F',
+        long,
+        conflicts_with_all = ["parallel", "checkpoint", "sort"],
+        long_help = "Keep polling for new results and stream them as they arrive, until Ctrl-C.
+
+The first poll covers --from up to now; each following poll reaches back
+--follow-lag seconds before the previous poll started, so records that become
+searchable up to that late are still picked up. Records are de-duplicated by ID,
+or by timestamp and message when they have none, so nothing is emitted twice.
+--to and --limit are ignored in follow mode.
+
+Examples:
+  --follow                            # Tail new logs
+  --follow --from now-5m              # Start with the last 5 minutes, then tail"
+    )]
+    pub follow: bool,
+
+    /// Seconds to wait between polls in follow mode
+    #[arg(long, default_value = "5", requires = "follow")]
+    pub poll_interval: u64,
+
+    /// Seconds each poll reaches back for records indexed late, in follow mode
+    #[arg(long, default_value = "30", requires = "follow")]
+    pub follow_lag: u64,
 }
@@ -0,0 +1,114 @@
+//! SLO domain command actions.
+
+use clap::Subcommand;
+
+use super::shared::TimeRange;
+use ddog::slo::BurnWindow;
+use ddog::time::{TimeSpec, Zone};
+
+/// Available actions for the SLO domain.
+#[derive(Subcommand, Debug)]
+pub enum SloAction {
+    /// List service level objectives
+    #[command(long_about = "List service level objectives.
+
+Output Format:
+  One JSON object per SLO, as returned by the Datadog API.
+
+Examples:
+  # Every SLO
+  ddog slo list
+
+  # A team's SLOs whose name contains \"checkout\"
+  ddog slo list --query checkout --tag team:payments")]
+    List {
+        /// Only SLOs whose name contains this text
+        #[arg(long)]
+        query: Option<String>,
+
+        /// Only SLOs with this tag (e.g. team:payments)
+        #[arg(long)]
+        tag: Option<String>,
+    },
+
+    /// Show a single SLO by ID
+    Get {
+        /// SLO ID
+        id: String,
+    },
+
+    /// Show the SLI and remaining error budget over a time window
+    #[command(
+        long_about = "Show the SLI and remaining error budget over a time window.
+
+The error budget is computed for the SLO's target, or for --target.
+
+Output Format:
+  One JSON object for the whole SLO (group \"*\"), then one per group or
+  monitor, with the SLI value and the percentage of error budget remaining.
+
+Examples:
+  # The last 30 days, per group
+  ddog slo history abc123 --last 30d
+
+  # Last week against a stricter target, as a table
+  ddog slo history abc123 --last 7d --target 99.95 -o table"
+    )]
+    History {
+        /// SLO ID
+        id: String,
+
+        #[command(flatten)]
+        time_range: TimeRange,
+
+        /// Target to compute the error budget for, in percent (default: the SLO's)
+        #[arg(long)]
+        target: Option<f64>,
+    },
+
+    /// Compute error-budget burn rates over several windows
+    #[command(long_about = "Compute error-budget burn rates over several windows.
+
+The burn rate is the error rate over a window divided by the error rate the
+target allows: at 1 the budget lasts exactly the SLO's timeframe, at 14.4 a
+30-day budget is gone in about two days. Each window ends at --to.
+
+Exits with code 12 when any window burns faster than its threshold, so the
+command can gate deploys or page from scripts. Give a window its own
+threshold as WINDOW:THRESHOLD; other windows use --threshold.
+
+Output Format:
+  One JSON object per window with the SLI, target, burn rate, threshold and
+  whether it was breached.
+
+Examples:
+  # Multi-window check: fast burn over 1h, slower burn over 6h and 24h
+  ddog slo burn-rate abc123 --windows 1h:14.4,6h:6,24h:3
+
+  # Stop a deploy when the last hour burned budget over twice as fast as allowed
+  ddog slo burn-rate abc123 --windows 1h --threshold 2 || exit 1")]
+    BurnRate {
+        /// SLO ID
+        id: String,
+
+        /// Windows ending at --to, optionally with their own threshold (e.g. 1h:14.4,6h:6)
+        #[arg(long, value_delimiter = ',', default_value = "1h,6h,24h")]
+        windows: Vec<BurnWindow>,
+
+        /// Burn rate above which a window fails, for windows without their own
+        #[arg(long, default_value = "1")]
+        threshold: f64,
+
+        /// Target to compute the burn rate against, in percent (default: the SLO's)
+        #[arg(long)]
+        target: Option<f64>,
+
+        /// End of every window - relative (now, now-1h), ISO8601 or Unix ms
+        #[arg(short, long, default_value = "now")]
+        to: TimeSpec,
+
+        /// Time zone for wall-clock times, day names and rounding (UTC, local, +02:00, Europe/Paris)
+        #[arg(long, default_value = "UTC")]
+        tz: Zone,
+    },
+}
@@ -2,7 +2,7 @@
 
 use clap::Subcommand;
 
-use super::shared::{Pagination, TimeRange};
+use super::shared::{Aggregation, Checkpointing, Pagination, Parallel, TimeRange};
 
 /// Available actions for the spans domain.
 #[derive(Subcommand, Debug)]
@@ -67,5 +67,92 @@ Examples:
 
         #[command(flatten)]
         pagination: Pagination,
+
+        #[command(flatten)]
+        parallel: Parallel,
+
+        #[command(flatten)]
+        checkpointing: Checkpointing,
+    },
+
+    /// Aggregate APM spans server-side: latency percentiles, error rates and counts
+    #[command(
+        long_about = "Aggregate APM spans server-side using Datadog's spans analytics endpoint.
+
+Computes statistics such as latency percentiles per facet without downloading
+spans, so results are neither slow nor truncated by --limit.
+
+Output Format:
+  Each line contains a flat JSON object for one bucket, with a field per
+  group-by facet and per compute, e.g.
+  {\"service\":\"api\",\"resource_name\":\"/users\",\"p99_duration\":812000000}
+  With --interval, one line per bucket and timestamp.
+
+Examples:
+  # p50/p95/p99 latency per resource
+  ddog spans aggregate \"service:api env:prod\" --group-by resource_name \\
+    --compute p50:@duration,p95:@duration,p99:@duration
+
+  # Error count per service
+  ddog spans aggregate \"error:true\" --group-by service
+
+  # Request count and error rate per resource
+  ddog spans aggregate \"service:api\" --group-by resource_name --compute count,error_rate
+
+  # Request rate per service in 1 minute buckets
+  ddog spans aggregate \"env:prod\" --group-by service:5 --interval 1m --from now-30m
+
+Documentation:
+  https://docs.datadoghq.com/tracing/trace_explorer/"
+    )]
+    Aggregate {
+        /// Datadog query string (e.g., "service:web env:prod")
+        query: String,
+
+        #[command(flatten)]
+        time_range: TimeRange,
+
+        #[command(flatten)]
+        aggregation: Aggregation,
+    },
+
+    /// Reconstruct a full trace and print it as a waterfall
+    #[command(
+        long_about = "Fetch every span of a trace and rebuild its parent/child hierarchy.
+
+By default prints an indented waterfall with one line per span:
+  offset from trace start, duration, self-time, service and resource.
+
+Spans whose parent was not found (e.g. outside the time range or not retained)
+are listed under \"Orphaned spans\" instead of being dropped.
+
+Output Format:
+  Waterfall text by default. With --ndjson or an explicit --output format,
+  each span is emitted as a record annotated with depth, children_count,
+  self_time and orphan fields, in depth-first order.
+
+Examples:
+  # Print the waterfall for a trace from the last hour
+  ddog spans trace 7d5d747be160e280504c099d984bcfe0
+
+  # Look further back
+  ddog spans trace 7d5d747be160e280504c099d984bcfe0 --from now-1d
+
+  # Show the span tree as a table
+  ddog spans trace 7d5d747be160e280504c099d984bcfe0 -o table --fields depth,attributes.resource_name,self_time
+
+  # Find the spans with the most self-time
+  ddog spans trace 7d5d747be160e280504c099d984bcfe0 --ndjson | jq -s 'sort_by(-.self_time) | .[:5]'"
+    )]
+    Trace {
+        /// Trace ID to reconstruct
+        trace_id: String,
+
+        #[command(flatten)]
+        time_range: TimeRange,
+
+        /// Emit annotated spans as NDJSON instead of the waterfall
+        #[arg(long)]
+        ndjson: bool,
     },
 }
@@ -0,0 +1,81 @@
+//! Datadog Dashboards API client wrapper.
+//!
+//! Provides listing, fetching, creating and updating dashboards (v1 API).
+
+use datadog_api_client::datadog::Configuration;
+use datadog_api_client::datadogV1::api_dashboards::{DashboardsAPI, ListDashboardsOptionalParams};
+use datadog_api_client::datadogV1::model::{Dashboard, DashboardSummaryDefinition};
+use futures_util::stream::{Stream, TryStreamExt};
+
+use super::RetryPolicy;
+use crate::error::AppError;
+
+/// Client for managing Datadog dashboards.
+///
+/// Wraps the Datadog SDK's v1 DashboardsAPI with automatic pagination support.
+pub struct DashboardsClient {
+    api: DashboardsAPI,
+}
+
+impl DashboardsClient {
+    /// Creates a new DashboardsClient with the given configuration and the default retry policy.
+    pub fn new(config: Configuration) -> Self {
+        Self::with_retry(config, &RetryPolicy::default())
+    }
+
+    /// Creates a new DashboardsClient that retries failed requests according to `policy`.
+    ///
+    /// Imports use [`RetryPolicy::write_client`], which never repeats a create.
+    pub fn with_retry(config: Configuration, policy: &RetryPolicy) -> Self {
+        Self {
+            api: DashboardsAPI::with_client_and_config(config, policy.write_client()),
+        }
+    }
+
+    /// Lists the org's custom dashboards.
+    ///
+    /// Returns an async stream of dashboard summaries (ID, title, layout,
+    /// author and timestamps; no widgets). Preset dashboards are not included.
+    pub fn list(
+        &self,
+    ) -> impl Stream<Item = Result<DashboardSummaryDefinition, AppError>> + use<'_> {
+        self.api
+            .list_dashboards_with_pagination(ListDashboardsOptionalParams::default())
+            .map_err(|e| AppError::from_api_error(e, Some(FORBIDDEN_HINT)))
+    }
+
+    /// Fetches a dashboard with all of its widgets.
+    pub async fn get(&self, dashboard_id: &str) -> Result<Dashboard, AppError> {
+        self.api
+            .get_dashboard(dashboard_id.to_string())
+            .await
+            .map_err(|e| AppError::from_api_error(e, Some(FORBIDDEN_HINT)))
+    }
+
+    /// Creates a dashboard, returning it with its new ID and URL.
+    pub async fn create(&self, dashboard: Dashboard) -> Result<Dashboard, AppError> {
+        self.api
+            .create_dashboard(dashboard)
+            .await
+            .map_err(|e| AppError::from_api_error(e, Some(WRITE_FORBIDDEN_HINT)))
+    }
+
+    /// Replaces a dashboard's title, settings and widgets.
+    pub async fn update(
+        &self,
+        dashboard_id: &str,
+        dashboard: Dashboard,
+    ) -> Result<Dashboard, AppError> {
+        self.api
+            .update_dashboard(dashboard_id.to_string(), dashboard)
+            .await
+            .map_err(|e| AppError::from_api_error(e, Some(WRITE_FORBIDDEN_HINT)))
+    }
+}
+
+/// Guidance appended to 403 errors when reading dashboards.
+const FORBIDDEN_HINT: &str = "Your API key may not have permission to read dashboards.";
+
+/// Guidance appended to 403 errors when changing dashboards.
+const WRITE_FORBIDDEN_HINT: &str =
+    "Importing dashboards requires an application key with the dashboards_write permission.";
@@ -0,0 +1,141 @@
+//! Datadog Events API client wrapper.
+//!
+//! Provides searching events with automatic pagination (v2 API) and posting
+//! new events (v1 API).
+
+use datadog_api_client::datadog::{self, Configuration};
+use datadog_api_client::datadogV1::api_events::{CreateEventError, EventsAPI as EventsV1API};
+use datadog_api_client::datadogV1::model::{Event, EventAlertType, EventCreateRequest};
+use datadog_api_client::datadogV2::api_events::{
+    EventsAPI, SearchEventsError, SearchEventsOptionalParams,
+};
+use datadog_api_client::datadogV2::model::{
+    EventResponse, EventsListRequest, EventsQueryFilter, EventsRequestPage, EventsSort,
+};
+use futures_util::Stream;
+use std::str::FromStr;
+
+use super::{PAGE_SIZE, RetryPolicy, SortOrder};
+use crate::time::ResolvedRange;
+
+/// Severity of a posted event, shown by its color in the event stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AlertType {
+    Error,
+    Warning,
+    #[default]
+    Info,
+    Success,
+}
+
+impl FromStr for AlertType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "error" => Ok(AlertType::Error),
+            "warning" | "warn" => Ok(AlertType::Warning),
+            "info" => Ok(AlertType::Info),
+            "success" => Ok(AlertType::Success),
+            other => Err(format!(
+                "Unknown alert type '{}'. Expected one of: error, warning, info, success",
+                other
+            )),
+        }
+    }
+}
+
+impl From<AlertType> for EventAlertType {
+    fn from(alert_type: AlertType) -> Self {
+        match alert_type {
+            AlertType::Error => EventAlertType::ERROR,
+            AlertType::Warning => EventAlertType::WARNING,
+            AlertType::Info => EventAlertType::INFO,
+            AlertType::Success => EventAlertType::SUCCESS,
+        }
+    }
+}
+
+/// Client for searching and posting Datadog events.
+///
+/// Wraps the Datadog SDK's v2 EventsAPI for searching and the v1 EventsAPI
+/// for posting, which accepts an alert type.
+pub struct EventsClient {
+    api: EventsAPI,
+    intake: EventsV1API,
+}
+
+impl EventsClient {
+    /// Creates a new EventsClient with the given configuration and the default retry policy.
+    pub fn new(config: Configuration) -> Self {
+        Self::with_retry(config, &RetryPolicy::default())
+    }
+
+    /// Creates a new EventsClient that retries failed requests according to `policy`.
+    ///
+    /// Searches are retried as usual; posting an event goes through
+    /// [`RetryPolicy::write_client`].
+    pub fn with_retry(config: Configuration, policy: &RetryPolicy) -> Self {
+        Self {
+            api: EventsAPI::with_client_and_config(config.clone(), policy.client()),
+            intake: EventsV1API::with_client_and_config(config, policy.write_client()),
+        }
+    }
+
+    /// Searches events matching the given query.
+    ///
+    /// Returns an async stream of events. The stream handles pagination
+    /// automatically, fetching up to 1000 records per API request.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - Event search syntax (e.g., "source:github tags:env:prod")
+    /// * `range` - Time window, resolved to Unix milliseconds
+    /// * `sort` - Oldest or newest events first
+    pub fn search(
+        &self,
+        query: &str,
+        range: &ResolvedRange,
+        sort: SortOrder,
+    ) -> impl Stream<Item = Result<EventResponse, datadog::Error<SearchEventsError>>> + use<'_>
+    {
+        let body = EventsListRequest::new()
+            .filter(
+                EventsQueryFilter::new()
+                    .query(query.to_string())
+                    .from(range.from_ms.to_string())
+                    .to(range.to_ms.to_string()),
+            )
+            .page(EventsRequestPage::new().limit(PAGE_SIZE))
+            .sort(match sort {
+                SortOrder::Asc => EventsSort::TIMESTAMP_ASCENDING,
+                SortOrder::Desc => EventsSort::TIMESTAMP_DESCENDING,
+            });
+        self.api
+            .search_events_with_pagination(SearchEventsOptionalParams::default().body(body))
+    }
+
+    /// Posts an event, returning it with its ID and URL.
+    ///
+    /// # Arguments
+    ///
+    /// * `title` - Event title
+    /// * `text` - Event body; Markdown when wrapped in `%%% \n` and `\n %%%`
+    /// * `tags` - Tags to attach (e.g., "service:api", "version:1.2.3")
+    /// * `alert_type` - Severity of the event
+    pub async fn post(
+        &self,
+        title: &str,
+        text: &str,
+        tags: Vec<String>,
+        alert_type: AlertType,
+    ) -> Result<Option<Event>, datadog::Error<CreateEventError>> {
+        let mut body = EventCreateRequest::new(text.to_string(), title.to_string())
+            .alert_type(alert_type.into());
+        if !tags.is_empty() {
+            body = body.tags(tags);
+        }
+        let response = self.intake.create_event(body).await?;
+        Ok(response.event)
+    }
+}
@@ -1,14 +1,22 @@
 //! Datadog Logs API client wrapper.
 //!
-//! Provides a simplified interface for searching logs with automatic pagination.
+//! Provides a simplified interface for searching logs with automatic pagination
+//! and for server-side aggregation.
 
 use datadog_api_client::datadog::{self, Configuration};
-use datadog_api_client::datadogV2::api_logs::{ListLogsOptionalParams, LogsAPI};
+use datadog_api_client::datadogV2::api_logs::{
+    AggregateLogsError, ListLogsError, ListLogsOptionalParams, LogsAPI,
+};
 use datadog_api_client::datadogV2::model::{
-    Log, LogsListRequest, LogsListRequestPage, LogsQueryFilter, LogsSort,
+    Log, LogsAggregateRequest, LogsCompute, LogsGroupBy, LogsListRequest, LogsListRequestPage,
+    LogsQueryFilter, LogsSort,
 };
 use futures_util::Stream;
 
+use super::{PAGE_SIZE, Page, RetryPolicy, SortOrder};
+use crate::aggregate::{AggregateRequest, AggregateRow, error_query};
+use crate::time::ResolvedRange;
+
 /// Client for querying Datadog logs.
 ///
 /// Wraps the Datadog SDK's LogsAPI with automatic pagination support.
@@ -17,10 +25,15 @@ pub struct LogsClient {
 }
 
 impl LogsClient {
-    /// Creates a new LogsClient with the given configuration.
+    /// Creates a new LogsClient with the given configuration and the default retry policy.
     pub fn new(config: Configuration) -> Self {
+        Self::with_retry(config, &RetryPolicy::default())
+    }
+
+    /// Creates a new LogsClient that retries failed requests according to `policy`.
+    pub fn with_retry(config: Configuration, policy: &RetryPolicy) -> Self {
         Self {
-            api: LogsAPI::with_config(config),
+            api: LogsAPI::with_client_and_config(config, policy.client()),
         }
     }
 
@@ -32,30 +45,136 @@ impl LogsClient {
     /// # Arguments
     ///
     /// * `query` - Datadog query syntax (e.g., "service:api AND status:error")
-    /// * `from` - Start time: relative ("now-1h"), ISO8601 ("2024-01-15T10:00:00Z"), or Unix ms ("1705315200000")
-    /// * `to` - End time: relative ("now"), ISO8601 ("2024-01-15T10:00:00Z"), or Unix ms ("1705315200000")
+    /// * `range` - Time window, resolved to Unix milliseconds
     /// * `indexes` - Log indexes to search (use ["*"] for all)
+    /// * `sort` - Oldest or newest logs first
     pub fn search(
         &self,
         query: &str,
-        from: &str,
-        to: &str,
+        range: &ResolvedRange,
         indexes: Vec<String>,
-    ) -> impl Stream<
-        Item = Result<Log, datadog::Error<datadog_api_client::datadogV2::api_logs::ListLogsError>>,
-    > + '_ {
-        let body = LogsListRequest::new()
+        sort: SortOrder,
+    ) -> impl Stream<Item = Result<Log, datadog::Error<ListLogsError>>> + use<'_> {
+        let body = list_request(query, range, indexes, sort, None);
+        self.api
+            .list_logs_with_pagination(ListLogsOptionalParams::default().body(body))
+    }
+
+    /// Fetches a single page of logs matching the given query.
+    ///
+    /// Takes the same arguments as [`LogsClient::search`], plus the cursor
+    /// returned with the previous page (`None` for the first page).
+    pub async fn search_page(
+        &self,
+        query: &str,
+        range: &ResolvedRange,
+        indexes: Vec<String>,
+        sort: SortOrder,
+        cursor: Option<String>,
+    ) -> Result<Page<Log>, datadog::Error<ListLogsError>> {
+        let body = list_request(query, range, indexes, sort, cursor);
+        let response = self
+            .api
+            .list_logs(ListLogsOptionalParams::default().body(body))
+            .await?;
+        let after = response.meta.and_then(|m| m.page).and_then(|p| p.after);
+        Ok(Page::new(response.data.unwrap_or_default(), after))
+    }
+
+    /// Aggregates logs matching the given query on the server.
+    ///
+    /// Sends a request to the logs analytics endpoint and flattens the
+    /// returned buckets into rows keyed by group-by facet and compute label.
+    /// Error rates take a second request counting the errors per group.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - Datadog query syntax (e.g., "service:api AND status:error")
+    /// * `range` - Time window, resolved to Unix milliseconds
+    /// * `indexes` - Log indexes to search (use ["*"] for all)
+    /// * `request` - Computes, group-bys and optional timeseries interval
+    pub async fn aggregate(
+        &self,
+        query: &str,
+        range: &ResolvedRange,
+        indexes: Vec<String>,
+        request: &AggregateRequest,
+    ) -> Result<Vec<AggregateRow>, datadog::Error<AggregateLogsError>> {
+        let mut rows = self
+            .aggregate_once(query, range, indexes.clone(), request)
+            .await?;
+        if let Some(errors) = request.error_request() {
+            let error_rows = self
+                .aggregate_once(&error_query(query), range, indexes, &errors)
+                .await?;
+            request.apply_error_rates(&mut rows, &error_rows);
+        }
+        Ok(rows)
+    }
+
+    /// Sends a single aggregation request and flattens its buckets.
+    async fn aggregate_once(
+        &self,
+        query: &str,
+        range: &ResolvedRange,
+        indexes: Vec<String>,
+        request: &AggregateRequest,
+    ) -> Result<Vec<AggregateRow>, datadog::Error<AggregateLogsError>> {
+        let labels: Vec<String> = request
+            .effective_computes()
+            .iter()
+            .map(|c| c.label())
+            .collect();
+        let computes: Vec<LogsCompute> =
+            serde_json::from_value(request.api_computes()).map_err(datadog::Error::Serde)?;
+        let group_by: Vec<LogsGroupBy> =
+            serde_json::from_value(request.api_group_by()).map_err(datadog::Error::Serde)?;
+
+        let body = LogsAggregateRequest::new()
             .filter(
                 LogsQueryFilter::new()
                     .query(query.to_string())
-                    .from(from.to_string())
-                    .to(to.to_string())
+                    .from(range.from_ms.to_string())
+                    .to(range.to_ms.to_string())
                     .indexes(indexes),
             )
-            .page(LogsListRequestPage::new().limit(1000))
-            .sort(LogsSort::TIMESTAMP_ASCENDING);
+            .compute(computes)
+            .group_by(group_by);
 
-        self.api
-            .list_logs_with_pagination(ListLogsOptionalParams::default().body(body))
+        let response = self.api.aggregate_logs(body).await?;
+        let buckets = response.data.and_then(|d| d.buckets).unwrap_or_default();
+
+        let mut rows = Vec::new();
+        for bucket in buckets {
+            let bucket = serde_json::to_value(&bucket).map_err(datadog::Error::Serde)?;
+            rows.extend(crate::aggregate::bucket_rows(&bucket, &labels));
+        }
+
+        Ok(rows)
     }
 }
+
+/// Builds a search request for one page of logs.
+fn list_request(
+    query: &str,
+    range: &ResolvedRange,
+    indexes: Vec<String>,
+    sort: SortOrder,
+    cursor: Option<String>,
+) -> LogsListRequest {
+    let mut page = LogsListRequestPage::new().limit(PAGE_SIZE);
+    page.cursor = cursor;
+    LogsListRequest::new()
+        .filter(
+            LogsQueryFilter::new()
+                .query(query.to_string())
+                .from(range.from_ms.to_string())
+                .to(range.to_ms.to_string())
+                .indexes(indexes),
+        )
+        .page(page)
+        .sort(match sort {
+            SortOrder::Asc => LogsSort::TIMESTAMP_ASCENDING,
+            SortOrder::Desc => LogsSort::TIMESTAMP_DESCENDING,
+        })
+}
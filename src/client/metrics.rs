@@ -1,26 +1,47 @@
 //! Datadog Metrics API client wrapper.
 //!
-//! Provides a simplified interface for querying metrics timeseries data and listing available metrics.
+//! Provides a simplified interface for querying metrics timeseries data (including
+//! v2 formula queries), reducing metrics to scalars and listing available metrics.
 
 use datadog_api_client::datadog::Configuration;
 use datadog_api_client::datadogV1::api_metrics::{ListActiveMetricsOptionalParams, MetricsAPI};
+use datadog_api_client::datadogV2::api_metrics::MetricsAPI as MetricsV2API;
+use datadog_api_client::datadogV2::model::{
+    MetricsAggregator, MetricsDataSource, MetricsScalarQuery, MetricsTimeseriesQuery, QueryFormula,
+    ScalarColumn, ScalarFormulaQueryRequest, ScalarFormulaRequest, ScalarFormulaRequestAttributes,
+    ScalarFormulaRequestType, ScalarQuery, TimeseriesFormulaQueryRequest, TimeseriesFormulaRequest,
+    TimeseriesFormulaRequestAttributes, TimeseriesFormulaRequestType, TimeseriesQuery,
+    TimeseriesResponseAttributes, Unit,
+};
 use futures_util::stream::{self, Stream, StreamExt};
 use std::pin::Pin;
 
+use super::RetryPolicy;
+use crate::aggregate::AggregateRow;
 use crate::error::AppError;
+use crate::time::ResolvedRange;
+use crate::timeseries::{DataSource, ScalarAggregator, TimeseriesRequest};
 
 /// Client for querying Datadog metrics.
 ///
-/// Wraps the Datadog SDK's MetricsAPI for querying timeseries data and listing metrics.
+/// Wraps the Datadog SDK's v1 MetricsAPI for querying timeseries data and listing
+/// metrics, and the v2 MetricsAPI for formula and scalar queries.
 pub struct MetricsClient {
     api: MetricsAPI,
+    api_v2: MetricsV2API,
 }
 
 impl MetricsClient {
-    /// Creates a new MetricsClient with the given configuration.
+    /// Creates a new MetricsClient with the given configuration and the default retry policy.
     pub fn new(config: Configuration) -> Self {
+        Self::with_retry(config, &RetryPolicy::default())
+    }
+
+    /// Creates a new MetricsClient that retries failed requests according to `policy`.
+    pub fn with_retry(config: Configuration, policy: &RetryPolicy) -> Self {
         Self {
-            api: MetricsAPI::with_config(config),
+            api: MetricsAPI::with_client_and_config(config.clone(), policy.client()),
+            api_v2: MetricsV2API::with_client_and_config(config, policy.client()),
         }
     }
 
@@ -33,15 +54,14 @@ impl MetricsClient {
     /// # Arguments
     ///
     /// * `query` - Datadog metric query string (e.g., "avg:system.cpu.user{*}")
-    /// * `from` - Start time in Unix seconds
-    /// * `to` - End time in Unix seconds
+    /// * `range` - Time window; the v1 API takes whole Unix seconds
     pub fn query(
         &self,
         query: &str,
-        from: i64,
-        to: i64,
+        range: &ResolvedRange,
     ) -> Pin<Box<dyn Stream<Item = Result<MetricPoint, AppError>> + Send + '_>> {
         let query = query.to_string();
+        let (from, to) = (range.from_secs(), range.to_secs());
         let api = &self.api;
 
         Box::pin(
@@ -91,7 +111,7 @@ impl MetricsClient {
                     }
                     Err(e) => {
                         // Convert the error and return it as a single-item stream
-                        let app_error = convert_datadog_error(e);
+                        let app_error = AppError::from_api_error(e, Some(FORBIDDEN_HINT));
                         stream::once(async move { Err(app_error) }).boxed()
                     }
                 }
@@ -100,6 +120,132 @@ impl MetricsClient {
         )
     }
 
+    /// Queries timeseries data with named queries and formulas (v2 API).
+    ///
+    /// Sends a single request and flattens the response into one point per
+    /// series and timestamp, labeled by formula (or query name when no formulas
+    /// are given) and group tags. Empty buckets are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - Named queries, formulas and optional rollup interval
+    /// * `range` - Time window, sent in Unix milliseconds
+    pub async fn timeseries(
+        &self,
+        request: &TimeseriesRequest,
+        range: &ResolvedRange,
+    ) -> Result<Vec<TimeseriesPoint>, AppError> {
+        let queries = request
+            .queries
+            .iter()
+            .map(|query| {
+                let data_source = match query.data_source {
+                    DataSource::Metrics => MetricsDataSource::METRICS,
+                    DataSource::CloudCost => MetricsDataSource::CLOUD_COST,
+                };
+                TimeseriesQuery::MetricsTimeseriesQuery(Box::new(
+                    MetricsTimeseriesQuery::new(data_source, query.query.clone())
+                        .name(query.name.clone()),
+                ))
+            })
+            .collect();
+
+        let mut attributes =
+            TimeseriesFormulaRequestAttributes::new(range.from_ms, queries, range.to_ms);
+        if !request.formulas.is_empty() {
+            attributes = attributes.formulas(
+                request
+                    .formulas
+                    .iter()
+                    .map(|formula| QueryFormula::new(formula.clone()))
+                    .collect(),
+            );
+        }
+        if let Some(interval) = request.interval_ms {
+            attributes = attributes.interval(interval);
+        }
+
+        let body = TimeseriesFormulaQueryRequest::new(TimeseriesFormulaRequest::new(
+            attributes,
+            TimeseriesFormulaRequestType::TIMESERIES_REQUEST,
+        ));
+
+        let response = self
+            .api_v2
+            .query_timeseries_data(body)
+            .await
+            .map_err(|e| AppError::from_api_error(e, Some(FORBIDDEN_HINT)))?;
+
+        let attributes = response.data.and_then(|data| data.attributes);
+        let points = attributes
+            .map(|attributes| flatten_timeseries(attributes, &request.labels()))
+            .unwrap_or_default();
+
+        // Datadog reports query errors alongside an otherwise empty response
+        match response.errors {
+            Some(errors) if points.is_empty() && !errors.is_empty() => {
+                Err(AppError::InvalidQuery(errors))
+            }
+            _ => Ok(points),
+        }
+    }
+
+    /// Reduces a metric query to one number per group (v2 scalar API).
+    ///
+    /// Returns one row per group, keyed by the group-by tag names, with the
+    /// reduced `value` and its `unit` when Datadog reports one.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - Datadog metric query, including any `by {tags}` clause
+    /// * `aggregator` - How each series is reduced over the time range
+    /// * `range` - Time window, sent in Unix milliseconds
+    pub async fn scalar(
+        &self,
+        query: &str,
+        aggregator: ScalarAggregator,
+        range: &ResolvedRange,
+    ) -> Result<Vec<AggregateRow>, AppError> {
+        let aggregator = match aggregator {
+            ScalarAggregator::Avg => MetricsAggregator::AVG,
+            ScalarAggregator::Sum => MetricsAggregator::SUM,
+            ScalarAggregator::Min => MetricsAggregator::MIN,
+            ScalarAggregator::Max => MetricsAggregator::MAX,
+            ScalarAggregator::Last => MetricsAggregator::LAST,
+            ScalarAggregator::Percentile => MetricsAggregator::PERCENTILE,
+        };
+        let queries = vec![ScalarQuery::MetricsScalarQuery(Box::new(
+            MetricsScalarQuery::new(aggregator, MetricsDataSource::METRICS, query.to_string())
+                .name("a".to_string()),
+        ))];
+
+        let body = ScalarFormulaQueryRequest::new(ScalarFormulaRequest::new(
+            ScalarFormulaRequestAttributes::new(range.from_ms, queries, range.to_ms),
+            ScalarFormulaRequestType::SCALAR_REQUEST,
+        ));
+
+        let response = self
+            .api_v2
+            .query_scalar_data(body)
+            .await
+            .map_err(|e| AppError::from_api_error(e, Some(FORBIDDEN_HINT)))?;
+
+        let columns = response
+            .data
+            .and_then(|data| data.attributes)
+            .and_then(|attributes| attributes.columns)
+            .unwrap_or_default();
+        let rows = flatten_scalar_columns(columns);
+
+        // Datadog reports query errors alongside an otherwise empty response
+        match response.errors {
+            Some(errors) if rows.is_empty() && !errors.is_empty() => {
+                Err(AppError::InvalidQuery(errors))
+            }
+            _ => Ok(rows),
+        }
+    }
+
     /// Lists active metrics within a time window.
     ///
     /// Returns an async stream of metric names that were actively reporting
@@ -107,11 +253,12 @@ impl MetricsClient {
     ///
     /// # Arguments
     ///
-    /// * `from` - Start time in Unix seconds
+    /// * `range` - Time window; only its start (in Unix seconds) is sent
     pub fn list_active(
         &self,
-        from: i64,
+        range: &ResolvedRange,
     ) -> Pin<Box<dyn Stream<Item = Result<String, AppError>> + Send + '_>> {
+        let from = range.from_secs();
         let api = &self.api;
 
         Box::pin(
@@ -130,7 +277,7 @@ impl MetricsClient {
                     }
                     Err(e) => {
                         // Convert the error and return it as a single-item stream
-                        let app_error = convert_datadog_error(e);
+                        let app_error = AppError::from_api_error(e, Some(FORBIDDEN_HINT));
                         stream::once(async move { Err(app_error) }).boxed()
                     }
                 }
@@ -140,26 +287,8 @@ impl MetricsClient {
     }
 }
 
-/// Converts a Datadog API error to an AppError.
-fn convert_datadog_error<T: std::fmt::Display>(e: T) -> AppError {
-    let msg = format!("{}", e);
-
-    if msg.contains("401") {
-        AppError::Auth(format!(
-            "Authentication failed (401): Invalid API or App key. {}",
-            msg
-        ))
-    } else if msg.contains("403") || msg.contains("Forbidden") {
-        AppError::Auth(format!(
-            "Access denied (403): Your API key may not have permission to access metrics. {}",
-            msg
-        ))
-    } else if msg.contains("400") || msg.contains("Bad Request") {
-        AppError::InvalidQuery(msg)
-    } else {
-        AppError::Api(msg)
-    }
-}
+/// Guidance appended to 403 errors from the metrics API.
+const FORBIDDEN_HINT: &str = "Your API key may not have permission to access metrics.";
 
 /// A single metric timeseries point.
 ///
@@ -193,3 +322,119 @@ pub struct MetricPoint {
     /// Metric value at this timestamp
     pub value: f64,
 }
+
+/// A single point of a v2 timeseries formula query.
+///
+/// The v2 sibling of [`MetricPoint`]: series are identified by formula and group
+/// tags rather than by metric name and scope.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TimeseriesPoint {
+    /// Formula (or query name, without formulas) that produced the series
+    pub formula: String,
+
+    /// Group-by tags of the series (e.g. ["env:prod", "host:web-1"])
+    pub group_tags: Vec<String>,
+
+    /// Unit of the series values, if Datadog reports one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+
+    /// Timestamp in Unix seconds
+    pub timestamp: i64,
+
+    /// Value at this timestamp
+    pub value: f64,
+}
+
+/// Flattens the columnar v2 response into points.
+///
+/// `values[i]` holds the values of `series[i]` at each of `times`, and each
+/// series' `query_index` points into `labels`.
+fn flatten_timeseries(
+    attributes: TimeseriesResponseAttributes,
+    labels: &[String],
+) -> Vec<TimeseriesPoint> {
+    let times = attributes.times.unwrap_or_default();
+    let values = attributes.values.unwrap_or_default();
+
+    attributes
+        .series
+        .unwrap_or_default()
+        .into_iter()
+        .zip(values)
+        .flat_map(|(series, values)| {
+            let formula = series
+                .query_index
+                .and_then(|index| labels.get(index as usize).cloned())
+                .unwrap_or_default();
+            let group_tags = series.group_tags.unwrap_or_default();
+            let unit = unit_name(series.unit.flatten());
+
+            times
+                .iter()
+                .zip(values)
+                .filter_map(|(timestamp, value)| {
+                    Some(TimeseriesPoint {
+                        formula: formula.clone(),
+                        group_tags: group_tags.clone(),
+                        unit: unit.clone(),
+                        timestamp: timestamp / 1000, // Convert to seconds
+                        value: value?,
+                    })
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Flattens the columnar v2 scalar response into one row per group.
+///
+/// Group columns hold each row's tag values (a single value is emitted as a
+/// string, several as an array); the data column holds the reduced values.
+fn flatten_scalar_columns(columns: Vec<ScalarColumn>) -> Vec<AggregateRow> {
+    fn row(rows: &mut Vec<AggregateRow>, index: usize) -> &mut AggregateRow {
+        if rows.len() <= index {
+            rows.resize_with(index + 1, AggregateRow::new);
+        }
+        &mut rows[index]
+    }
+
+    let mut rows: Vec<AggregateRow> = Vec::new();
+
+    for column in columns {
+        match column {
+            ScalarColumn::GroupScalarColumn(group) => {
+                let name = group.name.unwrap_or_default();
+                for (index, mut values) in group.values.unwrap_or_default().into_iter().enumerate()
+                {
+                    let value = if values.len() == 1 {
+                        values.remove(0).into()
+                    } else {
+                        values.into()
+                    };
+                    row(&mut rows, index).insert(name.clone(), value);
+                }
+            }
+            ScalarColumn::DataScalarColumn(data) => {
+                let unit = unit_name(data.meta.and_then(|meta| meta.unit.flatten()));
+                for (index, value) in data.values.unwrap_or_default().into_iter().enumerate() {
+                    let row = row(&mut rows, index);
+                    row.insert("value".into(), value.into());
+                    if let Some(unit) = &unit {
+                        row.insert("unit".into(), unit.clone().into());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    rows
+}
+
+/// Returns the display name of the first unit Datadog reports for a series.
+fn unit_name(units: Option<Vec<Option<Unit>>>) -> Option<String> {
+    units
+        .and_then(|units| units.into_iter().flatten().next())
+        .and_then(|unit| unit.short_name.or(unit.name))
+}
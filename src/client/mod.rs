@@ -1,11 +1,77 @@
 //! Datadog API client wrappers.
 //!
-//! Provides simplified interfaces to the Datadog SDK with automatic pagination.
+//! Provides simplified interfaces to the Datadog SDK with automatic pagination,
+//! retries and time-sliced parallel searches. Searches can also be fetched one
+//! [`Page`] at a time, for callers that persist the pagination cursor.
 
+mod dashboards;
+mod events;
 mod logs;
 mod metrics;
+mod monitors;
+mod retry;
+mod sliced;
+mod slo;
 mod spans;
 
+pub use dashboards::DashboardsClient;
+pub use events::{AlertType, EventsClient};
 pub use logs::LogsClient;
 pub use metrics::MetricsClient;
+pub use monitors::MonitorsClient;
+pub use retry::{DEFAULT_MAX_RETRIES, DEFAULT_RETRY_TIMEOUT, RetryEvent, RetryPolicy};
+pub use sliced::{SliceOptions, sliced};
+pub use slo::SloClient;
 pub use spans::SpansClient;
+
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Records requested per search API call.
+const PAGE_SIZE: i32 = 1000;
+
+/// Order in which search results are returned, by timestamp.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    /// Oldest first
+    #[default]
+    Asc,
+    /// Newest first
+    Desc,
+}
+
+impl FromStr for SortOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "asc" => Ok(SortOrder::Asc),
+            "desc" => Ok(SortOrder::Desc),
+            other => Err(format!(
+                "Unknown sort order '{}'. Expected one of: asc, desc",
+                other
+            )),
+        }
+    }
+}
+
+/// One page of search results.
+#[derive(Debug, Clone)]
+pub struct Page<T> {
+    /// Records on this page, in search order
+    pub records: Vec<T>,
+    /// Cursor for the next page, or `None` if this is the last one
+    pub next: Option<String>,
+}
+
+impl<T> Page<T> {
+    /// Builds a page, treating a short page as the last one like the SDK's paginated streams.
+    fn new(records: Vec<T>, after: Option<String>) -> Self {
+        let last = records.len() < PAGE_SIZE as usize;
+        Self {
+            records,
+            next: if last { None } else { after },
+        }
+    }
+}
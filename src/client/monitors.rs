@@ -0,0 +1,262 @@
+//! Datadog Monitors API client wrapper.
+//!
+//! Provides listing, fetching, searching and changing monitors (v1 API), and
+//! muting and unmuting them with downtimes (v2 API).
+
+use chrono::{DateTime, Utc};
+use datadog_api_client::datadog::Configuration;
+use datadog_api_client::datadogV1::api_monitors::{
+    DeleteMonitorOptionalParams, GetMonitorOptionalParams, ListMonitorsOptionalParams, MonitorsAPI,
+    SearchMonitorsOptionalParams,
+};
+use datadog_api_client::datadogV1::model::{Monitor, MonitorSearchResult, MonitorUpdateRequest};
+use datadog_api_client::datadogV2::api_downtimes::{
+    DowntimesAPI, GetDowntimeOptionalParams, ListMonitorDowntimesOptionalParams,
+};
+use datadog_api_client::datadogV2::model::{
+    DowntimeCreateRequest, DowntimeCreateRequestAttributes, DowntimeCreateRequestData,
+    DowntimeMonitorIdentifier, DowntimeMonitorIdentifierId, DowntimeResourceType,
+    DowntimeResponseData, DowntimeScheduleCreateRequest,
+    DowntimeScheduleOneTimeCreateUpdateRequest, MonitorDowntimeMatchResponseData,
+};
+use futures_util::future;
+use futures_util::stream::{self, Stream, StreamExt, TryStreamExt};
+
+use super::RetryPolicy;
+use crate::error::AppError;
+use crate::monitors::MonitorFilter;
+
+/// Monitors requested per search API call.
+const SEARCH_PAGE_SIZE: i64 = 100;
+
+/// Client for managing Datadog monitors.
+///
+/// Wraps the Datadog SDK's v1 MonitorsAPI for reading monitors, and the v2
+/// DowntimesAPI for muting them.
+pub struct MonitorsClient {
+    api: MonitorsAPI,
+    downtimes: DowntimesAPI,
+}
+
+impl MonitorsClient {
+    /// Creates a new MonitorsClient with the given configuration and the default retry policy.
+    pub fn new(config: Configuration) -> Self {
+        Self::with_retry(config, &RetryPolicy::default())
+    }
+
+    /// Creates a new MonitorsClient that retries failed requests according to `policy`.
+    ///
+    /// Monitor and downtime changes are retried as described on
+    /// [`RetryPolicy::write_client`].
+    pub fn with_retry(config: Configuration, policy: &RetryPolicy) -> Self {
+        Self {
+            api: MonitorsAPI::with_client_and_config(config.clone(), policy.write_client()),
+            downtimes: DowntimesAPI::with_client_and_config(config, policy.write_client()),
+        }
+    }
+
+    /// Lists monitors matching `filter`.
+    ///
+    /// Returns an async stream of monitors. The stream handles pagination
+    /// automatically; monitors whose overall state is not one of the filter's
+    /// statuses are skipped.
+    pub fn list(
+        &self,
+        filter: &MonitorFilter,
+    ) -> impl Stream<Item = Result<Monitor, AppError>> + use<'_> {
+        let mut params = ListMonitorsOptionalParams::default();
+        if let Some(name) = &filter.name {
+            params = params.name(name.clone());
+        }
+        if !filter.tags.is_empty() {
+            params = params.tags(filter.tags.join(","));
+        }
+        if !filter.monitor_tags.is_empty() {
+            params = params.monitor_tags(filter.monitor_tags.join(","));
+        }
+
+        let filter = filter.clone();
+        self.api
+            .list_monitors_with_pagination(params)
+            .map_err(|e| AppError::from_api_error(e, Some(FORBIDDEN_HINT)))
+            .try_filter(move |monitor| future::ready(filter.matches(monitor)))
+    }
+
+    /// Fetches a single monitor, including the state of each of its groups.
+    pub async fn get(&self, monitor_id: i64) -> Result<Monitor, AppError> {
+        self.api
+            .get_monitor(
+                monitor_id,
+                GetMonitorOptionalParams::default().group_states("all".to_string()),
+            )
+            .await
+            .map_err(|e| AppError::from_api_error(e, Some(FORBIDDEN_HINT)))
+    }
+
+    /// Searches monitors using the monitor search syntax.
+    ///
+    /// Returns an async stream of search results, requesting pages until the
+    /// page count reported by the API is reached.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - Monitor search query (e.g., "type:metric status:alert tag:team:payments")
+    pub fn search(
+        &self,
+        query: &str,
+    ) -> impl Stream<Item = Result<MonitorSearchResult, AppError>> + use<'_> {
+        let query = query.to_string();
+        let api = &self.api;
+
+        stream::unfold(Some(0), move |page| {
+            let query = query.clone();
+            async move {
+                let page = page?;
+                let params = SearchMonitorsOptionalParams::default()
+                    .query(query)
+                    .page(page)
+                    .per_page(SEARCH_PAGE_SIZE);
+
+                match api.search_monitors(params).await {
+                    Ok(response) => {
+                        let page_count = response
+                            .metadata
+                            .and_then(|metadata| metadata.page_count)
+                            .unwrap_or_default();
+                        let next = (page + 1 < page_count).then_some(page + 1);
+                        let monitors: Vec<_> = response
+                            .monitors
+                            .unwrap_or_default()
+                            .into_iter()
+                            .map(Ok)
+                            .collect();
+                        Some((stream::iter(monitors), next))
+                    }
+                    Err(e) => {
+                        let app_error = AppError::from_api_error(e, Some(FORBIDDEN_HINT));
+                        Some((stream::iter(vec![Err(app_error)]), None))
+                    }
+                }
+            }
+        })
+        .flatten()
+    }
+
+    /// Creates a monitor, returning it with its new ID.
+    pub async fn create(&self, monitor: Monitor) -> Result<Monitor, AppError> {
+        self.api
+            .create_monitor(monitor)
+            .await
+            .map_err(|e| AppError::from_api_error(e, Some(WRITE_FORBIDDEN_HINT)))
+    }
+
+    /// Updates the fields of a monitor that are set in `request`.
+    pub async fn update(
+        &self,
+        monitor_id: i64,
+        request: MonitorUpdateRequest,
+    ) -> Result<Monitor, AppError> {
+        self.api
+            .update_monitor(monitor_id, request)
+            .await
+            .map_err(|e| AppError::from_api_error(e, Some(WRITE_FORBIDDEN_HINT)))
+    }
+
+    /// Deletes a monitor.
+    pub async fn delete(&self, monitor_id: i64) -> Result<(), AppError> {
+        self.api
+            .delete_monitor(monitor_id, DeleteMonitorOptionalParams::default())
+            .await
+            .map_err(|e| AppError::from_api_error(e, Some(WRITE_FORBIDDEN_HINT)))?;
+        Ok(())
+    }
+
+    /// Mutes a monitor by scheduling a downtime that starts now.
+    ///
+    /// # Arguments
+    ///
+    /// * `monitor_id` - Monitor to mute
+    /// * `scope` - Groups to mute, e.g. "host:web-1"; "*" mutes the whole monitor
+    /// * `end` - When the downtime ends; `None` mutes until unmuted
+    /// * `message` - Optional message included in the downtime's notifications
+    pub async fn mute(
+        &self,
+        monitor_id: i64,
+        scope: &str,
+        end: Option<DateTime<Utc>>,
+        message: Option<String>,
+    ) -> Result<Option<DowntimeResponseData>, AppError> {
+        let identifier = DowntimeMonitorIdentifier::DowntimeMonitorIdentifierId(Box::new(
+            DowntimeMonitorIdentifierId::new(monitor_id),
+        ));
+        let mut attributes = DowntimeCreateRequestAttributes::new(identifier, scope.to_string());
+        if let Some(end) = end {
+            attributes = attributes.schedule(
+                DowntimeScheduleCreateRequest::DowntimeScheduleOneTimeCreateUpdateRequest(
+                    Box::new(DowntimeScheduleOneTimeCreateUpdateRequest::new().end(Some(end))),
+                ),
+            );
+        }
+        if let Some(message) = message {
+            attributes = attributes.message(Some(message));
+        }
+
+        let body = DowntimeCreateRequest::new(DowntimeCreateRequestData::new(
+            attributes,
+            DowntimeResourceType::DOWNTIME,
+        ));
+        let response = self
+            .downtimes
+            .create_downtime(body)
+            .await
+            .map_err(|e| AppError::from_api_error(e, Some(DOWNTIME_FORBIDDEN_HINT)))?;
+        Ok(response.data)
+    }
+
+    /// Lists the active downtimes that mute a monitor, or some of its groups.
+    pub fn downtimes(
+        &self,
+        monitor_id: i64,
+    ) -> impl Stream<Item = Result<MonitorDowntimeMatchResponseData, AppError>> + use<'_> {
+        self.downtimes
+            .list_monitor_downtimes_with_pagination(
+                monitor_id,
+                ListMonitorDowntimesOptionalParams::default(),
+            )
+            .map_err(|e| AppError::from_api_error(e, Some(DOWNTIME_FORBIDDEN_HINT)))
+    }
+
+    /// Fetches a downtime, including which monitors it covers.
+    pub async fn downtime(&self, downtime_id: &str) -> Result<DowntimeResponseData, AppError> {
+        let response = self
+            .downtimes
+            .get_downtime(
+                downtime_id.to_string(),
+                GetDowntimeOptionalParams::default(),
+            )
+            .await
+            .map_err(|e| AppError::from_api_error(e, Some(DOWNTIME_FORBIDDEN_HINT)))?;
+        response
+            .data
+            .ok_or_else(|| AppError::NotFound(format!("Downtime {} was not returned", downtime_id)))
+    }
+
+    /// Cancels a downtime, unmuting whatever it covered.
+    pub async fn cancel_downtime(&self, downtime_id: &str) -> Result<(), AppError> {
+        self.downtimes
+            .cancel_downtime(downtime_id.to_string())
+            .await
+            .map_err(|e| AppError::from_api_error(e, Some(DOWNTIME_FORBIDDEN_HINT)))
+    }
+}
+
+/// Guidance appended to 403 errors from the monitors API.
+const FORBIDDEN_HINT: &str = "Your API key may not have permission to read monitors.";
+
+/// Guidance appended to 403 errors when changing monitors.
+const WRITE_FORBIDDEN_HINT: &str =
+    "Changing monitors requires an application key with the monitors_write permission.";
+
+/// Guidance appended to 403 errors from the downtimes API.
+const DOWNTIME_FORBIDDEN_HINT: &str =
+    "Muting monitors requires an application key with the monitors_downtime permission.";
@@ -0,0 +1,328 @@
+//! Retry policy shared by all Datadog API clients.
+//!
+//! Retries are implemented as HTTP middleware, so they apply to every page a
+//! paginated stream fetches. A failed page is re-sent with the same cursor, and
+//! pagination resumes where it stopped instead of restarting.
+//!
+//! Requests are retried on 429 (rate limited), on 5xx responses other than
+//! 501, and on connection errors and timeouts. Rate-limited responses wait for
+//! the time given by `Retry-After` or `X-RateLimit-Reset`; everything else uses
+//! exponential backoff with jitter.
+//!
+//! Queries are safe to replay, including searches sent as POST. Requests that
+//! change something are not: a create that timed out may still have been
+//! applied, and sending it again makes a duplicate. Clients that write use
+//! [`RetryPolicy::write_client`], which only replays GET, HEAD and PUT
+//! requests. Other methods are only retried when the request was never
+//! processed: on 429, and when no connection could be made.
+
+use async_trait::async_trait;
+use rand::Rng;
+use reqwest::header::HeaderMap;
+use reqwest::{Method, Request, Response, StatusCode};
+use reqwest_middleware::{ClientBuilder, ClientWithMiddleware, Middleware, Next};
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use task_local_extensions::Extensions;
+
+/// Default number of retries per request.
+pub const DEFAULT_MAX_RETRIES: u32 = 5;
+
+/// Default total time budget for retrying a single request.
+pub const DEFAULT_RETRY_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Delay before the first backoff retry.
+const BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// Upper bound for a single backoff delay.
+const MAX_DELAY: Duration = Duration::from_secs(60);
+
+/// Details of a retry, passed to the [`RetryPolicy::on_retry`] callback.
+#[derive(Debug, Clone)]
+pub struct RetryEvent {
+    /// Retry number, starting at 1
+    pub attempt: u32,
+    pub max_retries: u32,
+    /// Time waited before the retry is sent
+    pub delay: Duration,
+    /// Why the previous attempt failed (status line or connection error)
+    pub reason: String,
+}
+
+/// Callback invoked before each retry.
+pub type RetryCallback = Arc<dyn Fn(&RetryEvent) + Send + Sync>;
+
+/// How failed requests are retried.
+#[derive(Clone)]
+pub struct RetryPolicy {
+    /// Maximum number of retries per request; 0 disables retrying
+    pub max_retries: u32,
+    /// Total time allowed for a request including all retries and waits
+    pub timeout: Duration,
+    on_retry: Option<RetryCallback>,
+}
+
+impl RetryPolicy {
+    /// Creates a retry policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_retries` - Maximum number of retries per request (0 disables retrying)
+    /// * `timeout` - Total time budget per request; no retry is started that would exceed it
+    pub fn new(max_retries: u32, timeout: Duration) -> Self {
+        Self {
+            max_retries,
+            timeout,
+            on_retry: None,
+        }
+    }
+
+    /// Sets a callback that is invoked before each retry, e.g. for logging.
+    pub fn on_retry(mut self, callback: impl Fn(&RetryEvent) + Send + Sync + 'static) -> Self {
+        self.on_retry = Some(Arc::new(callback));
+        self
+    }
+
+    /// Builds an HTTP client that applies this policy to every request.
+    ///
+    /// Only for clients that send queries, which are safe to replay.
+    pub fn client(&self) -> ClientWithMiddleware {
+        self.build(false)
+    }
+
+    /// Builds an HTTP client that applies this policy without sending a
+    /// change twice.
+    ///
+    /// GET, HEAD and PUT requests are retried as usual. Other methods are
+    /// only retried when rate limited or not sent at all, since a request
+    /// that failed or timed out may still have been applied. A retry
+    /// therefore never creates a duplicate.
+    pub fn write_client(&self) -> ClientWithMiddleware {
+        self.build(true)
+    }
+
+    fn build(&self, writes: bool) -> ClientWithMiddleware {
+        ClientBuilder::new(reqwest::Client::new())
+            .with(RetryMiddleware {
+                policy: self.clone(),
+                writes,
+            })
+            .build()
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_RETRIES, DEFAULT_RETRY_TIMEOUT)
+    }
+}
+
+impl fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_retries", &self.max_retries)
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+struct RetryMiddleware {
+    policy: RetryPolicy,
+    /// Whether requests may change something, so only idempotent ones are replayed
+    writes: bool,
+}
+
+#[async_trait]
+impl Middleware for RetryMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        let started = Instant::now();
+        let mut attempt = 0;
+        let replayable = !self.writes || is_idempotent(req.method());
+
+        loop {
+            // Requests with streaming bodies cannot be replayed
+            let Some(request) = req.try_clone() else {
+                return next.run(req, extensions).await;
+            };
+
+            let result = next.clone().run(request, extensions).await;
+            let Some((reason, server_delay)) = classify(&result, replayable) else {
+                return result;
+            };
+            if attempt >= self.policy.max_retries {
+                return result;
+            }
+
+            let delay = server_delay.unwrap_or_else(|| backoff_delay(attempt));
+            if started.elapsed() + delay > self.policy.timeout {
+                return result;
+            }
+
+            attempt += 1;
+            if let Some(callback) = &self.policy.on_retry {
+                callback(&RetryEvent {
+                    attempt,
+                    max_retries: self.policy.max_retries,
+                    delay,
+                    reason,
+                });
+            }
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+/// Decides whether a result is worth retrying.
+///
+/// Returns the failure reason and, for responses that say when to come back,
+/// the delay requested by the server. Requests that are not `replayable` are
+/// only retried if they never reached the server or were rate limited.
+fn classify(
+    result: &reqwest_middleware::Result<Response>,
+    replayable: bool,
+) -> Option<(String, Option<Duration>)> {
+    match result {
+        Ok(response) => {
+            let status = response.status();
+            if !is_retryable_status(status)
+                || (!replayable && status != StatusCode::TOO_MANY_REQUESTS)
+            {
+                return None;
+            }
+            Some((status.to_string(), server_delay(response.headers())))
+        }
+        Err(reqwest_middleware::Error::Reqwest(e))
+            if e.is_connect() || (replayable && e.is_timeout()) =>
+        {
+            Some((e.to_string(), None))
+        }
+        Err(_) => None,
+    }
+}
+
+/// Whether sending a request twice has the same effect as sending it once.
+///
+/// DELETE is left out: repeating one that was applied fails with a 404.
+fn is_idempotent(method: &Method) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::PUT)
+}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS
+        || (status.is_server_error() && status != StatusCode::NOT_IMPLEMENTED)
+}
+
+/// Reads the wait time requested by `Retry-After` or Datadog's `X-RateLimit-Reset`.
+///
+/// Both headers hold a number of seconds. A little jitter is added so parallel
+/// requests do not all resume at the same instant.
+fn server_delay(headers: &HeaderMap) -> Option<Duration> {
+    let seconds = ["retry-after", "x-ratelimit-reset"]
+        .iter()
+        .find_map(|name| {
+            headers
+                .get(*name)?
+                .to_str()
+                .ok()?
+                .trim()
+                .parse::<f64>()
+                .ok()
+        })
+        .filter(|s| s.is_finite() && *s >= 0.0)?;
+
+    let delay = Duration::from_secs_f64(seconds).min(MAX_DELAY);
+    let jitter = rand::thread_rng().gen_range(0..=BASE_DELAY.as_millis() as u64);
+    Some(delay + Duration::from_millis(jitter))
+}
+
+/// Exponential backoff with jitter for the given zero-based retry number.
+///
+/// The delay doubles each retry up to [`MAX_DELAY`] and is then randomized
+/// between half and the full value.
+fn backoff_delay(attempt: u32) -> Duration {
+    let ceiling = BASE_DELAY
+        .saturating_mul(2u32.saturating_pow(attempt))
+        .min(MAX_DELAY);
+    let half = ceiling / 2;
+    let jitter = rand::thread_rng().gen_range(0..=half.as_millis() as u64);
+    half + Duration::from_millis(jitter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn test_backoff_delay_grows_and_is_capped() {
+        for attempt in 0..20 {
+            let ceiling = BASE_DELAY
+                .saturating_mul(2u32.saturating_pow(attempt))
+                .min(MAX_DELAY);
+            let delay = backoff_delay(attempt);
+            assert!(delay >= ceiling / 2, "attempt {}: {:?}", attempt, delay);
+            assert!(delay <= ceiling, "attempt {}: {:?}", attempt, delay);
+        }
+    }
+
+    #[test]
+    fn test_server_delay_retry_after() {
+        let mut headers = HeaderMap::new();
+        headers.insert("retry-after", HeaderValue::from_static("3"));
+        let delay = server_delay(&headers).unwrap();
+        assert!(delay >= Duration::from_secs(3));
+        assert!(delay <= Duration::from_secs(3) + BASE_DELAY);
+    }
+
+    #[test]
+    fn test_server_delay_ratelimit_reset() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-reset", HeaderValue::from_static("7"));
+        assert!(server_delay(&headers).unwrap() >= Duration::from_secs(7));
+    }
+
+    #[test]
+    fn test_server_delay_is_capped() {
+        let mut headers = HeaderMap::new();
+        headers.insert("retry-after", HeaderValue::from_static("3600"));
+        assert!(server_delay(&headers).unwrap() <= MAX_DELAY + BASE_DELAY);
+    }
+
+    #[test]
+    fn test_server_delay_missing_or_invalid() {
+        assert!(server_delay(&HeaderMap::new()).is_none());
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "retry-after",
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert!(server_delay(&headers).is_none());
+    }
+
+    #[test]
+    fn test_idempotent_methods() {
+        assert!(is_idempotent(&Method::GET));
+        assert!(is_idempotent(&Method::HEAD));
+        assert!(is_idempotent(&Method::PUT));
+        assert!(!is_idempotent(&Method::POST));
+        assert!(!is_idempotent(&Method::PATCH));
+        assert!(!is_idempotent(&Method::DELETE));
+    }
+
+    #[test]
+    fn test_retryable_statuses() {
+        assert!(is_retryable_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable_status(StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(is_retryable_status(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!is_retryable_status(StatusCode::NOT_IMPLEMENTED));
+        assert!(!is_retryable_status(StatusCode::BAD_REQUEST));
+        assert!(!is_retryable_status(StatusCode::FORBIDDEN));
+    }
+}
@@ -0,0 +1,185 @@
+//! Time-sliced parallel fetching for large searches.
+//!
+//! Splits a time range into contiguous windows, runs one paginated search per
+//! window concurrently and merges the results back into a single stream.
+
+use futures_util::stream::{self, Stream, StreamExt};
+use std::collections::HashSet;
+use std::future;
+use std::pin::Pin;
+
+use super::SortOrder;
+use crate::time::ResolvedRange;
+
+/// Number of windows per concurrent search in ordered mode.
+///
+/// Ordered output can only start once the earliest window is complete, so
+/// smaller windows get the first records out sooner and bound what is buffered.
+const WINDOWS_PER_WORKER: usize = 4;
+
+/// How a sliced search is split and merged.
+#[derive(Debug, Clone, Copy)]
+pub struct SliceOptions {
+    /// Number of windows fetched concurrently
+    pub parallel: usize,
+    /// Emit records as they arrive instead of in timestamp order
+    pub unordered: bool,
+    /// Total number of records wanted (0 for unlimited); no window fetches more
+    pub limit: u64,
+    /// Order of windows in ordered mode; must match the order `fetch` sorts by
+    pub sort: SortOrder,
+}
+
+/// Fetches `range` in concurrent time slices and merges them into one stream.
+///
+/// `fetch` starts a search over one window, sorted by `options.sort`. In
+/// ordered mode, windows are emitted one after another in that order, so the
+/// output matches a serial search; in unordered mode records are emitted as soon as any window yields
+/// them. Adjacent windows share their boundary, so a record stamped exactly on
+/// it may be returned twice: `key` gives a record's timestamp (Unix ms) and ID,
+/// and such records are emitted once.
+pub fn sliced<'a, T, E, S>(
+    range: &ResolvedRange,
+    options: SliceOptions,
+    fetch: impl Fn(ResolvedRange) -> S + 'a,
+    key: impl Fn(&T) -> Option<(i64, String)> + 'a,
+) -> impl Stream<Item = Result<T, E>> + 'a
+where
+    T: 'a,
+    E: 'a,
+    S: Stream<Item = Result<T, E>> + 'a,
+{
+    let parallel = options.parallel.max(1);
+    let cap = match options.limit {
+        0 => usize::MAX,
+        limit => usize::try_from(limit).unwrap_or(usize::MAX),
+    };
+
+    let mut windows = if options.unordered {
+        range.split(parallel)
+    } else {
+        range.split(parallel * WINDOWS_PER_WORKER)
+    };
+    if options.sort == SortOrder::Desc {
+        windows.reverse();
+    }
+    let boundaries: HashSet<i64> = windows.iter().skip(1).map(|w| w.from_ms).collect();
+
+    let merged: Pin<Box<dyn Stream<Item = Result<T, E>> + 'a>> = if options.unordered {
+        Box::pin(stream::select_all(
+            windows
+                .into_iter()
+                .map(|window| Box::pin(fetch(window).take(cap))),
+        ))
+    } else {
+        Box::pin(
+            stream::iter(windows)
+                .map(move |window| fetch(window).take(cap).collect::<Vec<_>>())
+                .buffered(parallel)
+                .flat_map(stream::iter),
+        )
+    };
+
+    let mut on_boundary = HashSet::new();
+    merged.filter(move |item| {
+        let keep = match item.as_ref().ok().and_then(&key) {
+            Some((timestamp, id)) if boundaries.contains(&timestamp) => on_boundary.insert(id),
+            _ => true,
+        };
+        future::ready(keep)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fake search returning one record per 100ms in the window, with `to` inclusive.
+    fn fetch(window: ResolvedRange) -> impl Stream<Item = Result<i64, String>> {
+        let first = (window.from_ms + 99) / 100 * 100;
+        stream::iter((first..=window.to_ms).step_by(100).map(Ok))
+    }
+
+    fn key(record: &i64) -> Option<(i64, String)> {
+        Some((*record, record.to_string()))
+    }
+
+    async fn collect(range: ResolvedRange, options: SliceOptions) -> Vec<i64> {
+        sliced(&range, options, fetch, key)
+            .map(Result::unwrap)
+            .collect()
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_ordered_matches_serial_search() {
+        let range = ResolvedRange::new(0, 10_000);
+        let options = SliceOptions {
+            parallel: 3,
+            unordered: false,
+            limit: 0,
+            sort: SortOrder::Asc,
+        };
+        let serial: Vec<i64> = (0..=10_000).step_by(100).collect();
+        assert_eq!(collect(range, options).await, serial);
+    }
+
+    #[tokio::test]
+    async fn test_descending_windows_are_emitted_newest_first() {
+        let range = ResolvedRange::new(0, 10_000);
+        let options = SliceOptions {
+            parallel: 3,
+            unordered: false,
+            limit: 0,
+            sort: SortOrder::Desc,
+        };
+        // Each fake window is ascending, so the newest window's records come first
+        let records = collect(range, options).await;
+        assert_eq!(records.len(), 101);
+        assert_eq!(records[0], 9200);
+        assert_eq!(records.last(), Some(&800));
+    }
+
+    #[tokio::test]
+    async fn test_unordered_emits_every_record_once() {
+        let range = ResolvedRange::new(0, 10_000);
+        let options = SliceOptions {
+            parallel: 4,
+            unordered: true,
+            limit: 0,
+            sort: SortOrder::Asc,
+        };
+        let mut records = collect(range, options).await;
+        records.sort();
+        assert_eq!(records, (0..=10_000).step_by(100).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn test_limit_caps_each_window() {
+        let range = ResolvedRange::new(0, 10_000);
+        let options = SliceOptions {
+            parallel: 2,
+            unordered: false,
+            limit: 3,
+            sort: SortOrder::Asc,
+        };
+        // Each of the 8 windows stops after 3 records
+        let records = collect(range, options).await;
+        assert_eq!(&records[..4], &[0, 100, 200, 1300]);
+    }
+
+    #[tokio::test]
+    async fn test_errors_are_passed_through() {
+        let range = ResolvedRange::new(0, 1000);
+        let options = SliceOptions {
+            parallel: 2,
+            unordered: false,
+            limit: 0,
+            sort: SortOrder::Asc,
+        };
+        let failing = |_| stream::iter(vec![Err::<i64, _>("boom".to_string())]);
+        let results: Vec<_> = sliced(&range, options, failing, key).collect().await;
+        assert_eq!(results.len(), 8);
+        assert!(results.iter().all(Result::is_err));
+    }
+}
@@ -0,0 +1,100 @@
+//! Datadog Service Level Objectives API client wrapper.
+//!
+//! Provides listing and fetching SLOs, and their SLI history over a time
+//! window (v1 API).
+
+use datadog_api_client::datadog::Configuration;
+use datadog_api_client::datadogV1::api_service_level_objectives::{
+    GetSLOHistoryOptionalParams, GetSLOOptionalParams, ListSLOsOptionalParams,
+    ServiceLevelObjectivesAPI,
+};
+use datadog_api_client::datadogV1::model::{
+    SLOHistoryResponseData, SLOResponseData, ServiceLevelObjective,
+};
+use futures_util::stream::{Stream, TryStreamExt};
+
+use super::RetryPolicy;
+use crate::error::AppError;
+use crate::time::ResolvedRange;
+
+/// Client for reading Datadog service level objectives.
+///
+/// Wraps the Datadog SDK's v1 ServiceLevelObjectivesAPI with automatic
+/// pagination support.
+pub struct SloClient {
+    api: ServiceLevelObjectivesAPI,
+}
+
+impl SloClient {
+    /// Creates a new SloClient with the given configuration and the default retry policy.
+    pub fn new(config: Configuration) -> Self {
+        Self::with_retry(config, &RetryPolicy::default())
+    }
+
+    /// Creates a new SloClient that retries failed requests according to `policy`.
+    pub fn with_retry(config: Configuration, policy: &RetryPolicy) -> Self {
+        Self {
+            api: ServiceLevelObjectivesAPI::with_client_and_config(config, policy.client()),
+        }
+    }
+
+    /// Lists SLOs, optionally filtered by name and tag.
+    ///
+    /// Returns an async stream of SLOs. The stream handles pagination
+    /// automatically.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - Text the SLO name must contain
+    /// * `tag` - A single tag the SLO must have (e.g., "team:payments")
+    pub fn list(
+        &self,
+        query: Option<String>,
+        tag: Option<String>,
+    ) -> impl Stream<Item = Result<ServiceLevelObjective, AppError>> + use<'_> {
+        let mut params = ListSLOsOptionalParams::default();
+        params.query = query;
+        params.tags_query = tag;
+        self.api
+            .list_slos_with_pagination(params)
+            .map_err(|e| AppError::from_api_error(e, Some(FORBIDDEN_HINT)))
+    }
+
+    /// Fetches a single SLO.
+    pub async fn get(&self, slo_id: &str) -> Result<SLOResponseData, AppError> {
+        let response = self
+            .api
+            .get_slo(slo_id.to_string(), GetSLOOptionalParams::default())
+            .await
+            .map_err(|e| AppError::from_api_error(e, Some(FORBIDDEN_HINT)))?;
+        response
+            .data
+            .ok_or_else(|| AppError::NotFound(format!("SLO {} was not returned", slo_id)))
+    }
+
+    /// Fetches an SLO's SLI over `range`, overall and per group.
+    ///
+    /// The remaining error budget is computed for `target` (a percentage) and
+    /// reported under the `custom` timeframe.
+    pub async fn history(
+        &self,
+        slo_id: &str,
+        range: &ResolvedRange,
+        target: f64,
+    ) -> Result<SLOHistoryResponseData, AppError> {
+        let response = self
+            .api
+            .get_slo_history(
+                slo_id.to_string(),
+                range.from_secs(),
+                range.to_secs(),
+                GetSLOHistoryOptionalParams::default().target(target),
+            )
+            .await
+            .map_err(|e| AppError::from_api_error(e, Some(FORBIDDEN_HINT)))?;
+        Ok(response.data.unwrap_or_default())
+    }
+}
+
+/// Guidance appended to 403 errors from the SLO API.
+const FORBIDDEN_HINT: &str = "Your API key may not have permission to read SLOs.";
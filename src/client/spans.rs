@@ -1,15 +1,22 @@
 //! Datadog Spans (APM) API client wrapper.
 //!
-//! Provides a simplified interface for searching APM spans with automatic pagination.
+//! Provides a simplified interface for searching APM spans with automatic pagination
+//! and for server-side aggregation.
 
 use datadog_api_client::datadog::{self, Configuration};
-use datadog_api_client::datadogV2::api_spans::SpansAPI;
+use datadog_api_client::datadogV2::api_spans::{AggregateSpansError, ListSpansError, SpansAPI};
 use datadog_api_client::datadogV2::model::{
-    Span, SpansListRequest, SpansListRequestAttributes, SpansListRequestData, SpansListRequestPage,
-    SpansListRequestType, SpansQueryFilter, SpansSort,
+    Span, SpansAggregateData, SpansAggregateRequest, SpansAggregateRequestAttributes,
+    SpansAggregateRequestType, SpansCompute, SpansGroupBy, SpansListRequest,
+    SpansListRequestAttributes, SpansListRequestData, SpansListRequestPage, SpansListRequestType,
+    SpansQueryFilter, SpansSort,
 };
 use futures_util::Stream;
 
+use super::{PAGE_SIZE, Page, RetryPolicy, SortOrder};
+use crate::aggregate::{AggregateRequest, AggregateRow, error_query};
+use crate::time::ResolvedRange;
+
 /// Client for querying Datadog APM spans.
 ///
 /// Wraps the Datadog SDK's SpansAPI with automatic pagination support.
@@ -18,10 +25,15 @@ pub struct SpansClient {
 }
 
 impl SpansClient {
-    /// Creates a new SpansClient with the given configuration.
+    /// Creates a new SpansClient with the given configuration and the default retry policy.
     pub fn new(config: Configuration) -> Self {
+        Self::with_retry(config, &RetryPolicy::default())
+    }
+
+    /// Creates a new SpansClient that retries failed requests according to `policy`.
+    pub fn with_retry(config: Configuration, policy: &RetryPolicy) -> Self {
         Self {
-            api: SpansAPI::with_config(config),
+            api: SpansAPI::with_client_and_config(config, policy.client()),
         }
     }
 
@@ -33,35 +45,146 @@ impl SpansClient {
     /// # Arguments
     ///
     /// * `query` - Datadog query syntax (e.g., "service:web env:prod @duration:>1s")
-    /// * `from` - Start time: relative ("now-1h"), ISO8601 ("2024-01-15T10:00:00Z"), or Unix ms ("1705315200000")
-    /// * `to` - End time: relative ("now"), ISO8601 ("2024-01-15T10:00:00Z"), or Unix ms ("1705315200000")
+    /// * `range` - Time window, resolved to Unix milliseconds
+    /// * `sort` - Oldest or newest spans first
     pub fn search(
         &self,
         query: &str,
-        from: &str,
-        to: &str,
-    ) -> impl Stream<
-        Item = Result<
-            Span,
-            datadog::Error<datadog_api_client::datadogV2::api_spans::ListSpansError>,
-        >,
-    > + '_ {
-        let body = SpansListRequest::new().data(
-            SpansListRequestData::new()
+        range: &ResolvedRange,
+        sort: SortOrder,
+    ) -> impl Stream<Item = Result<Span, datadog::Error<ListSpansError>>> + use<'_> {
+        self.api
+            .list_spans_with_pagination(list_request(query, range, sort, None))
+    }
+
+    /// Fetches a single page of spans matching the given query.
+    ///
+    /// Takes the same arguments as [`SpansClient::search`], plus the cursor
+    /// returned with the previous page (`None` for the first page).
+    pub async fn search_page(
+        &self,
+        query: &str,
+        range: &ResolvedRange,
+        sort: SortOrder,
+        cursor: Option<String>,
+    ) -> Result<Page<Span>, datadog::Error<ListSpansError>> {
+        let response = self
+            .api
+            .list_spans(list_request(query, range, sort, cursor))
+            .await?;
+        let after = response.meta.and_then(|m| m.page).and_then(|p| p.after);
+        Ok(Page::new(response.data.unwrap_or_default(), after))
+    }
+
+    /// Fetches every span belonging to a single trace.
+    ///
+    /// Returns the same paginated stream as [`SpansClient::search`], filtered
+    /// to `trace_id:<trace_id>`. The time range must cover the whole trace.
+    pub fn trace(
+        &self,
+        trace_id: &str,
+        range: &ResolvedRange,
+    ) -> impl Stream<Item = Result<Span, datadog::Error<ListSpansError>>> + '_ {
+        self.search(&format!("trace_id:{}", trace_id), range, SortOrder::Asc)
+    }
+
+    /// Aggregates APM spans matching the given query on the server.
+    ///
+    /// Sends a request to the spans analytics endpoint and flattens the
+    /// returned buckets into rows keyed by group-by facet and compute label.
+    /// Error rates take a second request counting the errors per group.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - Datadog query syntax (e.g., "service:web env:prod")
+    /// * `range` - Time window, resolved to Unix milliseconds
+    /// * `request` - Computes, group-bys and optional timeseries interval
+    pub async fn aggregate(
+        &self,
+        query: &str,
+        range: &ResolvedRange,
+        request: &AggregateRequest,
+    ) -> Result<Vec<AggregateRow>, datadog::Error<AggregateSpansError>> {
+        let mut rows = self.aggregate_once(query, range, request).await?;
+        if let Some(errors) = request.error_request() {
+            let error_rows = self
+                .aggregate_once(&error_query(query), range, &errors)
+                .await?;
+            request.apply_error_rates(&mut rows, &error_rows);
+        }
+        Ok(rows)
+    }
+
+    /// Sends a single aggregation request and flattens its buckets.
+    async fn aggregate_once(
+        &self,
+        query: &str,
+        range: &ResolvedRange,
+        request: &AggregateRequest,
+    ) -> Result<Vec<AggregateRow>, datadog::Error<AggregateSpansError>> {
+        let labels: Vec<String> = request
+            .effective_computes()
+            .iter()
+            .map(|c| c.label())
+            .collect();
+        let computes: Vec<SpansCompute> =
+            serde_json::from_value(request.api_computes()).map_err(datadog::Error::Serde)?;
+        let group_by: Vec<SpansGroupBy> =
+            serde_json::from_value(request.api_group_by()).map_err(datadog::Error::Serde)?;
+
+        let body = SpansAggregateRequest::new().data(
+            SpansAggregateData::new()
                 .attributes(
-                    SpansListRequestAttributes::new()
+                    SpansAggregateRequestAttributes::new()
                         .filter(
                             SpansQueryFilter::new()
                                 .query(query.to_string())
-                                .from(from.to_string())
-                                .to(to.to_string()),
+                                .from(range.from_ms.to_string())
+                                .to(range.to_ms.to_string()),
                         )
-                        .page(SpansListRequestPage::new().limit(1000))
-                        .sort(SpansSort::TIMESTAMP_ASCENDING),
+                        .compute(computes)
+                        .group_by(group_by),
                 )
-                .type_(SpansListRequestType::SEARCH_REQUEST),
+                .type_(SpansAggregateRequestType::AGGREGATE_REQUEST),
         );
 
-        self.api.list_spans_with_pagination(body)
+        let response = self.api.aggregate_spans(body).await?;
+
+        let mut rows = Vec::new();
+        for bucket in response.data.unwrap_or_default() {
+            let bucket = serde_json::to_value(&bucket).map_err(datadog::Error::Serde)?;
+            rows.extend(crate::aggregate::bucket_rows(&bucket, &labels));
+        }
+
+        Ok(rows)
     }
 }
+
+/// Builds a search request for one page of spans.
+fn list_request(
+    query: &str,
+    range: &ResolvedRange,
+    sort: SortOrder,
+    cursor: Option<String>,
+) -> SpansListRequest {
+    let mut page = SpansListRequestPage::new().limit(PAGE_SIZE);
+    page.cursor = cursor;
+    SpansListRequest::new().data(
+        SpansListRequestData::new()
+            .attributes(
+                SpansListRequestAttributes::new()
+                    .filter(
+                        SpansQueryFilter::new()
+                            .query(query.to_string())
+                            .from(range.from_ms.to_string())
+                            .to(range.to_ms.to_string()),
+                    )
+                    .page(page)
+                    .sort(match sort {
+                        SortOrder::Asc => SpansSort::TIMESTAMP_ASCENDING,
+                        SortOrder::Desc => SpansSort::TIMESTAMP_DESCENDING,
+                    }),
+            )
+            .type_(SpansListRequestType::SEARCH_REQUEST),
+    )
+}
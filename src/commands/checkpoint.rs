@@ -0,0 +1,134 @@
+//! Checkpointed search exports.
+//!
+//! With `--checkpoint`, `logs search` and `spans search` fetch one page at a
+//! time and save their progress after every page, and whenever written
+//! records are flushed to the output within a page (every record on stdout).
+//! `--resume` can then continue an interrupted or killed export without
+//! repeating or skipping records.
+
+use serde::Serialize;
+use std::future::Future;
+use std::path::Path;
+
+use crate::cli::Checkpointing;
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::checkpoint::Checkpoint;
+use ddog::client::Page;
+use ddog::error::AppError;
+
+/// Starts a new export or loads the one being resumed.
+///
+/// `fresh` describes the export requested on the command line. When resuming,
+/// the saved checkpoint must be for the same command, query and indexes, and
+/// its time range, sort order and limit are used instead of the ones given.
+pub(crate) fn open(
+    args: &Checkpointing,
+    path: &Path,
+    fresh: Checkpoint,
+    writer: &mut dyn RecordWriter,
+    logger: &VerboseLogger,
+) -> Result<Checkpoint, AppError> {
+    if !writer.is_streaming() {
+        return Err(AppError::Config(
+            "--checkpoint needs a streaming output format; json and table output cannot be resumed"
+                .into(),
+        ));
+    }
+
+    if !args.resume {
+        if path.exists() {
+            return Err(AppError::Config(format!(
+                "Checkpoint {} already exists; pass --resume to continue that export or remove the file to start over",
+                path.display()
+            )));
+        }
+        fresh.save(path)?;
+        logger.log(&format!("Saving progress to {}", path.display()));
+        return Ok(fresh);
+    }
+
+    let saved = Checkpoint::load(path)?;
+    saved.ensure_matches(&fresh.command, &fresh.query, &fresh.indexes)?;
+    logger.log(&format!(
+        "Resuming {} after {} record(s), time range {}",
+        path.display(),
+        saved.records_written,
+        saved.range()
+    ));
+    if saved.records_written > 0 {
+        writer
+            .resume()
+            .map_err(|e| AppError::Config(e.to_string()))?;
+    }
+    Ok(saved)
+}
+
+/// Runs an export page by page, saving the checkpoint after every page.
+///
+/// `fetch` requests the page at the given cursor. The checkpoint is also saved
+/// whenever the writer has flushed every record written so far, and when
+/// writing fails. Records of a partly written page are skipped on resume, so
+/// nothing is written twice. Returns the number of
+/// records written by this run.
+pub(crate) async fn run<T, E, F, Fut>(
+    path: &Path,
+    mut checkpoint: Checkpoint,
+    fetch: F,
+    mut writer: Box<dyn RecordWriter>,
+    logger: &VerboseLogger,
+    map_error: impl Fn(E) -> AppError,
+) -> Result<u64, AppError>
+where
+    T: Serialize,
+    F: Fn(Option<String>) -> Fut,
+    Fut: Future<Output = Result<Page<T>, E>>,
+{
+    if checkpoint.complete {
+        logger.log("Checkpoint is already complete, nothing to fetch");
+    }
+
+    let mut count: u64 = 0;
+    while !checkpoint.complete {
+        let page = fetch(checkpoint.cursor.clone()).await.map_err(&map_error)?;
+        let wanted = checkpoint
+            .remaining()
+            .map_or(usize::MAX, |r| usize::try_from(r).unwrap_or(usize::MAX));
+
+        for record in page
+            .records
+            .iter()
+            .skip(checkpoint.page_offset)
+            .take(wanted)
+        {
+            if let Err(e) = writer.write(record) {
+                writer.flush()?;
+                checkpoint.save(path)?;
+                return Err(e.into());
+            }
+            checkpoint.advance(1);
+            count += 1;
+            // The output now holds every counted record
+            if writer.is_flushed() {
+                checkpoint.save(path)?;
+            }
+        }
+
+        if checkpoint.remaining() == Some(0) {
+            logger.log(&format!("Reached limit of {} results", checkpoint.limit));
+            checkpoint.complete = true;
+        } else {
+            checkpoint.next_page(page.next);
+        }
+        // Records must be on disk before the checkpoint says they were written
+        writer.flush()?;
+        checkpoint.save(path)?;
+        logger.log(&format!(
+            "Checkpoint saved: {} record(s) written",
+            checkpoint.records_written
+        ));
+    }
+
+    writer.finish()?;
+    Ok(count)
+}
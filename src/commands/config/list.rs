@@ -0,0 +1,54 @@
+//! Config list command implementation.
+//!
+//! Handles the `ddog config list` command, writing one record per profile.
+
+use serde::Serialize;
+
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::config::{self, PROFILE_ENV};
+use ddog::error::AppError;
+
+/// A profile summary for JSON serialization. Keys are intentionally omitted.
+#[derive(Debug, Serialize)]
+struct ProfileSummary {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    site: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    default_indexes: Option<Vec<String>>,
+    default: bool,
+    active: bool,
+}
+
+/// Executes the config list command.
+pub fn run(
+    profile_flag: Option<&str>,
+    mut writer: Box<dyn RecordWriter>,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let file = config::load_config_file()?;
+    if let Some(path) = config::config_path() {
+        logger.log(&format!("Config file: {}", path.display()));
+    }
+
+    let active = profile_flag
+        .map(str::to_string)
+        .or_else(|| std::env::var(PROFILE_ENV).ok().filter(|p| !p.is_empty()))
+        .or_else(|| file.default_profile.clone());
+
+    for (name, profile) in &file.profiles {
+        writer.write(&ProfileSummary {
+            name: name.clone(),
+            site: profile.site.clone(),
+            default_indexes: profile.default_indexes.clone(),
+            default: file.default_profile.as_deref() == Some(name.as_str()),
+            active: active.as_deref() == Some(name.as_str()),
+        })?;
+    }
+
+    writer.finish()?;
+
+    logger.log(&format!("Listed {} profile(s)", file.profiles.len()));
+    Ok(())
+}
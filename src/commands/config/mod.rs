@@ -0,0 +1,5 @@
+//! Config domain commands.
+
+pub mod list;
+pub mod show;
+pub mod use_profile;
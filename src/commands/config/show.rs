@@ -0,0 +1,75 @@
+//! Config show command implementation.
+//!
+//! Handles the `ddog config show` command, printing resolved settings with keys redacted.
+
+use serde::Serialize;
+
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::config::{self, PartialSettings, Retention, Source};
+use ddog::error::AppError;
+
+/// Shown instead of a key that is not configured.
+const NOT_SET: &str = "(not set)";
+
+/// Resolved settings for JSON serialization, with keys redacted.
+#[derive(Debug, Serialize)]
+struct ResolvedSettings {
+    profile: Option<String>,
+    config_path: Option<String>,
+    site: String,
+    site_source: Source,
+    api_key: String,
+    api_key_source: Option<Source>,
+    app_key: String,
+    app_key_source: Option<Source>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    default_indexes: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    api_url: Option<String>,
+    retention_days: Retention,
+}
+
+/// Executes the config show command.
+///
+/// Shows the named profile as if selected with `--profile`, or the active
+/// settings. Missing keys are shown as "(not set)" rather than failing.
+pub fn run(
+    name: Option<String>,
+    profile_flag: Option<&str>,
+    mut writer: Box<dyn RecordWriter>,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let settings = PartialSettings::load(name.as_deref().or(profile_flag))?;
+    let (api_key, api_key_source) = shown_key(settings.api_key.as_ref());
+    let (app_key, app_key_source) = shown_key(settings.app_key.as_ref());
+
+    writer.write(&ResolvedSettings {
+        profile: settings.profile.clone(),
+        config_path: config::config_path().map(|p| p.display().to_string()),
+        site: settings.site.clone(),
+        site_source: settings.site_source,
+        api_key,
+        api_key_source,
+        app_key,
+        app_key_source,
+        default_indexes: settings.default_indexes.clone(),
+        api_url: settings.api_url.clone(),
+        retention_days: settings.retention,
+    })?;
+    writer.finish()?;
+
+    logger.log(&format!(
+        "Resolved settings for profile: {}",
+        settings.profile.as_deref().unwrap_or("(none)")
+    ));
+    Ok(())
+}
+
+/// Redacts a key for display, or marks it as not set.
+fn shown_key(key: Option<&(String, Source)>) -> (String, Option<Source>) {
+    match key {
+        Some((key, source)) if !key.is_empty() => (config::redact(key), Some(*source)),
+        _ => (NOT_SET.to_string(), None),
+    }
+}
@@ -0,0 +1,49 @@
+//! Config use command implementation.
+//!
+//! Handles the `ddog config use` command, setting the default profile in the config file.
+
+use serde::Serialize;
+
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::config;
+use ddog::error::AppError;
+
+/// Result of changing the default profile.
+#[derive(Debug, Serialize)]
+struct DefaultProfile {
+    default_profile: String,
+    config_path: String,
+}
+
+/// Executes the config use command.
+pub fn run(
+    name: String,
+    mut writer: Box<dyn RecordWriter>,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let file = config::load_config_file()?;
+
+    if !file.profiles.contains_key(&name) {
+        let available: Vec<&str> = file.profiles.keys().map(String::as_str).collect();
+        return Err(AppError::Config(format!(
+            "Profile '{}' not found. Available profiles: {}",
+            name,
+            if available.is_empty() {
+                "(none)".to_string()
+            } else {
+                available.join(", ")
+            }
+        )));
+    }
+
+    let path = config::set_default_profile(&name)?;
+    logger.log(&format!("Default profile set to '{}'", name));
+
+    writer.write(&DefaultProfile {
+        default_profile: name,
+        config_path: path.display().to_string(),
+    })?;
+    writer.finish()?;
+    Ok(())
+}
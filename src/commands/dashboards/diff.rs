@@ -0,0 +1,45 @@
+//! Dashboards diff command implementation.
+//!
+//! Handles the `ddog dashboards diff` command, comparing a dashboard file with
+//! the live dashboard widget by widget.
+
+use std::path::PathBuf;
+
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::client::DashboardsClient;
+use ddog::dashboards;
+use ddog::error::AppError;
+
+/// Executes the dashboards diff command.
+///
+/// Writes one record per differing widget; an identical dashboard writes none.
+pub async fn run(
+    client: DashboardsClient,
+    id: String,
+    file: PathBuf,
+    mut writer: Box<dyn RecordWriter>,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let wanted = dashboards::normalize(&dashboards::load(&file)?)?;
+    logger.log(&format!("Fetching dashboard {}", id));
+    let current = dashboards::normalize(&client.get(&id).await?)?;
+
+    let differences = dashboards::compare(&current, &wanted);
+    for difference in &differences {
+        writer.write(difference)?;
+    }
+
+    writer.finish()?;
+    if differences.is_empty() {
+        logger.log(&format!("Dashboard {} matches {}", id, file.display()));
+    } else {
+        logger.log(&format!(
+            "{} difference(s) between dashboard {} and {}",
+            differences.len(),
+            id,
+            file.display()
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,70 @@
+//! Dashboards export command implementation.
+//!
+//! Handles the `ddog dashboards export` command, backing up each dashboard to
+//! its own JSON file.
+
+use futures_util::StreamExt;
+use serde::Serialize;
+use std::path::PathBuf;
+
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::client::DashboardsClient;
+use ddog::dashboards;
+use ddog::error::AppError;
+
+/// A backup file that was written.
+#[derive(Debug, Serialize)]
+struct Exported {
+    id: String,
+    title: String,
+    file: PathBuf,
+}
+
+/// Executes the dashboards export command.
+///
+/// Exports the dashboards in `ids`, or every dashboard when it is empty, in
+/// ID order.
+pub async fn run(
+    client: DashboardsClient,
+    dir: PathBuf,
+    mut ids: Vec<String>,
+    mut writer: Box<dyn RecordWriter>,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    if ids.is_empty() {
+        let mut stream = std::pin::pin!(client.list());
+        while let Some(result) = stream.next().await {
+            if let Some(id) = result?.id {
+                ids.push(id);
+            }
+        }
+    }
+    ids.sort();
+    ids.dedup();
+
+    std::fs::create_dir_all(&dir)?;
+    for id in &ids {
+        logger.log(&format!("Fetching dashboard {}", id));
+        let dashboard = client.get(id).await?;
+        let file = dir.join(format!("{}.json", id));
+        std::fs::write(
+            &file,
+            dashboards::render(&dashboards::normalize(&dashboard)?)?,
+        )?;
+
+        writer.write(&Exported {
+            id: id.clone(),
+            title: dashboard.title,
+            file,
+        })?;
+    }
+
+    writer.finish()?;
+    logger.log(&format!(
+        "Exported {} dashboard(s) to {}",
+        ids.len(),
+        dir.display()
+    ));
+    Ok(())
+}
@@ -0,0 +1,22 @@
+//! Dashboards get command implementation.
+//!
+//! Handles the `ddog dashboards get` command, writing a single dashboard to stdout.
+
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::client::DashboardsClient;
+use ddog::error::AppError;
+
+/// Executes the dashboards get command.
+pub async fn run(
+    client: DashboardsClient,
+    id: String,
+    mut writer: Box<dyn RecordWriter>,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    logger.log(&format!("Fetching dashboard {}", id));
+    let dashboard = client.get(&id).await?;
+    writer.write(&dashboard)?;
+    writer.finish()?;
+    Ok(())
+}
@@ -0,0 +1,73 @@
+//! Dashboards import command implementation.
+//!
+//! Handles the `ddog dashboards import` command, creating dashboards from
+//! files or replacing an existing dashboard with one.
+
+use serde::Serialize;
+use std::path::PathBuf;
+
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::client::DashboardsClient;
+use ddog::dashboards;
+use ddog::error::AppError;
+
+/// A dashboard that was created or replaced.
+#[derive(Debug, Serialize)]
+struct Imported {
+    id: Option<String>,
+    title: String,
+    url: Option<String>,
+    file: PathBuf,
+}
+
+/// Executes the dashboards import command.
+///
+/// Every file is read and checked before anything is created, so an invalid
+/// file leaves the org unchanged.
+pub async fn run(
+    client: DashboardsClient,
+    files: Vec<PathBuf>,
+    replace: Option<String>,
+    mut writer: Box<dyn RecordWriter>,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    if replace.is_some() && files.len() != 1 {
+        return Err(AppError::InvalidQuery(format!(
+            "--replace takes a single dashboard file, got {}",
+            files.len()
+        )));
+    }
+
+    let loaded = files
+        .iter()
+        .map(|file| dashboards::load(file))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for (file, dashboard) in files.into_iter().zip(loaded) {
+        let dashboard = match &replace {
+            Some(id) => {
+                logger.log(&format!(
+                    "Replacing dashboard {} with {}",
+                    id,
+                    file.display()
+                ));
+                client.update(id, dashboard).await?
+            }
+            None => {
+                logger.log(&format!("Creating dashboard from {}", file.display()));
+                client.create(dashboard).await?
+            }
+        };
+
+        writer.write(&Imported {
+            id: dashboard.id,
+            title: dashboard.title,
+            url: dashboard.url,
+            file,
+        })?;
+    }
+
+    writer.finish()?;
+    Ok(())
+}
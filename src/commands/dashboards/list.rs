@@ -0,0 +1,29 @@
+//! Dashboards list command implementation.
+//!
+//! Handles the `ddog dashboards list` command, streaming dashboard summaries to stdout.
+
+use futures_util::StreamExt;
+
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::client::DashboardsClient;
+use ddog::error::AppError;
+
+/// Executes the dashboards list command.
+pub async fn run(
+    client: DashboardsClient,
+    mut writer: Box<dyn RecordWriter>,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let mut stream = std::pin::pin!(client.list());
+    let mut count: u64 = 0;
+
+    while let Some(result) = stream.next().await {
+        writer.write(&result?)?;
+        count += 1;
+    }
+
+    writer.finish()?;
+    logger.log(&format!("Listed {} dashboard(s)", count));
+    Ok(())
+}
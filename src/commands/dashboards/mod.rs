@@ -0,0 +1,7 @@
+//! Dashboards domain commands.
+
+pub mod diff;
+pub mod export;
+pub mod get;
+pub mod import;
+pub mod list;
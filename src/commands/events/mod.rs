@@ -0,0 +1,25 @@
+//! Events domain commands.
+
+pub mod post;
+pub mod search;
+
+use datadog_api_client::datadog;
+
+use crate::logging::VerboseLogger;
+use ddog::error::AppError;
+
+/// Guidance appended to 403 errors from the events API.
+const FORBIDDEN_HINT: &str = "Your API key may not have permission to read events.";
+
+/// Converts an events API error into an AppError, logging it in verbose mode.
+///
+/// Shared by all events commands so they report errors consistently; `hint`
+/// replaces the read permission hint for commands that write events.
+pub(crate) fn map_api_error<T: std::fmt::Debug>(
+    e: datadog::Error<T>,
+    hint: Option<&str>,
+    logger: &VerboseLogger,
+) -> AppError {
+    logger.log_error(&e.to_string(), "events API request");
+    AppError::from_api_error(e, Some(hint.unwrap_or(FORBIDDEN_HINT)))
+}
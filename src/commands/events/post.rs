@@ -0,0 +1,36 @@
+//! Events post command implementation.
+//!
+//! Handles the `ddog events post` command, posting a single event and writing
+//! it to stdout.
+
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::client::{AlertType, EventsClient};
+use ddog::error::AppError;
+
+/// Guidance appended to 403 errors when posting events.
+const POST_FORBIDDEN_HINT: &str =
+    "Posting events requires an API key that is valid for this Datadog site.";
+
+/// Executes the events post command.
+pub async fn run(
+    client: EventsClient,
+    title: String,
+    text: String,
+    tags: Vec<String>,
+    alert_type: AlertType,
+    mut writer: Box<dyn RecordWriter>,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    logger.log(&format!("Posting event \"{}\"", title));
+    let event = client
+        .post(&title, &text, tags, alert_type)
+        .await
+        .map_err(|e| super::map_api_error(e, Some(POST_FORBIDDEN_HINT), &logger))?;
+
+    if let Some(event) = &event {
+        writer.write(event)?;
+    }
+    writer.finish()?;
+    Ok(())
+}
@@ -0,0 +1,44 @@
+//! Events search command implementation.
+//!
+//! Handles the `ddog events search` command, streaming event results to stdout.
+
+use futures_util::StreamExt;
+
+use crate::cli::Pagination;
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::client::EventsClient;
+use ddog::error::AppError;
+use ddog::time::ResolvedRange;
+
+/// Executes the events search command.
+///
+/// Streams matching events to stdout until the limit is reached or all
+/// results are exhausted.
+pub async fn run(
+    client: EventsClient,
+    query: String,
+    range: ResolvedRange,
+    pagination: Pagination,
+    mut writer: Box<dyn RecordWriter>,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let mut stream = std::pin::pin!(client.search(&query, &range, pagination.sort));
+    let mut count: u64 = 0;
+
+    while let Some(result) = stream.next().await {
+        let event = result.map_err(|e| super::map_api_error(e, None, &logger))?;
+
+        writer.write(&event)?;
+        count += 1;
+
+        if pagination.limit > 0 && count >= pagination.limit {
+            logger.log(&format!("Reached limit of {} results", pagination.limit));
+            break;
+        }
+    }
+
+    writer.finish()?;
+    logger.log(&format!("Returned {} event(s)", count));
+    Ok(())
+}
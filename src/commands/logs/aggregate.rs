@@ -0,0 +1,44 @@
+//! Logs aggregate command implementation.
+//!
+//! Handles the `ddog logs aggregate` command, writing one row per bucket to stdout.
+
+use crate::cli::Aggregation;
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::aggregate::AggregateRequest;
+use ddog::client::LogsClient;
+use ddog::error::AppError;
+use ddog::time::ResolvedRange;
+
+/// Executes the logs aggregate command.
+///
+/// Runs the aggregation server-side and writes the flattened buckets to stdout.
+pub async fn run(
+    client: LogsClient,
+    query: String,
+    range: ResolvedRange,
+    aggregation: Aggregation,
+    indexes: Vec<String>,
+    mut writer: Box<dyn RecordWriter>,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let request = AggregateRequest {
+        computes: aggregation.compute,
+        group_by: aggregation.group_by,
+        group_sort: aggregation.group_sort,
+        interval: aggregation.interval,
+    };
+
+    let rows = client
+        .aggregate(&query, &range, indexes, &request)
+        .await
+        .map_err(|e| super::map_api_error(e, &logger))?;
+
+    for row in &rows {
+        writer.write(row)?;
+    }
+    writer.finish()?;
+
+    logger.log(&format!("Returned {} bucket(s)", rows.len()));
+    Ok(())
+}
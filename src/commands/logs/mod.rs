@@ -1,3 +1,24 @@
 //! Logs domain commands.
 
+pub mod aggregate;
 pub mod search;
+
+use datadog_api_client::datadog;
+
+use crate::logging::VerboseLogger;
+use ddog::error::AppError;
+
+/// Guidance appended to 403 errors from the logs API.
+const FORBIDDEN_HINT: &str = "Your API key may not have permission to access logs.";
+
+/// Converts a logs API error into an AppError, logging it in verbose mode.
+///
+/// Shared by all logs commands so they report authentication, permission and
+/// query errors consistently.
+pub(crate) fn map_api_error<T: std::fmt::Debug>(
+    e: datadog::Error<T>,
+    logger: &VerboseLogger,
+) -> AppError {
+    logger.log_error(&e.to_string(), "logs API request");
+    AppError::from_api_error(e, Some(FORBIDDEN_HINT))
+}
@@ -1,53 +1,101 @@
 //! Logs search command implementation.
 //!
 //! Handles the `ddog logs search` command, streaming log results to stdout.
-
-use futures_util::StreamExt;
-
-use crate::cli::{Pagination, TimeRange};
+//! With `--follow`, keeps polling for new logs until interrupted; with
+//! `--parallel`, fetches time slices concurrently; with `--checkpoint`, saves
+//! progress so an interrupted export can be resumed.
+
+use datadog_api_client::datadog;
+use datadog_api_client::datadogV2::api_logs::ListLogsError;
+use datadog_api_client::datadogV2::model::Log;
+use futures_util::{Stream, StreamExt};
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::cli::{Checkpointing, Follow, Pagination, Parallel};
+use crate::commands::checkpoint;
 use crate::logging::VerboseLogger;
-use crate::output::NdjsonWriter;
-use ddog::client::LogsClient;
+use crate::output::RecordWriter;
+use ddog::checkpoint::Checkpoint;
+use ddog::client::{LogsClient, SliceOptions, SortOrder};
 use ddog::error::AppError;
+use ddog::time::ResolvedRange;
 
 /// Executes the logs search command.
 ///
-/// Streams matching log records to stdout as NDJSON until the limit is reached
-/// or all results are exhausted.
+/// Streams matching log records to stdout until the limit is reached
+/// or all results are exhausted. In follow mode, polls until Ctrl-C instead.
+#[allow(clippy::too_many_arguments)]
 pub async fn run(
     client: LogsClient,
     query: String,
-    time_range: TimeRange,
+    range: ResolvedRange,
     pagination: Pagination,
     indexes: Vec<String>,
+    follow: Follow,
+    parallel: Parallel,
+    checkpointing: Checkpointing,
+    mut writer: Box<dyn RecordWriter>,
     logger: VerboseLogger,
 ) -> Result<(), AppError> {
-    let mut writer = NdjsonWriter::new();
-    let mut stream =
-        std::pin::pin!(client.search(&query, &time_range.from, &time_range.to, indexes));
+    if follow.follow {
+        return run_follow(client, query, range, indexes, follow, writer, logger).await;
+    }
+
+    if let Some(path) = &checkpointing.checkpoint {
+        let fresh = Checkpoint::new(
+            "logs search",
+            &query,
+            indexes.clone(),
+            &range,
+            pagination.sort,
+            pagination.limit,
+        );
+        let saved = checkpoint::open(&checkpointing, path, fresh, writer.as_mut(), &logger)?;
+        let (range, sort) = (saved.range(), saved.sort);
+        let count = checkpoint::run(
+            path,
+            saved,
+            |cursor| client.search_page(&query, &range, indexes.clone(), sort, cursor),
+            writer,
+            &logger,
+            |e| super::map_api_error(e, &logger),
+        )
+        .await?;
+        logger.log(&format!("Returned {} log(s)", count));
+        return Ok(());
+    }
+
+    let mut stream: Pin<Box<dyn Stream<Item = Result<Log, datadog::Error<ListLogsError>>>>> =
+        if parallel.parallel > 1 {
+            let options = SliceOptions {
+                parallel: parallel.parallel.into(),
+                unordered: parallel.unordered,
+                limit: pagination.limit,
+                sort: pagination.sort,
+            };
+            logger.log(&format!(
+                "Fetching {} time slices concurrently{}",
+                options.parallel,
+                if options.unordered { ", unordered" } else { "" }
+            ));
+            Box::pin(ddog::client::sliced(
+                &range,
+                options,
+                |window| client.search(&query, &window, indexes.clone(), pagination.sort),
+                |log: &Log| {
+                    let timestamp = log.attributes.as_ref()?.timestamp?.timestamp_millis();
+                    Some((timestamp, log.id.clone()?))
+                },
+            ))
+        } else {
+            Box::pin(client.search(&query, &range, indexes, pagination.sort))
+        };
     let mut count: u64 = 0;
 
     while let Some(result) = stream.next().await {
-        let log = result.map_err(|e| {
-            let msg = format!("{}", e);
-            logger.log_error(&msg, "logs API request");
-
-            if msg.contains("401") {
-                AppError::Auth(format!(
-                    "Authentication failed (401): Invalid API or App key. {}",
-                    msg
-                ))
-            } else if msg.contains("403") || msg.contains("Forbidden") {
-                AppError::Auth(format!(
-                    "Access denied (403): Your API key may not have permission to access logs. {}",
-                    msg
-                ))
-            } else if msg.contains("400") || msg.contains("Bad Request") {
-                AppError::InvalidQuery(msg)
-            } else {
-                AppError::Api(msg)
-            }
-        })?;
+        let log = result.map_err(|e| super::map_api_error(e, &logger))?;
 
         writer.write(&log)?;
         count += 1;
@@ -58,70 +106,133 @@ pub async fn run(
         }
     }
 
+    writer.finish()?;
     logger.log(&format!("Returned {} log(s)", count));
     Ok(())
 }
 
-#[cfg(test)]
-mod tests {
-    use ddog::error::AppError;
-
-    fn parse_error_message(msg: &str) -> AppError {
-        if msg.contains("401") || msg.contains("403") || msg.contains("Forbidden") {
-            AppError::Auth(format!("Authentication failed: {}", msg))
-        } else if msg.contains("400") || msg.contains("Bad Request") {
-            AppError::InvalidQuery(msg.to_string())
-        } else {
-            AppError::Api(msg.to_string())
+/// Polls for new logs with a sliding window until interrupted with Ctrl-C.
+///
+/// Each poll searches from `--follow-lag` before the previous poll started up
+/// to now, so a log is picked up as long as it becomes searchable within that
+/// lag of its timestamp. Logs emitted inside the overlap are remembered so
+/// they are skipped on the next poll; older ones are pruned as the window
+/// moves forward. Buffering formats (JSON, table) are written once following
+/// stops.
+async fn run_follow(
+    client: LogsClient,
+    query: String,
+    range: ResolvedRange,
+    indexes: Vec<String>,
+    follow: Follow,
+    mut writer: Box<dyn RecordWriter>,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let mut seen: HashMap<String, i64> = HashMap::new();
+    let mut from = range.from_ms;
+    let mut count: u64 = 0;
+    let interval = Duration::from_secs(follow.poll_interval.max(1));
+    let lag_ms = Duration::from_secs(follow.follow_lag).as_millis() as i64;
+
+    logger.log(&format!(
+        "Following logs every {}s (Ctrl-C to stop)",
+        interval.as_secs()
+    ));
+
+    let shutdown = tokio::signal::ctrl_c();
+    tokio::pin!(shutdown);
+
+    loop {
+        let poll_started_ms = now_millis();
+
+        tokio::select! {
+            _ = &mut shutdown => break,
+            result = poll_once(
+                &client, &query, from, &indexes, writer.as_mut(), &mut seen, &mut count, &logger,
+            ) => result?,
+        };
+
+        // Slide the window forward, reaching back for logs indexed late
+        from = poll_started_ms - lag_ms;
+        seen.retain(|_, ts| *ts >= from);
+
+        tokio::select! {
+            _ = &mut shutdown => break,
+            _ = tokio::time::sleep(interval) => {}
         }
     }
 
-    #[test]
-    fn test_error_parsing_401() {
-        let error = parse_error_message("401 Unauthorized");
-        assert!(matches!(error, AppError::Auth(_)));
-        assert_eq!(error.exit_code(), 2);
-    }
+    writer.finish()?;
+    logger.log(&format!("Interrupted, returned {} log(s)", count));
+    Ok(())
+}
 
-    #[test]
-    fn test_error_parsing_403() {
-        let error = parse_error_message("403 Forbidden");
-        assert!(matches!(error, AppError::Auth(_)));
-        assert_eq!(error.exit_code(), 2);
-    }
+/// Runs one follow poll, writing logs not seen before.
+#[allow(clippy::too_many_arguments)]
+async fn poll_once(
+    client: &LogsClient,
+    query: &str,
+    from: i64,
+    indexes: &[String],
+    writer: &mut dyn RecordWriter,
+    seen: &mut HashMap<String, i64>,
+    count: &mut u64,
+    logger: &VerboseLogger,
+) -> Result<(), AppError> {
+    let window = ResolvedRange::new(from, now_millis().max(from));
+    let mut stream =
+        std::pin::pin!(client.search(query, &window, indexes.to_vec(), SortOrder::Asc));
+    let before = *count;
 
-    #[test]
-    fn test_error_parsing_forbidden() {
-        let error = parse_error_message("Forbidden access");
-        assert!(matches!(error, AppError::Auth(_)));
-        assert_eq!(error.exit_code(), 2);
-    }
+    while let Some(result) = stream.next().await {
+        let log = result.map_err(|e| super::map_api_error(e, logger))?;
+
+        let timestamp = log
+            .attributes
+            .as_ref()
+            .and_then(|a| a.timestamp)
+            .map(|t| t.timestamp_millis());
+        if seen
+            .insert(follow_key(&log), timestamp.unwrap_or_else(now_millis))
+            .is_some()
+        {
+            continue;
+        }
 
-    #[test]
-    fn test_error_parsing_400() {
-        let error = parse_error_message("400 Bad Request");
-        assert!(matches!(error, AppError::InvalidQuery(_)));
-        assert_eq!(error.exit_code(), 4);
+        writer.write(&log)?;
+        *count += 1;
     }
 
-    #[test]
-    fn test_error_parsing_bad_request() {
-        let error = parse_error_message("Bad Request: invalid syntax");
-        assert!(matches!(error, AppError::InvalidQuery(_)));
-        assert_eq!(error.exit_code(), 4);
-    }
+    logger.log(&format!(
+        "Poll over {} returned {} new log(s)",
+        window,
+        *count - before
+    ));
+    Ok(())
+}
 
-    #[test]
-    fn test_error_parsing_generic_api_error() {
-        let error = parse_error_message("500 Internal Server Error");
-        assert!(matches!(error, AppError::Api(_)));
-        assert_eq!(error.exit_code(), 3);
+/// Identifies a log across follow polls: its ID, or its timestamp and message
+/// when it has none (identical ID-less logs are then only emitted once).
+fn follow_key(log: &Log) -> String {
+    match &log.id {
+        Some(id) => id.clone(),
+        None => {
+            let attributes = log.attributes.as_ref();
+            format!(
+                "{:?}\n{}",
+                attributes.and_then(|a| a.timestamp),
+                attributes
+                    .and_then(|a| a.message.as_deref())
+                    .unwrap_or_default()
+            )
+        }
     }
+}
 
-    #[test]
-    fn test_error_parsing_network_error() {
-        let error = parse_error_message("Connection timeout");
-        assert!(matches!(error, AppError::Api(_)));
-        assert_eq!(error.exit_code(), 3);
-    }
+/// Current time in Unix milliseconds.
+fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or_default()
 }
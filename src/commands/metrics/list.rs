@@ -5,12 +5,11 @@
 use futures_util::StreamExt;
 use serde::Serialize;
 
-use crate::cli::TimeFrom;
 use crate::logging::VerboseLogger;
-use crate::output::NdjsonWriter;
+use crate::output::RecordWriter;
 use ddog::client::MetricsClient;
 use ddog::error::AppError;
-use ddog::time::parse_to_unix_seconds;
+use ddog::time::ResolvedRange;
 
 /// A metric name wrapper for JSON serialization.
 #[derive(Debug, Serialize)]
@@ -20,19 +19,19 @@ struct MetricName {
 
 /// Executes the metrics list command.
 ///
-/// Lists active metrics after the specified start time and streams them to stdout as NDJSON.
+/// Lists active metrics after the specified start time and streams them to stdout.
 pub async fn run(
     client: MetricsClient,
-    time_from: TimeFrom,
+    range: ResolvedRange,
+    mut writer: Box<dyn RecordWriter>,
     logger: VerboseLogger,
 ) -> Result<(), AppError> {
-    // Convert time string to Unix seconds
-    let from_secs = parse_to_unix_seconds(&time_from.from)?;
+    logger.log(&format!(
+        "Listing active metrics from {} (Unix seconds)",
+        range.from_secs()
+    ));
 
-    logger.log(&format!("Listing active metrics from {}", from_secs));
-
-    let mut writer = NdjsonWriter::new();
-    let mut stream = std::pin::pin!(client.list_active(from_secs));
+    let mut stream = std::pin::pin!(client.list_active(&range));
     let mut count: u64 = 0;
 
     while let Some(result) = stream.next().await {
@@ -43,6 +42,7 @@ pub async fn run(
         count += 1;
     }
 
+    writer.finish()?;
     logger.log(&format!("Listed {} active metric(s)", count));
     Ok(())
 }
@@ -2,3 +2,5 @@
 
 pub mod list;
 pub mod query;
+pub mod scalar;
+pub mod timeseries;
@@ -4,35 +4,31 @@
 
 use futures_util::StreamExt;
 
-use crate::cli::TimeRangeRelativeOnly;
 use crate::logging::VerboseLogger;
-use crate::output::NdjsonWriter;
+use crate::output::RecordWriter;
 use ddog::client::MetricsClient;
 use ddog::error::AppError;
-use ddog::time::parse_to_unix_seconds;
+use ddog::time::ResolvedRange;
 
 /// Executes the metrics query command.
 ///
-/// Queries metrics timeseries data and streams individual points to stdout as NDJSON
+/// Queries metrics timeseries data and streams individual points to stdout
 /// until the limit is reached or all results are exhausted.
 pub async fn run(
     client: MetricsClient,
     query: String,
-    time_range: TimeRangeRelativeOnly,
+    range: ResolvedRange,
     limit: u64,
+    mut writer: Box<dyn RecordWriter>,
     logger: VerboseLogger,
 ) -> Result<(), AppError> {
-    // Convert time strings to Unix seconds
-    let from_secs = parse_to_unix_seconds(&time_range.from)?;
-    let to_secs = parse_to_unix_seconds(&time_range.to)?;
-
     logger.log(&format!(
         "Querying metrics from {} to {} (Unix seconds)",
-        from_secs, to_secs
+        range.from_secs(),
+        range.to_secs()
     ));
 
-    let mut writer = NdjsonWriter::new();
-    let mut stream = std::pin::pin!(client.query(&query, from_secs, to_secs));
+    let mut stream = std::pin::pin!(client.query(&query, &range));
     let mut count: u64 = 0;
 
     while let Some(result) = stream.next().await {
@@ -46,6 +42,7 @@ pub async fn run(
         }
     }
 
+    writer.finish()?;
     logger.log(&format!("Returned {} metric point(s)", count));
     Ok(())
 }
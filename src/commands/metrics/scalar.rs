@@ -0,0 +1,34 @@
+//! Metrics scalar command implementation.
+//!
+//! Handles the `ddog metrics scalar` command, writing one reduced value per group to stdout.
+
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::client::MetricsClient;
+use ddog::error::AppError;
+use ddog::time::ResolvedRange;
+use ddog::timeseries::ScalarAggregator;
+
+/// Executes the metrics scalar command.
+///
+/// Reduces the query server-side and writes one row per group to stdout.
+pub async fn run(
+    client: MetricsClient,
+    query: String,
+    range: ResolvedRange,
+    aggregator: ScalarAggregator,
+    mut writer: Box<dyn RecordWriter>,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    logger.log(&format!("Reducing with {} over {}", aggregator, range));
+
+    let rows = client.scalar(&query, aggregator, &range).await?;
+
+    for row in &rows {
+        writer.write(row)?;
+    }
+    writer.finish()?;
+
+    logger.log(&format!("Returned {} group(s)", rows.len()));
+    Ok(())
+}
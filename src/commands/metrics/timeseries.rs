@@ -0,0 +1,57 @@
+//! Metrics timeseries command implementation.
+//!
+//! Handles the `ddog metrics timeseries` command, writing formula points from the
+//! v2 timeseries API to stdout.
+
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::client::MetricsClient;
+use ddog::error::AppError;
+use ddog::time::ResolvedRange;
+use ddog::timeseries::TimeseriesRequest;
+
+/// Executes the metrics timeseries command.
+///
+/// Validates the formulas against the named queries, runs the request and writes
+/// the flattened points to stdout until the limit is reached.
+pub async fn run(
+    client: MetricsClient,
+    request: TimeseriesRequest,
+    range: ResolvedRange,
+    limit: u64,
+    mut writer: Box<dyn RecordWriter>,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    request.validate().map_err(AppError::InvalidQuery)?;
+
+    logger.log(&format!("Querying timeseries over {}", range));
+    for query in &request.queries {
+        logger.log(&format!(
+            "Query {} ({}): {}",
+            query.name, query.data_source, query.query
+        ));
+    }
+    for formula in &request.formulas {
+        logger.log(&format!("Formula: {}", formula));
+    }
+    if let Some(interval) = request.interval_ms {
+        logger.log(&format!("Interval: {}ms", interval));
+    }
+
+    let points = client.timeseries(&request, &range).await?;
+    let mut count: u64 = 0;
+
+    for point in &points {
+        writer.write(point)?;
+        count += 1;
+
+        if limit > 0 && count >= limit {
+            logger.log(&format!("Reached limit of {} results", limit));
+            break;
+        }
+    }
+
+    writer.finish()?;
+    logger.log(&format!("Returned {} timeseries point(s)", count));
+    Ok(())
+}
@@ -1,5 +1,11 @@
 //! Command implementations organized by domain.
 
+pub mod checkpoint;
+pub mod config;
+pub mod dashboards;
+pub mod events;
 pub mod logs;
 pub mod metrics;
+pub mod monitors;
+pub mod slo;
 pub mod spans;
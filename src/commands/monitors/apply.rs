@@ -0,0 +1,119 @@
+//! Monitors apply command implementation.
+//!
+//! Handles the `ddog monitors apply` command, planning and optionally making
+//! the changes that bring monitors in line with a directory of definitions.
+
+use futures_util::StreamExt;
+use serde::Serialize;
+use std::path::PathBuf;
+
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::client::MonitorsClient;
+use ddog::error::AppError;
+use ddog::monitors::MonitorFilter;
+use ddog::monitors::definitions::{self, Action, Definition, Existing, Step};
+
+/// A plan step and whether it was carried out.
+#[derive(Debug, Serialize)]
+struct Outcome<'a> {
+    #[serde(flatten)]
+    step: &'a Step,
+    applied: bool,
+}
+
+/// Executes the monitors apply command.
+///
+/// Compares the definitions in `dir` with the monitors selected by `filter`
+/// and writes one record per planned step. Changes are only made with `yes`;
+/// monitors without a definition are only deleted with `prune`.
+pub async fn run(
+    client: MonitorsClient,
+    filter: MonitorFilter,
+    dir: PathBuf,
+    prune: bool,
+    yes: bool,
+    mut writer: Box<dyn RecordWriter>,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let definitions = Definition::load_dir(&dir)?;
+    logger.log(&format!(
+        "Read {} definition(s) from {}",
+        definitions.len(),
+        dir.display()
+    ));
+
+    let mut existing = Vec::new();
+    let mut stream = std::pin::pin!(client.list(&filter));
+    while let Some(result) = stream.next().await {
+        existing.extend(Existing::from_monitor(&result?)?);
+    }
+
+    let plan = definitions::plan(&definitions, &existing, prune)?;
+    let summary = format!(
+        "{} to create, {} to update, {} to delete, {} unchanged",
+        plan.count(Action::Create),
+        plan.count(Action::Update),
+        plan.count(Action::Delete),
+        plan.unchanged
+    );
+    if plan.unmanaged > 0 {
+        logger.log(&format!(
+            "Leaving {} monitor(s) without a definition; pass --prune to delete them",
+            plan.unmanaged
+        ));
+    }
+
+    for step in &plan.steps {
+        if !yes {
+            writer.write(&Outcome {
+                step,
+                applied: false,
+            })?;
+            continue;
+        }
+
+        let mut step = step.clone();
+        let definition = step.definition.map(|index| &definitions[index]);
+        match (step.action, definition, step.monitor_id) {
+            (Action::Create, Some(definition), _) => {
+                logger.log(&format!("Creating monitor \"{}\"", step.name));
+                let created = client.create(definition.monitor.clone()).await?;
+                step.monitor_id = created.id;
+            }
+            (Action::Update, Some(definition), Some(id)) => {
+                logger.log(&format!("Updating monitor {} \"{}\"", id, step.name));
+                let request = serde_json::from_value(definition.body.clone())?;
+                client.update(id, request).await?;
+            }
+            (Action::Delete, _, Some(id)) => {
+                logger.log(&format!("Deleting monitor {} \"{}\"", id, step.name));
+                client.delete(id).await?;
+            }
+            (action, _, _) => {
+                return Err(AppError::Config(format!(
+                    "Cannot {} monitor \"{}\": the plan step is missing its definition or monitor ID",
+                    format!("{:?}", action).to_lowercase(),
+                    step.name
+                )));
+            }
+        }
+        writer.write(&Outcome {
+            step: &step,
+            applied: true,
+        })?;
+    }
+
+    writer.finish()?;
+    if yes {
+        logger.log(&format!("Applied: {}", summary));
+    } else if plan.steps.is_empty() {
+        logger.log(&format!("No changes: {}", summary));
+    } else {
+        logger.warn(&format!(
+            "Plan not applied ({}); pass --yes to apply it",
+            summary
+        ));
+    }
+    Ok(())
+}
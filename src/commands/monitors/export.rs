@@ -0,0 +1,69 @@
+//! Monitors export command implementation.
+//!
+//! Handles the `ddog monitors export` command, writing each selected monitor's
+//! definition to its own file.
+
+use futures_util::StreamExt;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::client::MonitorsClient;
+use ddog::error::AppError;
+use ddog::monitors::MonitorFilter;
+use ddog::monitors::definitions::{self, DefinitionFormat};
+
+/// A definition file that was written.
+#[derive(Debug, Serialize)]
+struct Exported {
+    monitor_id: Option<i64>,
+    name: String,
+    file: PathBuf,
+}
+
+/// Executes the monitors export command.
+///
+/// Monitors are written in ID order, so file names stay the same between
+/// exports when two monitors would share one.
+pub async fn run(
+    client: MonitorsClient,
+    filter: MonitorFilter,
+    dir: PathBuf,
+    format: DefinitionFormat,
+    mut writer: Box<dyn RecordWriter>,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let mut monitors = Vec::new();
+    let mut stream = std::pin::pin!(client.list(&filter));
+    while let Some(result) = stream.next().await {
+        monitors.push(result?);
+    }
+    monitors.sort_by_key(|monitor| monitor.id);
+
+    std::fs::create_dir_all(&dir)?;
+    let mut taken = HashSet::new();
+    for monitor in &monitors {
+        let name = monitor.name.clone().unwrap_or_default();
+        let file = dir.join(definitions::file_name(
+            &name, monitor.id, format, &mut taken,
+        ));
+        let definition = definitions::normalize(monitor)?;
+        std::fs::write(&file, format.render(&definition)?)?;
+
+        writer.write(&Exported {
+            monitor_id: monitor.id,
+            name,
+            file,
+        })?;
+    }
+
+    writer.finish()?;
+    logger.log(&format!(
+        "Exported {} monitor(s) to {}",
+        monitors.len(),
+        dir.display()
+    ));
+    Ok(())
+}
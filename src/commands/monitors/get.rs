@@ -0,0 +1,22 @@
+//! Monitors get command implementation.
+//!
+//! Handles the `ddog monitors get` command, writing a single monitor to stdout.
+
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::client::MonitorsClient;
+use ddog::error::AppError;
+
+/// Executes the monitors get command.
+pub async fn run(
+    client: MonitorsClient,
+    id: i64,
+    mut writer: Box<dyn RecordWriter>,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    logger.log(&format!("Fetching monitor {}", id));
+    let monitor = client.get(id).await?;
+    writer.write(&monitor)?;
+    writer.finish()?;
+    Ok(())
+}
@@ -0,0 +1,33 @@
+//! Monitors list command implementation.
+//!
+//! Handles the `ddog monitors list` command, streaming matching monitors to stdout.
+
+use futures_util::StreamExt;
+
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::client::MonitorsClient;
+use ddog::error::AppError;
+use ddog::monitors::MonitorFilter;
+
+/// Executes the monitors list command.
+///
+/// Lists monitors matching the filter and writes one record per monitor.
+pub async fn run(
+    client: MonitorsClient,
+    filter: MonitorFilter,
+    mut writer: Box<dyn RecordWriter>,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let mut stream = std::pin::pin!(client.list(&filter));
+    let mut count: u64 = 0;
+
+    while let Some(result) = stream.next().await {
+        writer.write(&result?)?;
+        count += 1;
+    }
+
+    writer.finish()?;
+    logger.log(&format!("Listed {} monitor(s)", count));
+    Ok(())
+}
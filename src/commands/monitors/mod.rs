@@ -0,0 +1,10 @@
+//! Monitors domain commands.
+
+pub mod apply;
+pub mod export;
+pub mod get;
+pub mod list;
+pub mod mute;
+pub mod search;
+pub mod status;
+pub mod unmute;
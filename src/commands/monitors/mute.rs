@@ -0,0 +1,41 @@
+//! Monitors mute command implementation.
+//!
+//! Handles the `ddog monitors mute` command, scheduling a downtime for a
+//! monitor and writing the created downtime to stdout.
+
+use chrono::{DateTime, Utc};
+
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::client::MonitorsClient;
+use ddog::error::AppError;
+
+/// Executes the monitors mute command.
+///
+/// Mutes `scope` of the monitor from now until `end`, or until unmuted when
+/// `end` is `None`.
+pub async fn run(
+    client: MonitorsClient,
+    id: i64,
+    scope: String,
+    end: Option<DateTime<Utc>>,
+    message: Option<String>,
+    mut writer: Box<dyn RecordWriter>,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    match end {
+        Some(end) => logger.log(&format!(
+            "Muting monitor {} ({}) until {}",
+            id,
+            scope,
+            end.to_rfc3339()
+        )),
+        None => logger.log(&format!("Muting monitor {} ({}) until unmuted", id, scope)),
+    }
+
+    if let Some(downtime) = client.mute(id, &scope, end, message).await? {
+        writer.write(&downtime)?;
+    }
+    writer.finish()?;
+    Ok(())
+}
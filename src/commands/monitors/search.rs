@@ -0,0 +1,33 @@
+//! Monitors search command implementation.
+//!
+//! Handles the `ddog monitors search` command, streaming monitors that match a
+//! monitor search query to stdout.
+
+use futures_util::StreamExt;
+
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::client::MonitorsClient;
+use ddog::error::AppError;
+
+/// Executes the monitors search command.
+pub async fn run(
+    client: MonitorsClient,
+    query: String,
+    mut writer: Box<dyn RecordWriter>,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    logger.log(&format!("Monitor query: {}", query));
+
+    let mut stream = std::pin::pin!(client.search(&query));
+    let mut count: u64 = 0;
+
+    while let Some(result) = stream.next().await {
+        writer.write(&result?)?;
+        count += 1;
+    }
+
+    writer.finish()?;
+    logger.log(&format!("Found {} monitor(s)", count));
+    Ok(())
+}
@@ -0,0 +1,36 @@
+//! Monitors status command implementation.
+//!
+//! Handles the `ddog monitors status` command, writing the state of each
+//! monitor group to stdout.
+
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::client::MonitorsClient;
+use ddog::error::AppError;
+use ddog::monitors::{self, MonitorStatus};
+
+/// Executes the monitors status command.
+///
+/// Writes one record per group whose state is one of `statuses` (all groups
+/// when empty).
+pub async fn run(
+    client: MonitorsClient,
+    id: i64,
+    statuses: Vec<MonitorStatus>,
+    mut writer: Box<dyn RecordWriter>,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let monitor = client.get(id).await?;
+    let groups = monitors::group_states(&monitor, &statuses);
+    for group in &groups {
+        writer.write(group)?;
+    }
+
+    writer.finish()?;
+    logger.log(&format!(
+        "Monitor {} has {} matching group(s)",
+        id,
+        groups.len()
+    ));
+    Ok(())
+}
@@ -0,0 +1,86 @@
+//! Monitors unmute command implementation.
+//!
+//! Handles the `ddog monitors unmute` command, cancelling the downtimes that
+//! mute a monitor and writing one record per cancelled downtime to stdout.
+//! Downtimes shared with other monitors are left alone unless asked for.
+
+use futures_util::StreamExt;
+use serde::Serialize;
+
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::client::MonitorsClient;
+use ddog::error::AppError;
+use ddog::monitors::mutes_only;
+
+/// A cancelled downtime.
+#[derive(Debug, Serialize)]
+struct Cancelled {
+    downtime_id: String,
+    monitor_id: i64,
+    scope: Option<String>,
+}
+
+/// Executes the monitors unmute command.
+///
+/// Cancels the downtimes that mute this monitor alone, or only those whose
+/// scope is exactly `scope`. Downtimes shared with other monitors (by tags or
+/// another monitor's ID) are skipped with a warning unless `include_shared`
+/// is set. Finding nothing to cancel is not an error.
+pub async fn run(
+    client: MonitorsClient,
+    id: i64,
+    scope: Option<String>,
+    include_shared: bool,
+    mut writer: Box<dyn RecordWriter>,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    // Collect first so cancelling does not shift the pages being read
+    let mut matches = Vec::new();
+    let mut stream = std::pin::pin!(client.downtimes(id));
+    while let Some(result) = stream.next().await {
+        let downtime = result?;
+        let downtime_scope = downtime.attributes.as_ref().and_then(|a| a.scope.clone());
+        if scope.is_some() && downtime_scope != scope {
+            continue;
+        }
+        if let Some(downtime_id) = downtime.id.flatten() {
+            matches.push((downtime_id, downtime_scope));
+        }
+    }
+
+    let mut cancelled = 0;
+    let mut shared = 0;
+    for (downtime_id, scope) in matches {
+        if !include_shared && !mutes_only(&client.downtime(&downtime_id).await?, id) {
+            logger.warn(&format!(
+                "Skipping downtime {}, which also mutes other monitors (use --include-shared to cancel it)",
+                downtime_id
+            ));
+            shared += 1;
+            continue;
+        }
+
+        logger.log(&format!("Cancelling downtime {}", downtime_id));
+        client.cancel_downtime(&downtime_id).await?;
+        writer.write(&Cancelled {
+            downtime_id,
+            monitor_id: id,
+            scope,
+        })?;
+        cancelled += 1;
+    }
+
+    if cancelled == 0 && shared == 0 {
+        match &scope {
+            Some(scope) => logger.warn(&format!(
+                "Monitor {} has no active downtime with scope {}",
+                id, scope
+            )),
+            None => logger.warn(&format!("Monitor {} is not muted", id)),
+        }
+    }
+
+    writer.finish()?;
+    Ok(())
+}
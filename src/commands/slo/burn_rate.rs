@@ -0,0 +1,74 @@
+//! SLO burn-rate command implementation.
+//!
+//! Handles the `ddog slo burn-rate` command, computing how fast an SLO spends
+//! its error budget over several windows and failing when one is too fast.
+
+use chrono::{DateTime, Utc};
+
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::client::SloClient;
+use ddog::error::AppError;
+use ddog::slo::{self, BurnRate, BurnWindow};
+use ddog::time::{ResolvedRange, TimeSpec, Zone};
+
+/// Executes the SLO burn-rate command.
+///
+/// Every window is written before the command fails, so the output shows
+/// which windows crossed their threshold.
+#[allow(clippy::too_many_arguments)]
+pub async fn run(
+    client: SloClient,
+    id: String,
+    windows: Vec<BurnWindow>,
+    threshold: f64,
+    target: Option<f64>,
+    to: TimeSpec,
+    tz: Zone,
+    now: DateTime<Utc>,
+    mut writer: Box<dyn RecordWriter>,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let target = match target {
+        Some(target) => target,
+        None => slo::target(&client.get(&id).await?, None)?,
+    };
+    logger.log(&format!("SLO {} target: {}%", id, target));
+
+    let mut breached = Vec::new();
+    for window in &windows {
+        let range = ResolvedRange::last(&window.span, &to, now, &tz)?;
+        let history = client.history(&id, &range, target).await?;
+        let sli_value = history
+            .overall
+            .and_then(|overall| overall.sli_value.flatten());
+
+        let rate = BurnRate::new(&id, window, &range, sli_value, target, threshold);
+        logger.log(&format!(
+            "Window {} ({}): burn rate {}",
+            rate.window,
+            range,
+            rate.burn_rate
+                .map_or_else(|| "unknown (no data)".to_string(), |r| format!("{:.2}", r))
+        ));
+        if rate.breached {
+            breached.push(format!(
+                "{:.2} over {} (threshold {})",
+                rate.burn_rate.unwrap_or_default(),
+                rate.window,
+                rate.threshold
+            ));
+        }
+        writer.write(&rate)?;
+    }
+
+    writer.finish()?;
+    if !breached.is_empty() {
+        return Err(AppError::Threshold(format!(
+            "SLO {} is burning its error budget too fast: {}",
+            id,
+            breached.join(", ")
+        )));
+    }
+    Ok(())
+}
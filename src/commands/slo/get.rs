@@ -0,0 +1,22 @@
+//! SLO get command implementation.
+//!
+//! Handles the `ddog slo get` command, writing a single SLO to stdout.
+
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::client::SloClient;
+use ddog::error::AppError;
+
+/// Executes the SLO get command.
+pub async fn run(
+    client: SloClient,
+    id: String,
+    mut writer: Box<dyn RecordWriter>,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    logger.log(&format!("Fetching SLO {}", id));
+    let slo = client.get(&id).await?;
+    writer.write(&slo)?;
+    writer.finish()?;
+    Ok(())
+}
@@ -0,0 +1,37 @@
+//! SLO history command implementation.
+//!
+//! Handles the `ddog slo history` command, writing the SLI and remaining error
+//! budget of an SLO and each of its groups over a time window.
+
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::client::SloClient;
+use ddog::error::AppError;
+use ddog::slo;
+use ddog::time::ResolvedRange;
+
+/// Executes the SLO history command.
+pub async fn run(
+    client: SloClient,
+    id: String,
+    range: ResolvedRange,
+    target: Option<f64>,
+    mut writer: Box<dyn RecordWriter>,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let target = match target {
+        Some(target) => target,
+        None => slo::target(&client.get(&id).await?, None)?,
+    };
+    logger.log(&format!("SLO {} target: {}%", id, target));
+
+    let history = client.history(&id, &range, target).await?;
+    let records = slo::group_history(&id, &history, target, &range);
+    for record in &records {
+        writer.write(record)?;
+    }
+
+    writer.finish()?;
+    logger.log(&format!("Returned {} group(s)", records.len()));
+    Ok(())
+}
@@ -0,0 +1,31 @@
+//! SLO list command implementation.
+//!
+//! Handles the `ddog slo list` command, streaming matching SLOs to stdout.
+
+use futures_util::StreamExt;
+
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::client::SloClient;
+use ddog::error::AppError;
+
+/// Executes the SLO list command.
+pub async fn run(
+    client: SloClient,
+    query: Option<String>,
+    tag: Option<String>,
+    mut writer: Box<dyn RecordWriter>,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let mut stream = std::pin::pin!(client.list(query, tag));
+    let mut count: u64 = 0;
+
+    while let Some(result) = stream.next().await {
+        writer.write(&result?)?;
+        count += 1;
+    }
+
+    writer.finish()?;
+    logger.log(&format!("Listed {} SLO(s)", count));
+    Ok(())
+}
@@ -0,0 +1,6 @@
+//! SLO domain commands.
+
+pub mod burn_rate;
+pub mod get;
+pub mod history;
+pub mod list;
@@ -0,0 +1,43 @@
+//! Spans aggregate command implementation.
+//!
+//! Handles the `ddog spans aggregate` command, writing one row per bucket to stdout.
+
+use crate::cli::Aggregation;
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::aggregate::AggregateRequest;
+use ddog::client::SpansClient;
+use ddog::error::AppError;
+use ddog::time::ResolvedRange;
+
+/// Executes the spans aggregate command.
+///
+/// Runs the aggregation server-side and writes the flattened buckets to stdout.
+pub async fn run(
+    client: SpansClient,
+    query: String,
+    range: ResolvedRange,
+    aggregation: Aggregation,
+    mut writer: Box<dyn RecordWriter>,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let request = AggregateRequest {
+        computes: aggregation.compute,
+        group_by: aggregation.group_by,
+        group_sort: aggregation.group_sort,
+        interval: aggregation.interval,
+    };
+
+    let rows = client
+        .aggregate(&query, &range, &request)
+        .await
+        .map_err(|e| super::map_api_error(e, &logger))?;
+
+    for row in &rows {
+        writer.write(row)?;
+    }
+    writer.finish()?;
+
+    logger.log(&format!("Returned {} bucket(s)", rows.len()));
+    Ok(())
+}
@@ -1,3 +1,29 @@
 //! Spans domain commands.
 
+pub mod aggregate;
 pub mod search;
+pub mod trace;
+
+use datadog_api_client::datadog;
+
+use crate::logging::VerboseLogger;
+use ddog::error::AppError;
+
+/// Guidance appended to 403 errors from the spans API.
+///
+/// APM permissions differ from logs, so this points at the scope that is
+/// usually missing.
+const FORBIDDEN_HINT: &str = "Your API key may not have permission to access APM spans. \
+    Note: APM spans require different permissions than logs. \
+    Ensure your API key has 'APM and Infrastructure' read permissions.";
+
+/// Converts a spans API error into an AppError, logging it in verbose mode.
+///
+/// Shared by all spans commands so they report errors consistently.
+pub(crate) fn map_api_error<T: std::fmt::Debug>(
+    e: datadog::Error<T>,
+    logger: &VerboseLogger,
+) -> AppError {
+    logger.log_error(&e.to_string(), "spans API request");
+    AppError::from_api_error(e, Some(FORBIDDEN_HINT))
+}
@@ -1,50 +1,96 @@
 //! Spans search command implementation.
 //!
 //! Handles the `ddog spans search` command, streaming APM span results to stdout.
+//! With `--parallel`, fetches time slices concurrently; with `--checkpoint`,
+//! saves progress so an interrupted export can be resumed.
 
-use futures_util::StreamExt;
+use datadog_api_client::datadog;
+use datadog_api_client::datadogV2::api_spans::ListSpansError;
+use datadog_api_client::datadogV2::model::Span;
+use futures_util::{Stream, StreamExt};
+use std::pin::Pin;
 
-use crate::cli::{Pagination, TimeRange};
+use crate::cli::{Checkpointing, Pagination, Parallel};
+use crate::commands::checkpoint;
 use crate::logging::VerboseLogger;
-use crate::output::NdjsonWriter;
-use ddog::client::SpansClient;
+use crate::output::RecordWriter;
+use ddog::checkpoint::Checkpoint;
+use ddog::client::{SliceOptions, SpansClient};
 use ddog::error::AppError;
+use ddog::time::ResolvedRange;
 
 /// Executes the spans search command.
 ///
-/// Streams matching span records to stdout as NDJSON until the limit is reached
+/// Streams matching span records to stdout until the limit is reached
 /// or all results are exhausted.
+#[allow(clippy::too_many_arguments)]
 pub async fn run(
     client: SpansClient,
     query: String,
-    time_range: TimeRange,
+    range: ResolvedRange,
     pagination: Pagination,
+    parallel: Parallel,
+    checkpointing: Checkpointing,
+    mut writer: Box<dyn RecordWriter>,
     logger: VerboseLogger,
 ) -> Result<(), AppError> {
-    let mut writer = NdjsonWriter::new();
-    let mut stream = std::pin::pin!(client.search(&query, &time_range.from, &time_range.to));
+    if let Some(path) = &checkpointing.checkpoint {
+        let fresh = Checkpoint::new(
+            "spans search",
+            &query,
+            vec![],
+            &range,
+            pagination.sort,
+            pagination.limit,
+        );
+        let saved = checkpoint::open(&checkpointing, path, fresh, writer.as_mut(), &logger)?;
+        let (range, sort) = (saved.range(), saved.sort);
+        let count = checkpoint::run(
+            path,
+            saved,
+            |cursor| client.search_page(&query, &range, sort, cursor),
+            writer,
+            &logger,
+            |e| super::map_api_error(e, &logger),
+        )
+        .await?;
+        logger.log(&format!("Returned {} span(s)", count));
+        return Ok(());
+    }
+
+    let mut stream: Pin<Box<dyn Stream<Item = Result<Span, datadog::Error<ListSpansError>>>>> =
+        if parallel.parallel > 1 {
+            let options = SliceOptions {
+                parallel: parallel.parallel.into(),
+                unordered: parallel.unordered,
+                limit: pagination.limit,
+                sort: pagination.sort,
+            };
+            logger.log(&format!(
+                "Fetching {} time slices concurrently{}",
+                options.parallel,
+                if options.unordered { ", unordered" } else { "" }
+            ));
+            Box::pin(ddog::client::sliced(
+                &range,
+                options,
+                |window| client.search(&query, &window, pagination.sort),
+                |span: &Span| {
+                    let timestamp = span
+                        .attributes
+                        .as_ref()?
+                        .start_timestamp?
+                        .timestamp_millis();
+                    Some((timestamp, span.id.clone()?))
+                },
+            ))
+        } else {
+            Box::pin(client.search(&query, &range, pagination.sort))
+        };
     let mut count: u64 = 0;
 
     while let Some(result) = stream.next().await {
-        let span = result.map_err(|e| {
-            let msg = format!("{}", e);
-            logger.log_error(&msg, "spans API request");
-
-            if msg.contains("401") {
-                AppError::Auth(format!("Authentication failed (401): Invalid API or App key. {}", msg))
-            } else if msg.contains("403") || msg.contains("Forbidden") {
-                AppError::Auth(format!(
-                    "Access denied (403): Your API key may not have permission to access APM spans. \
-                    Note: APM spans require different permissions than logs. \
-                    Ensure your API key has 'APM and Infrastructure' read permissions. {}",
-                    msg
-                ))
-            } else if msg.contains("400") || msg.contains("Bad Request") {
-                AppError::InvalidQuery(msg)
-            } else {
-                AppError::Api(msg)
-            }
-        })?;
+        let span = result.map_err(|e| super::map_api_error(e, &logger))?;
 
         writer.write(&span)?;
         count += 1;
@@ -55,70 +101,7 @@ pub async fn run(
         }
     }
 
+    writer.finish()?;
     logger.log(&format!("Returned {} span(s)", count));
     Ok(())
 }
-
-#[cfg(test)]
-mod tests {
-    use ddog::error::AppError;
-
-    fn parse_error_message(msg: &str) -> AppError {
-        if msg.contains("401") || msg.contains("403") || msg.contains("Forbidden") {
-            AppError::Auth(format!("Authentication failed: {}", msg))
-        } else if msg.contains("400") || msg.contains("Bad Request") {
-            AppError::InvalidQuery(msg.to_string())
-        } else {
-            AppError::Api(msg.to_string())
-        }
-    }
-
-    #[test]
-    fn test_error_parsing_401() {
-        let error = parse_error_message("401 Unauthorized");
-        assert!(matches!(error, AppError::Auth(_)));
-        assert_eq!(error.exit_code(), 2);
-    }
-
-    #[test]
-    fn test_error_parsing_403() {
-        let error = parse_error_message("403 Forbidden");
-        assert!(matches!(error, AppError::Auth(_)));
-        assert_eq!(error.exit_code(), 2);
-    }
-
-    #[test]
-    fn test_error_parsing_forbidden() {
-        let error = parse_error_message("Forbidden access");
-        assert!(matches!(error, AppError::Auth(_)));
-        assert_eq!(error.exit_code(), 2);
-    }
-
-    #[test]
-    fn test_error_parsing_400() {
-        let error = parse_error_message("400 Bad Request");
-        assert!(matches!(error, AppError::InvalidQuery(_)));
-        assert_eq!(error.exit_code(), 4);
-    }
-
-    #[test]
-    fn test_error_parsing_bad_request() {
-        let error = parse_error_message("Bad Request: invalid syntax");
-        assert!(matches!(error, AppError::InvalidQuery(_)));
-        assert_eq!(error.exit_code(), 4);
-    }
-
-    #[test]
-    fn test_error_parsing_generic_api_error() {
-        let error = parse_error_message("500 Internal Server Error");
-        assert!(matches!(error, AppError::Api(_)));
-        assert_eq!(error.exit_code(), 3);
-    }
-
-    #[test]
-    fn test_error_parsing_network_error() {
-        let error = parse_error_message("Connection timeout");
-        assert!(matches!(error, AppError::Api(_)));
-        assert_eq!(error.exit_code(), 3);
-    }
-}
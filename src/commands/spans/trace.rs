@@ -0,0 +1,85 @@
+//! Spans trace command implementation.
+//!
+//! Handles the `ddog spans trace` command, rebuilding a trace from its spans and
+//! printing it as a waterfall or as annotated span records.
+
+use futures_util::StreamExt;
+use std::io::{self, Write};
+
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::client::SpansClient;
+use ddog::error::AppError;
+use ddog::time::ResolvedRange;
+use ddog::trace::{self, TraceSpan};
+
+/// Executes the spans trace command.
+///
+/// Fetches all spans for the trace, rebuilds the hierarchy and writes either the
+/// waterfall or, when `records` is set, one annotated record per span to stdout.
+pub async fn run(
+    client: SpansClient,
+    trace_id: String,
+    range: ResolvedRange,
+    records: bool,
+    mut writer: Box<dyn RecordWriter>,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let mut stream = std::pin::pin!(client.trace(&trace_id, &range));
+    let mut spans = Vec::new();
+    let mut skipped: u64 = 0;
+
+    while let Some(result) = stream.next().await {
+        let span = result.map_err(|e| super::map_api_error(e, &logger))?;
+        match TraceSpan::from_span(&span) {
+            Some(span) => spans.push(span),
+            None => skipped += 1,
+        }
+    }
+
+    if skipped > 0 {
+        logger.log(&format!("Skipped {} span(s) without a span ID", skipped));
+    }
+    if spans.is_empty() {
+        return Err(AppError::InvalidQuery(format!(
+            "No spans found for trace {} between {}. Try a wider --from.",
+            trace_id, range
+        )));
+    }
+
+    let trace = trace::build_trace(spans);
+    let orphans = trace.orphan_count();
+    if orphans > 0 {
+        logger.log(&format!(
+            "{} span(s) reference a parent that was not found",
+            orphans
+        ));
+    }
+
+    if records {
+        for node in &trace.nodes {
+            let mut record = match &node.span.raw {
+                serde_json::Value::Object(map) => map.clone(),
+                _ => serde_json::Map::new(),
+            };
+            record.insert("depth".into(), node.depth.into());
+            record.insert("children_count".into(), node.children_count.into());
+            record.insert("self_time".into(), node.self_time_ns.into());
+            record.insert("orphan".into(), node.orphan.into());
+            writer.write(&record)?;
+        }
+        writer.finish()?;
+    } else {
+        let mut stdout = io::stdout().lock();
+        for line in trace::render_waterfall(&trace) {
+            writeln!(stdout, "{}", line)?;
+        }
+        stdout.flush()?;
+    }
+
+    logger.log(&format!(
+        "Reconstructed trace with {} span(s)",
+        trace.nodes.len()
+    ));
+    Ok(())
+}
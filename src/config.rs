@@ -1,14 +1,509 @@
-//! Configuration loading from environment variables.
+//! Configuration loading from environment variables and profile files.
 //!
 //! Validates that required Datadog credentials are set before creating
 //! the API client configuration.
+//!
+//! Settings can come from four places, in order of precedence:
+//!
+//! 1. **Flag**: a profile selected explicitly with `--profile`
+//! 2. **Environment**: `DD_API_KEY`, `DD_APP_KEY`, `DD_SITE`
+//! 3. **Profile**: the default profile from the config file (or `DDOG_PROFILE`)
+//! 4. **Defaults**: `datadoghq.com`, all indexes
+//!
+//! The config file lives at `~/.config/ddog/config.toml` (or under
+//! `$XDG_CONFIG_HOME`), and can be overridden with `DDOG_CONFIG`:
+//!
+//! ```toml
+//! default_profile = "us1-prod"
+//!
+//! [profiles.us1-prod]
+//! site = "datadoghq.com"
+//! api_key_env = "DD_US1_API_KEY"
+//! app_key_env = "DD_US1_APP_KEY"
+//! default_indexes = ["main"]
+//!
+//! [profiles.us1-prod.retention_days]
+//! logs = 30
+//!
+//! [profiles.eu]
+//! site = "datadoghq.eu"
+//! api_key_env = "DD_EU_API_KEY"
+//! app_key_env = "DD_EU_APP_KEY"
+//! ```
+//!
+//! `DDOG_API_URL` sends all API requests to a fixed base URL instead of the
+//! site's API host, e.g. a proxy or a local stand-in server for tests.
 
-use datadog_api_client::datadog::Configuration;
+use datadog_api_client::datadog::{APIKey, Configuration};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
 use crate::error::AppError;
 
+/// Environment variable overriding the config file location.
+pub const CONFIG_PATH_ENV: &str = "DDOG_CONFIG";
+
+/// Environment variable selecting a profile when `--profile` is not given.
+pub const PROFILE_ENV: &str = "DDOG_PROFILE";
+
+/// Environment variable overriding the API base URL (e.g. `http://127.0.0.1:8080`).
+pub const API_URL_ENV: &str = "DDOG_API_URL";
+
+/// Default Datadog site when neither the environment nor a profile sets one.
+pub const DEFAULT_SITE: &str = "datadoghq.com";
+
+/// Contents of the `config.toml` file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConfigFile {
+    /// Profile used when neither `--profile` nor `DDOG_PROFILE` is set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_profile: Option<String>,
+
+    /// Named profiles, e.g. one per Datadog organization
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+/// A named set of connection settings for one Datadog organization.
+///
+/// Keys are best referenced through environment variables (`api_key_env`) so
+/// the file itself holds no secrets; literal keys are accepted as a fallback.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Profile {
+    /// Datadog site (e.g. "datadoghq.com", "datadoghq.eu", "us5.datadoghq.com")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub site: Option<String>,
+
+    /// Name of the environment variable holding the API key
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key_env: Option<String>,
+
+    /// Literal API key (prefer `api_key_env`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+
+    /// Name of the environment variable holding the application key
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub app_key_env: Option<String>,
+
+    /// Literal application key (prefer `app_key_env`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub app_key: Option<String>,
+
+    /// Log indexes searched when `--indexes` is not given
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_indexes: Option<Vec<String>>,
+
+    /// Data retention of the organization, if it differs from the defaults
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retention_days: Option<Retention>,
+}
+
+/// How many days of data Datadog keeps, per product.
+///
+/// Ranges reaching further back are still queried, with a warning that older
+/// results may be missing. Unset products keep the default retention.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Retention {
+    pub logs: u32,
+    pub spans: u32,
+    pub metrics: u32,
+}
+
+impl Default for Retention {
+    /// Datadog's standard retention: 15 days of logs and indexed spans, 15 months of metrics.
+    fn default() -> Self {
+        Self {
+            logs: 15,
+            spans: 15,
+            metrics: 455,
+        }
+    }
+}
+
+impl Profile {
+    fn api_key(&self, env: &dyn Fn(&str) -> Option<String>) -> Option<String> {
+        resolve_key(self.api_key_env.as_deref(), self.api_key.as_deref(), env)
+    }
+
+    fn app_key(&self, env: &dyn Fn(&str) -> Option<String>) -> Option<String> {
+        resolve_key(self.app_key_env.as_deref(), self.app_key.as_deref(), env)
+    }
+}
+
+fn resolve_key(
+    env_name: Option<&str>,
+    literal: Option<&str>,
+    env: &dyn Fn(&str) -> Option<String>,
+) -> Option<String> {
+    env_name
+        .and_then(env)
+        .or_else(|| literal.map(str::to_string))
+}
+
+/// Where a resolved setting came from, for diagnostics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Source {
+    Flag,
+    Env,
+    Profile,
+    Default,
+}
+
+impl std::fmt::Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Source::Flag => "flag",
+            Source::Env => "env",
+            Source::Profile => "profile",
+            Source::Default => "default",
+        })
+    }
+}
+
+/// Fully resolved connection settings for one invocation.
+#[derive(Debug, Clone)]
+pub struct Settings {
+    /// Name of the profile in use, if any
+    pub profile: Option<String>,
+    pub site: String,
+    pub site_source: Source,
+    pub api_key: String,
+    pub api_key_source: Source,
+    pub app_key: String,
+    pub app_key_source: Source,
+    /// Default log indexes from the profile, if configured
+    pub default_indexes: Option<Vec<String>>,
+    /// Data retention from the profile, or the defaults
+    pub retention: Retention,
+    /// API base URL overriding the site, from `DDOG_API_URL`
+    pub api_url: Option<String>,
+}
+
+impl Settings {
+    /// Resolves settings from the config file and process environment.
+    ///
+    /// # Arguments
+    ///
+    /// * `profile_flag` - Profile name given with `--profile`, if any
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Config` if the config file is invalid, the requested
+    /// profile does not exist, or no API/App key can be found.
+    pub fn load(profile_flag: Option<&str>) -> Result<Self, AppError> {
+        let file = load_config_file()?;
+        Self::resolve(&file, profile_flag, &|name| std::env::var(name).ok())
+    }
+
+    /// Resolves settings from an already-loaded config file and an environment lookup.
+    pub fn resolve(
+        file: &ConfigFile,
+        profile_flag: Option<&str>,
+        env: &dyn Fn(&str) -> Option<String>,
+    ) -> Result<Self, AppError> {
+        let partial = PartialSettings::resolve(file, profile_flag, env)?;
+
+        let profile_hint = match &partial.profile {
+            Some(name) => format!(" and profile '{}' does not provide one", name),
+            None => String::new(),
+        };
+        let (api_key, api_key_source) = partial.api_key.ok_or_else(|| {
+            AppError::Config(format!(
+                "DD_API_KEY environment variable not set{}",
+                profile_hint
+            ))
+        })?;
+        let (app_key, app_key_source) = partial.app_key.ok_or_else(|| {
+            AppError::Config(format!(
+                "DD_APP_KEY environment variable not set{}",
+                profile_hint
+            ))
+        })?;
+
+        if api_key.is_empty() {
+            return Err(AppError::Config("DD_API_KEY is empty".into()));
+        }
+        if app_key.is_empty() {
+            return Err(AppError::Config("DD_APP_KEY is empty".into()));
+        }
+
+        Ok(Settings {
+            profile: partial.profile,
+            site: partial.site,
+            site_source: partial.site_source,
+            api_key,
+            api_key_source,
+            app_key,
+            app_key_source,
+            default_indexes: partial.default_indexes,
+            retention: partial.retention,
+            api_url: partial.api_url,
+        })
+    }
+
+    /// Builds the Datadog SDK configuration for these settings.
+    pub fn to_configuration(&self) -> Configuration {
+        let mut config = Configuration::new();
+        config.set_auth_key(
+            "apiKeyAuth",
+            APIKey {
+                key: self.api_key.clone(),
+                prefix: "".to_owned(),
+            },
+        );
+        config.set_auth_key(
+            "appKeyAuth",
+            APIKey {
+                key: self.app_key.clone(),
+                prefix: "".to_owned(),
+            },
+        );
+        config
+            .server_variables
+            .insert("site".into(), self.site.clone());
+
+        // Server index 1 is the SDK's free-form "{protocol}://{name}" server
+        if let Some(url) = &self.api_url {
+            let (protocol, name) = split_api_url(url);
+            config.server_index = 1;
+            config
+                .server_variables
+                .insert("protocol".into(), protocol.to_string());
+            config
+                .server_variables
+                .insert("name".into(), name.to_string());
+        }
+        config
+    }
+}
+
+/// Settings resolved without requiring the API and application keys.
+///
+/// Lets `config show` report what is configured while keys are missing;
+/// [`Settings::resolve`] fails instead.
+#[derive(Debug, Clone)]
+pub struct PartialSettings {
+    /// Name of the profile in use, if any
+    pub profile: Option<String>,
+    pub site: String,
+    pub site_source: Source,
+    /// API key and where it came from, if one is set
+    pub api_key: Option<(String, Source)>,
+    /// Application key and where it came from, if one is set
+    pub app_key: Option<(String, Source)>,
+    /// Default log indexes from the profile, if configured
+    pub default_indexes: Option<Vec<String>>,
+    /// Data retention from the profile, or the defaults
+    pub retention: Retention,
+    /// API base URL overriding the site, from `DDOG_API_URL`
+    pub api_url: Option<String>,
+}
+
+impl PartialSettings {
+    /// Resolves settings from the config file and process environment.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Config` if the config file is invalid or the
+    /// requested profile does not exist.
+    pub fn load(profile_flag: Option<&str>) -> Result<Self, AppError> {
+        let file = load_config_file()?;
+        Self::resolve(&file, profile_flag, &|name| std::env::var(name).ok())
+    }
+
+    /// Resolves settings from an already-loaded config file and an environment lookup.
+    pub fn resolve(
+        file: &ConfigFile,
+        profile_flag: Option<&str>,
+        env: &dyn Fn(&str) -> Option<String>,
+    ) -> Result<Self, AppError> {
+        let env_profile = env(PROFILE_ENV).filter(|p| !p.is_empty());
+        let profile_name = profile_flag
+            .map(str::to_string)
+            .or(env_profile)
+            .or_else(|| file.default_profile.clone());
+
+        let profile = match &profile_name {
+            Some(name) => Some(file.profiles.get(name).ok_or_else(|| {
+                AppError::Config(format!(
+                    "Profile '{}' not found in {}. Available profiles: {}",
+                    name,
+                    config_path_display(),
+                    profile_names(file)
+                ))
+            })?),
+            None => None,
+        };
+
+        // A profile chosen explicitly with --profile wins over the environment;
+        // otherwise environment variables override the default profile.
+        let profile_source = if profile_flag.is_some() {
+            Source::Flag
+        } else {
+            Source::Profile
+        };
+        let pick = |from_profile: Option<String>, env_name: &str| {
+            let from_env = env(env_name);
+            match (profile_source, from_profile, from_env) {
+                (Source::Flag, Some(value), _) => Some((value, Source::Flag)),
+                (_, _, Some(value)) => Some((value, Source::Env)),
+                (_, Some(value), None) => Some((value, profile_source)),
+                (_, None, None) => None,
+            }
+        };
+
+        let (site, site_source) = pick(profile.and_then(|p| p.site.clone()), "DD_SITE")
+            .unwrap_or_else(|| (DEFAULT_SITE.to_string(), Source::Default));
+
+        Ok(PartialSettings {
+            site,
+            site_source,
+            api_key: pick(profile.and_then(|p| p.api_key(env)), "DD_API_KEY"),
+            app_key: pick(profile.and_then(|p| p.app_key(env)), "DD_APP_KEY"),
+            default_indexes: profile.and_then(|p| p.default_indexes.clone()),
+            retention: profile.and_then(|p| p.retention_days).unwrap_or_default(),
+            api_url: env(API_URL_ENV).filter(|url| !url.is_empty()),
+            profile: profile_name,
+        })
+    }
+}
+
+/// Splits an API base URL into protocol and host (with port), defaulting to https.
+fn split_api_url(url: &str) -> (&str, &str) {
+    let (protocol, rest) = url.split_once("://").unwrap_or(("https", url));
+    (protocol, rest.trim_end_matches('/'))
+}
+
+/// Returns the config file path: `$DDOG_CONFIG`, else `$XDG_CONFIG_HOME/ddog/config.toml`,
+/// else `~/.config/ddog/config.toml`.
+pub fn config_path() -> Option<PathBuf> {
+    if let Ok(path) = std::env::var(CONFIG_PATH_ENV)
+        && !path.is_empty()
+    {
+        return Some(PathBuf::from(path));
+    }
+
+    let base = std::env::var("XDG_CONFIG_HOME")
+        .ok()
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var("HOME")
+                .ok()
+                .map(|home| Path::new(&home).join(".config"))
+        })?;
+
+    Some(base.join("ddog").join("config.toml"))
+}
+
+fn config_path_display() -> String {
+    config_path()
+        .map(|p| p.display().to_string())
+        .unwrap_or_else(|| "config file".to_string())
+}
+
+fn profile_names(file: &ConfigFile) -> String {
+    if file.profiles.is_empty() {
+        "(none)".to_string()
+    } else {
+        file.profiles.keys().cloned().collect::<Vec<_>>().join(", ")
+    }
+}
+
+/// Loads the config file, returning an empty configuration if it does not exist.
+///
+/// # Errors
+///
+/// Returns `AppError::Config` if the file exists but cannot be read or parsed.
+pub fn load_config_file() -> Result<ConfigFile, AppError> {
+    let Some(path) = config_path() else {
+        return Ok(ConfigFile::default());
+    };
+
+    match std::fs::read_to_string(&path) {
+        Ok(contents) => toml::from_str(&contents).map_err(|e| {
+            AppError::Config(format!("Invalid config file {}: {}", path.display(), e))
+        }),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(ConfigFile::default()),
+        Err(e) => Err(AppError::Config(format!(
+            "Failed to read config file {}: {}",
+            path.display(),
+            e
+        ))),
+    }
+}
+
+/// Sets `default_profile` in the config file, creating the file if needed.
+///
+/// Only that setting changes; comments, profile order and formatting in the
+/// rest of the file are kept as written.
+///
+/// # Errors
+///
+/// Returns `AppError::Config` if the file cannot be read or parsed.
+pub fn set_default_profile(name: &str) -> Result<PathBuf, AppError> {
+    let path = config_path()
+        .ok_or_else(|| AppError::Config("Cannot determine config file location".into()))?;
+
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => {
+            return Err(AppError::Config(format!(
+                "Failed to read config file {}: {}",
+                path.display(),
+                e
+            )));
+        }
+    };
+    let contents = with_default_profile(&contents, name)
+        .map_err(|e| AppError::Config(format!("Invalid config file {}: {}", path.display(), e)))?;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, contents)?;
+
+    Ok(path)
+}
+
+/// Returns config file contents with `default_profile` set to `name`.
+fn with_default_profile(contents: &str, name: &str) -> Result<String, toml_edit::TomlError> {
+    let mut document: toml_edit::DocumentMut = contents.parse()?;
+    match document
+        .get_mut("default_profile")
+        .and_then(|item| item.as_value_mut())
+    {
+        // Keep the spacing and any comment after the old value
+        Some(value) => {
+            let decor = value.decor().clone();
+            *value = name.into();
+            *value.decor_mut() = decor;
+        }
+        None => document["default_profile"] = toml_edit::value(name),
+    }
+    Ok(document.to_string())
+}
+
+/// Masks a secret for display, keeping only the last four characters.
+pub fn redact(secret: &str) -> String {
+    let chars: Vec<char> = secret.chars().collect();
+    if chars.len() <= 8 {
+        "****".to_string()
+    } else {
+        let tail: String = chars[chars.len() - 4..].iter().collect();
+        format!("****{}", tail)
+    }
+}
+
 /// Loads and validates Datadog configuration from environment variables.
 ///
+/// Ignores the config file; use [`Settings::load`] for profile-aware loading.
+///
 /// # Required Environment Variables
 ///
 /// - `DD_API_KEY` - Datadog API key
@@ -22,23 +517,15 @@ use crate::error::AppError;
 ///
 /// Returns `AppError::Config` if required environment variables are missing or empty.
 pub fn load_config() -> Result<Configuration, AppError> {
-    let api_key = std::env::var("DD_API_KEY")
-        .map_err(|_| AppError::Config("DD_API_KEY environment variable not set".into()))?;
-
-    let app_key = std::env::var("DD_APP_KEY")
-        .map_err(|_| AppError::Config("DD_APP_KEY environment variable not set".into()))?;
-
-    if api_key.is_empty() {
-        return Err(AppError::Config("DD_API_KEY is empty".into()));
-    }
-    if app_key.is_empty() {
-        return Err(AppError::Config("DD_APP_KEY is empty".into()));
-    }
-
-    // DD_SITE is optional - the SDK reads it automatically
-    // Defaults to datadoghq.com if not set
-
-    Ok(Configuration::new())
+    // Profiles live in the config file, so DDOG_PROFILE has nothing to select here
+    let settings = Settings::resolve(&ConfigFile::default(), None, &|name| {
+        if name == PROFILE_ENV {
+            None
+        } else {
+            std::env::var(name).ok()
+        }
+    })?;
+    Ok(settings.to_configuration())
 }
 
 #[cfg(test)]
@@ -252,4 +739,203 @@ mod tests {
             },
         );
     }
+
+    fn sample_file() -> ConfigFile {
+        toml::from_str(
+            r#"
+default_profile = "prod"
+
+[profiles.prod]
+site = "datadoghq.com"
+api_key_env = "PROD_API_KEY"
+app_key_env = "PROD_APP_KEY"
+default_indexes = ["main"]
+
+[profiles.prod.retention_days]
+logs = 30
+
+[profiles.eu]
+site = "datadoghq.eu"
+api_key = "eu-api-key-literal"
+app_key = "eu-app-key-literal"
+"#,
+        )
+        .unwrap()
+    }
+
+    fn env_from(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: Vec<(String, String)> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |name| vars.iter().find(|(k, _)| k == name).map(|(_, v)| v.clone())
+    }
+
+    #[test]
+    fn test_resolve_default_profile() {
+        let env = env_from(&[("PROD_API_KEY", "prod-api"), ("PROD_APP_KEY", "prod-app")]);
+        let settings = Settings::resolve(&sample_file(), None, &env).unwrap();
+
+        assert_eq!(settings.profile.as_deref(), Some("prod"));
+        assert_eq!(settings.site, "datadoghq.com");
+        assert_eq!(settings.api_key, "prod-api");
+        assert_eq!(settings.api_key_source, Source::Profile);
+        assert_eq!(settings.default_indexes, Some(vec!["main".to_string()]));
+    }
+
+    #[test]
+    fn test_resolve_env_overrides_default_profile() {
+        let env = env_from(&[
+            ("PROD_API_KEY", "prod-api"),
+            ("PROD_APP_KEY", "prod-app"),
+            ("DD_API_KEY", "env-api"),
+            ("DD_SITE", "us5.datadoghq.com"),
+        ]);
+        let settings = Settings::resolve(&sample_file(), None, &env).unwrap();
+
+        assert_eq!(settings.api_key, "env-api");
+        assert_eq!(settings.api_key_source, Source::Env);
+        assert_eq!(settings.app_key, "prod-app");
+        assert_eq!(settings.site, "us5.datadoghq.com");
+        assert_eq!(settings.site_source, Source::Env);
+    }
+
+    #[test]
+    fn test_resolve_flag_overrides_env() {
+        let env = env_from(&[
+            ("DD_API_KEY", "env-api"),
+            ("DD_APP_KEY", "env-app"),
+            ("DD_SITE", "datadoghq.com"),
+        ]);
+        let settings = Settings::resolve(&sample_file(), Some("eu"), &env).unwrap();
+
+        assert_eq!(settings.profile.as_deref(), Some("eu"));
+        assert_eq!(settings.site, "datadoghq.eu");
+        assert_eq!(settings.site_source, Source::Flag);
+        assert_eq!(settings.api_key, "eu-api-key-literal");
+        assert_eq!(settings.default_indexes, None);
+    }
+
+    #[test]
+    fn test_resolve_profile_from_env_var() {
+        let env = env_from(&[(PROFILE_ENV, "eu")]);
+        let settings = Settings::resolve(&sample_file(), None, &env).unwrap();
+        assert_eq!(settings.profile.as_deref(), Some("eu"));
+        assert_eq!(settings.api_key_source, Source::Profile);
+    }
+
+    #[test]
+    fn test_resolve_unknown_profile() {
+        let env = env_from(&[]);
+        let result = Settings::resolve(&sample_file(), Some("staging"), &env);
+        match result {
+            Err(AppError::Config(msg)) => {
+                assert!(msg.contains("staging"));
+                assert!(msg.contains("eu, prod"));
+            }
+            other => panic!("Expected Config error, got: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_resolve_defaults_without_file() {
+        let env = env_from(&[("DD_API_KEY", "a"), ("DD_APP_KEY", "b")]);
+        let settings = Settings::resolve(&ConfigFile::default(), None, &env).unwrap();
+        assert_eq!(settings.profile, None);
+        assert_eq!(settings.site, DEFAULT_SITE);
+        assert_eq!(settings.site_source, Source::Default);
+        assert_eq!(settings.retention, Retention::default());
+    }
+
+    #[test]
+    fn test_resolve_partial_retention_keeps_defaults() {
+        let env = env_from(&[("PROD_API_KEY", "a"), ("PROD_APP_KEY", "b")]);
+        let settings = Settings::resolve(&sample_file(), None, &env).unwrap();
+        assert_eq!(settings.retention.logs, 30);
+        assert_eq!(settings.retention.spans, 15);
+        assert_eq!(settings.retention.metrics, 455);
+    }
+
+    #[test]
+    fn test_resolve_missing_key_mentions_profile() {
+        let env = env_from(&[]);
+        let result = Settings::resolve(&sample_file(), None, &env);
+        match result {
+            Err(AppError::Config(msg)) => {
+                assert!(msg.contains("DD_API_KEY"));
+                assert!(msg.contains("prod"));
+            }
+            other => panic!("Expected Config error, got: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_partial_resolve_without_keys() {
+        let env = env_from(&[("DD_APP_KEY", "env-app")]);
+        let settings = PartialSettings::resolve(&sample_file(), None, &env).unwrap();
+        assert_eq!(settings.profile.as_deref(), Some("prod"));
+        assert_eq!(settings.api_key, None);
+        assert_eq!(settings.app_key, Some(("env-app".to_string(), Source::Env)));
+        assert_eq!(settings.retention.logs, 30);
+
+        assert!(PartialSettings::resolve(&sample_file(), Some("staging"), &env).is_err());
+    }
+
+    #[test]
+    fn test_resolve_api_url_override() {
+        let env = env_from(&[
+            ("DD_API_KEY", "a"),
+            ("DD_APP_KEY", "b"),
+            (API_URL_ENV, "http://127.0.0.1:8080"),
+        ]);
+        let settings = Settings::resolve(&ConfigFile::default(), None, &env).unwrap();
+        assert_eq!(settings.api_url.as_deref(), Some("http://127.0.0.1:8080"));
+
+        let config = settings.to_configuration();
+        assert_eq!(config.server_index, 1);
+        assert_eq!(config.server_variables["protocol"], "http");
+        assert_eq!(config.server_variables["name"], "127.0.0.1:8080");
+    }
+
+    #[test]
+    fn test_split_api_url() {
+        assert_eq!(
+            split_api_url("http://localhost:9000/"),
+            ("http", "localhost:9000")
+        );
+        assert_eq!(split_api_url("proxy.internal"), ("https", "proxy.internal"));
+    }
+
+    #[test]
+    fn test_with_default_profile_keeps_comments_and_order() {
+        let contents = r#"# team config
+default_profile = "prod" # the usual one
+
+[profiles.prod]
+site = "datadoghq.com"
+
+# EU org
+[profiles.eu]
+site = "datadoghq.eu"
+"#;
+        let edited = with_default_profile(contents, "eu").unwrap();
+        assert_eq!(
+            edited,
+            contents.replace(r#""prod" # the usual one"#, r#""eu" # the usual one"#)
+        );
+
+        let added = with_default_profile("[profiles.eu]\nsite = \"datadoghq.eu\"\n", "eu").unwrap();
+        assert!(added.starts_with("default_profile = \"eu\"\n"));
+        assert_eq!(
+            with_default_profile("", "eu").unwrap(),
+            "default_profile = \"eu\"\n"
+        );
+        assert!(with_default_profile("[profiles", "eu").is_err());
+    }
+
+    #[test]
+    fn test_redact() {
+        assert_eq!(redact("short"), "****");
+        assert_eq!(redact("abcdef0123456789"), "****6789");
+    }
 }
@@ -0,0 +1,309 @@
+//! Dashboard backups and widget-level comparison.
+//!
+//! Dashboards are backed up as JSON without the fields Datadog manages itself
+//! (ID, author, timestamps, URL) and with widget IDs removed, so a backup can
+//! be imported into another org. Keys are sorted and widgets placed on a grid
+//! are ordered by position, so exporting an unchanged dashboard gives the same
+//! file every time.
+
+use datadog_api_client::datadogV1::model::Dashboard;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::diff::{Change, diff, remove_nulls};
+use crate::error::AppError;
+
+/// Fields set by Datadog that are not part of a dashboard's definition.
+const SERVER_MANAGED: &[&str] = &[
+    "author_handle",
+    "author_name",
+    "created_at",
+    "id",
+    "is_read_only",
+    "modified_at",
+    "url",
+];
+
+/// Target of dashboard-level differences (title, template variables, ...).
+const DASHBOARD_TARGET: &str = "dashboard";
+
+/// Converts a dashboard into its backup form.
+///
+/// Server-managed fields, widget IDs and null fields are removed. Widgets that
+/// all have a grid position are ordered top to bottom, then left to right;
+/// other widget lists keep their order, which is their layout.
+pub fn normalize(dashboard: &Dashboard) -> Result<Value, AppError> {
+    let mut value = serde_json::to_value(dashboard)?;
+    remove_nulls(&mut value);
+    if let Value::Object(fields) = &mut value {
+        for field in SERVER_MANAGED {
+            fields.remove(*field);
+        }
+        if let Some(Value::Array(widgets)) = fields.get_mut("widgets") {
+            normalize_widgets(widgets);
+        }
+    }
+    Ok(value)
+}
+
+fn normalize_widgets(widgets: &mut [Value]) {
+    for widget in widgets.iter_mut() {
+        let Value::Object(fields) = widget else {
+            continue;
+        };
+        fields.remove("id");
+        // Group and powerpack widgets hold widgets of their own
+        if let Some(Value::Array(children)) = fields
+            .get_mut("definition")
+            .and_then(|definition| definition.get_mut("widgets"))
+        {
+            normalize_widgets(children);
+        }
+    }
+
+    let positions: Option<Vec<(i64, i64)>> = widgets.iter().map(position).collect();
+    if positions.is_some() {
+        widgets.sort_by_key(|widget| position(widget).unwrap_or_default());
+    }
+}
+
+/// Returns a widget's grid position as (y, x).
+fn position(widget: &Value) -> Option<(i64, i64)> {
+    let layout = widget.get("layout")?;
+    Some((layout.get("y")?.as_i64()?, layout.get("x")?.as_i64()?))
+}
+
+/// Renders a normalized dashboard as pretty-printed JSON with a final newline.
+pub fn render(dashboard: &Value) -> Result<String, AppError> {
+    Ok(format!("{}\n", serde_json::to_string_pretty(dashboard)?))
+}
+
+/// Reads a dashboard file, checking it against the API model.
+///
+/// The file may be a backup or a dashboard as returned by the API; either way
+/// the result is normalized, ready to be created in any org.
+pub fn load(path: &Path) -> Result<Dashboard, AppError> {
+    let invalid = |e: serde_json::Error| {
+        AppError::Config(format!("Invalid dashboard {}: {}", path.display(), e))
+    };
+    let contents = std::fs::read_to_string(path).map_err(|e| {
+        AppError::Config(format!(
+            "Failed to read dashboard {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
+    let dashboard: Dashboard = serde_json::from_str(&contents).map_err(invalid)?;
+    serde_json::from_value(normalize(&dashboard)?).map_err(invalid)
+}
+
+/// How a widget, or the dashboard itself, differs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// The changes to one widget, or to the dashboard's own fields.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Difference {
+    /// `dashboard`, or the widget's type and title, e.g. `timeseries "CPU"`
+    pub target: String,
+    pub status: Status,
+    pub changes: Vec<Change>,
+}
+
+/// Compares two normalized dashboards widget by widget.
+///
+/// Widgets are matched by type and title; widgets sharing both are matched in
+/// order. Changes are reported from `current` to `wanted`: the dashboard's own
+/// fields first, then widgets in `wanted` order, then removed widgets.
+pub fn compare(current: &Value, wanted: &Value) -> Vec<Difference> {
+    let mut differences = Vec::new();
+
+    let changes = diff(&without_widgets(current), &without_widgets(wanted));
+    if !changes.is_empty() {
+        differences.push(Difference {
+            target: DASHBOARD_TARGET.to_string(),
+            status: Status::Changed,
+            changes,
+        });
+    }
+
+    let current_widgets = keyed_widgets(current);
+    let wanted_widgets = keyed_widgets(wanted);
+    let empty = Value::Object(Map::new());
+
+    for (key, widget) in &wanted_widgets {
+        let (status, changes) = match current_widgets.iter().find(|(k, _)| k == key) {
+            Some((_, existing)) => (Status::Changed, diff(existing, widget)),
+            None => (Status::Added, diff(&empty, widget)),
+        };
+        if !changes.is_empty() {
+            differences.push(Difference {
+                target: key.clone(),
+                status,
+                changes,
+            });
+        }
+    }
+    for (key, widget) in &current_widgets {
+        if !wanted_widgets.iter().any(|(k, _)| k == key) {
+            differences.push(Difference {
+                target: key.clone(),
+                status: Status::Removed,
+                changes: diff(widget, &empty),
+            });
+        }
+    }
+
+    differences
+}
+
+fn without_widgets(dashboard: &Value) -> Value {
+    let mut dashboard = dashboard.clone();
+    if let Value::Object(fields) = &mut dashboard {
+        fields.remove("widgets");
+    }
+    dashboard
+}
+
+/// Names each top-level widget by type and title, numbering repeats.
+fn keyed_widgets(dashboard: &Value) -> Vec<(String, &Value)> {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    dashboard
+        .get("widgets")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .map(|widget| {
+            let definition = widget.get("definition");
+            let kind = definition
+                .and_then(|d| d.get("type"))
+                .and_then(Value::as_str)
+                .unwrap_or("widget");
+            let mut key = match definition
+                .and_then(|d| d.get("title"))
+                .and_then(Value::as_str)
+            {
+                Some(title) => format!("{} \"{}\"", kind, title),
+                None => kind.to_string(),
+            };
+            let count = seen.entry(key.clone()).or_default();
+            *count += 1;
+            if *count > 1 {
+                key = format!("{} #{}", key, count);
+            }
+            (key, widget)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn note(id: i64, content: &str, x: i64, y: i64) -> Value {
+        json!({
+            "id": id,
+            "definition": {"type": "note", "content": content},
+            "layout": {"x": x, "y": y, "width": 4, "height": 2}
+        })
+    }
+
+    fn timeseries(title: &str, query: &str) -> Value {
+        json!({
+            "definition": {
+                "type": "timeseries",
+                "title": title,
+                "requests": [{"q": query}]
+            }
+        })
+    }
+
+    fn dashboard(widgets: Vec<Value>) -> Value {
+        json!({"title": "Service", "layout_type": "ordered", "widgets": widgets})
+    }
+
+    #[test]
+    fn test_normalize_strips_ids_and_orders_by_position() {
+        let value = json!({
+            "id": "abc-def-ghi",
+            "title": "Service",
+            "layout_type": "free",
+            "author_handle": "someone@example.com",
+            "created_at": "2024-01-15T10:00:00Z",
+            "url": "/dashboard/abc-def-ghi/service",
+            "widgets": [note(2, "second", 0, 2), note(1, "first", 4, 0)]
+        });
+        let dashboard: Dashboard = serde_json::from_value(value).unwrap();
+        let normalized = normalize(&dashboard).unwrap();
+
+        assert!(normalized.get("id").is_none());
+        assert!(normalized.get("author_handle").is_none());
+        assert!(normalized.get("url").is_none());
+        let widgets = normalized["widgets"].as_array().unwrap();
+        assert_eq!(widgets[0]["definition"]["content"], "first");
+        assert!(widgets[0].get("id").is_none());
+
+        // Exporting the normalized form again changes nothing
+        let again: Dashboard = serde_json::from_value(normalized.clone()).unwrap();
+        assert_eq!(normalize(&again).unwrap(), normalized);
+    }
+
+    #[test]
+    fn test_normalize_keeps_order_without_positions() {
+        let dashboard: Dashboard = serde_json::from_value(dashboard(vec![
+            timeseries("B", "avg:b{*}"),
+            timeseries("A", "avg:a{*}"),
+        ]))
+        .unwrap();
+        let normalized = normalize(&dashboard).unwrap();
+        assert_eq!(normalized["widgets"][0]["definition"]["title"], "B");
+    }
+
+    #[test]
+    fn test_compare_reports_widget_changes() {
+        let current = dashboard(vec![
+            timeseries("CPU", "avg:system.cpu.user{*}"),
+            timeseries("Memory", "avg:system.mem.used{*}"),
+        ]);
+        let mut wanted = dashboard(vec![
+            timeseries("CPU", "max:system.cpu.user{*}"),
+            timeseries("Disk", "avg:system.disk.in_use{*}"),
+        ]);
+        wanted["title"] = json!("Service v2");
+
+        let differences = compare(&current, &wanted);
+        let summary: Vec<(&str, Status)> = differences
+            .iter()
+            .map(|d| (d.target.as_str(), d.status))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("dashboard", Status::Changed),
+                ("timeseries \"CPU\"", Status::Changed),
+                ("timeseries \"Disk\"", Status::Added),
+                ("timeseries \"Memory\"", Status::Removed),
+            ]
+        );
+        assert_eq!(differences[1].changes[0].path, "definition.requests");
+    }
+
+    #[test]
+    fn test_compare_numbers_repeated_widgets() {
+        let current = dashboard(vec![timeseries("CPU", "a"), timeseries("CPU", "b")]);
+        let wanted = dashboard(vec![timeseries("CPU", "a"), timeseries("CPU", "c")]);
+
+        let differences = compare(&current, &wanted);
+        assert_eq!(differences.len(), 1);
+        assert_eq!(differences[0].target, "timeseries \"CPU\" #2");
+        assert!(compare(&current, &current).is_empty());
+    }
+}
@@ -0,0 +1,139 @@
+//! Field-level differences between JSON documents.
+//!
+//! Used to show what applying a monitor definition or importing a dashboard
+//! would change. Objects are compared field by field, recursively; other
+//! values, including lists, are compared whole. Changes are reported by dotted
+//! path, in field name order.
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// One changed field. `from` is absent for added fields, `to` for removed ones.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Change {
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<Value>,
+}
+
+/// Lists every field that differs between `current` and `wanted`, including
+/// fields only one of them has.
+pub fn diff(current: &Value, wanted: &Value) -> Vec<Change> {
+    let mut changes = Vec::new();
+    diff_into("", Some(current), Some(wanted), false, &mut changes);
+    changes
+}
+
+/// Lists the fields of `wanted` that differ from `current`, ignoring fields
+/// only `current` has.
+pub fn changed_fields(current: &Value, wanted: &Value) -> Vec<Change> {
+    let mut changes = Vec::new();
+    diff_into("", Some(current), Some(wanted), true, &mut changes);
+    changes
+}
+
+fn diff_into(
+    path: &str,
+    current: Option<&Value>,
+    wanted: Option<&Value>,
+    partial: bool,
+    changes: &mut Vec<Change>,
+) {
+    match (current, wanted) {
+        (Some(Value::Object(current)), Some(Value::Object(wanted))) => {
+            let mut keys: Vec<&String> = wanted.keys().collect();
+            if !partial {
+                keys.extend(current.keys().filter(|key| !wanted.contains_key(*key)));
+                keys.sort();
+            }
+            for key in keys {
+                let path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                diff_into(&path, current.get(key), wanted.get(key), partial, changes);
+            }
+        }
+        (current, wanted) if current == wanted => {}
+        (current, wanted) => changes.push(Change {
+            path: path.to_string(),
+            from: current.cloned(),
+            to: wanted.cloned(),
+        }),
+    }
+}
+
+/// Removes null fields at every depth, so a null field and a missing one
+/// compare equal.
+pub(crate) fn remove_nulls(value: &mut Value) {
+    match value {
+        Value::Object(fields) => {
+            fields.retain(|_, v| !v.is_null());
+            fields.values_mut().for_each(remove_nulls);
+        }
+        Value::Array(items) => items.iter_mut().for_each(remove_nulls),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_changed_fields_recurses_into_objects() {
+        let changes = changed_fields(
+            &json!({"options": {"thresholds": {"critical": 90.0, "warning": 80.0}}}),
+            &json!({"options": {"thresholds": {"critical": 95.0}, "notify_no_data": true}}),
+        );
+        assert_eq!(
+            changes,
+            vec![
+                Change {
+                    path: "options.notify_no_data".into(),
+                    from: None,
+                    to: Some(json!(true)),
+                },
+                Change {
+                    path: "options.thresholds.critical".into(),
+                    from: Some(json!(90.0)),
+                    to: Some(json!(95.0)),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_reports_removed_fields() {
+        let changes = diff(
+            &json!({"title": "CPU", "description": "old", "layout": {"x": 0, "y": 0}}),
+            &json!({"title": "CPU", "layout": {"x": 0, "y": 2}}),
+        );
+        assert_eq!(
+            changes,
+            vec![
+                Change {
+                    path: "description".into(),
+                    from: Some(json!("old")),
+                    to: None,
+                },
+                Change {
+                    path: "layout.y".into(),
+                    from: Some(json!(0)),
+                    to: Some(json!(2)),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lists_are_compared_whole() {
+        let changes = diff(&json!({"tags": ["a", "b"]}), &json!({"tags": ["a"]}));
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].path, "tags");
+    }
+}
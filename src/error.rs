@@ -1,11 +1,18 @@
 //! Application error types and exit codes.
 //!
 //! Provides a unified error type for the application with appropriate
-//! exit codes for different failure modes.
+//! exit codes for different failure modes, and the conversion that classifies
+//! Datadog API errors by HTTP status.
 
+use datadog_api_client::datadog;
+use serde_json::Value;
+use std::fmt;
 use std::io;
 use thiserror::Error;
 
+/// Longest raw response body included in an error message.
+const MAX_BODY_IN_MESSAGE: usize = 500;
+
 /// Application error type covering all failure modes.
 ///
 /// Each variant maps to a specific exit code for scripting compatibility.
@@ -23,11 +30,42 @@ pub enum AppError {
     #[error("Configuration error: {0}")]
     Config(String),
 
+    /// Invalid command-line arguments, e.g. an unknown flag or a bad value.
+    #[error("Invalid arguments: {0}")]
+    Usage(String),
+
     #[error("IO error: {0}")]
     Io(#[from] io::Error),
 
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
+
+    #[error("Not found: {0}")]
+    NotFound(String),
+
+    #[error("Rate limited: {0}")]
+    RateLimited(String),
+
+    #[error("Request timed out: {0}")]
+    Timeout(String),
+
+    #[error("Datadog server error: {0}")]
+    Server(String),
+
+    /// A checked value crossed its threshold, e.g. an SLO burning its budget too fast.
+    #[error("Threshold crossed: {0}")]
+    Threshold(String),
+
+    /// An error response from the Datadog API, carrying its HTTP details.
+    ///
+    /// The wrapped error determines the kind and exit code.
+    #[error("{error}")]
+    Http {
+        status: u16,
+        request_id: Option<String>,
+        #[source]
+        error: Box<AppError>,
+    },
 }
 
 impl AppError {
@@ -37,19 +75,193 @@ impl AppError {
     /// - 2: Authentication failure (401/403)
     /// - 3: API error
     /// - 4: Invalid query syntax
-    /// - 5: Configuration error
+    /// - 5: Configuration error or invalid arguments
     /// - 6: IO error
     /// - 7: Serialization error
+    /// - 8: Resource not found (404)
+    /// - 9: Rate limited after retries were exhausted (429)
+    /// - 10: Request timed out
+    /// - 11: Datadog server error (5xx)
+    /// - 12: Threshold crossed (e.g. SLO burn rate)
     pub fn exit_code(&self) -> i32 {
         match self {
             AppError::Auth(_) => 2,
             AppError::Api(_) => 3,
             AppError::InvalidQuery(_) => 4,
-            AppError::Config(_) => 5,
+            AppError::Config(_) | AppError::Usage(_) => 5,
             AppError::Io(_) => 6,
             AppError::Serialization(_) => 7,
+            AppError::NotFound(_) => 8,
+            AppError::RateLimited(_) => 9,
+            AppError::Timeout(_) => 10,
+            AppError::Server(_) => 11,
+            AppError::Threshold(_) => 12,
+            AppError::Http { error, .. } => error.exit_code(),
+        }
+    }
+
+    /// Returns a stable, machine-readable name for the error variant.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AppError::Auth(_) => "auth",
+            AppError::Api(_) => "api",
+            AppError::InvalidQuery(_) => "invalid_query",
+            AppError::Config(_) => "config",
+            AppError::Usage(_) => "usage",
+            AppError::Io(_) => "io",
+            AppError::Serialization(_) => "serialization",
+            AppError::NotFound(_) => "not_found",
+            AppError::RateLimited(_) => "rate_limited",
+            AppError::Timeout(_) => "timeout",
+            AppError::Server(_) => "server",
+            AppError::Threshold(_) => "threshold",
+            AppError::Http { error, .. } => error.kind(),
+        }
+    }
+
+    /// Returns the HTTP status of the failed response, if the error came from one.
+    pub fn http_status(&self) -> Option<u16> {
+        match self {
+            AppError::Http { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+
+    /// Returns the request ID reported in the error response, if any.
+    pub fn request_id(&self) -> Option<&str> {
+        match self {
+            AppError::Http { request_id, .. } => request_id.as_deref(),
+            _ => None,
         }
     }
+
+    /// Classifies a Datadog API error by its HTTP status and response body.
+    ///
+    /// The message includes the entries of Datadog's `errors` array when the
+    /// response has one, or the raw body otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - Error returned by a Datadog SDK call
+    /// * `forbidden_hint` - Extra guidance appended to 403 messages (e.g. the permission usually missing)
+    pub fn from_api_error<T: fmt::Debug>(
+        error: datadog::Error<T>,
+        forbidden_hint: Option<&str>,
+    ) -> Self {
+        match error {
+            datadog::Error::ResponseError(response) => {
+                let status = response.status.as_u16();
+                let body = serde_json::from_str::<Value>(&response.content).ok();
+                let details = response_details(&response.content, body.as_ref())
+                    .unwrap_or_else(|| response.status.to_string());
+                AppError::Http {
+                    status,
+                    request_id: body.as_ref().and_then(request_id),
+                    error: Box::new(from_status(status, details, forbidden_hint)),
+                }
+            }
+            datadog::Error::Reqwest(e) => from_transport_error(&e),
+            datadog::Error::ReqwestMiddleware(reqwest_middleware::Error::Reqwest(e)) => {
+                from_transport_error(&e)
+            }
+            other => AppError::Api(other.to_string()),
+        }
+    }
+}
+
+impl<T: fmt::Debug> From<datadog::Error<T>> for AppError {
+    fn from(error: datadog::Error<T>) -> Self {
+        AppError::from_api_error(error, None)
+    }
+}
+
+/// Maps an HTTP error status to the matching variant.
+fn from_status(status: u16, details: String, forbidden_hint: Option<&str>) -> AppError {
+    match status {
+        401 => AppError::Auth(format!("Invalid API or App key (401): {}", details)),
+        403 => AppError::Auth(match forbidden_hint {
+            Some(hint) => format!("Access denied (403): {} {}", details, hint),
+            None => format!("Access denied (403): {}", details),
+        }),
+        400 | 422 => AppError::InvalidQuery(format!("{} ({})", details, status)),
+        404 => AppError::NotFound(format!("{} (404)", details)),
+        408 => AppError::Timeout(format!("{} (408)", details)),
+        429 => AppError::RateLimited(format!(
+            "{} (429). Retries were exhausted; wait and try again, or raise --max-retries/--retry-timeout",
+            details
+        )),
+        500..=599 => AppError::Server(format!("{} ({})", details, status)),
+        _ => AppError::Api(format!("{} ({})", details, status)),
+    }
+}
+
+/// Maps a transport-level failure (no HTTP response) to the matching variant.
+fn from_transport_error(error: &reqwest::Error) -> AppError {
+    if error.is_timeout() {
+        AppError::Timeout(error.to_string())
+    } else {
+        AppError::Api(error.to_string())
+    }
+}
+
+/// Extracts a readable message from an error response body.
+///
+/// Datadog returns `{"errors": [...]}` where entries are plain strings (v1) or
+/// JSON:API objects with `title`/`detail` (v2). Other bodies are returned as-is,
+/// truncated. Returns `None` for an empty body.
+fn response_details(content: &str, body: Option<&Value>) -> Option<String> {
+    let content = content.trim();
+    if content.is_empty() {
+        return None;
+    }
+
+    let errors: Vec<String> = body
+        .and_then(|body| body.get("errors")?.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| match entry {
+            Value::String(message) => Some(message.clone()),
+            Value::Object(object) => {
+                let field = |name: &str| object.get(name).and_then(Value::as_str);
+                match (field("title"), field("detail")) {
+                    (Some(title), Some(detail)) if title != detail => {
+                        Some(format!("{}: {}", title, detail))
+                    }
+                    (_, Some(text)) | (Some(text), None) => Some(text.to_string()),
+                    (None, None) => None,
+                }
+            }
+            _ => None,
+        })
+        .collect();
+
+    if !errors.is_empty() {
+        return Some(errors.join("; "));
+    }
+
+    Some(match content.char_indices().nth(MAX_BODY_IN_MESSAGE) {
+        Some((cut, _)) => format!("{}…", &content[..cut]),
+        None => content.to_string(),
+    })
+}
+
+/// Finds a request ID in an error response body.
+///
+/// Looks for a top-level `request_id`, then for the `id` of the first JSON:API
+/// error object. Most Datadog error responses carry neither.
+fn request_id(body: &Value) -> Option<String> {
+    let first_error_id = || {
+        body.get("errors")?
+            .as_array()?
+            .iter()
+            .find_map(|entry| entry.get("id"))
+    };
+    let id = body.get("request_id").or_else(first_error_id)?;
+    match id {
+        Value::String(id) => Some(id.clone()),
+        Value::Number(id) => Some(id.to_string()),
+        _ => None,
+    }
 }
 
 #[cfg(test)]
@@ -78,6 +290,10 @@ mod tests {
     fn test_config_error_exit_code() {
         let error = AppError::Config("test".to_string());
         assert_eq!(error.exit_code(), 5);
+
+        let error = AppError::Usage("test".to_string());
+        assert_eq!(error.exit_code(), 5);
+        assert_eq!(error.kind(), "usage");
     }
 
     #[test]
@@ -95,6 +311,120 @@ mod tests {
         assert_eq!(error.exit_code(), 7);
     }
 
+    #[test]
+    fn test_new_variant_exit_codes() {
+        assert_eq!(AppError::NotFound("test".to_string()).exit_code(), 8);
+        assert_eq!(AppError::RateLimited("test".to_string()).exit_code(), 9);
+        assert_eq!(AppError::Timeout("test".to_string()).exit_code(), 10);
+        assert_eq!(AppError::Server("test".to_string()).exit_code(), 11);
+        assert_eq!(AppError::Threshold("test".to_string()).exit_code(), 12);
+    }
+
+    fn response_error(status: u16, content: &str) -> datadog::Error<()> {
+        datadog::Error::ResponseError(datadog::ResponseContent {
+            status: reqwest::StatusCode::from_u16(status).unwrap(),
+            content: content.to_string(),
+            entity: None,
+        })
+    }
+
+    #[test]
+    fn test_from_api_error_uses_status_not_message() {
+        // A 400 inside the error text must not be mistaken for the status
+        let error = AppError::from(response_error(
+            500,
+            r#"{"errors":["query 'status:400' failed"]}"#,
+        ));
+        assert_eq!(error.kind(), "server");
+        assert_eq!(error.exit_code(), 11);
+        assert_eq!(error.http_status(), Some(500));
+    }
+
+    #[test]
+    fn test_from_api_error_statuses() {
+        let cases = [
+            (401, 2),
+            (403, 2),
+            (400, 4),
+            (422, 4),
+            (404, 8),
+            (408, 10),
+            (429, 9),
+            (503, 11),
+            (418, 3),
+        ];
+        for (status, exit_code) in cases {
+            let error = AppError::from(response_error(status, ""));
+            assert_eq!(error.exit_code(), exit_code, "status {}", status);
+        }
+    }
+
+    #[test]
+    fn test_from_api_error_forbidden_hint() {
+        let error = AppError::from_api_error(
+            response_error(403, r#"{"errors":["Forbidden"]}"#),
+            Some("Check the key's permissions."),
+        );
+        assert_eq!(
+            error.to_string(),
+            "Authentication failed: Access denied (403): Forbidden Check the key's permissions."
+        );
+    }
+
+    #[test]
+    fn test_from_api_error_request_id() {
+        let error = AppError::from(response_error(
+            400,
+            r#"{"errors":[{"id":"abc-123","title":"Bad Request"}]}"#,
+        ));
+        assert_eq!(error.request_id(), Some("abc-123"));
+        assert_eq!(error.kind(), "invalid_query");
+
+        let error = AppError::from(response_error(400, r#"{"errors":["Bad Request"]}"#));
+        assert_eq!(error.request_id(), None);
+    }
+
+    #[test]
+    fn test_kind_and_http_status_for_local_errors() {
+        let error = AppError::Config("missing key".to_string());
+        assert_eq!(error.kind(), "config");
+        assert_eq!(error.http_status(), None);
+        assert_eq!(error.request_id(), None);
+    }
+
+    fn details(content: &str) -> Option<String> {
+        let body = serde_json::from_str::<Value>(content).ok();
+        response_details(content, body.as_ref())
+    }
+
+    #[test]
+    fn test_response_details_v1_errors() {
+        let details = details(r#"{"errors": ["Invalid query", "Bad metric"]}"#);
+        assert_eq!(details.as_deref(), Some("Invalid query; Bad metric"));
+    }
+
+    #[test]
+    fn test_response_details_v2_errors() {
+        let details = details(
+            r#"{"errors": [{"status": "400", "title": "Bad Request", "detail": "unknown facet @foo"}]}"#,
+        );
+        assert_eq!(details.as_deref(), Some("Bad Request: unknown facet @foo"));
+    }
+
+    #[test]
+    fn test_response_details_raw_body() {
+        assert_eq!(
+            details("<html>Bad Gateway</html>").as_deref(),
+            Some("<html>Bad Gateway</html>")
+        );
+        assert_eq!(details("  "), None);
+
+        let long = "x".repeat(MAX_BODY_IN_MESSAGE + 10);
+        let details = details(&long).unwrap();
+        assert!(details.ends_with('…'));
+        assert_eq!(details.chars().count(), MAX_BODY_IN_MESSAGE + 1);
+    }
+
     #[test]
     fn test_error_display() {
         let auth_error = AppError::Auth("invalid credentials".to_string());
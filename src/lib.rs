@@ -3,7 +3,15 @@
 //! Library crate exposing the core functionality for integration tests.
 
 // Re-export modules that are also used in main.rs
+pub mod aggregate;
+pub mod checkpoint;
 pub mod client;
 pub mod config;
+pub mod dashboards;
+pub mod diff;
 pub mod error;
+pub mod monitors;
+pub mod slo;
 pub mod time;
+pub mod timeseries;
+pub mod trace;
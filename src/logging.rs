@@ -2,9 +2,14 @@
 //!
 //! Provides functions for verbose/debug output when the --verbose flag is enabled.
 
+use ddog::client::RetryEvent;
+use ddog::config::Settings;
+use ddog::time::ResolvedRange;
+
 /// Logger for verbose output.
 ///
 /// Writes to stderr to avoid interfering with NDJSON output on stdout.
+#[derive(Clone)]
 pub struct VerboseLogger {
     enabled: bool,
 }
@@ -26,21 +31,24 @@ impl VerboseLogger {
         }
     }
 
-    /// Constructs and logs a Datadog UI URL for viewing logs/spans.
+    /// Logs a warning, whether or not verbose mode is enabled.
+    pub fn warn(&self, message: &str) {
+        eprintln!("Warning: {}", message);
+    }
+
+    /// Constructs and logs a Datadog UI URL for viewing logs, spans or events.
     ///
     /// # Arguments
     ///
-    /// * `resource_type` - Either "logs" or "spans"
+    /// * `resource_type` - "logs", "spans" or "events"
     /// * `query` - The search query
-    /// * `from` - Start time
-    /// * `to` - End time
+    /// * `range` - The resolved time range sent to the API
     /// * `site` - Datadog site (e.g., "datadoghq.com" or "datadoghq.eu")
     pub fn log_datadog_url(
         &self,
         resource_type: &str,
         query: &str,
-        from: &str,
-        to: &str,
+        range: &ResolvedRange,
         site: &str,
     ) {
         if !self.enabled {
@@ -57,100 +65,37 @@ impl VerboseLogger {
             &format!("https://app.{}", site)
         };
 
-        // For Datadog UI, we need to convert times to milliseconds since epoch
-        // For relative times like "now-1h", we'll approximate or note that user needs to adjust
-        let (from_ts, to_ts) = self.convert_times_for_url(from, to);
-
         let url = match resource_type {
             "logs" => format!(
                 "{}/logs?query={}&from_ts={}&to_ts={}&live=false",
-                base_url, query_param, from_ts, to_ts
+                base_url, query_param, range.from_ms, range.to_ms
             ),
             "spans" => format!(
                 "{}/apm/traces?query={}&from_ts={}&to_ts={}",
-                base_url, query_param, from_ts, to_ts
+                base_url, query_param, range.from_ms, range.to_ms
+            ),
+            "events" => format!(
+                "{}/event/explorer?query={}&from_ts={}&to_ts={}",
+                base_url, query_param, range.from_ms, range.to_ms
             ),
             _ => return,
         };
 
         self.log(&format!("Datadog UI URL: {}", url));
-        if from.starts_with("now") || to.starts_with("now") {
-            self.log("Note: URL uses approximate timestamps. Adjust time range in UI if needed.");
-        }
-    }
-
-    /// Converts time strings to Unix timestamps in milliseconds for URL construction.
-    fn convert_times_for_url(&self, from: &str, to: &str) -> (String, String) {
-        use std::time::{SystemTime, UNIX_EPOCH};
-
-        let now_ms = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_millis() as u64;
-
-        let from_ts = if from == "now" {
-            now_ms.to_string()
-        } else if let Some(rest) = from.strip_prefix("now-") {
-            // Approximate relative time (this is a simplified conversion)
-            // For better accuracy, we'd need to parse the full relative time syntax
-            let offset_ms = self.parse_relative_time(rest);
-            (now_ms.saturating_sub(offset_ms)).to_string()
-        } else if from.chars().all(|c| c.is_ascii_digit()) {
-            // Already a Unix timestamp
-            from.to_string()
-        } else {
-            // ISO8601 or other format - approximate to now-1h for URL
-            // User can adjust in UI
-            (now_ms - 3600000).to_string()
-        };
-
-        let to_ts = if to == "now" {
-            now_ms.to_string()
-        } else if let Some(rest) = to.strip_prefix("now-") {
-            let offset_ms = self.parse_relative_time(rest);
-            (now_ms.saturating_sub(offset_ms)).to_string()
-        } else if to.chars().all(|c| c.is_ascii_digit()) {
-            to.to_string()
-        } else {
-            now_ms.to_string()
-        };
-
-        (from_ts, to_ts)
-    }
-
-    /// Parses relative time string (e.g., "1h", "30m") to milliseconds.
-    fn parse_relative_time(&self, time_str: &str) -> u64 {
-        // Simple parser for common formats
-        if let Some(num_str) = time_str.strip_suffix('s') {
-            if let Ok(secs) = num_str.parse::<u64>() {
-                return secs * 1000;
-            }
-        } else if let Some(num_str) = time_str.strip_suffix('m') {
-            if let Ok(mins) = num_str.parse::<u64>() {
-                return mins * 60 * 1000;
-            }
-        } else if let Some(num_str) = time_str.strip_suffix('h') {
-            if let Ok(hours) = num_str.parse::<u64>() {
-                return hours * 3600 * 1000;
-            }
-        } else if let Some(num_str) = time_str.strip_suffix('d')
-            && let Ok(days) = num_str.parse::<u64>()
-        {
-            return days * 24 * 3600 * 1000;
-        }
-        // Default to 1 hour if we can't parse
-        3600000
     }
 
     /// Logs request details.
-    pub fn log_request(&self, resource_type: &str, query: &str, from: &str, to: &str) {
+    pub fn log_request(&self, resource_type: &str, query: &str, range: &ResolvedRange) {
         if !self.enabled {
             return;
         }
 
         self.log(&format!("Resource type: {}", resource_type));
         self.log(&format!("Query: {}", query));
-        self.log(&format!("Time range: {} to {}", from, to));
+        self.log(&format!(
+            "Time range: {} ({} to {} Unix ms)",
+            range, range.from_ms, range.to_ms
+        ));
     }
 
     /// Logs API endpoint information.
@@ -161,19 +106,34 @@ impl VerboseLogger {
     }
 
     /// Logs configuration information (without sensitive data).
-    pub fn log_config(&self, site: &str, has_api_key: bool, has_app_key: bool) {
+    pub fn log_config(&self, settings: &Settings) {
         if !self.enabled {
             return;
         }
 
-        self.log(&format!("Datadog site: {}", site));
         self.log(&format!(
-            "API key: {}",
-            if has_api_key { "set" } else { "not set" }
+            "Profile: {}",
+            settings.profile.as_deref().unwrap_or("(none)")
+        ));
+        self.log(&format!(
+            "Datadog site: {} (from {})",
+            settings.site, settings.site_source
         ));
+        if let Some(url) = &settings.api_url {
+            self.log(&format!("API URL override: {}", url));
+        }
+        self.log(&format!("API key: set (from {})", settings.api_key_source));
+        self.log(&format!("App key: set (from {})", settings.app_key_source));
+    }
+
+    /// Logs a retry of a failed API request.
+    pub fn log_retry(&self, event: &RetryEvent) {
         self.log(&format!(
-            "App key: {}",
-            if has_app_key { "set" } else { "not set" }
+            "Retrying request ({}/{}) in {:.1}s after: {}",
+            event.attempt,
+            event.max_retries,
+            event.delay.as_secs_f64(),
+            event.reason
         ));
     }
 
@@ -1,23 +1,38 @@
 //! # ddog
 //!
-//! A command-line tool for querying Datadog logs, APM spans, and metrics.
+//! A command-line tool for querying Datadog logs, APM spans, metrics, events,
+//! monitors, SLOs and dashboards.
 //!
 //! ## Usage
 //!
 //! ```bash
 //! ddog logs search "service:api AND status:error" --from now-1h
+//! ddog logs aggregate "status:error" --group-by service --compute count
 //! ddog spans search "service:web env:prod" --limit 50
+//! ddog spans search "status:error" --around 14:32 --window 10m --tz Europe/Paris
+//! ddog spans aggregate "service:api" --group-by resource_name --compute p99:@duration
 //! ddog metrics query "avg:system.cpu.user{*}" --from now-1h
+//! ddog metrics scalar "avg:system.cpu.user{*}" --group-by host
+//! ddog metrics timeseries -q "a=sum:errors{*}" -q "b=sum:hits{*}" --formula "a / b * 100"
 //! ddog metrics list --from now-1h
+//! ddog --profile eu config show
+//! ddog logs search "status:error" --output table --fields attributes.service,attributes.message
 //! ```
 //!
 //! ## Environment Variables
 //!
-//! - `DD_API_KEY` - Datadog API key (required)
-//! - `DD_APP_KEY` - Datadog application key (required)
+//! - `DD_API_KEY` - Datadog API key (required unless set by a profile)
+//! - `DD_APP_KEY` - Datadog application key (required unless set by a profile)
 //! - `DD_SITE` - Datadog site (optional, defaults to datadoghq.com)
+//! - `DDOG_PROFILE` - Profile to use when `--profile` is not given
+//! - `DDOG_CONFIG` - Config file path (defaults to `~/.config/ddog/config.toml`)
+//! - `DDOG_API_URL` - API base URL override (e.g. a proxy or local test server)
 
-use clap::Parser;
+use chrono::{DateTime, Utc};
+use clap::error::ErrorKind;
+use clap::{ArgMatches, CommandFactory, FromArgMatches};
+use std::ffi::OsString;
+use std::time::Duration;
 
 mod cli;
 mod commands;
@@ -28,80 +43,567 @@ mod output;
 use ddog::client;
 use ddog::config;
 use ddog::error::AppError;
+use ddog::time::ResolvedRange;
 
-use cli::{Cli, Domain, LogsAction, MetricsAction, SpansAction};
+use cli::{
+    ApiDomain, Cli, ConfigAction, DashboardsAction, Domain, EventsAction, LogsAction,
+    MetricsAction, MonitorsAction, SloAction, SpansAction,
+};
 use logging::VerboseLogger;
+use output::ErrorFormat;
+
+/// Exit code after Ctrl-C, as the shell reports for a process killed by SIGINT.
+const INTERRUPTED: i32 = 130;
 
 #[tokio::main]
 async fn main() {
-    if let Err(e) = run().await {
-        eprintln!("Error: {}", e);
-        std::process::exit(e.exit_code());
+    let matches = Cli::command()
+        .try_get_matches()
+        .unwrap_or_else(|e| exit_usage(e));
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| exit_usage(e));
+    let command = command_name(&matches);
+    let error_format = cli.error_format;
+
+    // Follow mode stops on Ctrl-C itself and completes its output. Any other
+    // command is dropped on Ctrl-C, which completes compressed output files.
+    let result = if is_following(&matches) {
+        Some(run(cli).await)
+    } else {
+        tokio::select! {
+            result = run(cli) => Some(result),
+            _ = tokio::signal::ctrl_c() => None,
+        }
+    };
+    match result {
+        Some(Ok(())) => {}
+        Some(Err(e)) => {
+            output::report_error(&e, error_format, &command);
+            std::process::exit(e.exit_code());
+        }
+        None => std::process::exit(INTERRUPTED),
+    }
+}
+
+/// Reports invalid command-line arguments and exits.
+///
+/// `--help` and `--version` are printed by clap as usual. Other parsing
+/// errors exit as usage errors; they are printed by clap in text mode, or
+/// reported like any other failure with `--error-format json`.
+fn exit_usage(error: clap::Error) -> ! {
+    if matches!(
+        error.kind(),
+        ErrorKind::DisplayHelp
+            | ErrorKind::DisplayVersion
+            | ErrorKind::DisplayHelpOnMissingArgumentOrSubcommand
+    ) {
+        error.exit();
+    }
+
+    let args: Vec<OsString> = std::env::args_os().collect();
+    let rendered = error.render().to_string();
+    // Keep the error and its tips, without the usage and help lines clap appends
+    let message = rendered
+        .split("\n\nUsage:")
+        .next()
+        .unwrap_or_default()
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with("For more information"))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let usage = AppError::Usage(message.trim_start_matches("error: ").to_string());
+
+    match ErrorFormat::from_args(&args) {
+        ErrorFormat::Json => {
+            output::report_error(&usage, ErrorFormat::Json, &command_from_args(&args))
+        }
+        ErrorFormat::Text => {
+            let _ = error.print();
+        }
+    }
+    std::process::exit(usage.exit_code());
+}
+
+/// Returns the subcommand path named in raw arguments, e.g. "logs search".
+fn command_from_args(args: &[OsString]) -> String {
+    let mut names = Vec::new();
+    let mut command = Cli::command();
+    for arg in args.iter().skip(1) {
+        let Some(sub) = command.find_subcommand(arg).cloned() else {
+            continue;
+        };
+        names.push(sub.get_name().to_string());
+        command = sub;
+    }
+    names.join(" ")
+}
+
+/// Returns the invoked subcommand path, e.g. "logs search".
+fn command_name(matches: &ArgMatches) -> String {
+    let mut names = Vec::new();
+    let mut current = matches;
+    while let Some((name, sub)) = current.subcommand() {
+        names.push(name);
+        current = sub;
     }
+    names.join(" ")
 }
 
-async fn run() -> Result<(), AppError> {
-    let cli = Cli::parse();
+/// Returns whether the invoked subcommand was given `--follow`.
+fn is_following(matches: &ArgMatches) -> bool {
+    let mut current = matches;
+    while let Some((_, sub)) = current.subcommand() {
+        current = sub;
+    }
+    current
+        .try_get_one::<bool>("follow")
+        .ok()
+        .flatten()
+        .is_some_and(|follow| *follow)
+}
+
+async fn run(cli: Cli) -> Result<(), AppError> {
     let logger = VerboseLogger::new(cli.verbose);
-    let config = config::load_config()?;
+    let to_file = cli.out.is_some();
+    let destination = output::Destination {
+        path: cli.out,
+        compress: cli.compress,
+        rotate: cli.rotate_every,
+        flush_every: cli.flush_every,
+    };
+    let writer = output::create_writer(cli.output.unwrap_or_default(), cli.fields, destination);
+
+    let domain = match cli.domain {
+        // Config commands manage profiles and must work without credentials
+        Domain::Config { action } => {
+            return run_config(action, cli.profile.as_deref(), writer, logger);
+        }
+        Domain::Api(domain) => *domain,
+    };
 
-    // Get site for URL construction
-    let site = std::env::var("DD_SITE").unwrap_or_else(|_| "datadoghq.com".to_string());
-    let has_api_key = std::env::var("DD_API_KEY").is_ok();
-    let has_app_key = std::env::var("DD_APP_KEY").is_ok();
+    let settings = config::Settings::load(cli.profile.as_deref())?;
+    logger.log_config(&settings);
 
-    logger.log_config(&site, has_api_key, has_app_key);
+    let config = settings.to_configuration();
+    let retry_logger = logger.clone();
+    let retry = client::RetryPolicy::new(cli.max_retries, Duration::from_secs(cli.retry_timeout))
+        .on_retry(move |event| retry_logger.log_retry(event));
+    // Every relative time in this invocation resolves against the same instant
+    let now = Utc::now();
+    // Site for URL construction
+    let site = settings.site.clone();
+    let default_indexes = settings
+        .default_indexes
+        .clone()
+        .unwrap_or_else(|| vec!["*".to_string()]);
 
-    match cli.domain {
-        Domain::Logs { action } => match action {
+    match domain {
+        ApiDomain::Logs { action } => match action {
             LogsAction::Search {
                 query,
                 time_range,
                 pagination,
                 indexes,
+                follow,
+                parallel,
+                checkpointing,
             } => {
-                logger.log_request("logs", &query, &time_range.from, &time_range.to);
+                let range = time_range.resolve(now)?;
+                warn_beyond_retention(&logger, "logs", &range, settings.retention.logs, now);
+                logger.log_request("logs", &query, &range);
                 logger.log_api_endpoint("/api/v2/logs/events", "POST");
-                logger.log_datadog_url("logs", &query, &time_range.from, &time_range.to, &site);
+                logger.log_datadog_url("logs", &query, &range, &site);
 
-                let client = client::LogsClient::new(config);
-                commands::logs::search::run(client, query, time_range, pagination, indexes, logger)
-                    .await
+                let indexes = indexes.unwrap_or(default_indexes);
+                let client = client::LogsClient::with_retry(config, &retry);
+                commands::logs::search::run(
+                    client,
+                    query,
+                    range,
+                    pagination,
+                    indexes,
+                    follow,
+                    parallel,
+                    checkpointing,
+                    writer,
+                    logger,
+                )
+                .await
+            }
+            LogsAction::Aggregate {
+                query,
+                time_range,
+                aggregation,
+                indexes,
+            } => {
+                let range = time_range.resolve(now)?;
+                warn_beyond_retention(&logger, "logs", &range, settings.retention.logs, now);
+                logger.log_request("logs", &query, &range);
+                logger.log_api_endpoint("/api/v2/logs/analytics/aggregate", "POST");
+
+                let indexes = indexes.unwrap_or(default_indexes);
+                let client = client::LogsClient::with_retry(config, &retry);
+                commands::logs::aggregate::run(
+                    client,
+                    query,
+                    range,
+                    aggregation,
+                    indexes,
+                    writer,
+                    logger,
+                )
+                .await
             }
         },
-        Domain::Spans { action } => match action {
+        ApiDomain::Spans { action } => match action {
             SpansAction::Search {
                 query,
                 time_range,
                 pagination,
+                parallel,
+                checkpointing,
+            } => {
+                let range = time_range.resolve(now)?;
+                warn_beyond_retention(&logger, "spans", &range, settings.retention.spans, now);
+                logger.log_request("spans", &query, &range);
+                logger.log_api_endpoint("/api/v2/spans/events/search", "POST");
+                logger.log_datadog_url("spans", &query, &range, &site);
+
+                let client = client::SpansClient::with_retry(config, &retry);
+                commands::spans::search::run(
+                    client,
+                    query,
+                    range,
+                    pagination,
+                    parallel,
+                    checkpointing,
+                    writer,
+                    logger,
+                )
+                .await
+            }
+            SpansAction::Aggregate {
+                query,
+                time_range,
+                aggregation,
+            } => {
+                let range = time_range.resolve(now)?;
+                warn_beyond_retention(&logger, "spans", &range, settings.retention.spans, now);
+                logger.log_request("spans", &query, &range);
+                logger.log_api_endpoint("/api/v2/spans/analytics/aggregate", "POST");
+
+                let client = client::SpansClient::with_retry(config, &retry);
+                commands::spans::aggregate::run(client, query, range, aggregation, writer, logger)
+                    .await
+            }
+            SpansAction::Trace {
+                trace_id,
+                time_range,
+                ndjson,
             } => {
-                logger.log_request("spans", &query, &time_range.from, &time_range.to);
+                // Any explicit output format replaces the waterfall with span records
+                let records = ndjson || cli.output.is_some();
+                if to_file && !records {
+                    return Err(AppError::Config(
+                        "--out writes span records; pass --ndjson or --output with spans trace"
+                            .to_string(),
+                    ));
+                }
+                let query = format!("trace_id:{}", trace_id);
+                let range = time_range.resolve(now)?;
+                warn_beyond_retention(&logger, "spans", &range, settings.retention.spans, now);
+                logger.log_request("spans", &query, &range);
                 logger.log_api_endpoint("/api/v2/spans/events/search", "POST");
-                logger.log_datadog_url("spans", &query, &time_range.from, &time_range.to, &site);
+                logger.log_datadog_url("spans", &query, &range, &site);
 
-                let client = client::SpansClient::new(config);
-                commands::spans::search::run(client, query, time_range, pagination, logger).await
+                let client = client::SpansClient::with_retry(config, &retry);
+                commands::spans::trace::run(client, trace_id, range, records, writer, logger).await
             }
         },
-        Domain::Metrics { action } => match action {
+        ApiDomain::Metrics { action } => match action {
             MetricsAction::Query {
                 query,
                 time_range,
                 limit,
             } => {
-                logger.log_request("metrics", &query, &time_range.from, &time_range.to);
+                let range = time_range.resolve(now)?;
+                warn_beyond_retention(&logger, "metrics", &range, settings.retention.metrics, now);
+                logger.log_request("metrics", &query, &range);
                 logger.log_api_endpoint("/api/v1/query", "GET");
 
-                let client = client::MetricsClient::new(config);
-                commands::metrics::query::run(client, query, time_range, limit, logger).await
+                let client = client::MetricsClient::with_retry(config, &retry);
+                commands::metrics::query::run(client, query, range, limit, writer, logger).await
+            }
+            MetricsAction::Scalar {
+                query,
+                time_range,
+                aggregator,
+                group_by,
+            } => {
+                let query = ddog::timeseries::with_group_by(&query, &group_by);
+                let range = time_range.resolve(now)?;
+                warn_beyond_retention(&logger, "metrics", &range, settings.retention.metrics, now);
+                logger.log_request("metrics", &query, &range);
+                logger.log_api_endpoint("/api/v2/query/scalar", "POST");
+
+                let client = client::MetricsClient::with_retry(config, &retry);
+                commands::metrics::scalar::run(client, query, range, aggregator, writer, logger)
+                    .await
+            }
+            MetricsAction::Timeseries {
+                queries,
+                formulas,
+                time_range,
+                interval,
+                limit,
+            } => {
+                let range = time_range.resolve(now)?;
+                warn_beyond_retention(&logger, "metrics", &range, settings.retention.metrics, now);
+                logger.log(&format!("Time range: {}", range));
+                logger.log_api_endpoint("/api/v2/query/timeseries", "POST");
+
+                let request = ddog::timeseries::TimeseriesRequest {
+                    queries,
+                    formulas,
+                    interval_ms: interval,
+                };
+                let client = client::MetricsClient::with_retry(config, &retry);
+                commands::metrics::timeseries::run(client, request, range, limit, writer, logger)
+                    .await
             }
             MetricsAction::List { time_from } => {
-                logger.log(&format!("Listing active metrics from {}", time_from.from));
+                let range = time_from.resolve(now)?;
+                warn_beyond_retention(&logger, "metrics", &range, settings.retention.metrics, now);
+                logger.log(&format!("Time range: {}", range));
                 logger.log_api_endpoint("/api/v1/metrics", "GET");
 
-                let client = client::MetricsClient::new(config);
-                commands::metrics::list::run(client, time_from, logger).await
+                let client = client::MetricsClient::with_retry(config, &retry);
+                commands::metrics::list::run(client, range, writer, logger).await
+            }
+        },
+        ApiDomain::Monitors { action } => match action {
+            MonitorsAction::List { selection, status } => {
+                logger.log_api_endpoint("/api/v1/monitor", "GET");
+
+                let filter = selection.filter(status);
+                let client = client::MonitorsClient::with_retry(config, &retry);
+                commands::monitors::list::run(client, filter, writer, logger).await
+            }
+            MonitorsAction::Get { id } => {
+                logger.log_api_endpoint(&format!("/api/v1/monitor/{}", id), "GET");
+
+                let client = client::MonitorsClient::with_retry(config, &retry);
+                commands::monitors::get::run(client, id, writer, logger).await
+            }
+            MonitorsAction::Search { query } => {
+                logger.log_api_endpoint("/api/v1/monitor/search", "GET");
+
+                let client = client::MonitorsClient::with_retry(config, &retry);
+                commands::monitors::search::run(client, query, writer, logger).await
+            }
+            MonitorsAction::Mute {
+                id,
+                scope,
+                end,
+                tz,
+                message,
+            } => {
+                let end = end.map(|end| end.resolve(now, &tz)).transpose()?;
+                if let Some(end) = end
+                    && end <= now
+                {
+                    return Err(AppError::InvalidQuery(format!(
+                        "Mute end time {} is in the past",
+                        end.to_rfc3339()
+                    )));
+                }
+                logger.log_api_endpoint("/api/v2/downtime", "POST");
+
+                let client = client::MonitorsClient::with_retry(config, &retry);
+                commands::monitors::mute::run(client, id, scope, end, message, writer, logger).await
+            }
+            MonitorsAction::Unmute {
+                id,
+                scope,
+                include_shared,
+            } => {
+                logger.log_api_endpoint(&format!("/api/v2/monitor/{}/downtime_matches", id), "GET");
+                if !include_shared {
+                    logger.log_api_endpoint("/api/v2/downtime/{downtime_id}", "GET");
+                }
+                logger.log_api_endpoint("/api/v2/downtime/{downtime_id}", "DELETE");
+
+                let client = client::MonitorsClient::with_retry(config, &retry);
+                commands::monitors::unmute::run(client, id, scope, include_shared, writer, logger)
+                    .await
+            }
+            MonitorsAction::Status { id, status } => {
+                logger.log_api_endpoint(&format!("/api/v1/monitor/{}", id), "GET");
+
+                let client = client::MonitorsClient::with_retry(config, &retry);
+                commands::monitors::status::run(client, id, status, writer, logger).await
+            }
+            MonitorsAction::Export {
+                dir,
+                format,
+                selection,
+            } => {
+                logger.log_api_endpoint("/api/v1/monitor", "GET");
+
+                let client = client::MonitorsClient::with_retry(config, &retry);
+                let filter = selection.filter(Vec::new());
+                commands::monitors::export::run(client, filter, dir, format, writer, logger).await
+            }
+            MonitorsAction::Apply {
+                dir,
+                selection,
+                prune,
+                yes,
+            } => {
+                logger.log_api_endpoint("/api/v1/monitor", "GET");
+
+                let client = client::MonitorsClient::with_retry(config, &retry);
+                let filter = selection.filter(Vec::new());
+                commands::monitors::apply::run(client, filter, dir, prune, yes, writer, logger)
+                    .await
+            }
+        },
+        ApiDomain::Slo { action } => match action {
+            SloAction::List { query, tag } => {
+                logger.log_api_endpoint("/api/v1/slo", "GET");
+
+                let client = client::SloClient::with_retry(config, &retry);
+                commands::slo::list::run(client, query, tag, writer, logger).await
+            }
+            SloAction::Get { id } => {
+                logger.log_api_endpoint(&format!("/api/v1/slo/{}", id), "GET");
+
+                let client = client::SloClient::with_retry(config, &retry);
+                commands::slo::get::run(client, id, writer, logger).await
+            }
+            SloAction::History {
+                id,
+                time_range,
+                target,
+            } => {
+                let range = time_range.resolve(now)?;
+                logger.log(&format!("Time range: {}", range));
+                logger.log_api_endpoint(&format!("/api/v1/slo/{}/history", id), "GET");
+
+                let client = client::SloClient::with_retry(config, &retry);
+                commands::slo::history::run(client, id, range, target, writer, logger).await
+            }
+            SloAction::BurnRate {
+                id,
+                windows,
+                threshold,
+                target,
+                to,
+                tz,
+            } => {
+                logger.log_api_endpoint(&format!("/api/v1/slo/{}/history", id), "GET");
+
+                let client = client::SloClient::with_retry(config, &retry);
+                commands::slo::burn_rate::run(
+                    client, id, windows, threshold, target, to, tz, now, writer, logger,
+                )
+                .await
+            }
+        },
+        ApiDomain::Events { action } => match action {
+            EventsAction::Search {
+                query,
+                time_range,
+                pagination,
+            } => {
+                let range = time_range.resolve(now)?;
+                logger.log_request("events", &query, &range);
+                logger.log_api_endpoint("/api/v2/events/search", "POST");
+                logger.log_datadog_url("events", &query, &range, &site);
+
+                let client = client::EventsClient::with_retry(config, &retry);
+                commands::events::search::run(client, query, range, pagination, writer, logger)
+                    .await
+            }
+            EventsAction::Post {
+                title,
+                text,
+                tags,
+                alert_type,
+            } => {
+                logger.log_api_endpoint("/api/v1/events", "POST");
+
+                let client = client::EventsClient::with_retry(config, &retry);
+                commands::events::post::run(client, title, text, tags, alert_type, writer, logger)
+                    .await
+            }
+        },
+        ApiDomain::Dashboards { action } => match action {
+            DashboardsAction::List => {
+                logger.log_api_endpoint("/api/v1/dashboard", "GET");
+
+                let client = client::DashboardsClient::with_retry(config, &retry);
+                commands::dashboards::list::run(client, writer, logger).await
+            }
+            DashboardsAction::Get { id } => {
+                logger.log_api_endpoint(&format!("/api/v1/dashboard/{}", id), "GET");
+
+                let client = client::DashboardsClient::with_retry(config, &retry);
+                commands::dashboards::get::run(client, id, writer, logger).await
+            }
+            DashboardsAction::Export { dir, ids } => {
+                if ids.is_empty() {
+                    logger.log_api_endpoint("/api/v1/dashboard", "GET");
+                }
+                logger.log_api_endpoint("/api/v1/dashboard/{dashboard_id}", "GET");
+
+                let client = client::DashboardsClient::with_retry(config, &retry);
+                commands::dashboards::export::run(client, dir, ids, writer, logger).await
+            }
+            DashboardsAction::Import { files, replace } => {
+                match &replace {
+                    Some(id) => {
+                        logger.log_api_endpoint(&format!("/api/v1/dashboard/{}", id), "PUT")
+                    }
+                    None => logger.log_api_endpoint("/api/v1/dashboard", "POST"),
+                }
+
+                let client = client::DashboardsClient::with_retry(config, &retry);
+                commands::dashboards::import::run(client, files, replace, writer, logger).await
+            }
+            DashboardsAction::Diff { id, file } => {
+                logger.log_api_endpoint(&format!("/api/v1/dashboard/{}", id), "GET");
+
+                let client = client::DashboardsClient::with_retry(config, &retry);
+                commands::dashboards::diff::run(client, id, file, writer, logger).await
             }
         },
     }
 }
+
+/// Runs a config command, which needs no credentials or settings.
+fn run_config(
+    action: ConfigAction,
+    profile: Option<&str>,
+    writer: Box<dyn output::RecordWriter>,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    match action {
+        ConfigAction::List => commands::config::list::run(profile, writer, logger),
+        ConfigAction::Show { name } => commands::config::show::run(name, profile, writer, logger),
+        ConfigAction::Use { name } => commands::config::use_profile::run(name, writer, logger),
+    }
+}
+
+/// Warns when a range starts before the oldest data Datadog retains.
+fn warn_beyond_retention(
+    logger: &VerboseLogger,
+    data: &str,
+    range: &ResolvedRange,
+    retention_days: u32,
+    now: DateTime<Utc>,
+) {
+    if let Some(warning) = range.retention_warning(data, retention_days, now) {
+        logger.warn(&warning);
+    }
+}
@@ -0,0 +1,548 @@
+//! Monitor definitions stored as files, for keeping monitors in version control.
+//!
+//! A definition is a monitor as returned by the API with the fields Datadog
+//! manages itself (IDs, timestamps, creator, current state) removed. Monitors
+//! are matched to definitions by name: [`plan`] compares a directory of
+//! definitions with the monitors in the org and lists what to create, update
+//! and delete to make them agree.
+
+use datadog_api_client::datadogV1::model::Monitor;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::diff::{Change, changed_fields, remove_nulls};
+use crate::error::AppError;
+
+/// Fields set by Datadog that are not part of a monitor's definition.
+const SERVER_MANAGED: &[&str] = &[
+    "assets",
+    "created",
+    "created_at",
+    "creator",
+    "deleted",
+    "id",
+    "matching_downtimes",
+    "modified",
+    "multi",
+    "org_id",
+    "overall_state",
+    "overall_state_modified",
+    "state",
+];
+
+/// List fields whose order carries no meaning, sorted so they diff cleanly.
+const UNORDERED: &[&str] = &["restricted_roles", "tags"];
+
+/// Longest file name stem derived from a monitor name.
+const MAX_STEM_LEN: usize = 60;
+
+/// File format of monitor definitions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DefinitionFormat {
+    #[default]
+    Json,
+    Yaml,
+}
+
+impl DefinitionFormat {
+    /// File extension written for this format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Yaml => "yaml",
+        }
+    }
+
+    /// Renders a definition in this format.
+    pub fn render(&self, definition: &Value) -> Result<String, AppError> {
+        match self {
+            Self::Json => Ok(format!("{}\n", serde_json::to_string_pretty(definition)?)),
+            Self::Yaml => serde_yaml::to_string(definition)
+                .map_err(|e| AppError::Config(format!("Failed to render YAML: {}", e))),
+        }
+    }
+
+    fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "json" => Some(Self::Json),
+            "yaml" | "yml" => Some(Self::Yaml),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for DefinitionFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "yaml" | "yml" => Ok(Self::Yaml),
+            _ => Err(format!(
+                "Unknown definition format '{}'. Expected one of: json, yaml",
+                s
+            )),
+        }
+    }
+}
+
+/// Converts a monitor into its definition: server-managed and null fields are
+/// removed and tags are sorted.
+pub fn normalize(monitor: &Monitor) -> Result<Value, AppError> {
+    let mut value = serde_json::to_value(monitor)?;
+    if let Value::Object(fields) = &mut value {
+        for field in SERVER_MANAGED {
+            fields.remove(*field);
+        }
+        for field in UNORDERED {
+            if let Some(Value::Array(items)) = fields.get_mut(*field) {
+                items.sort_by_key(|item| item.to_string());
+            }
+        }
+    }
+    remove_nulls(&mut value);
+    Ok(value)
+}
+
+/// Returns a file name for a monitor's definition, unique among `taken`.
+///
+/// The name is derived from the monitor name; the monitor ID is appended when
+/// two monitors would otherwise share a file.
+pub fn file_name(
+    name: &str,
+    id: Option<i64>,
+    format: DefinitionFormat,
+    taken: &mut HashSet<String>,
+) -> String {
+    let mut stem = String::new();
+    for c in name.chars().flat_map(char::to_lowercase) {
+        if c.is_ascii_alphanumeric() {
+            stem.push(c);
+        } else if !stem.is_empty() && !stem.ends_with('-') {
+            stem.push('-');
+        }
+        if stem.len() >= MAX_STEM_LEN {
+            break;
+        }
+    }
+    let mut stem = stem.trim_end_matches('-').to_string();
+    if stem.is_empty() {
+        stem = "monitor".to_string();
+    }
+
+    let mut candidate = format!("{}.{}", stem, format.extension());
+    if taken.contains(&candidate) {
+        let suffix = id.map_or_else(|| taken.len().to_string(), |id| id.to_string());
+        candidate = format!("{}-{}.{}", stem, suffix, format.extension());
+    }
+    taken.insert(candidate.clone());
+    candidate
+}
+
+/// A monitor definition read from a file.
+#[derive(Debug, Clone)]
+pub struct Definition {
+    pub path: PathBuf,
+    pub name: String,
+    /// The monitor to create, validated against the API model
+    pub monitor: Monitor,
+    /// Normalized fields given in the file
+    pub body: Value,
+}
+
+impl Definition {
+    /// Parses a definition, checking it is a valid monitor with a name.
+    pub fn parse(path: &Path, contents: &str, format: DefinitionFormat) -> Result<Self, AppError> {
+        let invalid = |e: &dyn fmt::Display| {
+            AppError::Config(format!(
+                "Invalid monitor definition {}: {}",
+                path.display(),
+                e
+            ))
+        };
+
+        let value: Value = match format {
+            DefinitionFormat::Json => serde_json::from_str(contents).map_err(|e| invalid(&e))?,
+            DefinitionFormat::Yaml => serde_yaml::from_str(contents).map_err(|e| invalid(&e))?,
+        };
+        if !value.is_object() {
+            return Err(invalid(&"expected an object"));
+        }
+        let monitor: Monitor = serde_json::from_value(value).map_err(|e| invalid(&e))?;
+        let name = monitor
+            .name
+            .clone()
+            .filter(|name| !name.trim().is_empty())
+            .ok_or_else(|| invalid(&"missing field `name`"))?;
+        let body = normalize(&monitor)?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            name,
+            monitor,
+            body,
+        })
+    }
+
+    /// Reads every `.json`, `.yaml` and `.yml` file in `dir`, in file name order.
+    ///
+    /// Fails when a file is invalid or two files define monitors with the same name.
+    pub fn load_dir(dir: &Path) -> Result<Vec<Self>, AppError> {
+        let entries = std::fs::read_dir(dir).map_err(|e| {
+            AppError::Config(format!(
+                "Failed to read definitions from {}: {}",
+                dir.display(),
+                e
+            ))
+        })?;
+
+        let mut paths = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            if path.is_file()
+                && let Some(format) = DefinitionFormat::from_path(&path)
+            {
+                paths.push((path, format));
+            }
+        }
+        paths.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut definitions: Vec<Self> = Vec::new();
+        for (path, format) in paths {
+            let contents = std::fs::read_to_string(&path)?;
+            let definition = Self::parse(&path, &contents, format)?;
+            if let Some(other) = definitions.iter().find(|d| d.name == definition.name) {
+                return Err(AppError::Config(format!(
+                    "Monitor \"{}\" is defined in both {} and {}",
+                    definition.name,
+                    other.path.display(),
+                    definition.path.display()
+                )));
+            }
+            definitions.push(definition);
+        }
+        Ok(definitions)
+    }
+}
+
+/// A monitor in the org, as compared with definitions.
+#[derive(Debug, Clone)]
+pub struct Existing {
+    pub id: i64,
+    pub name: String,
+    /// The monitor's normalized definition
+    pub body: Value,
+}
+
+impl Existing {
+    /// Builds the comparison view of a monitor; `None` for monitors without an ID.
+    pub fn from_monitor(monitor: &Monitor) -> Result<Option<Self>, AppError> {
+        let Some(id) = monitor.id else {
+            return Ok(None);
+        };
+        Ok(Some(Self {
+            id,
+            name: monitor.name.clone().unwrap_or_default(),
+            body: normalize(monitor)?,
+        }))
+    }
+}
+
+/// What a plan step does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    Create,
+    Update,
+    Delete,
+}
+
+/// A monitor to create, update or delete.
+#[derive(Debug, Clone, Serialize)]
+pub struct Step {
+    pub action: Action,
+    pub name: String,
+    /// ID of the monitor to update or delete
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub monitor_id: Option<i64>,
+    /// Definition file for creates and updates
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<PathBuf>,
+    pub changes: Vec<Change>,
+    /// Index of the definition in the list given to [`plan`]
+    #[serde(skip)]
+    pub definition: Option<usize>,
+}
+
+/// The steps needed to make the org match a set of definitions.
+#[derive(Debug, Clone, Default)]
+pub struct Plan {
+    /// Creates first, then updates, then deletes
+    pub steps: Vec<Step>,
+    /// Definitions that already match their monitor
+    pub unchanged: usize,
+    /// Monitors without a definition that are kept because pruning is off
+    pub unmanaged: usize,
+}
+
+impl Plan {
+    /// Number of steps with the given action.
+    pub fn count(&self, action: Action) -> usize {
+        self.steps.iter().filter(|s| s.action == action).count()
+    }
+}
+
+/// Compares `definitions` with the `existing` monitors they may manage.
+///
+/// Definitions are matched to monitors by name. Only fields present in a
+/// definition are compared, so fields left out of a file keep their current
+/// value. With `prune`, monitors that no definition names are deleted.
+///
+/// Fails when several existing monitors share a name that a definition uses,
+/// since the definition could then apply to any of them.
+pub fn plan(
+    definitions: &[Definition],
+    existing: &[Existing],
+    prune: bool,
+) -> Result<Plan, AppError> {
+    let mut by_name: BTreeMap<&str, Vec<&Existing>> = BTreeMap::new();
+    for monitor in existing {
+        by_name.entry(&monitor.name).or_default().push(monitor);
+    }
+
+    let mut plan = Plan::default();
+    let mut creates = Vec::new();
+    let mut updates = Vec::new();
+
+    for (index, definition) in definitions.iter().enumerate() {
+        match by_name.get(definition.name.as_str()).map(Vec::as_slice) {
+            None | Some([]) => creates.push(Step {
+                action: Action::Create,
+                name: definition.name.clone(),
+                monitor_id: None,
+                file: Some(definition.path.clone()),
+                changes: changed_fields(&Value::Object(Map::new()), &definition.body),
+                definition: Some(index),
+            }),
+            Some([monitor]) => {
+                let changes = changed_fields(&monitor.body, &definition.body);
+                if changes.is_empty() {
+                    plan.unchanged += 1;
+                } else {
+                    updates.push(Step {
+                        action: Action::Update,
+                        name: definition.name.clone(),
+                        monitor_id: Some(monitor.id),
+                        file: Some(definition.path.clone()),
+                        changes,
+                        definition: Some(index),
+                    });
+                }
+            }
+            Some(monitors) => {
+                let ids: Vec<String> = monitors.iter().map(|m| m.id.to_string()).collect();
+                return Err(AppError::Config(format!(
+                    "{} matches several monitors named \"{}\" (IDs {}); rename them so each name is unique",
+                    definition.path.display(),
+                    definition.name,
+                    ids.join(", ")
+                )));
+            }
+        }
+    }
+
+    let defined: HashSet<&str> = definitions.iter().map(|d| d.name.as_str()).collect();
+    let mut deletes = Vec::new();
+    for monitor in existing
+        .iter()
+        .filter(|m| !defined.contains(m.name.as_str()))
+    {
+        if !prune {
+            plan.unmanaged += 1;
+            continue;
+        }
+        deletes.push(Step {
+            action: Action::Delete,
+            name: monitor.name.clone(),
+            monitor_id: Some(monitor.id),
+            file: None,
+            changes: Vec::new(),
+            definition: None,
+        });
+    }
+
+    plan.steps = creates.into_iter().chain(updates).chain(deletes).collect();
+    Ok(plan)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn monitor(value: Value) -> Monitor {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn definition(name: &str, body: Value) -> Definition {
+        let path = PathBuf::from(format!("{}.json", name));
+        Definition::parse(&path, &body.to_string(), DefinitionFormat::Json).unwrap()
+    }
+
+    fn existing(id: i64, mut body: Value) -> Existing {
+        body["id"] = json!(id);
+        Existing::from_monitor(&monitor(body)).unwrap().unwrap()
+    }
+
+    #[test]
+    fn test_normalize_strips_server_fields() {
+        let normalized = normalize(&monitor(json!({
+            "id": 1,
+            "name": "High CPU",
+            "query": "avg(last_5m):avg:system.cpu.user{*} > 90",
+            "type": "metric alert",
+            "created": "2024-01-15T10:00:00Z",
+            "overall_state": "Alert",
+            "creator": {"email": "someone@example.com"},
+            "priority": null,
+            "tags": ["team:b", "env:prod"]
+        })))
+        .unwrap();
+
+        assert_eq!(
+            normalized,
+            json!({
+                "name": "High CPU",
+                "query": "avg(last_5m):avg:system.cpu.user{*} > 90",
+                "type": "metric alert",
+                "tags": ["env:prod", "team:b"]
+            })
+        );
+    }
+
+    #[test]
+    fn test_file_name_slugs_and_dedupes() {
+        let mut taken = HashSet::new();
+        let format = DefinitionFormat::Yaml;
+        assert_eq!(
+            file_name("[Prod] High CPU!", Some(1), format, &mut taken),
+            "prod-high-cpu.yaml"
+        );
+        assert_eq!(
+            file_name("prod high cpu", Some(2), format, &mut taken),
+            "prod-high-cpu-2.yaml"
+        );
+        assert_eq!(file_name("!!!", None, format, &mut taken), "monitor.yaml");
+    }
+
+    #[test]
+    fn test_parse_rejects_definition_without_name() {
+        let err = Definition::parse(
+            Path::new("cpu.yaml"),
+            "query: avg(last_5m):avg:system.cpu.user{*} > 90\ntype: metric alert\n",
+            DefinitionFormat::Yaml,
+        )
+        .unwrap_err();
+        assert!(matches!(err, AppError::Config(_)));
+        assert!(err.to_string().contains("cpu.yaml"));
+    }
+
+    #[test]
+    fn test_parse_yaml_matches_api_numbers() {
+        let parsed = Definition::parse(
+            Path::new("cpu.yaml"),
+            "name: High CPU\nquery: q\ntype: metric alert\noptions:\n  thresholds:\n    critical: 90\n",
+            DefinitionFormat::Yaml,
+        )
+        .unwrap();
+        let current = existing(
+            1,
+            json!({
+                "name": "High CPU",
+                "query": "q",
+                "type": "metric alert",
+                "options": {"thresholds": {"critical": 90.0}}
+            }),
+        );
+        assert!(changed_fields(&current.body, &parsed.body).is_empty());
+    }
+
+    #[test]
+    fn test_plan_creates_updates_and_prunes() {
+        let definitions = vec![
+            definition(
+                "cpu",
+                json!({"name": "High CPU", "query": "q > 95", "type": "metric alert"}),
+            ),
+            definition(
+                "disk",
+                json!({"name": "Disk", "query": "d", "type": "metric alert"}),
+            ),
+            definition(
+                "new",
+                json!({"name": "New", "query": "n", "type": "metric alert"}),
+            ),
+        ];
+        let existing = vec![
+            existing(
+                1,
+                json!({"name": "High CPU", "query": "q > 90", "type": "metric alert", "message": "kept"}),
+            ),
+            existing(
+                2,
+                json!({"name": "Disk", "query": "d", "type": "metric alert"}),
+            ),
+            existing(
+                3,
+                json!({"name": "Old", "query": "o", "type": "metric alert"}),
+            ),
+        ];
+
+        let kept = plan(&definitions, &existing, false).unwrap();
+        assert_eq!(kept.count(Action::Create), 1);
+        assert_eq!(kept.count(Action::Update), 1);
+        assert_eq!(kept.count(Action::Delete), 0);
+        assert_eq!(kept.unchanged, 1);
+        assert_eq!(kept.unmanaged, 1);
+
+        let update = &kept.steps[1];
+        assert_eq!(update.monitor_id, Some(1));
+        assert_eq!(
+            update.changes,
+            vec![Change {
+                path: "query".into(),
+                from: Some(json!("q > 90")),
+                to: Some(json!("q > 95")),
+            }]
+        );
+
+        let pruned = plan(&definitions, &existing, true).unwrap();
+        let delete = pruned.steps.last().unwrap();
+        assert_eq!(delete.action, Action::Delete);
+        assert_eq!(delete.monitor_id, Some(3));
+    }
+
+    #[test]
+    fn test_plan_rejects_ambiguous_names() {
+        let definitions = vec![definition(
+            "cpu",
+            json!({"name": "High CPU", "query": "q", "type": "metric alert"}),
+        )];
+        let existing = vec![
+            existing(
+                1,
+                json!({"name": "High CPU", "query": "q", "type": "metric alert"}),
+            ),
+            existing(
+                2,
+                json!({"name": "High CPU", "query": "q", "type": "metric alert"}),
+            ),
+        ];
+        let err = plan(&definitions, &existing, false).unwrap_err();
+        assert!(err.to_string().contains("IDs 1, 2"));
+    }
+}
@@ -0,0 +1,284 @@
+//! Monitor status filters, per-group monitor states and downtime scopes.
+//!
+//! Datadog reports an overall state for each monitor and, for multi-alert
+//! monitors, a state per group (e.g. per host). This module parses the status
+//! names accepted on the command line and flattens a monitor's group states
+//! into one record per group. It also tells downtimes that mute a single
+//! monitor from those shared with other monitors.
+
+pub mod definitions;
+
+use datadog_api_client::datadogV1::model::{Monitor, MonitorOverallStates};
+use datadog_api_client::datadogV2::model::{DowntimeMonitorIdentifier, DowntimeResponseData};
+use serde::Serialize;
+use std::str::FromStr;
+
+/// Group name used for monitors that report no per-group state.
+const WHOLE_MONITOR: &str = "*";
+
+/// A monitor or group state to filter on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MonitorStatus {
+    Alert,
+    Warn,
+    NoData,
+    Ok,
+    Ignored,
+    Skipped,
+    Unknown,
+}
+
+impl MonitorStatus {
+    /// Returns whether `state`, as reported by the API, is this status.
+    pub fn matches(&self, state: &MonitorOverallStates) -> bool {
+        matches!(
+            (self, state),
+            (Self::Alert, MonitorOverallStates::ALERT)
+                | (Self::Warn, MonitorOverallStates::WARN)
+                | (Self::NoData, MonitorOverallStates::NO_DATA)
+                | (Self::Ok, MonitorOverallStates::OK)
+                | (Self::Ignored, MonitorOverallStates::IGNORED)
+                | (Self::Skipped, MonitorOverallStates::SKIPPED)
+                | (Self::Unknown, MonitorOverallStates::UNKNOWN)
+        )
+    }
+
+    /// Returns whether `state` is any of `statuses`; an empty list matches every state.
+    pub fn any_matches(statuses: &[MonitorStatus], state: Option<&MonitorOverallStates>) -> bool {
+        statuses.is_empty() || state.is_some_and(|state| statuses.iter().any(|s| s.matches(state)))
+    }
+}
+
+impl FromStr for MonitorStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "alert" => Ok(Self::Alert),
+            "warn" | "warning" => Ok(Self::Warn),
+            "no-data" | "no_data" | "nodata" | "no data" => Ok(Self::NoData),
+            "ok" => Ok(Self::Ok),
+            "ignored" => Ok(Self::Ignored),
+            "skipped" => Ok(Self::Skipped),
+            "unknown" => Ok(Self::Unknown),
+            _ => Err(format!(
+                "Unknown monitor status '{}'. Expected one of: alert, warn, no-data, ok, ignored, skipped, unknown",
+                s
+            )),
+        }
+    }
+}
+
+/// Filters for listing monitors.
+///
+/// The name and tags are sent to the API; statuses are matched against each
+/// monitor's overall state after it is fetched.
+#[derive(Debug, Clone, Default)]
+pub struct MonitorFilter {
+    /// Substring of the monitor name
+    pub name: Option<String>,
+    /// Scope tags the monitor queries, e.g. `host:web-1`
+    pub tags: Vec<String>,
+    /// Tags set on the monitor itself, e.g. `team:payments`
+    pub monitor_tags: Vec<String>,
+    /// Overall states to keep; empty keeps every monitor
+    pub statuses: Vec<MonitorStatus>,
+}
+
+impl MonitorFilter {
+    /// Returns whether `monitor` passes the status filter.
+    pub fn matches(&self, monitor: &Monitor) -> bool {
+        MonitorStatus::any_matches(&self.statuses, monitor.overall_state.as_ref())
+    }
+}
+
+/// The state of one monitor group.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GroupState {
+    pub monitor_id: Option<i64>,
+    pub monitor_name: String,
+    /// Group name, e.g. `host:web-1`; `*` for monitors without groups
+    pub group: String,
+    pub status: String,
+    /// Unix seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_triggered_ts: Option<i64>,
+    /// Unix seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_resolved_ts: Option<i64>,
+    /// Unix seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_notified_ts: Option<i64>,
+    /// Unix seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_nodata_ts: Option<i64>,
+}
+
+/// Flattens a monitor's state into one record per group, ordered by group name.
+///
+/// Monitors that report no groups (simple alerts, or monitors fetched without
+/// group states) yield a single `*` record with the overall state. Groups are
+/// kept when their status is one of `statuses`, or always when it is empty.
+pub fn group_states(monitor: &Monitor, statuses: &[MonitorStatus]) -> Vec<GroupState> {
+    let monitor_name = monitor.name.clone().unwrap_or_default();
+    let groups = monitor
+        .state
+        .as_ref()
+        .and_then(|state| state.groups.as_ref())
+        .filter(|groups| !groups.is_empty());
+
+    let Some(groups) = groups else {
+        if !MonitorStatus::any_matches(statuses, monitor.overall_state.as_ref()) {
+            return Vec::new();
+        }
+        return vec![GroupState {
+            monitor_id: monitor.id,
+            monitor_name,
+            group: WHOLE_MONITOR.to_string(),
+            status: state_name(monitor.overall_state.as_ref()),
+            last_triggered_ts: None,
+            last_resolved_ts: None,
+            last_notified_ts: None,
+            last_nodata_ts: None,
+        }];
+    };
+
+    groups
+        .iter()
+        .filter(|(_, group)| MonitorStatus::any_matches(statuses, group.status.as_ref()))
+        .map(|(name, group)| GroupState {
+            monitor_id: monitor.id,
+            monitor_name: monitor_name.clone(),
+            group: group.name.clone().unwrap_or_else(|| name.clone()),
+            status: state_name(group.status.as_ref()),
+            last_triggered_ts: group.last_triggered_ts,
+            last_resolved_ts: group.last_resolved_ts,
+            last_notified_ts: group.last_notified_ts,
+            last_nodata_ts: group.last_nodata_ts,
+        })
+        .collect()
+}
+
+fn state_name(state: Option<&MonitorOverallStates>) -> String {
+    state.map_or_else(|| "Unknown".to_string(), |s| s.to_string())
+}
+
+/// Returns whether a downtime mutes `monitor_id` alone.
+///
+/// Downtimes for another monitor's ID, or for monitor tags, may also cover
+/// `monitor_id` but are shared with other monitors.
+pub fn mutes_only(downtime: &DowntimeResponseData, monitor_id: i64) -> bool {
+    let identifier = downtime
+        .attributes
+        .as_ref()
+        .and_then(|a| a.monitor_identifier.as_ref());
+    matches!(
+        identifier,
+        Some(DowntimeMonitorIdentifier::DowntimeMonitorIdentifierId(id)) if id.monitor_id == monitor_id
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn monitor(value: serde_json::Value) -> Monitor {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_status_parses_aliases() {
+        assert_eq!("Alert".parse::<MonitorStatus>(), Ok(MonitorStatus::Alert));
+        assert_eq!(
+            "no-data".parse::<MonitorStatus>(),
+            Ok(MonitorStatus::NoData)
+        );
+        assert_eq!("nodata".parse::<MonitorStatus>(), Ok(MonitorStatus::NoData));
+        assert_eq!("warning".parse::<MonitorStatus>(), Ok(MonitorStatus::Warn));
+        assert!("firing".parse::<MonitorStatus>().is_err());
+    }
+
+    #[test]
+    fn test_filter_matches_overall_state() {
+        let alerting = monitor(json!({
+            "query": "avg(last_5m):avg:system.cpu.user{*} > 90",
+            "type": "metric alert",
+            "overall_state": "Alert"
+        }));
+        let filter = MonitorFilter {
+            statuses: vec![MonitorStatus::Warn, MonitorStatus::Alert],
+            ..Default::default()
+        };
+        assert!(filter.matches(&alerting));
+        assert!(MonitorFilter::default().matches(&alerting));
+
+        let ok_only = MonitorFilter {
+            statuses: vec![MonitorStatus::Ok],
+            ..Default::default()
+        };
+        assert!(!ok_only.matches(&alerting));
+    }
+
+    #[test]
+    fn test_group_states_one_record_per_group() {
+        let monitor = monitor(json!({
+            "id": 42,
+            "name": "High CPU",
+            "query": "avg(last_5m):avg:system.cpu.user{*} by {host} > 90",
+            "type": "metric alert",
+            "overall_state": "Alert",
+            "state": {"groups": {
+                "host:b": {"name": "host:b", "status": "OK", "last_resolved_ts": 1700000100},
+                "host:a": {"name": "host:a", "status": "Alert", "last_triggered_ts": 1700000000}
+            }}
+        }));
+
+        let states = group_states(&monitor, &[]);
+        assert_eq!(states.len(), 2);
+        assert_eq!(states[0].group, "host:a");
+        assert_eq!(states[0].status, "Alert");
+        assert_eq!(states[0].last_triggered_ts, Some(1700000000));
+        assert_eq!(states[1].status, "OK");
+
+        let alerting = group_states(&monitor, &[MonitorStatus::Alert]);
+        assert_eq!(alerting.len(), 1);
+        assert_eq!(alerting[0].group, "host:a");
+    }
+
+    #[test]
+    fn test_group_states_without_groups_uses_overall_state() {
+        let monitor = monitor(json!({
+            "id": 7,
+            "name": "Queue depth",
+            "query": "max(last_5m):max:queue.depth{*} > 100",
+            "type": "metric alert",
+            "overall_state": "No Data"
+        }));
+
+        let states = group_states(&monitor, &[]);
+        assert_eq!(states.len(), 1);
+        assert_eq!(states[0].group, "*");
+        assert_eq!(states[0].status, "No Data");
+        assert!(group_states(&monitor, &[MonitorStatus::Alert]).is_empty());
+    }
+
+    #[test]
+    fn test_mutes_only_this_monitor() {
+        let downtime = |identifier: serde_json::Value| -> DowntimeResponseData {
+            serde_json::from_value(json!({
+                "id": "dt-1",
+                "type": "downtime",
+                "attributes": {"monitor_identifier": identifier}
+            }))
+            .unwrap()
+        };
+
+        assert!(mutes_only(&downtime(json!({"monitor_id": 123})), 123));
+        assert!(!mutes_only(&downtime(json!({"monitor_id": 456})), 123));
+        assert!(!mutes_only(
+            &downtime(json!({"monitor_tags": ["team:payments"]})),
+            123
+        ));
+    }
+}
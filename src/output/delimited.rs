@@ -0,0 +1,165 @@
+//! CSV and TSV output writers.
+
+use serde_json::Value;
+use std::io::{self, BufWriter, Write};
+
+use super::RecordWriter;
+use super::fields::{lookup, render_cell, select};
+
+/// Writes records as delimiter-separated rows with a header row.
+///
+/// Columns come from `--fields`, or are inferred from the first record when no
+/// fields are given. Later records are written with the same columns; fields
+/// they lack are left empty.
+pub struct DelimitedWriter {
+    writer: BufWriter<Box<dyn Write>>,
+    delimiter: char,
+    columns: Vec<String>,
+    header_written: bool,
+}
+
+impl DelimitedWriter {
+    /// Creates a comma-separated writer.
+    pub fn csv(writer: Box<dyn Write>, fields: Vec<String>) -> Self {
+        Self::new(writer, ',', fields)
+    }
+
+    /// Creates a tab-separated writer.
+    pub fn tsv(writer: Box<dyn Write>, fields: Vec<String>) -> Self {
+        Self::new(writer, '\t', fields)
+    }
+
+    fn new(writer: Box<dyn Write>, delimiter: char, fields: Vec<String>) -> Self {
+        Self {
+            writer: BufWriter::new(writer),
+            delimiter,
+            columns: fields,
+            header_written: false,
+        }
+    }
+
+    fn write_row<I: IntoIterator<Item = String>>(&mut self, cells: I) -> io::Result<()> {
+        let separator = self.delimiter.to_string();
+        let line = cells
+            .into_iter()
+            .map(|cell| self.escape(&cell))
+            .collect::<Vec<_>>()
+            .join(&separator);
+        writeln!(self.writer, "{}", line)
+    }
+
+    fn write_header(&mut self) -> io::Result<()> {
+        self.header_written = true;
+        let columns = self.columns.clone();
+        self.write_row(columns)
+    }
+
+    fn escape(&self, cell: &str) -> String {
+        if self.delimiter == '\t' {
+            // TSV has no quoting; escape the characters that would break the row
+            cell.replace('\\', "\\\\")
+                .replace('\t', "\\t")
+                .replace('\n', "\\n")
+                .replace('\r', "\\r")
+        } else if cell.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", cell.replace('"', "\"\""))
+        } else {
+            cell.to_string()
+        }
+    }
+}
+
+impl RecordWriter for DelimitedWriter {
+    fn write_value(&mut self, record: &Value) -> io::Result<()> {
+        if self.columns.is_empty() {
+            self.columns = select(record, &[]).into_iter().map(|(k, _)| k).collect();
+        }
+        if !self.header_written {
+            self.write_header()?;
+        }
+
+        let cells: Vec<String> = self
+            .columns
+            .iter()
+            .map(|column| lookup(record, column).map(render_cell).unwrap_or_default())
+            .collect();
+        self.write_row(cells)?;
+        self.writer.flush()
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        // With explicit fields, an empty result still gets a header row
+        if !self.header_written && !self.columns.is_empty() {
+            self.write_header()?;
+        }
+        self.writer.flush()
+    }
+
+    fn resume(&mut self) -> io::Result<()> {
+        self.header_written = true;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::tests::SharedBuffer;
+    use serde_json::json;
+
+    #[test]
+    fn test_csv_infers_columns_from_first_record() {
+        let buffer = SharedBuffer::default();
+        let mut writer = DelimitedWriter::csv(Box::new(buffer.clone()), vec![]);
+        writer
+            .write_value(&json!({"id": 1, "attributes": {"service": "api"}}))
+            .unwrap();
+        writer.write_value(&json!({"id": 2})).unwrap();
+        writer.finish().unwrap();
+
+        assert_eq!(buffer.contents(), "attributes.service,id\napi,1\n,2\n");
+    }
+
+    #[test]
+    fn test_csv_quotes_special_characters() {
+        let buffer = SharedBuffer::default();
+        let mut writer = DelimitedWriter::csv(Box::new(buffer.clone()), vec!["msg".to_string()]);
+        writer
+            .write_value(&json!({"msg": "a, \"quoted\" value"}))
+            .unwrap();
+
+        assert_eq!(buffer.contents(), "msg\n\"a, \"\"quoted\"\" value\"\n");
+    }
+
+    #[test]
+    fn test_tsv_escapes_tabs_and_newlines() {
+        let buffer = SharedBuffer::default();
+        let mut writer = DelimitedWriter::tsv(
+            Box::new(buffer.clone()),
+            vec!["a".to_string(), "b".to_string()],
+        );
+        writer
+            .write_value(&json!({"a": "x\ty", "b": "line1\nline2"}))
+            .unwrap();
+
+        assert_eq!(buffer.contents(), "a\tb\nx\\ty\tline1\\nline2\n");
+    }
+
+    #[test]
+    fn test_header_written_for_empty_result_with_fields() {
+        let buffer = SharedBuffer::default();
+        let mut writer = DelimitedWriter::csv(Box::new(buffer.clone()), vec!["id".to_string()]);
+        writer.finish().unwrap();
+        assert_eq!(buffer.contents(), "id\n");
+    }
+
+    #[test]
+    fn test_resumed_output_has_no_header() {
+        let buffer = SharedBuffer::default();
+        let mut writer = DelimitedWriter::csv(Box::new(buffer.clone()), vec![]);
+        writer.resume().unwrap();
+        writer.write_value(&json!({"id": 3})).unwrap();
+        writer.finish().unwrap();
+        assert_eq!(buffer.contents(), "3\n");
+    }
+}
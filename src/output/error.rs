@@ -0,0 +1,165 @@
+//! Error reporting on stderr.
+
+use clap::ValueEnum;
+use serde::Serialize;
+use std::ffi::OsString;
+use std::io::{self, Write};
+
+use ddog::error::AppError;
+
+/// Formats selectable with `--error-format`.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorFormat {
+    /// A human-readable `Error: ...` line (default)
+    #[default]
+    Text,
+    /// A single JSON object on one line
+    Json,
+}
+
+impl ErrorFormat {
+    /// Finds the `--error-format` given in raw command-line arguments.
+    ///
+    /// Used for errors in the arguments themselves, when they could not be
+    /// parsed. Falls back to text when the option is missing or invalid.
+    pub fn from_args(args: &[OsString]) -> Self {
+        let mut args = args.iter().map(|arg| arg.to_string_lossy());
+        while let Some(arg) = args.next() {
+            if arg == "--" {
+                break;
+            }
+            let value = match arg.strip_prefix("--error-format") {
+                Some("") => args.next().map(|value| value.into_owned()),
+                Some(rest) => rest.strip_prefix('=').map(String::from),
+                None => continue,
+            };
+            if let Some(format) = value.and_then(|v| ErrorFormat::from_str(&v, true).ok()) {
+                return format;
+            }
+        }
+        ErrorFormat::default()
+    }
+}
+
+/// A failure as reported with `--error-format json`.
+#[derive(Debug, Serialize)]
+struct ErrorReport<'a> {
+    kind: &'static str,
+    exit_code: i32,
+    http_status: Option<u16>,
+    message: String,
+    request_id: Option<&'a str>,
+    command: &'a str,
+}
+
+/// Writes an error to stderr in the given format.
+///
+/// # Arguments
+///
+/// * `error` - The error that ended the command
+/// * `format` - Text or JSON
+/// * `command` - The command that failed (e.g. "logs search")
+pub fn report_error(error: &AppError, format: ErrorFormat, command: &str) {
+    // Nothing sensible is left to do if stderr itself is gone
+    let _ = write_error(&mut io::stderr().lock(), error, format, command);
+}
+
+fn write_error(
+    out: &mut dyn Write,
+    error: &AppError,
+    format: ErrorFormat,
+    command: &str,
+) -> io::Result<()> {
+    match format {
+        ErrorFormat::Text => writeln!(out, "Error: {}", error),
+        ErrorFormat::Json => {
+            let report = ErrorReport {
+                kind: error.kind(),
+                exit_code: error.exit_code(),
+                http_status: error.http_status(),
+                message: error.to_string(),
+                request_id: error.request_id(),
+                command,
+            };
+            serde_json::to_writer(&mut *out, &report)?;
+            writeln!(out)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn args(args: &[&str]) -> Vec<OsString> {
+        args.iter().map(OsString::from).collect()
+    }
+
+    #[test]
+    fn test_format_from_args() {
+        let format = |a: &[&str]| ErrorFormat::from_args(&args(a));
+        assert_eq!(
+            format(&["ddog", "--error-format", "json", "logs"]),
+            ErrorFormat::Json
+        );
+        assert_eq!(
+            format(&["ddog", "logs", "search", "--error-format=json"]),
+            ErrorFormat::Json
+        );
+        assert_eq!(format(&["ddog", "logs", "search"]), ErrorFormat::Text);
+        assert_eq!(
+            format(&["ddog", "--error-format", "xml"]),
+            ErrorFormat::Text
+        );
+        assert_eq!(
+            format(&["ddog", "logs", "search", "--", "--error-format=json"]),
+            ErrorFormat::Text
+        );
+    }
+
+    fn render(error: &AppError, format: ErrorFormat) -> String {
+        let mut buffer = Vec::new();
+        write_error(&mut buffer, error, format, "logs search").unwrap();
+        String::from_utf8(buffer).unwrap()
+    }
+
+    #[test]
+    fn test_text_format() {
+        let error = AppError::Config("DD_API_KEY is empty".to_string());
+        assert_eq!(
+            render(&error, ErrorFormat::Text),
+            "Error: Configuration error: DD_API_KEY is empty\n"
+        );
+    }
+
+    #[test]
+    fn test_json_format() {
+        let error = AppError::Http {
+            status: 429,
+            request_id: Some("abc".to_string()),
+            error: Box::new(AppError::RateLimited("slow down".to_string())),
+        };
+
+        let output = render(&error, ErrorFormat::Json);
+        assert_eq!(output.lines().count(), 1);
+
+        let report: Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(report["kind"], "rate_limited");
+        assert_eq!(report["exit_code"], 9);
+        assert_eq!(report["http_status"], 429);
+        assert_eq!(report["message"], "Rate limited: slow down");
+        assert_eq!(report["request_id"], "abc");
+        assert_eq!(report["command"], "logs search");
+    }
+
+    #[test]
+    fn test_json_format_without_http_details() {
+        let error = AppError::InvalidQuery("bad time".to_string());
+        let report: Value = serde_json::from_str(&render(&error, ErrorFormat::Json)).unwrap();
+        assert_eq!(report["kind"], "invalid_query");
+        assert_eq!(report["exit_code"], 4);
+        assert!(report["http_status"].is_null());
+        assert!(report["request_id"].is_null());
+    }
+}
@@ -0,0 +1,233 @@
+//! Field flattening and selection for tabular output formats, and field
+//! projection for structured ones (NDJSON, JSON, YAML).
+
+use serde_json::{Map, Value};
+use std::io;
+
+use super::RecordWriter;
+
+/// Flattens a JSON value into dotted-path columns.
+///
+/// Nested objects become `parent.child` keys; arrays and scalars are leaves.
+/// A non-object value is returned as a single column named `value`.
+pub fn flatten(value: &Value) -> Vec<(String, Value)> {
+    let mut columns = Vec::new();
+    match value {
+        Value::Object(map) => flatten_into(map, "", &mut columns),
+        other => columns.push(("value".to_string(), other.clone())),
+    }
+    columns
+}
+
+fn flatten_into(map: &Map<String, Value>, prefix: &str, columns: &mut Vec<(String, Value)>) {
+    for (key, value) in map {
+        let path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        match value {
+            Value::Object(nested) if !nested.is_empty() => flatten_into(nested, &path, columns),
+            other => columns.push((path, other.clone())),
+        }
+    }
+}
+
+/// Looks up a dotted path in a JSON value.
+///
+/// Keys may themselves contain dots (e.g. `attributes.http.status_code` where
+/// `http.status_code` is one key), so the longest matching key is tried first
+/// at each level.
+pub fn lookup<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    lookup_keys(value, path).map(|(_, found)| found)
+}
+
+/// Looks up a dotted path like [`lookup`], also returning the keys it matched.
+fn lookup_keys<'a, 'p>(value: &'a Value, path: &'p str) -> Option<(Vec<&'p str>, &'a Value)> {
+    if path.is_empty() {
+        return Some((Vec::new(), value));
+    }
+
+    let map = value.as_object()?;
+    if let Some(found) = map.get(path) {
+        return Some((vec![path], found));
+    }
+
+    // Try the longest key prefix first, then recurse into the remainder
+    let mut split_points: Vec<usize> = path.match_indices('.').map(|(i, _)| i).collect();
+    split_points.reverse();
+    split_points.into_iter().find_map(|i| {
+        let (mut keys, found) = lookup_keys(map.get(&path[..i])?, &path[i + 1..])?;
+        keys.insert(0, &path[..i]);
+        Some((keys, found))
+    })
+}
+
+/// Trims a record to the given dotted paths, keeping their nesting.
+///
+/// Paths missing from the record are left out, so `attributes.service` turns
+/// `{"id": 1, "attributes": {"service": "api", "host": "a"}}` into
+/// `{"attributes": {"service": "api"}}`.
+pub fn project(record: &Value, fields: &[String]) -> Value {
+    let mut projected = Map::new();
+    for field in fields {
+        if let Some((keys, found)) = lookup_keys(record, field) {
+            insert_at(&mut projected, &keys, found.clone());
+        }
+    }
+    Value::Object(projected)
+}
+
+fn insert_at(map: &mut Map<String, Value>, keys: &[&str], value: Value) {
+    match keys {
+        [] => {}
+        [key] => {
+            map.insert(key.to_string(), value);
+        }
+        [key, rest @ ..] => {
+            let entry = map
+                .entry(key.to_string())
+                .or_insert_with(|| Value::Object(Map::new()));
+            if let Value::Object(nested) = entry {
+                insert_at(nested, rest, value);
+            }
+        }
+    }
+}
+
+/// Trims each record to `--fields` before handing it to a structured format writer.
+pub(super) struct ProjectedWriter {
+    inner: Box<dyn RecordWriter>,
+    fields: Vec<String>,
+}
+
+impl ProjectedWriter {
+    /// Wraps `inner`, or returns it unchanged when no fields are selected.
+    pub(super) fn wrap(inner: Box<dyn RecordWriter>, fields: Vec<String>) -> Box<dyn RecordWriter> {
+        if fields.is_empty() {
+            inner
+        } else {
+            Box::new(Self { inner, fields })
+        }
+    }
+}
+
+impl RecordWriter for ProjectedWriter {
+    fn write_value(&mut self, record: &Value) -> io::Result<()> {
+        self.inner.write_value(&project(record, &self.fields))
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.inner.finish()
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+
+    fn is_streaming(&self) -> bool {
+        self.inner.is_streaming()
+    }
+
+    fn resume(&mut self) -> io::Result<()> {
+        self.inner.resume()
+    }
+}
+
+/// Renders a JSON value as a single cell of text.
+///
+/// Strings are written without quotes, null as an empty cell, and everything
+/// else as compact JSON.
+pub fn render_cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Selects the columns to write for a record.
+///
+/// With explicit `fields`, each path is looked up (missing paths yield null).
+/// Otherwise the record is flattened.
+pub(super) fn select(record: &Value, fields: &[String]) -> Vec<(String, Value)> {
+    if fields.is_empty() {
+        return flatten(record);
+    }
+    fields
+        .iter()
+        .map(|field| {
+            let value = lookup(record, field).cloned().unwrap_or(Value::Null);
+            (field.clone(), value)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_flatten_nested_objects() {
+        let columns = flatten(&json!({
+            "id": "abc",
+            "attributes": {"service": "api", "tags": ["a", "b"]}
+        }));
+        let keys: Vec<&str> = columns.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(keys, vec!["attributes.service", "attributes.tags", "id"]);
+        assert_eq!(columns[1].1, json!(["a", "b"]));
+    }
+
+    #[test]
+    fn test_lookup_dotted_path() {
+        let record = json!({"attributes": {"service": "api"}});
+        assert_eq!(lookup(&record, "attributes.service"), Some(&json!("api")));
+        assert_eq!(lookup(&record, "attributes.missing"), None);
+    }
+
+    #[test]
+    fn test_lookup_key_containing_dots() {
+        let record = json!({"attributes": {"http.status_code": 500}});
+        assert_eq!(
+            lookup(&record, "attributes.http.status_code"),
+            Some(&json!(500))
+        );
+    }
+
+    #[test]
+    fn test_project_keeps_nesting() {
+        let record = json!({
+            "id": "abc",
+            "attributes": {"service": "api", "host": "web-1", "http.status_code": 500}
+        });
+        let fields = vec![
+            "id".to_string(),
+            "attributes.service".to_string(),
+            "attributes.http.status_code".to_string(),
+            "attributes.missing".to_string(),
+        ];
+        assert_eq!(
+            project(&record, &fields),
+            json!({
+                "id": "abc",
+                "attributes": {"service": "api", "http.status_code": 500}
+            })
+        );
+    }
+
+    #[test]
+    fn test_render_cell() {
+        assert_eq!(render_cell(&json!("text")), "text");
+        assert_eq!(render_cell(&Value::Null), "");
+        assert_eq!(render_cell(&json!(1.5)), "1.5");
+        assert_eq!(render_cell(&json!({"a": 1})), r#"{"a":1}"#);
+    }
+
+    #[test]
+    fn test_select_missing_field_is_null() {
+        let columns = select(&json!({"a": 1}), &["a".to_string(), "b".to_string()]);
+        assert_eq!(columns[0], ("a".to_string(), json!(1)));
+        assert_eq!(columns[1], ("b".to_string(), Value::Null));
+    }
+}
@@ -0,0 +1,78 @@
+//! logfmt output writer.
+
+use serde_json::Value;
+use std::io::{self, BufWriter, Write};
+
+use super::RecordWriter;
+use super::fields::{render_cell, select};
+
+/// Writes each record as a line of `key=value` pairs.
+///
+/// Nested objects are flattened into dotted keys, or restricted to the paths
+/// given with `--fields`. Values containing spaces, quotes or `=` are quoted.
+pub struct LogfmtWriter {
+    writer: BufWriter<Box<dyn Write>>,
+    fields: Vec<String>,
+}
+
+impl LogfmtWriter {
+    /// Creates a new logfmt writer to the given destination.
+    pub fn new(writer: Box<dyn Write>, fields: Vec<String>) -> Self {
+        Self {
+            writer: BufWriter::new(writer),
+            fields,
+        }
+    }
+}
+
+impl RecordWriter for LogfmtWriter {
+    fn write_value(&mut self, record: &Value) -> io::Result<()> {
+        let line = select(record, &self.fields)
+            .into_iter()
+            .map(|(key, value)| format!("{}={}", key, quote(&render_cell(&value))))
+            .collect::<Vec<_>>()
+            .join(" ");
+        writeln!(self.writer, "{}", line)?;
+        self.writer.flush()
+    }
+}
+
+fn quote(value: &str) -> String {
+    if value.is_empty() || value.contains([' ', '"', '=', '\n', '\r', '\t']) {
+        format!("{:?}", value)
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::tests::SharedBuffer;
+    use serde_json::json;
+
+    #[test]
+    fn test_logfmt_flattens_and_quotes() {
+        let buffer = SharedBuffer::default();
+        let mut writer = LogfmtWriter::new(Box::new(buffer.clone()), vec![]);
+        writer
+            .write_value(&json!({
+                "attributes": {"service": "api", "message": "user \"bob\" logged in"},
+                "id": 7
+            }))
+            .unwrap();
+
+        assert_eq!(
+            buffer.contents(),
+            "attributes.message=\"user \\\"bob\\\" logged in\" attributes.service=api id=7\n"
+        );
+    }
+
+    #[test]
+    fn test_logfmt_selected_fields() {
+        let buffer = SharedBuffer::default();
+        let mut writer = LogfmtWriter::new(Box::new(buffer.clone()), vec!["missing".to_string()]);
+        writer.write_value(&json!({"id": 7})).unwrap();
+        assert_eq!(buffer.contents(), "missing=\"\"\n");
+    }
+}
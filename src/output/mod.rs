@@ -0,0 +1,168 @@
+//! Output writers for command results.
+//!
+//! Every command writes its records through the [`RecordWriter`] trait, so all
+//! output formats work everywhere. Records are serialized to JSON first;
+//! tabular formats then flatten nested objects into dotted column names
+//! (e.g. `attributes.service`) or pick the columns given with `--fields`.
+//! Structured formats (NDJSON, JSON, YAML) trim each record to `--fields`,
+//! keeping its nesting.
+//!
+//! Streaming formats (NDJSON, CSV, TSV, YAML, logfmt) write each record as it
+//! arrives. JSON and table output buffer records until [`RecordWriter::finish`].
+//! Output goes to stdout, or to files with `--out` (see [`sink`]).
+//!
+//! Errors are reported separately on stderr, as text or JSON (`--error-format`).
+
+mod delimited;
+mod error;
+mod fields;
+mod logfmt;
+mod ndjson;
+mod sink;
+mod table;
+mod yaml;
+
+use clap::ValueEnum;
+use serde::Serialize;
+use serde_json::Value;
+use std::io;
+
+pub use error::{ErrorFormat, report_error};
+pub use sink::{Compression, Destination, Rotation};
+
+use delimited::DelimitedWriter;
+use fields::ProjectedWriter;
+use logfmt::LogfmtWriter;
+use ndjson::{JsonArrayWriter, NdjsonWriter};
+use sink::SinkWriter;
+use table::TableWriter;
+use yaml::YamlWriter;
+
+/// Output formats selectable with `--output`.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// Newline-delimited JSON, one record per line (default)
+    #[default]
+    Ndjson,
+    /// A single pretty-printed JSON array
+    Json,
+    /// Comma-separated values with a header row
+    Csv,
+    /// Tab-separated values with a header row
+    Tsv,
+    /// Aligned columns sized to the terminal width
+    Table,
+    /// YAML documents separated by ---
+    Yaml,
+    /// key=value pairs, one record per line
+    Logfmt,
+}
+
+/// A sink for command output records.
+///
+/// Implementations receive each record as a JSON value. Call
+/// [`RecordWriter::finish`] once all records are written so buffering formats
+/// can emit their output.
+pub trait RecordWriter {
+    /// Writes a single record.
+    fn write_value(&mut self, record: &Value) -> io::Result<()>;
+
+    /// Completes the output. Buffering writers emit their records here.
+    fn finish(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Flushes records written so far to the destination.
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Returns whether every record written so far has been flushed.
+    fn is_flushed(&self) -> bool {
+        true
+    }
+
+    /// Returns false for writers that buffer every record until [`RecordWriter::finish`].
+    fn is_streaming(&self) -> bool {
+        true
+    }
+
+    /// Continues output started by an earlier run, so header rows are not repeated.
+    ///
+    /// Fails when the destination cannot be continued.
+    fn resume(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl dyn RecordWriter + '_ {
+    /// Serializes and writes a single record.
+    pub fn write<T: Serialize>(&mut self, record: &T) -> io::Result<()> {
+        let value = serde_json::to_value(record)?;
+        self.write_value(&value)
+    }
+}
+
+/// Creates a writer for the given format and destination.
+///
+/// # Arguments
+///
+/// * `format` - Output format
+/// * `fields` - Dotted paths to keep (columns in tabular formats); empty for everything
+/// * `destination` - Stdout or output files
+pub fn create_writer(
+    format: OutputFormat,
+    fields: Vec<String>,
+    destination: Destination,
+) -> Box<dyn RecordWriter> {
+    Box::new(SinkWriter::new(destination, move |out| {
+        format_writer(format, fields.clone(), out)
+    }))
+}
+
+/// Creates the writer for one format, writing to `out`.
+fn format_writer(
+    format: OutputFormat,
+    fields: Vec<String>,
+    out: Box<dyn io::Write>,
+) -> Box<dyn RecordWriter> {
+    match format {
+        OutputFormat::Ndjson => {
+            ProjectedWriter::wrap(Box::new(NdjsonWriter::with_writer(out)), fields)
+        }
+        OutputFormat::Json => ProjectedWriter::wrap(Box::new(JsonArrayWriter::new(out)), fields),
+        OutputFormat::Csv => Box::new(DelimitedWriter::csv(out, fields)),
+        OutputFormat::Tsv => Box::new(DelimitedWriter::tsv(out, fields)),
+        OutputFormat::Table => Box::new(TableWriter::new(out, fields, table::terminal_width())),
+        OutputFormat::Yaml => ProjectedWriter::wrap(Box::new(YamlWriter::new(out)), fields),
+        OutputFormat::Logfmt => Box::new(LogfmtWriter::new(out, fields)),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::cell::RefCell;
+    use std::io::{self, Write};
+    use std::rc::Rc;
+
+    /// An in-memory writer whose contents stay readable after being boxed.
+    #[derive(Clone, Default)]
+    pub(crate) struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+    impl SharedBuffer {
+        pub(crate) fn contents(&self) -> String {
+            String::from_utf8(self.0.borrow().clone()).unwrap()
+        }
+    }
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+}
@@ -1,24 +1,32 @@
-//! NDJSON (Newline Delimited JSON) output writer.
+//! NDJSON (Newline Delimited JSON) and JSON array output writers.
 //!
 //! Provides streaming output of JSON records, one per line, suitable for
 //! piping to tools like `jq` or processing line-by-line.
 
 use serde::Serialize;
-use std::io::{self, BufWriter, Stdout, Write};
+use serde_json::Value;
+use std::io::{self, BufWriter, Write};
+
+use super::RecordWriter;
 
 /// Writes JSON records as newline-delimited JSON (NDJSON) to stdout.
 ///
 /// Each record is serialized as compact JSON followed by a newline.
 /// Output is flushed after each record for real-time streaming.
 pub struct NdjsonWriter {
-    writer: BufWriter<Stdout>,
+    writer: BufWriter<Box<dyn Write>>,
 }
 
 impl NdjsonWriter {
     /// Creates a new NDJSON writer to stdout.
     pub fn new() -> Self {
+        Self::with_writer(Box::new(io::stdout()))
+    }
+
+    /// Creates a new NDJSON writer to the given destination.
+    pub fn with_writer(writer: Box<dyn Write>) -> Self {
         Self {
-            writer: BufWriter::new(io::stdout()),
+            writer: BufWriter::new(writer),
         }
     }
 
@@ -38,6 +46,48 @@ impl Default for NdjsonWriter {
     }
 }
 
+impl RecordWriter for NdjsonWriter {
+    fn write_value(&mut self, record: &Value) -> io::Result<()> {
+        self.write(record)
+    }
+}
+
+/// Writes all records as a single pretty-printed JSON array.
+///
+/// Records are buffered until [`RecordWriter::finish`] so the array is valid
+/// JSON even if the command stops early at a limit.
+pub struct JsonArrayWriter {
+    writer: Box<dyn Write>,
+    records: Vec<Value>,
+}
+
+impl JsonArrayWriter {
+    /// Creates a new JSON array writer to the given destination.
+    pub fn new(writer: Box<dyn Write>) -> Self {
+        Self {
+            writer,
+            records: Vec::new(),
+        }
+    }
+}
+
+impl RecordWriter for JsonArrayWriter {
+    fn write_value(&mut self, record: &Value) -> io::Result<()> {
+        self.records.push(record.clone());
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        serde_json::to_writer_pretty(&mut self.writer, &self.records)?;
+        self.writer.write_all(b"\n")?;
+        self.writer.flush()
+    }
+
+    fn is_streaming(&self) -> bool {
+        false
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -108,4 +158,25 @@ mod tests {
         // Just verify it doesn't panic
         drop(writer);
     }
+
+    #[test]
+    fn test_json_array_writer_wraps_records() {
+        let buffer = super::super::tests::SharedBuffer::default();
+        let mut writer = JsonArrayWriter::new(Box::new(buffer.clone()));
+        writer.write_value(&serde_json::json!({"id": 1})).unwrap();
+        writer.write_value(&serde_json::json!({"id": 2})).unwrap();
+        writer.finish().unwrap();
+
+        let parsed: Vec<Value> = serde_json::from_str(&buffer.contents()).unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[1]["id"], 2);
+    }
+
+    #[test]
+    fn test_json_array_writer_empty() {
+        let buffer = super::super::tests::SharedBuffer::default();
+        let mut writer = JsonArrayWriter::new(Box::new(buffer.clone()));
+        writer.finish().unwrap();
+        assert_eq!(buffer.contents().trim(), "[]");
+    }
 }
@@ -0,0 +1,688 @@
+//! Output destinations: stdout or files, with compression, rotation and batched flushing.
+//!
+//! Format writers write into a [`SinkWriter`], which owns the destination. On
+//! stdout every record is flushed as it is written so output streams in real
+//! time. Files written with `--out` are flushed only when their buffers fill,
+//! and uncompressed files always between two records, so they never end in a
+//! partial record. Files can be compressed with gzip or zstd, and can be rotated into numbered parts
+//! after a number of records or bytes. Each part gets its own format writer,
+//! so every CSV part has a header row and every JSON part is a complete array.
+
+use clap::ValueEnum;
+use flate2::write::GzEncoder;
+use serde_json::Value;
+use std::cell::RefCell;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Stdout, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::str::FromStr;
+
+use super::RecordWriter;
+
+/// Buffer size for output files.
+const FILE_BUFFER_SIZE: usize = 256 * 1024;
+
+/// Compression applied to files written with `--out`.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    /// gzip, readable with gunzip or zcat
+    Gzip,
+    /// Zstandard, faster and smaller; readable with zstd -d or zstdcat
+    Zstd,
+}
+
+impl Compression {
+    /// File name extension for this compression.
+    fn extension(self) -> &'static str {
+        match self {
+            Compression::Gzip => ".gz",
+            Compression::Zstd => ".zst",
+        }
+    }
+}
+
+/// When to start a new output file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    /// After this many records
+    Records(u64),
+    /// Once this many bytes (before compression) have been written
+    Bytes(u64),
+}
+
+impl FromStr for Rotation {
+    type Err = String;
+
+    /// Parses a record count ("100000") or a size with a unit ("512KB", "256MB", "1GB").
+    ///
+    /// Sizes use multiples of 1024.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "Invalid rotation '{}': expected a record count (e.g. 100000) or a size (e.g. 256MB)",
+                s
+            )
+        };
+        let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let (digits, unit) = s.split_at(split);
+        let amount: u64 = digits.parse().map_err(|_| invalid())?;
+        if amount == 0 {
+            return Err(format!("Invalid rotation '{}': must be greater than 0", s));
+        }
+
+        let multiplier: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+            "" => return Ok(Rotation::Records(amount)),
+            "B" => 1,
+            "K" | "KB" => 1024,
+            "M" | "MB" => 1024 * 1024,
+            "G" | "GB" => 1024 * 1024 * 1024,
+            _ => return Err(invalid()),
+        };
+        amount
+            .checked_mul(multiplier)
+            .map(Rotation::Bytes)
+            .ok_or_else(invalid)
+    }
+}
+
+/// Where command output goes.
+#[derive(Debug, Clone, Default)]
+pub struct Destination {
+    /// Output file, or `None` for stdout
+    pub path: Option<PathBuf>,
+    /// Compression for the output file
+    pub compress: Option<Compression>,
+    /// When to start a new output file
+    pub rotate: Option<Rotation>,
+    /// Flush after this many records; defaults to every record on stdout and
+    /// to whenever the buffer fills for files
+    pub flush_every: Option<u64>,
+}
+
+impl Destination {
+    /// Returns the path of the given part, adding the compression extension
+    /// and, when rotating, a part number before the format extension.
+    ///
+    /// For example, part 3 of `logs.ndjson` with gzip is `logs-00003.ndjson.gz`.
+    fn part_path(&self, path: &Path, number: usize) -> PathBuf {
+        let mut name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let compression = self.compress.map(Compression::extension).unwrap_or("");
+        if let Some(base) = name.strip_suffix(compression) {
+            name = base.to_string();
+        }
+
+        if self.rotate.is_some() {
+            let (stem, extension) = match name.rfind('.') {
+                Some(dot) if dot > 0 => name.split_at(dot),
+                _ => (name.as_str(), ""),
+            };
+            name = format!("{}-{:05}{}", stem, number, extension);
+        }
+        path.with_file_name(name + compression)
+    }
+}
+
+/// Holds an uncompressed file's output until the sink flushes it.
+///
+/// Unlike a `BufWriter`, it never writes part of a record when it fills up;
+/// the sink flushes it after a record once it holds [`FILE_BUFFER_SIZE`] bytes.
+struct FileBuffer {
+    file: File,
+    buffer: Vec<u8>,
+}
+
+impl Write for FileBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.write_all(&self.buffer)?;
+        self.buffer.clear();
+        self.file.flush()
+    }
+}
+
+/// An open output stream.
+enum Stream {
+    Stdout(BufWriter<Stdout>),
+    Plain(FileBuffer),
+    Gzip(GzEncoder<BufWriter<File>>),
+    Zstd(zstd::Encoder<'static, BufWriter<File>>),
+}
+
+impl Stream {
+    fn writer(&mut self) -> &mut dyn Write {
+        match self {
+            Stream::Stdout(w) => w,
+            Stream::Plain(w) => w,
+            Stream::Gzip(w) => w,
+            Stream::Zstd(w) => w,
+        }
+    }
+
+    /// Bytes held until the next flush that the stream does not write by itself.
+    fn buffered(&self) -> usize {
+        match self {
+            Stream::Plain(w) => w.buffer.len(),
+            _ => 0,
+        }
+    }
+
+    /// Writes any buffered data and, for compressed files, the stream trailer.
+    fn finish(self) -> io::Result<()> {
+        match self {
+            Stream::Stdout(mut w) => w.flush(),
+            Stream::Plain(mut w) => w.flush(),
+            Stream::Gzip(w) => w.finish()?.flush(),
+            Stream::Zstd(w) => w.finish()?.flush(),
+        }
+    }
+}
+
+/// Shared handle to the current output part, given to its format writer.
+///
+/// Counts the bytes written to the part. Flushes from the format writer are
+/// ignored; the [`SinkWriter`] decides when to flush.
+#[derive(Clone)]
+struct Part(Rc<RefCell<PartState>>);
+
+struct PartState {
+    stream: Option<Stream>,
+    bytes: u64,
+}
+
+impl Part {
+    fn new(stream: Stream) -> Self {
+        Self(Rc::new(RefCell::new(PartState {
+            stream: Some(stream),
+            bytes: 0,
+        })))
+    }
+
+    fn bytes(&self) -> u64 {
+        self.0.borrow().bytes
+    }
+
+    fn buffered(&self) -> usize {
+        self.0.borrow().stream.as_ref().map_or(0, Stream::buffered)
+    }
+
+    fn flush_stream(&self) -> io::Result<()> {
+        match self.0.borrow_mut().stream.as_mut() {
+            Some(stream) => stream.writer().flush(),
+            None => Ok(()),
+        }
+    }
+
+    fn finish_stream(&self) -> io::Result<()> {
+        match self.0.borrow_mut().stream.take() {
+            Some(stream) => stream.finish(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Write for Part {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut state = self.0.borrow_mut();
+        let written = match state.stream.as_mut() {
+            Some(stream) => stream.writer().write(buf)?,
+            None => return Err(io::Error::other("output part is already closed")),
+        };
+        state.bytes += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Factory for the format writer of each output part.
+type MakeWriter = Box<dyn Fn(Box<dyn Write>) -> Box<dyn RecordWriter>>;
+
+/// Writes records in one format to a [`Destination`].
+pub struct SinkWriter {
+    destination: Destination,
+    make: MakeWriter,
+    streaming: bool,
+    current: Option<(Box<dyn RecordWriter>, Part)>,
+    parts_opened: usize,
+    part_number: usize,
+    part_records: u64,
+    unflushed: u64,
+    append: bool,
+}
+
+impl SinkWriter {
+    /// Creates a writer; `make` creates the format writer for each part.
+    ///
+    /// Nothing is opened until the first record is written.
+    pub fn new(
+        destination: Destination,
+        make: impl Fn(Box<dyn Write>) -> Box<dyn RecordWriter> + 'static,
+    ) -> Self {
+        let streaming = make(Box::new(io::sink())).is_streaming();
+        Self {
+            destination,
+            make: Box::new(make),
+            streaming,
+            current: None,
+            parts_opened: 0,
+            part_number: 0,
+            part_records: 0,
+            unflushed: 0,
+            append: false,
+        }
+    }
+
+    /// Records written between flushes.
+    fn flush_every(&self) -> Option<u64> {
+        self.destination
+            .flush_every
+            .or(self.destination.path.is_none().then_some(1))
+    }
+
+    fn rotation_due(&self, part: &Part) -> bool {
+        match self.destination.rotate {
+            Some(Rotation::Records(limit)) => self.part_records >= limit,
+            Some(Rotation::Bytes(limit)) => part.bytes() >= limit,
+            None => false,
+        }
+    }
+
+    fn open_part(&mut self) -> io::Result<()> {
+        // Only the first part of a resumed run continues earlier output
+        let append = self.append && self.parts_opened == 0;
+        let stream = match &self.destination.path {
+            None => Stream::Stdout(BufWriter::new(io::stdout())),
+            Some(path) => {
+                self.part_number += 1;
+                let mut part_path = self.destination.part_path(path, self.part_number);
+                if append && self.destination.rotate.is_some() {
+                    // Continue after the last part already written
+                    while part_path.exists() {
+                        self.part_number += 1;
+                        part_path = self.destination.part_path(path, self.part_number);
+                    }
+                }
+                let file = OpenOptions::new()
+                    .create(true)
+                    .write(true)
+                    .append(append)
+                    .truncate(!append)
+                    .open(&part_path)
+                    .map_err(|e| {
+                        io::Error::new(e.kind(), format!("{}: {}", part_path.display(), e))
+                    })?;
+                match self.destination.compress {
+                    None => Stream::Plain(FileBuffer {
+                        file,
+                        buffer: Vec::with_capacity(FILE_BUFFER_SIZE),
+                    }),
+                    Some(compression) => {
+                        let file = BufWriter::with_capacity(FILE_BUFFER_SIZE, file);
+                        match compression {
+                            Compression::Gzip => {
+                                Stream::Gzip(GzEncoder::new(file, flate2::Compression::default()))
+                            }
+                            Compression::Zstd => Stream::Zstd(zstd::Encoder::new(file, 0)?),
+                        }
+                    }
+                }
+            }
+        };
+
+        let part = Part::new(stream);
+        let mut writer = (self.make)(Box::new(part.clone()));
+        // Appending to existing output, so headers are not repeated
+        if append && self.destination.rotate.is_none() {
+            writer.resume()?;
+        }
+        self.current = Some((writer, part));
+        self.parts_opened += 1;
+        self.part_records = 0;
+        Ok(())
+    }
+
+    fn close_part(&mut self) -> io::Result<()> {
+        if let Some((mut writer, part)) = self.current.take() {
+            writer.finish()?;
+            part.finish_stream()?;
+        }
+        self.unflushed = 0;
+        Ok(())
+    }
+}
+
+impl RecordWriter for SinkWriter {
+    fn write_value(&mut self, record: &Value) -> io::Result<()> {
+        if let Some((_, part)) = &self.current
+            && self.rotation_due(part)
+        {
+            self.close_part()?;
+        }
+        if self.current.is_none() {
+            self.open_part()?;
+        }
+
+        if let Some((writer, _)) = self.current.as_mut() {
+            writer.write_value(record)?;
+        }
+        self.part_records += 1;
+        self.unflushed += 1;
+
+        let buffer_full = self
+            .current
+            .as_ref()
+            .is_some_and(|(_, part)| part.buffered() >= FILE_BUFFER_SIZE);
+        if buffer_full
+            || self
+                .flush_every()
+                .is_some_and(|every| self.unflushed >= every)
+        {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        // Empty results still produce output (e.g. a header row or "[]")
+        if self.current.is_none() && self.parts_opened == 0 && !self.append {
+            self.open_part()?;
+        }
+        self.close_part()
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.unflushed = 0;
+        match &self.current {
+            Some((_, part)) => part.flush_stream(),
+            None => Ok(()),
+        }
+    }
+
+    fn is_streaming(&self) -> bool {
+        self.streaming
+    }
+
+    fn is_flushed(&self) -> bool {
+        self.unflushed == 0
+    }
+
+    /// Continues in the existing output file, or in a new part when rotating.
+    ///
+    /// A compressed file cannot be continued: the earlier run may have stopped
+    /// before finishing its stream, and anything appended after it could not
+    /// be decompressed.
+    fn resume(&mut self) -> io::Result<()> {
+        if let Some(path) = &self.destination.path
+            && self.destination.compress.is_some()
+            && self.destination.rotate.is_none()
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Cannot append to compressed output {}; pass --rotate-every so the resumed export starts a new part",
+                    self.destination.part_path(path, 1).display()
+                ),
+            ));
+        }
+        self.append = true;
+        Ok(())
+    }
+}
+
+impl Drop for SinkWriter {
+    /// Completes compressed files when a command fails or is interrupted, so
+    /// records already written stay readable. Format writers are not finished, so buffering
+    /// formats write nothing for failed commands.
+    fn drop(&mut self) {
+        if let Some((_, part)) = &self.current {
+            let _ = part.finish_stream();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::delimited::DelimitedWriter;
+    use crate::output::ndjson::NdjsonWriter;
+    use serde_json::json;
+    use std::io::Read;
+
+    /// A fresh, empty directory for test output.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ddog-sink-{}-{}", std::process::id(), name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn destination(path: &Path) -> Destination {
+        Destination {
+            path: Some(path.to_path_buf()),
+            ..Destination::default()
+        }
+    }
+
+    fn csv_sink(destination: Destination) -> SinkWriter {
+        SinkWriter::new(destination, |out| {
+            Box::new(DelimitedWriter::csv(out, vec![]))
+        })
+    }
+
+    fn ndjson_sink(destination: Destination) -> SinkWriter {
+        SinkWriter::new(destination, |out| Box::new(NdjsonWriter::with_writer(out)))
+    }
+
+    fn write_ids(writer: &mut SinkWriter, ids: std::ops::Range<u64>) {
+        for id in ids {
+            writer.write_value(&json!({ "id": id })).unwrap();
+        }
+    }
+
+    fn read(path: &Path) -> String {
+        std::fs::read_to_string(path).unwrap()
+    }
+
+    #[test]
+    fn test_parse_rotation() {
+        assert_eq!("100000".parse(), Ok(Rotation::Records(100_000)));
+        assert_eq!("512KB".parse(), Ok(Rotation::Bytes(512 * 1024)));
+        assert_eq!("256mb".parse(), Ok(Rotation::Bytes(256 * 1024 * 1024)));
+        assert_eq!("1G".parse(), Ok(Rotation::Bytes(1024 * 1024 * 1024)));
+        assert_eq!("10B".parse(), Ok(Rotation::Bytes(10)));
+        assert!("0".parse::<Rotation>().is_err());
+        assert!("MB".parse::<Rotation>().is_err());
+        assert!("10 parsecs".parse::<Rotation>().is_err());
+    }
+
+    #[test]
+    fn test_part_path() {
+        let path = Path::new("/tmp/export/logs.ndjson");
+        let mut destination = destination(path);
+        assert_eq!(destination.part_path(path, 1), path);
+
+        destination.compress = Some(Compression::Gzip);
+        assert_eq!(
+            destination.part_path(path, 1),
+            Path::new("/tmp/export/logs.ndjson.gz")
+        );
+
+        destination.rotate = Some(Rotation::Records(10));
+        assert_eq!(
+            destination.part_path(path, 3),
+            Path::new("/tmp/export/logs-00003.ndjson.gz")
+        );
+        assert_eq!(
+            destination.part_path(Path::new("logs.ndjson.gz"), 3),
+            Path::new("logs-00003.ndjson.gz")
+        );
+
+        destination.compress = None;
+        assert_eq!(
+            destination.part_path(Path::new("export"), 12),
+            Path::new("export-00012")
+        );
+    }
+
+    #[test]
+    fn test_rotates_by_record_count_with_header_per_part() {
+        let dir = temp_dir("records");
+        let mut destination = destination(&dir.join("out.csv"));
+        destination.rotate = Some(Rotation::Records(2));
+
+        let mut writer = csv_sink(destination);
+        write_ids(&mut writer, 0..5);
+        writer.finish().unwrap();
+
+        assert_eq!(read(&dir.join("out-00001.csv")), "id\n0\n1\n");
+        assert_eq!(read(&dir.join("out-00002.csv")), "id\n2\n3\n");
+        assert_eq!(read(&dir.join("out-00003.csv")), "id\n4\n");
+        assert!(!dir.join("out-00004.csv").exists());
+    }
+
+    #[test]
+    fn test_rotates_by_size() {
+        let dir = temp_dir("bytes");
+        let mut destination = destination(&dir.join("out.ndjson"));
+        // Each record is 9 bytes, so a part holds two records
+        destination.rotate = Some(Rotation::Bytes(10));
+
+        let mut writer = ndjson_sink(destination);
+        write_ids(&mut writer, 0..3);
+        writer.finish().unwrap();
+
+        assert_eq!(read(&dir.join("out-00001.ndjson")).lines().count(), 2);
+        assert_eq!(read(&dir.join("out-00002.ndjson")).lines().count(), 1);
+    }
+
+    #[test]
+    fn test_file_is_written_between_records() {
+        let dir = temp_dir("boundaries");
+        let path = dir.join("out.ndjson");
+        let mut writer = ndjson_sink(destination(&path));
+        let message = "x".repeat(1000);
+        for id in 0..300 {
+            writer
+                .write_value(&json!({ "id": id, "message": message }))
+                .unwrap();
+        }
+
+        // Part of the output is on disk, and it ends with a whole record
+        let written = read(&path);
+        assert!(!written.is_empty());
+        assert!(written.ends_with('\n'));
+        assert!(written.lines().count() < 300);
+    }
+
+    #[test]
+    fn test_gzip_and_zstd_round_trip() {
+        let dir = temp_dir("compress");
+        for compression in [Compression::Gzip, Compression::Zstd] {
+            let mut destination = destination(&dir.join("out.ndjson"));
+            destination.compress = Some(compression);
+            let mut writer = ndjson_sink(destination);
+            write_ids(&mut writer, 0..3);
+            writer.finish().unwrap();
+
+            let file =
+                File::open(dir.join(format!("out.ndjson{}", compression.extension()))).unwrap();
+            let mut contents = String::new();
+            match compression {
+                Compression::Gzip => flate2::read::GzDecoder::new(file)
+                    .read_to_string(&mut contents)
+                    .unwrap(),
+                Compression::Zstd => zstd::Decoder::new(file)
+                    .unwrap()
+                    .read_to_string(&mut contents)
+                    .unwrap(),
+            };
+            assert_eq!(contents, "{\"id\":0}\n{\"id\":1}\n{\"id\":2}\n");
+        }
+    }
+
+    #[test]
+    fn test_resume_appends_without_repeating_header() {
+        let dir = temp_dir("append");
+        let path = dir.join("out.csv");
+
+        let mut first = csv_sink(destination(&path));
+        write_ids(&mut first, 0..2);
+        first.finish().unwrap();
+
+        let mut resumed = csv_sink(destination(&path));
+        resumed.resume().unwrap();
+        write_ids(&mut resumed, 2..3);
+        resumed.finish().unwrap();
+
+        assert_eq!(read(&path), "id\n0\n1\n2\n");
+    }
+
+    #[test]
+    fn test_resume_with_rotation_starts_a_new_part() {
+        let dir = temp_dir("append-rotated");
+        let mut destination = destination(&dir.join("out.csv"));
+        destination.rotate = Some(Rotation::Records(2));
+
+        let mut first = csv_sink(destination.clone());
+        write_ids(&mut first, 0..3);
+        first.finish().unwrap();
+
+        let mut resumed = csv_sink(destination);
+        resumed.resume().unwrap();
+        write_ids(&mut resumed, 3..4);
+        resumed.finish().unwrap();
+
+        assert_eq!(read(&dir.join("out-00002.csv")), "id\n2\n");
+        assert_eq!(read(&dir.join("out-00003.csv")), "id\n3\n");
+    }
+
+    #[test]
+    fn test_resume_refuses_compressed_file_without_rotation() {
+        let dir = temp_dir("append-compressed");
+        let mut destination = destination(&dir.join("out.ndjson"));
+        destination.compress = Some(Compression::Gzip);
+        let error = ndjson_sink(destination.clone()).resume().unwrap_err();
+        assert!(error.to_string().contains("--rotate-every"));
+
+        destination.rotate = Some(Rotation::Records(10));
+        let mut first = ndjson_sink(destination.clone());
+        write_ids(&mut first, 0..1);
+        // Dropped without finishing, like an interrupted export
+        drop(first);
+
+        let mut resumed = ndjson_sink(destination);
+        resumed.resume().unwrap();
+        write_ids(&mut resumed, 1..2);
+        resumed.finish().unwrap();
+
+        for (part, expected) in [(1, "{\"id\":0}\n"), (2, "{\"id\":1}\n")] {
+            let file = File::open(dir.join(format!("out-{:05}.ndjson.gz", part))).unwrap();
+            let mut contents = String::new();
+            flate2::read::GzDecoder::new(file)
+                .read_to_string(&mut contents)
+                .unwrap();
+            assert_eq!(contents, expected);
+        }
+    }
+
+    #[test]
+    fn test_empty_result_still_creates_file() {
+        let dir = temp_dir("empty");
+        let path = dir.join("out.csv");
+        let mut writer = SinkWriter::new(destination(&path), |out| {
+            Box::new(DelimitedWriter::csv(out, vec!["id".to_string()]))
+        });
+        writer.finish().unwrap();
+        assert_eq!(read(&path), "id\n");
+    }
+}
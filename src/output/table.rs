@@ -0,0 +1,217 @@
+//! Aligned table output writer.
+
+use serde_json::Value;
+use std::io::{self, Write};
+
+use super::RecordWriter;
+use super::fields::{lookup, render_cell, select};
+
+/// Width used when the terminal size cannot be determined (e.g. when piped).
+const DEFAULT_WIDTH: usize = 120;
+
+/// Narrowest a column is shrunk to when fitting the terminal width.
+const MIN_COLUMN_WIDTH: usize = 3;
+
+/// Spacing between columns.
+const COLUMN_GAP: &str = "  ";
+
+/// Writes records as an aligned table sized to the terminal width.
+///
+/// Records are buffered until [`RecordWriter::finish`] so column widths can be
+/// computed from the full result. Columns come from `--fields`, or from the
+/// union of flattened keys across all records. Cells that do not fit are
+/// truncated with an ellipsis.
+pub struct TableWriter {
+    writer: Box<dyn Write>,
+    fields: Vec<String>,
+    width: usize,
+    records: Vec<Value>,
+}
+
+impl TableWriter {
+    /// Creates a new table writer.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - Output destination
+    /// * `fields` - Dotted paths to use as columns; empty to infer from the records
+    /// * `width` - Maximum line width in characters
+    pub fn new(writer: Box<dyn Write>, fields: Vec<String>, width: usize) -> Self {
+        Self {
+            writer,
+            fields,
+            width,
+            records: Vec::new(),
+        }
+    }
+
+    fn columns(&self) -> Vec<String> {
+        if !self.fields.is_empty() {
+            return self.fields.clone();
+        }
+        let mut columns: Vec<String> = Vec::new();
+        for record in &self.records {
+            for (key, _) in select(record, &[]) {
+                if !columns.contains(&key) {
+                    columns.push(key);
+                }
+            }
+        }
+        columns
+    }
+}
+
+impl RecordWriter for TableWriter {
+    fn write_value(&mut self, record: &Value) -> io::Result<()> {
+        self.records.push(record.clone());
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        let columns = self.columns();
+        if columns.is_empty() {
+            return self.writer.flush();
+        }
+
+        let rows: Vec<Vec<String>> = self
+            .records
+            .iter()
+            .map(|record| {
+                columns
+                    .iter()
+                    .map(|column| {
+                        lookup(record, column)
+                            .map(render_cell)
+                            .unwrap_or_default()
+                            .replace(['\n', '\r', '\t'], " ")
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let mut widths: Vec<usize> = columns.iter().map(|c| c.chars().count()).collect();
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+        fit_widths(&mut widths, self.width);
+
+        write_line(&mut self.writer, &columns, &widths)?;
+        for row in &rows {
+            write_line(&mut self.writer, row, &widths)?;
+        }
+        self.writer.flush()
+    }
+
+    fn is_streaming(&self) -> bool {
+        false
+    }
+}
+
+/// Shrinks the widest columns until the table fits in `max_width`.
+fn fit_widths(widths: &mut [usize], max_width: usize) {
+    let gaps = COLUMN_GAP.len() * widths.len().saturating_sub(1);
+    let available = max_width.saturating_sub(gaps);
+
+    while widths.iter().sum::<usize>() > available {
+        let Some(widest) = widths
+            .iter_mut()
+            .filter(|w| **w > MIN_COLUMN_WIDTH)
+            .max_by_key(|w| **w)
+        else {
+            break;
+        };
+        *widest -= 1;
+    }
+}
+
+fn write_line(writer: &mut dyn Write, cells: &[String], widths: &[usize]) -> io::Result<()> {
+    let line = cells
+        .iter()
+        .zip(widths)
+        .map(|(cell, width)| {
+            let cell = truncate(cell, *width);
+            let padding = width - cell.chars().count();
+            format!("{}{}", cell, " ".repeat(padding))
+        })
+        .collect::<Vec<_>>()
+        .join(COLUMN_GAP);
+    writeln!(writer, "{}", line.trim_end())
+}
+
+/// Truncates a cell to `width` characters, marking the cut with an ellipsis.
+fn truncate(cell: &str, width: usize) -> String {
+    if cell.chars().count() <= width {
+        return cell.to_string();
+    }
+    let mut truncated: String = cell.chars().take(width.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
+}
+
+/// Returns the terminal width, falling back to `$COLUMNS` and then a default.
+pub(super) fn terminal_width() -> usize {
+    if let Some((terminal_size::Width(width), _)) = terminal_size::terminal_size() {
+        return width as usize;
+    }
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|c| c.parse().ok())
+        .unwrap_or(DEFAULT_WIDTH)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::tests::SharedBuffer;
+    use serde_json::json;
+
+    fn render(fields: Vec<String>, width: usize, records: &[Value]) -> String {
+        let buffer = SharedBuffer::default();
+        let mut writer = TableWriter::new(Box::new(buffer.clone()), fields, width);
+        for record in records {
+            writer.write_value(record).unwrap();
+        }
+        writer.finish().unwrap();
+        buffer.contents()
+    }
+
+    #[test]
+    fn test_table_aligns_columns() {
+        let output = render(
+            vec!["service".to_string(), "count".to_string()],
+            80,
+            &[
+                json!({"service": "api", "count": 10}),
+                json!({"service": "checkout", "count": 2}),
+            ],
+        );
+        assert_eq!(output, "service   count\napi       10\ncheckout  2\n");
+    }
+
+    #[test]
+    fn test_table_truncates_to_width() {
+        let output = render(
+            vec!["message".to_string()],
+            10,
+            &[json!({"message": "a very long log message"})],
+        );
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines[1], "a very lo…");
+        assert!(lines.iter().all(|l| l.chars().count() <= 10));
+    }
+
+    #[test]
+    fn test_table_infers_union_of_columns() {
+        let output = render(vec![], 80, &[json!({"a": 1}), json!({"b": 2})]);
+        assert_eq!(output.lines().next().unwrap(), "a  b");
+    }
+
+    #[test]
+    fn test_fit_widths_respects_minimum() {
+        let mut widths = vec![20, 5];
+        fit_widths(&mut widths, 4);
+        assert_eq!(widths, vec![3, 3]);
+    }
+}
@@ -0,0 +1,48 @@
+//! YAML output writer.
+
+use serde_json::Value;
+use std::io::{self, BufWriter, Write};
+
+use super::RecordWriter;
+
+/// Writes each record as a YAML document, separated by `---`.
+pub struct YamlWriter {
+    writer: BufWriter<Box<dyn Write>>,
+}
+
+impl YamlWriter {
+    /// Creates a new YAML writer to the given destination.
+    pub fn new(writer: Box<dyn Write>) -> Self {
+        Self {
+            writer: BufWriter::new(writer),
+        }
+    }
+}
+
+impl RecordWriter for YamlWriter {
+    fn write_value(&mut self, record: &Value) -> io::Result<()> {
+        let document = serde_yaml::to_string(record).map_err(io::Error::other)?;
+        self.writer.write_all(b"---\n")?;
+        self.writer.write_all(document.as_bytes())?;
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::tests::SharedBuffer;
+    use serde_json::json;
+
+    #[test]
+    fn test_yaml_writes_separated_documents() {
+        let buffer = SharedBuffer::default();
+        let mut writer = YamlWriter::new(Box::new(buffer.clone()));
+        writer
+            .write_value(&json!({"id": 1, "tags": ["a"]}))
+            .unwrap();
+        writer.write_value(&json!({"id": 2})).unwrap();
+
+        assert_eq!(buffer.contents(), "---\nid: 1\ntags:\n- a\n---\nid: 2\n");
+    }
+}
@@ -0,0 +1,300 @@
+//! SLO history breakdowns and error-budget burn rates.
+//!
+//! The SLO history API reports an SLI value and remaining error budget for the
+//! SLO as a whole and for each of its groups (or monitors). This module
+//! flattens that into one record per group, and computes burn rates: how fast
+//! the error budget is being spent over a window, relative to the rate that
+//! would use exactly the whole budget by the end of the SLO's timeframe.
+
+use datadog_api_client::datadogV1::model::{SLOHistoryResponseData, SLOResponseData};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::AppError;
+use crate::time::{ResolvedRange, Span};
+
+/// Group name used for the SLO as a whole.
+const WHOLE_SLO: &str = "*";
+
+/// Timeframe key of the error budget computed for an explicit target.
+const CUSTOM_TIMEFRAME: &str = "custom";
+
+/// Returns the SLO's target, in percent, or `target` when one was given.
+///
+/// Uses the SLO's primary target, falling back to its first threshold.
+pub fn target(slo: &SLOResponseData, target: Option<f64>) -> Result<f64, AppError> {
+    target
+        .or(slo.target_threshold)
+        .or_else(|| slo.thresholds.as_ref()?.first().map(|t| t.target))
+        .ok_or_else(|| {
+            AppError::InvalidQuery(format!(
+                "SLO {} has no target; pass --target",
+                slo.id.as_deref().unwrap_or_default()
+            ))
+        })
+}
+
+/// The SLI over a time window for the SLO, or one of its groups.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GroupHistory {
+    pub slo_id: String,
+    /// Group name, e.g. `env:prod`, or the monitor name; `*` for the whole SLO
+    pub group: String,
+    /// Percentage of good events or uptime; absent without data
+    pub sli_value: Option<f64>,
+    /// Percentage of the error budget left for the target
+    pub error_budget_remaining: Option<f64>,
+    pub target: f64,
+    /// Unix seconds
+    pub from_ts: i64,
+    /// Unix seconds
+    pub to_ts: i64,
+}
+
+/// Flattens an SLO history into a `*` record for the whole SLO followed by
+/// one record per group or monitor, in the order Datadog returns them.
+pub fn group_history(
+    slo_id: &str,
+    history: &SLOHistoryResponseData,
+    target: f64,
+    range: &ResolvedRange,
+) -> Vec<GroupHistory> {
+    let record = |group: String, sli_value, budget| GroupHistory {
+        slo_id: slo_id.to_string(),
+        group,
+        sli_value,
+        error_budget_remaining: budget,
+        target,
+        from_ts: history.from_ts.unwrap_or(range.from_secs()),
+        to_ts: history.to_ts.unwrap_or(range.to_secs()),
+    };
+
+    let mut records = Vec::new();
+    if let Some(overall) = &history.overall {
+        records.push(record(
+            WHOLE_SLO.to_string(),
+            overall.sli_value.flatten(),
+            remaining(overall.error_budget_remaining.as_ref()),
+        ));
+    }
+
+    let groups = history
+        .groups
+        .iter()
+        .chain(history.monitors.iter())
+        .flatten();
+    records.extend(groups.map(|group| {
+        record(
+            group
+                .group
+                .clone()
+                .or_else(|| group.name.clone())
+                .unwrap_or_default(),
+            group.sli_value.flatten(),
+            remaining(group.error_budget_remaining.as_ref()),
+        )
+    }));
+    records
+}
+
+/// Picks the budget computed for the requested target out of the per-timeframe map.
+fn remaining(budgets: Option<&BTreeMap<String, f64>>) -> Option<f64> {
+    let budgets = budgets?;
+    budgets
+        .get(CUSTOM_TIMEFRAME)
+        .or_else(|| budgets.values().next())
+        .copied()
+}
+
+/// A window to compute the burn rate over, with an optional threshold of its own.
+///
+/// Written `1h` or `1h:14.4`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BurnWindow {
+    pub span: Span,
+    pub threshold: Option<f64>,
+}
+
+impl FromStr for BurnWindow {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (span, threshold) = match s.split_once(':') {
+            Some((span, threshold)) => {
+                let threshold: f64 = threshold.trim().parse().map_err(|_| {
+                    format!(
+                        "Invalid burn rate threshold '{}' in '{}', expected e.g. 1h:14.4",
+                        threshold, s
+                    )
+                })?;
+                if threshold.is_nan() || threshold <= 0.0 {
+                    return Err(format!("Burn rate threshold in '{}' must be positive", s));
+                }
+                (span, Some(threshold))
+            }
+            None => (s, None),
+        };
+        Ok(BurnWindow {
+            span: span.parse()?,
+            threshold,
+        })
+    }
+}
+
+impl fmt::Display for BurnWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.threshold {
+            Some(threshold) => write!(f, "{}:{}", self.span, threshold),
+            None => write!(f, "{}", self.span),
+        }
+    }
+}
+
+/// Returns how many times faster than sustainable the error budget is spent.
+///
+/// `sli_value` and `target` are percentages. A burn rate of 1 spends exactly
+/// the budget over the SLO's timeframe; `None` when the target leaves no
+/// budget (100%).
+pub fn burn_rate(sli_value: f64, target: f64) -> Option<f64> {
+    let budget = 100.0 - target;
+    (budget > 0.0).then(|| ((100.0 - sli_value) / budget).max(0.0))
+}
+
+/// The burn rate of an SLO over one window.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BurnRate {
+    pub slo_id: String,
+    /// Window length, e.g. `1h`
+    pub window: String,
+    /// SLI over the window; absent without data
+    pub sli_value: Option<f64>,
+    pub target: f64,
+    pub burn_rate: Option<f64>,
+    pub threshold: f64,
+    /// Whether the burn rate is above the threshold
+    pub breached: bool,
+    /// Unix seconds
+    pub from_ts: i64,
+    /// Unix seconds
+    pub to_ts: i64,
+}
+
+impl BurnRate {
+    /// Computes the burn rate for `window` from the SLI over `range`.
+    ///
+    /// The window's own threshold is used when it has one, else `threshold`.
+    /// A window without data is never breached.
+    pub fn new(
+        slo_id: &str,
+        window: &BurnWindow,
+        range: &ResolvedRange,
+        sli_value: Option<f64>,
+        target: f64,
+        threshold: f64,
+    ) -> Self {
+        let threshold = window.threshold.unwrap_or(threshold);
+        let burn_rate = sli_value.and_then(|sli| burn_rate(sli, target));
+        Self {
+            slo_id: slo_id.to_string(),
+            window: window.span.to_string(),
+            sli_value,
+            target,
+            burn_rate,
+            threshold,
+            breached: burn_rate.is_some_and(|rate| rate > threshold),
+            from_ts: range.from_secs(),
+            to_ts: range.to_secs(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_burn_rate_relative_to_budget() {
+        // 99.9% target leaves 0.1% budget; 99% SLI spends it 10x too fast
+        let rate = burn_rate(99.0, 99.9).unwrap();
+        assert!((rate - 10.0).abs() < 1e-9);
+        assert_eq!(burn_rate(100.0, 99.9), Some(0.0));
+        assert_eq!(burn_rate(99.0, 100.0), None);
+    }
+
+    #[test]
+    fn test_burn_window_parses_threshold() {
+        let window: BurnWindow = "1h:14.4".parse().unwrap();
+        assert_eq!(window.threshold, Some(14.4));
+        assert_eq!(window.to_string(), "1h:14.4");
+
+        let window: BurnWindow = "6h".parse().unwrap();
+        assert_eq!(window.threshold, None);
+
+        assert!("1h:fast".parse::<BurnWindow>().is_err());
+        assert!("1h:0".parse::<BurnWindow>().is_err());
+        assert!("soon".parse::<BurnWindow>().is_err());
+    }
+
+    #[test]
+    fn test_burn_rate_uses_window_threshold() {
+        let range = ResolvedRange::new(0, 3_600_000);
+        let window: BurnWindow = "1h:14.4".parse().unwrap();
+        let rate = BurnRate::new("abc", &window, &range, Some(99.0), 99.9, 1.0);
+        assert_eq!(rate.threshold, 14.4);
+        assert!(!rate.breached);
+
+        let window: BurnWindow = "1h".parse().unwrap();
+        let rate = BurnRate::new("abc", &window, &range, Some(99.0), 99.9, 1.0);
+        assert!(rate.breached);
+
+        let rate = BurnRate::new("abc", &window, &range, None, 99.9, 1.0);
+        assert!(!rate.breached);
+    }
+
+    #[test]
+    fn test_group_history_lists_overall_then_groups() {
+        let history: SLOHistoryResponseData = serde_json::from_value(json!({
+            "from_ts": 1705276800,
+            "to_ts": 1705881600,
+            "overall": {"sli_value": 99.5, "error_budget_remaining": {"custom": 50.0}},
+            "groups": [
+                {"group": "env:prod", "sli_value": 99.2, "error_budget_remaining": {"custom": 20.0}},
+                {"group": "env:staging", "sli_value": null}
+            ]
+        }))
+        .unwrap();
+        let range = ResolvedRange::new(0, 1000);
+
+        let records = group_history("abc", &history, 99.0, &range);
+        let summary: Vec<(&str, Option<f64>, Option<f64>)> = records
+            .iter()
+            .map(|r| (r.group.as_str(), r.sli_value, r.error_budget_remaining))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("*", Some(99.5), Some(50.0)),
+                ("env:prod", Some(99.2), Some(20.0)),
+                ("env:staging", None, None),
+            ]
+        );
+        assert_eq!(records[0].from_ts, 1705276800);
+    }
+
+    #[test]
+    fn test_target_prefers_explicit_then_slo() {
+        let slo: SLOResponseData = serde_json::from_value(json!({
+            "id": "abc",
+            "thresholds": [{"target": 99.5, "timeframe": "30d"}]
+        }))
+        .unwrap();
+        assert_eq!(target(&slo, Some(99.0)).unwrap(), 99.0);
+        assert_eq!(target(&slo, None).unwrap(), 99.5);
+
+        let slo: SLOResponseData = serde_json::from_value(json!({"id": "abc"})).unwrap();
+        assert_eq!(target(&slo, None).unwrap_err().exit_code(), 4);
+    }
+}
@@ -2,120 +2,486 @@
 //!
 //! Provides functions to validate and parse Datadog time range formats.
 //!
-//! According to [Datadog API v2 documentation](https://docs.datadoghq.com/api/latest/logs/),
-//! the Logs and Spans APIs support three time formats:
+//! Every command accepts the same formats:
 //!
-//! 1. **Date Math Expressions**: Relative time calculations
-//!    - `now` - Current time
+//! 1. **Date Math Expressions**: `now`, optionally followed by offsets and rounding
 //!    - `now-15m` - 15 minutes ago
-//!    - `now-1h` - 1 hour ago
-//!    - `now-1d` - 1 day ago
+//!    - `now-1d+2h` - 22 hours ago (offsets can be chained)
+//!    - `now/d` - Start of today (UTC)
+//!    - `now-1d/h` - 1 day ago, rounded down to the hour
 //!    - Units: `s` (seconds), `m` (minutes), `h` (hours), `d` (days), `w` (weeks), `mo` (months), `y` (years)
 //!
-//! 2. **ISO8601 Date-Time Strings**: Standard timestamp format
+//!    Month and year offsets are calendar-aware: `now-1mo` on March 31 is the
+//!    last day of February, not 30 days earlier. Weeks start on Monday.
+//!
+//! 2. **ISO8601 Date-Time Strings**
 //!    - `2024-01-15T10:00:00Z` - UTC timezone
-//!    - `2024-01-15T10:00:00+00:00` - With timezone offset
-//!    - `2024-01-15T10:00:00.123Z` - With milliseconds
+//!    - `2024-01-15T10:00:00+02:00` - With timezone offset
+//!    - `2024-01-15T10:00:00.123Z` - With fractional seconds
+//!    - `2024-01-15T10:00:00` - Without offset (wall-clock time, see below)
+//!    - `2024-01-15` - Date only (midnight wall-clock time)
+//!
+//! 3. **Unix Timestamps**: `1705315200000` (milliseconds) or `1705315200` (seconds)
 //!
-//! 3. **Unix Timestamps**: Milliseconds since Unix epoch
-//!    - `1705315200000` - Milliseconds since January 1, 1970 UTC
+//! 4. **Wall-Clock Times**, read in the range's [`Zone`] (UTC unless `--tz` is given)
+//!    - `today`, `yesterday` - Start of the day, optionally followed by date math (`yesterday+9h`)
+//!    - `14:32`, `14:32:10` - The most recent occurrence of that time of day
+//!    - `2024-06-01 09:00` - A date and time without an offset
 //!
-//! All formats are passed directly to Datadog's API, which handles the parsing.
+//!    Rounding (`now/d`) and day, week, month and year offsets also follow the
+//!    zone's calendar; second, minute and hour offsets are exact durations.
 //!
-//! For the Metrics API (V1), which requires Unix timestamps in seconds, use the
-//! `parse_to_unix_seconds` function to convert time strings to i64.
+//! Time arguments are parsed once into a [`TimeSpec`] and resolved into a
+//! [`ResolvedRange`] of exact epoch milliseconds against a single captured
+//! "now". Every client and the Datadog UI link use that range, so all outputs of
+//! one invocation agree on the same window.
+
+use chrono::{
+    DateTime, Datelike, Duration, FixedOffset, Local, Months, NaiveDate, NaiveDateTime, NaiveTime,
+    SecondsFormat, TimeZone, Timelike, Utc,
+};
+use chrono_tz::Tz;
+use std::fmt;
+use std::str::FromStr;
 
 use crate::error::AppError;
 
-/// Validates that a time string is in a format Datadog accepts.
-///
-/// Datadog accepts three formats:
-/// 1. **Relative times**: "now", "now-NUMBERUNIT" where unit is s, m, h, d, w, mo, y
-/// 2. **ISO8601 timestamps**: "2024-01-15T10:00:00Z" or with timezone offsets
-/// 3. **Unix timestamps**: milliseconds since Unix epoch (numeric string)
-///
-/// This function performs basic validation. The actual parsing is done by Datadog's API.
-///
-/// # Arguments
-///
-/// * `time_str` - Time string to validate
-///
-/// # Returns
+/// Latest accepted Unix timestamp in milliseconds (year 2100).
+const MAX_UNIX_MILLIS: i64 = 4_102_444_800_000;
+
+/// A parsed time argument: date math relative to "now", or an absolute instant.
 ///
-/// `true` if the format appears valid, `false` otherwise
-pub fn is_valid_time_format(time_str: &str) -> bool {
-    if time_str.is_empty() {
-        return false;
+/// Parsing validates the syntax once; [`TimeSpec::resolve`] then evaluates it
+/// against a caller-provided "now".
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeSpec {
+    text: String,
+    kind: SpecKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum SpecKind {
+    /// `now` followed by offsets and roundings, applied in order
+    Relative(Vec<DateMath>),
+    Absolute(DateTime<Utc>),
+    /// A date and time without an offset, read in the range's zone
+    Wall(NaiveDateTime),
+    /// A time of day: its most recent occurrence in the range's zone
+    Clock(NaiveTime),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DateMath {
+    Offset(i64, Unit),
+    RoundDown(Unit),
+}
+
+impl DateMath {
+    /// Applies the step to `time`; calendar units follow the wall clock in `zone`.
+    fn apply(&self, time: DateTime<Utc>, zone: &Zone) -> Option<DateTime<Utc>> {
+        match self {
+            DateMath::Offset(amount, unit) if unit.is_exact() => {
+                Some(unit.add(time.naive_utc(), *amount)?.and_utc())
+            }
+            DateMath::Offset(amount, unit) => zone.localize(unit.add(zone.wall(time), *amount)?),
+            DateMath::RoundDown(unit) => zone.localize(unit.round_down(zone.wall(time))?),
+        }
     }
+}
 
-    // Check for relative time format: "now" or "now-<number><unit>"
-    if time_str == "now" {
-        return true;
+impl TimeSpec {
+    /// The time argument as given on the command line.
+    pub fn as_str(&self) -> &str {
+        &self.text
     }
 
-    if let Some(rest) = time_str.strip_prefix("now-") {
-        if rest.is_empty() {
-            return false;
-        }
+    /// Whether the spec depends on the current time.
+    pub fn is_relative(&self) -> bool {
+        matches!(self.kind, SpecKind::Relative(_))
+    }
 
-        // Parse number and unit
-        let mut chars = rest.chars();
-        let mut num_str = String::new();
-
-        // Collect digits
-        while let Some(c) = chars.next() {
-            if c.is_ascii_digit() {
-                num_str.push(c);
-            } else {
-                // Check if it's a valid unit
-                let unit = format!("{}{}", c, chars.as_str());
-                return matches!(unit.as_str(), "s" | "m" | "h" | "d" | "w" | "mo" | "y")
-                    && !num_str.is_empty();
+    /// Evaluates the spec against `now`, reading wall-clock times in `zone`.
+    pub fn resolve(&self, now: DateTime<Utc>, zone: &Zone) -> Result<DateTime<Utc>, AppError> {
+        let resolved = match &self.kind {
+            SpecKind::Absolute(time) => Some(*time),
+            SpecKind::Relative(steps) => steps
+                .iter()
+                .try_fold(now, |time, step| step.apply(time, zone)),
+            SpecKind::Wall(wall) => zone.localize(*wall),
+            SpecKind::Clock(clock) => {
+                let today = zone.wall(now);
+                let mut wall = today.date().and_time(*clock);
+                if wall > today {
+                    wall -= Duration::days(1);
+                }
+                zone.localize(wall)
             }
+        };
+        resolved
+            .ok_or_else(|| AppError::InvalidQuery(format!("Time '{}' is out of range", self.text)))
+    }
+}
+
+impl FromStr for TimeSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let text = s.trim();
+        if text.is_empty() {
+            return Err("Time cannot be empty".to_string());
         }
 
-        // If we got here, there was no unit
-        return false;
+        let kind = if let Some((mut steps, expression)) = parse_anchor(text) {
+            steps.extend(
+                parse_date_math(expression)
+                    .map_err(|e| format!("Invalid relative time '{}': {}", text, e))?,
+            );
+            SpecKind::Relative(steps)
+        } else if text.chars().all(|c| c.is_ascii_digit()) {
+            SpecKind::Absolute(parse_unix(text)?)
+        } else if let Some(clock) = parse_clock(text) {
+            SpecKind::Clock(clock)
+        } else {
+            parse_iso8601(text).ok_or_else(|| {
+                format!(
+                    "Time format '{}' not supported. Use relative times (now-1h, now-1d/d, yesterday), ISO8601 (2024-01-15T10:00:00Z, 2024-01-15), wall-clock times (14:32, 2024-06-01 09:00) or Unix timestamps",
+                    text
+                )
+            })?
+        };
+
+        Ok(TimeSpec {
+            text: text.to_string(),
+            kind,
+        })
     }
+}
 
-    // Check for ISO8601 format (basic check)
-    // Format: YYYY-MM-DDTHH:MM:SS[.SSS][Z|+HH:MM|-HH:MM]
-    if time_str.len() >= 19 {
-        // Check for date part: YYYY-MM-DD
-        let date_part = &time_str[0..10];
-        if date_part.chars().filter(|c| c == &'-').count() == 2 {
-            // Check for T separator
-            if time_str.len() > 10 && time_str.chars().nth(10) == Some('T') {
-                // Basic ISO8601 format detected
-                return true;
-            }
+impl fmt::Display for TimeSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+/// A time range resolved to exact Unix milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResolvedRange {
+    pub from_ms: i64,
+    pub to_ms: i64,
+}
+
+impl ResolvedRange {
+    /// Creates a range from Unix milliseconds.
+    pub fn new(from_ms: i64, to_ms: i64) -> Self {
+        Self { from_ms, to_ms }
+    }
+
+    /// Resolves both ends against the same `now`; see [`ResolvedRange::validate`].
+    pub fn resolve(
+        from: &TimeSpec,
+        to: &TimeSpec,
+        now: DateTime<Utc>,
+        zone: &Zone,
+    ) -> Result<Self, AppError> {
+        let range = Self::new(
+            from.resolve(now, zone)?.timestamp_millis(),
+            to.resolve(now, zone)?.timestamp_millis(),
+        );
+        range.validate(from, to, now)?;
+        Ok(range)
+    }
+
+    /// Resolves the range reaching `window` before and after `center`.
+    pub fn around(
+        center: &TimeSpec,
+        window: &Span,
+        now: DateTime<Utc>,
+        zone: &Zone,
+    ) -> Result<Self, AppError> {
+        let resolved = center.resolve(now, zone)?;
+        let range = Self::new(
+            window.shift(resolved, -1, zone)?.timestamp_millis(),
+            window.shift(resolved, 1, zone)?.timestamp_millis(),
+        );
+        range.validate(
+            &format_args!("{} - {}", center, window),
+            &format_args!("{} + {}", center, window),
+            now,
+        )?;
+        Ok(range)
+    }
+
+    /// Resolves the range covering the `span` that ends at `to`.
+    pub fn last(
+        span: &Span,
+        to: &TimeSpec,
+        now: DateTime<Utc>,
+        zone: &Zone,
+    ) -> Result<Self, AppError> {
+        let end = to.resolve(now, zone)?;
+        let range = Self::new(
+            span.shift(end, -1, zone)?.timestamp_millis(),
+            end.timestamp_millis(),
+        );
+        range.validate(&format_args!("{} - {}", to, span), to, now)?;
+        Ok(range)
+    }
+
+    /// Checks that the range is non-empty and does not start in the future.
+    ///
+    /// `from` and `to` describe the bounds as the user gave them, so the error
+    /// can name the one at fault.
+    pub fn validate(
+        &self,
+        from: &dyn fmt::Display,
+        to: &dyn fmt::Display,
+        now: DateTime<Utc>,
+    ) -> Result<(), AppError> {
+        if self.from_ms > now.timestamp_millis() {
+            return Err(AppError::InvalidQuery(format!(
+                "Start time '{}' ({}) is in the future (now is {})",
+                from,
+                format_millis(self.from_ms),
+                format_millis(now.timestamp_millis())
+            )));
+        }
+        if self.from_ms >= self.to_ms {
+            return Err(AppError::InvalidQuery(format!(
+                "End time '{}' ({}) must be after start time '{}' ({})",
+                to,
+                format_millis(self.to_ms),
+                from,
+                format_millis(self.from_ms)
+            )));
+        }
+        Ok(())
+    }
+
+    /// Describes how far the range reaches past `retention_days` of `data`, if it does.
+    pub fn retention_warning(
+        &self,
+        data: &str,
+        retention_days: u32,
+        now: DateTime<Utc>,
+    ) -> Option<String> {
+        let cutoff = now.timestamp_millis() - i64::from(retention_days) * 86_400_000;
+        (self.from_ms < cutoff).then(|| {
+            format!(
+                "Start time {} is beyond the {}-day {} retention; results before {} may be missing",
+                format_millis(self.from_ms),
+                retention_days,
+                data,
+                format_millis(cutoff)
+            )
+        })
+    }
+
+    /// Parses and resolves a range against the current time, in UTC.
+    pub fn parse(from: &str, to: &str) -> Result<Self, AppError> {
+        let from: TimeSpec = from.parse().map_err(AppError::Config)?;
+        let to: TimeSpec = to.parse().map_err(AppError::Config)?;
+        Self::resolve(&from, &to, Utc::now(), &Zone::Utc)
+    }
+
+    /// Splits the range into up to `parts` contiguous windows of nearly equal length.
+    ///
+    /// Adjacent windows share their boundary, so no instant is left out. A range
+    /// shorter than `parts` milliseconds yields one window per millisecond.
+    pub fn split(&self, parts: usize) -> Vec<ResolvedRange> {
+        let length = self.to_ms - self.from_ms;
+        let parts = i64::try_from(parts)
+            .unwrap_or(i64::MAX)
+            .clamp(1, length.max(1));
+        (0..parts)
+            .map(|i| {
+                Self::new(
+                    self.from_ms + length * i / parts,
+                    self.from_ms + length * (i + 1) / parts,
+                )
+            })
+            .collect()
+    }
+
+    /// Start time in Unix seconds.
+    pub fn from_secs(&self) -> i64 {
+        self.from_ms.div_euclid(1000)
+    }
+
+    /// End time in Unix seconds.
+    pub fn to_secs(&self) -> i64 {
+        self.to_ms.div_euclid(1000)
+    }
+}
+
+impl fmt::Display for ResolvedRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} to {}",
+            format_millis(self.from_ms),
+            format_millis(self.to_ms)
+        )
+    }
+}
+
+/// A length of time such as `15m`, `2h` or `1mo`, used by `--window` and `--last`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    amount: i64,
+    unit: Unit,
+}
+
+impl Span {
+    /// Moves `time` by the span, backwards when `direction` is negative.
+    fn shift(
+        &self,
+        time: DateTime<Utc>,
+        direction: i64,
+        zone: &Zone,
+    ) -> Result<DateTime<Utc>, AppError> {
+        DateMath::Offset(self.amount * direction.signum(), self.unit)
+            .apply(time, zone)
+            .ok_or_else(|| {
+                AppError::InvalidQuery(format!("Moving {} from {} is out of range", self, time))
+            })
+    }
+}
+
+impl FromStr for Span {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let (number, unit) = s.split_at(split);
+        let amount: i64 = number
+            .parse()
+            .map_err(|_| format!("Invalid duration '{}', expected e.g. 15m, 2h or 1d", s))?;
+        if amount == 0 {
+            return Err("Duration must be positive".to_string());
         }
+        let unit = Unit::parse(unit).map_err(|e| format!("Invalid duration '{}': {}", s, e))?;
+        Ok(Span { amount, unit })
     }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.amount, self.unit.as_str())
+    }
+}
 
-    // Check for Unix timestamp (milliseconds since epoch)
-    // Should be all digits and represent a reasonable timestamp
-    // (between 1970 and 2100, roughly 0 to 4102444800000)
-    if time_str.chars().all(|c| c.is_ascii_digit())
-        && !time_str.is_empty()
-        && let Ok(timestamp) = time_str.parse::<u64>()
-    {
-        // Valid Unix timestamp range: 0 to ~4102444800000 (year 2100)
-        if timestamp <= 4_102_444_800_000 {
-            return true;
+/// Time zone for wall-clock times, `today`/`yesterday` and calendar date math.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Zone {
+    #[default]
+    Utc,
+    /// The system time zone
+    Local,
+    /// A fixed offset from UTC, e.g. `+02:00`
+    Fixed(FixedOffset),
+    /// An IANA time zone, e.g. `Europe/Paris`
+    Named(Tz),
+}
+
+impl Zone {
+    /// The wall-clock time in this zone at `time`.
+    fn wall(&self, time: DateTime<Utc>) -> NaiveDateTime {
+        match self {
+            Zone::Utc => time.naive_utc(),
+            Zone::Local => time.with_timezone(&Local).naive_local(),
+            Zone::Fixed(offset) => time.with_timezone(offset).naive_local(),
+            Zone::Named(tz) => time.with_timezone(tz).naive_local(),
         }
     }
 
-    false
+    /// The instant at which this zone's clocks show `wall`.
+    ///
+    /// Ambiguous times (clocks turned back) take the earlier instant; times skipped
+    /// by a clock change move forward by an hour.
+    fn localize(&self, wall: NaiveDateTime) -> Option<DateTime<Utc>> {
+        fn localize_in<Z: TimeZone>(zone: &Z, wall: NaiveDateTime) -> Option<DateTime<Utc>> {
+            zone.from_local_datetime(&wall)
+                .earliest()
+                .or_else(|| {
+                    zone.from_local_datetime(&(wall + Duration::hours(1)))
+                        .earliest()
+                })
+                .map(|time| time.with_timezone(&Utc))
+        }
+
+        match self {
+            Zone::Utc => Some(wall.and_utc()),
+            Zone::Local => localize_in(&Local, wall),
+            Zone::Fixed(offset) => localize_in(offset, wall),
+            Zone::Named(tz) => localize_in(tz, wall),
+        }
+    }
 }
 
-/// Validates that a time range is logically correct.
+impl FromStr for Zone {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        match s {
+            "UTC" | "utc" | "Z" => Ok(Zone::Utc),
+            "local" => Ok(Zone::Local),
+            _ if s.starts_with(['+', '-']) => s
+                .parse::<FixedOffset>()
+                .map(Zone::Fixed)
+                .map_err(|_| format!("Invalid UTC offset '{}', expected e.g. +02:00", s)),
+            _ => s.parse::<Tz>().map(Zone::Named).map_err(|_| {
+                format!(
+                    "Unknown time zone '{}'. Use UTC, local, an offset (+02:00) or an IANA name (Europe/Paris)",
+                    s
+                )
+            }),
+        }
+    }
+}
+
+impl fmt::Display for Zone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Zone::Utc => f.write_str("UTC"),
+            Zone::Local => f.write_str("local"),
+            Zone::Fixed(offset) => write!(f, "{}", offset),
+            Zone::Named(tz) => f.write_str(tz.name()),
+        }
+    }
+}
+
+/// Formats Unix milliseconds as an ISO8601 UTC timestamp.
+fn format_millis(millis: i64) -> String {
+    Utc.timestamp_millis_opt(millis)
+        .single()
+        .map(|time| time.to_rfc3339_opts(SecondsFormat::Millis, true))
+        .unwrap_or_else(|| millis.to_string())
+}
+
+/// Validates that a time string is in a supported format.
+///
+/// Accepts date math (`now-1h`, `now-1d/h`, `now-1d+2h`), ISO8601 date-times and
+/// dates, and Unix timestamps. See the module documentation for the full list.
+///
+/// # Arguments
+///
+/// * `time_str` - Time string to validate
+///
+/// # Returns
 ///
-/// Checks that:
-/// - Both times are in valid formats
-/// - `from` is before `to` (when both are absolute timestamps)
+/// `true` if the format is valid, `false` otherwise
+pub fn is_valid_time_format(time_str: &str) -> bool {
+    time_str
+        .parse::<TimeSpec>()
+        .is_ok_and(|spec| spec.resolve(Utc::now(), &Zone::Utc).is_ok())
+}
+
+/// Validates that a time range is logically correct.
 ///
-/// Note: Relative times like "now-1h" and "now" are always considered valid
-/// as they're evaluated by Datadog at query time.
+/// Resolves both times against the current time (UTC) and checks that the
+/// start is before the end and not in the future.
 ///
 /// # Arguments
 ///
@@ -124,31 +490,15 @@ pub fn is_valid_time_format(time_str: &str) -> bool {
 ///
 /// # Returns
 ///
-/// `true` if the range appears valid, `false` otherwise
+/// `true` if the range is valid, `false` otherwise
 pub fn is_valid_time_range(from: &str, to: &str) -> bool {
-    if !is_valid_time_format(from) || !is_valid_time_format(to) {
-        return false;
-    }
-
-    // If both are relative times, they're always valid
-    if (from == "now" || from.starts_with("now-")) && (to == "now" || to.starts_with("now-")) {
-        return true;
-    }
-
-    // If both are absolute timestamps, we could validate ordering,
-    // but for now we'll let Datadog handle it
-    true
+    ResolvedRange::parse(from, to).is_ok()
 }
 
 /// Parses a time string into Unix seconds.
 ///
-/// This function is used for APIs that require Unix timestamps (like Metrics V1 API).
-/// The Logs and Spans V2 APIs accept time strings directly and handle parsing themselves.
-///
-/// Supports three formats:
-/// 1. **Relative times**: "now", "now-15m", "now-1h", etc.
-/// 2. **Unix timestamps**: "1705315200000" (milliseconds) or "1705315200" (seconds)
-/// 3. **ISO8601 timestamps**: Currently not supported, returns error suggesting alternatives
+/// Accepts every format described in the module documentation. Commands resolve
+/// a whole [`ResolvedRange`] instead, so both ends share the same "now".
 ///
 /// # Arguments
 ///
@@ -174,75 +524,205 @@ pub fn is_valid_time_range(from: &str, to: &str) -> bool {
 /// // Unix milliseconds (auto-converted to seconds)
 /// let timestamp = parse_to_unix_seconds("1705315200000").unwrap();
 /// assert_eq!(timestamp, 1705315200);
+///
+/// // ISO8601 with offset
+/// let timestamp = parse_to_unix_seconds("2024-01-15T12:00:00+02:00").unwrap();
+/// assert_eq!(timestamp, 1705312800);
 /// ```
 pub fn parse_to_unix_seconds(time_str: &str) -> Result<i64, AppError> {
-    // Handle "now"
-    if time_str == "now" {
-        return Ok(std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map_err(|e| AppError::Config(format!("Failed to get current time: {}", e)))?
-            .as_secs() as i64);
+    let spec: TimeSpec = time_str.parse().map_err(AppError::Config)?;
+    Ok(spec.resolve(Utc::now(), &Zone::Utc)?.timestamp())
+}
+
+/// Splits a leading `now`, `today` or `yesterday` into its date math and the rest.
+fn parse_anchor(text: &str) -> Option<(Vec<DateMath>, &str)> {
+    if let Some(rest) = text.strip_prefix("now") {
+        Some((Vec::new(), rest))
+    } else if let Some(rest) = text.strip_prefix("today") {
+        Some((vec![DateMath::RoundDown(Unit::Day)], rest))
+    } else {
+        text.strip_prefix("yesterday").map(|rest| {
+            (
+                vec![
+                    DateMath::Offset(-1, Unit::Day),
+                    DateMath::RoundDown(Unit::Day),
+                ],
+                rest,
+            )
+        })
     }
+}
 
-    // Handle relative times like "now-1h"
-    if let Some(rest) = time_str.strip_prefix("now-") {
-        let now_secs = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map_err(|e| AppError::Config(format!("Failed to get current time: {}", e)))?
-            .as_secs() as i64;
+/// Parses a time of day such as `14:32` or `14:32:10`.
+fn parse_clock(time_str: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(time_str, "%H:%M")
+        .or_else(|_| NaiveTime::parse_from_str(time_str, "%H:%M:%S"))
+        .ok()
+}
 
-        // Parse the number and unit
-        let mut num_str = String::new();
-        let mut unit = String::new();
+/// Parses the offsets and roundings following `now`, e.g. `-1d+2h/h`.
+fn parse_date_math(expression: &str) -> Result<Vec<DateMath>, String> {
+    let mut steps = Vec::new();
+    let mut rest = expression;
 
-        for c in rest.chars() {
-            if c.is_ascii_digit() {
-                num_str.push(c);
-            } else {
-                unit.push(c);
+    while let Some(op) = rest.chars().next() {
+        rest = &rest[op.len_utf8()..];
+        let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        let (number, after_number) = rest.split_at(digits);
+        let letters = after_number.len()
+            - after_number
+                .trim_start_matches(|c: char| c.is_ascii_alphabetic())
+                .len();
+        let (unit, after_unit) = after_number.split_at(letters);
+        let unit = Unit::parse(unit)?;
+        rest = after_unit;
+
+        steps.push(match op {
+            '+' | '-' => {
+                let amount: i64 = number
+                    .parse()
+                    .map_err(|_| format!("missing amount before '{}'", unit.as_str()))?;
+                DateMath::Offset(if op == '-' { -amount } else { amount }, unit)
             }
+            '/' if number.is_empty() => DateMath::RoundDown(unit),
+            '/' => return Err("rounding takes a unit only, e.g. '/d'".to_string()),
+            other => return Err(format!("unexpected '{}'", other)),
+        });
+    }
+
+    Ok(steps)
+}
+
+/// Parses a Unix timestamp in seconds or milliseconds.
+///
+/// Values up to the year 2100 in seconds are read as seconds, larger ones as milliseconds.
+fn parse_unix(time_str: &str) -> Result<DateTime<Utc>, String> {
+    let timestamp: i64 = time_str
+        .parse()
+        .map_err(|_| format!("Invalid Unix timestamp '{}'", time_str))?;
+    let millis = if timestamp > MAX_UNIX_MILLIS / 1000 {
+        timestamp
+    } else {
+        timestamp * 1000
+    };
+    if millis > MAX_UNIX_MILLIS {
+        return Err(format!(
+            "Unix timestamp '{}' is beyond the year 2100",
+            time_str
+        ));
+    }
+    Utc.timestamp_millis_opt(millis)
+        .single()
+        .ok_or_else(|| format!("Invalid Unix timestamp '{}'", time_str))
+}
+
+/// Parses ISO8601 date-times and plain dates; those without an offset are wall-clock times.
+fn parse_iso8601(time_str: &str) -> Option<SpecKind> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(time_str) {
+        return Some(SpecKind::Absolute(time.with_timezone(&Utc)));
+    }
+    for format in [
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%dT%H:%M",
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%Y-%m-%d %H:%M",
+    ] {
+        if let Ok(time) = NaiveDateTime::parse_from_str(time_str, format) {
+            return Some(SpecKind::Wall(time));
         }
+    }
+    NaiveDate::parse_from_str(time_str, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(SpecKind::Wall)
+}
 
-        let num: i64 = num_str
-            .parse()
-            .map_err(|_| AppError::Config(format!("Invalid time format: {}", time_str)))?;
-
-        let offset_secs = match unit.as_str() {
-            "s" => num,
-            "m" => num * 60,
-            "h" => num * 60 * 60,
-            "d" => num * 60 * 60 * 24,
-            "w" => num * 60 * 60 * 24 * 7,
-            "mo" => num * 60 * 60 * 24 * 30, // Approximate
-            "y" => num * 60 * 60 * 24 * 365, // Approximate
-            _ => {
-                return Err(AppError::Config(format!(
-                    "Invalid time unit in: {}",
-                    time_str
-                )));
-            }
-        };
+/// A date math unit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Unit {
+    Second,
+    Minute,
+    Hour,
+    Day,
+    Week,
+    Month,
+    Year,
+}
 
-        return Ok(now_secs - offset_secs);
+impl Unit {
+    fn parse(s: &str) -> Result<Self, String> {
+        match s {
+            "s" => Ok(Unit::Second),
+            "m" => Ok(Unit::Minute),
+            "h" => Ok(Unit::Hour),
+            "d" => Ok(Unit::Day),
+            "w" => Ok(Unit::Week),
+            "mo" => Ok(Unit::Month),
+            "y" => Ok(Unit::Year),
+            "" => Err("missing unit (s, m, h, d, w, mo, y)".to_string()),
+            other => Err(format!(
+                "unknown unit '{}' (expected s, m, h, d, w, mo, y)",
+                other
+            )),
+        }
     }
 
-    // Try parsing as Unix timestamp (could be seconds or milliseconds)
-    if let Ok(timestamp) = time_str.parse::<i64>() {
-        // If the timestamp looks like milliseconds (> year 2000 in seconds), convert to seconds
-        if timestamp > 946684800 && timestamp.to_string().len() >= 13 {
-            // Looks like milliseconds
-            return Ok(timestamp / 1000);
-        } else {
-            // Assume seconds
-            return Ok(timestamp);
+    fn as_str(&self) -> &'static str {
+        match self {
+            Unit::Second => "s",
+            Unit::Minute => "m",
+            Unit::Hour => "h",
+            Unit::Day => "d",
+            Unit::Week => "w",
+            Unit::Month => "mo",
+            Unit::Year => "y",
         }
     }
 
-    // ISO8601 not yet supported - would need chrono or similar
-    Err(AppError::Config(format!(
-        "Time format '{}' not supported. Please use relative times (now, now-1h) or Unix timestamps",
-        time_str
-    )))
+    /// Whether the unit has a fixed length, unaffected by clock changes.
+    fn is_exact(&self) -> bool {
+        matches!(self, Unit::Second | Unit::Minute | Unit::Hour)
+    }
+
+    /// Adds `amount` units to a wall-clock time; months and years clamp the day of month.
+    fn add(&self, time: NaiveDateTime, amount: i64) -> Option<NaiveDateTime> {
+        let duration = match self {
+            Unit::Second => Duration::try_seconds(amount)?,
+            Unit::Minute => Duration::try_minutes(amount)?,
+            Unit::Hour => Duration::try_hours(amount)?,
+            Unit::Day => Duration::try_days(amount)?,
+            Unit::Week => Duration::try_weeks(amount)?,
+            Unit::Month | Unit::Year => {
+                let months = if *self == Unit::Year {
+                    amount.checked_mul(12)?
+                } else {
+                    amount
+                };
+                let magnitude = Months::new(u32::try_from(months.unsigned_abs()).ok()?);
+                return if months < 0 {
+                    time.checked_sub_months(magnitude)
+                } else {
+                    time.checked_add_months(magnitude)
+                };
+            }
+        };
+        time.checked_add_signed(duration)
+    }
+
+    /// Rounds a wall-clock time down to the start of the unit (weeks start on Monday).
+    fn round_down(&self, time: NaiveDateTime) -> Option<NaiveDateTime> {
+        let date = time.date();
+        let start = match self {
+            Unit::Second => return time.with_nanosecond(0),
+            Unit::Minute => return time.with_nanosecond(0)?.with_second(0),
+            Unit::Hour => return time.with_nanosecond(0)?.with_second(0)?.with_minute(0),
+            Unit::Day => date,
+            Unit::Week => date - Duration::days(date.weekday().num_days_from_monday() as i64),
+            Unit::Month => date.with_day(1)?,
+            Unit::Year => NaiveDate::from_ymd_opt(date.year(), 1, 1)?,
+        };
+        start.and_hms_opt(0, 0, 0)
+    }
 }
 
 #[cfg(test)]
@@ -342,9 +822,11 @@ mod tests {
     fn test_invalid_time_formats() {
         assert!(!is_valid_time_format(""));
         assert!(!is_valid_time_format("invalid"));
-        assert!(!is_valid_time_format("2024-01-15"));
-        assert!(!is_valid_time_format("10:00:00"));
-        assert!(!is_valid_time_format("now+1h"));
+        assert!(!is_valid_time_format("2024-13-15"));
+        assert!(!is_valid_time_format("25:00"));
+        assert!(!is_valid_time_format("now*1h"));
+        assert!(!is_valid_time_format("now/1d"));
+        assert!(!is_valid_time_format("now-1d/"));
     }
 
     #[test]
@@ -361,8 +843,10 @@ mod tests {
         ));
 
         // Mixed (relative and absolute)
-        assert!(is_valid_time_range("now-1h", "2024-01-15T11:00:00Z"));
         assert!(is_valid_time_range("2024-01-15T10:00:00Z", "now"));
+
+        // The end may lie in the future
+        assert!(is_valid_time_range("now-1h", "now+1h"));
     }
 
     #[test]
@@ -371,6 +855,15 @@ mod tests {
         assert!(!is_valid_time_range("now", ""));
         assert!(!is_valid_time_range("invalid", "now"));
         assert!(!is_valid_time_range("now", "invalid"));
+
+        // Inverted, empty and future ranges
+        assert!(!is_valid_time_range("now", "now-1h"));
+        assert!(!is_valid_time_range("now-1h", "2024-01-15T11:00:00Z"));
+        assert!(!is_valid_time_range(
+            "2024-01-15T10:00:00Z",
+            "2024-01-15T10:00:00Z"
+        ));
+        assert!(!is_valid_time_range("now+1h", "now+2h"));
     }
 
     #[test]
@@ -424,7 +917,268 @@ mod tests {
         let result = parse_to_unix_seconds("invalid");
         assert!(result.is_err());
 
-        let result = parse_to_unix_seconds("2024-01-15T10:00:00Z");
-        assert!(result.is_err()); // ISO8601 not yet supported
+        let result = parse_to_unix_seconds("now-1q");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_parse_to_unix_seconds_iso8601() {
+        assert_eq!(
+            parse_to_unix_seconds("2024-01-15T10:00:00Z").unwrap(),
+            1705312800
+        );
+        assert_eq!(
+            parse_to_unix_seconds("2024-01-15T12:00:00+02:00").unwrap(),
+            1705312800
+        );
+        assert_eq!(
+            parse_to_unix_seconds("2024-01-15T10:00:00.750Z").unwrap(),
+            1705312800
+        );
+        assert_eq!(
+            parse_to_unix_seconds("2024-01-15T10:00:00").unwrap(),
+            1705312800
+        );
+        assert_eq!(parse_to_unix_seconds("2024-01-15").unwrap(), 1705276800);
+    }
+
+    /// 2024-03-31T10:30:45.5Z, a Sunday.
+    fn fixed_now() -> DateTime<Utc> {
+        "2024-03-31T10:30:45.5Z".parse().unwrap()
+    }
+
+    fn at(time_str: &str) -> String {
+        time_str
+            .parse::<TimeSpec>()
+            .unwrap()
+            .resolve(fixed_now(), &Zone::Utc)
+            .unwrap()
+            .to_rfc3339_opts(SecondsFormat::Millis, true)
+    }
+
+    #[test]
+    fn test_calendar_offsets() {
+        // Day of month is clamped to the shorter month
+        assert_eq!(at("now-1mo"), "2024-02-29T10:30:45.500Z");
+        assert_eq!(at("now-13mo"), "2023-02-28T10:30:45.500Z");
+        assert_eq!(at("now-1y"), "2023-03-31T10:30:45.500Z");
+        assert_eq!(at("now+1mo"), "2024-04-30T10:30:45.500Z");
+    }
+
+    #[test]
+    fn test_chained_offsets() {
+        assert_eq!(at("now-1d+2h"), "2024-03-30T12:30:45.500Z");
+        assert_eq!(at("now+1h-30m"), "2024-03-31T11:00:45.500Z");
+    }
+
+    #[test]
+    fn test_rounding() {
+        assert_eq!(at("now/s"), "2024-03-31T10:30:45.000Z");
+        assert_eq!(at("now/m"), "2024-03-31T10:30:00.000Z");
+        assert_eq!(at("now/h"), "2024-03-31T10:00:00.000Z");
+        assert_eq!(at("now/d"), "2024-03-31T00:00:00.000Z");
+        assert_eq!(at("now/w"), "2024-03-25T00:00:00.000Z");
+        assert_eq!(at("now/mo"), "2024-03-01T00:00:00.000Z");
+        assert_eq!(at("now/y"), "2024-01-01T00:00:00.000Z");
+        assert_eq!(at("now-1d/h"), "2024-03-30T10:00:00.000Z");
+        assert_eq!(at("now/d-1d"), "2024-03-30T00:00:00.000Z");
+    }
+
+    #[test]
+    fn test_time_spec_keeps_text() {
+        let spec: TimeSpec = " now-1d/h ".parse().unwrap();
+        assert_eq!(spec.as_str(), "now-1d/h");
+        assert!(spec.is_relative());
+
+        let spec: TimeSpec = "2024-01-15".parse().unwrap();
+        assert_eq!(spec.to_string(), "2024-01-15");
+        assert!(!spec.is_relative());
+
+        assert!("tomorrow".parse::<TimeSpec>().is_err());
+    }
+
+    #[test]
+    fn test_resolved_range_shares_now() {
+        let from: TimeSpec = "now-1h".parse().unwrap();
+        let to: TimeSpec = "now".parse().unwrap();
+        let range = ResolvedRange::resolve(&from, &to, fixed_now(), &Zone::Utc).unwrap();
+        assert_eq!(range.to_ms - range.from_ms, 3_600_000);
+        assert_eq!(range.to_ms, 1711881045500);
+        assert_eq!(range.to_secs(), 1711881045);
+        assert_eq!(
+            range.to_string(),
+            "2024-03-31T09:30:45.500Z to 2024-03-31T10:30:45.500Z"
+        );
+    }
+
+    fn range_error(from: &str, to: &str) -> String {
+        let from: TimeSpec = from.parse().unwrap();
+        let to: TimeSpec = to.parse().unwrap();
+        match ResolvedRange::resolve(&from, &to, fixed_now(), &Zone::Utc).unwrap_err() {
+            AppError::InvalidQuery(message) => message,
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_resolved_range_rejects_inverted_range() {
+        let message = range_error("now", "now-1h");
+        assert!(message.starts_with("End time 'now-1h'"), "{}", message);
+
+        let message = range_error("2024-01-15", "2024-01-15");
+        assert!(message.contains("must be after start time"), "{}", message);
+    }
+
+    #[test]
+    fn test_resolved_range_rejects_future_start() {
+        let message = range_error("now+1h", "now+2h");
+        assert!(message.starts_with("Start time 'now+1h'"), "{}", message);
+        assert!(message.contains("in the future"), "{}", message);
+
+        let center: TimeSpec = "now+1h".parse().unwrap();
+        let window: Span = "15m".parse().unwrap();
+        let error = ResolvedRange::around(&center, &window, fixed_now(), &Zone::Utc).unwrap_err();
+        assert!(error.to_string().contains("'now+1h - 15m'"), "{}", error);
+    }
+
+    #[test]
+    fn test_resolved_range_rejects_times_out_of_range() {
+        let message = range_error("now-1000000y", "now");
+        assert!(message.contains("is out of range"), "{}", message);
+
+        let center: TimeSpec = "now-1h".parse().unwrap();
+        let window: Span = "1000000y".parse().unwrap();
+        let error = ResolvedRange::around(&center, &window, fixed_now(), &Zone::Utc).unwrap_err();
+        assert!(matches!(error, AppError::InvalidQuery(_)), "{:?}", error);
+    }
+
+    #[test]
+    fn test_retention_warning() {
+        let to: TimeSpec = "now".parse().unwrap();
+        let span: Span = "20d".parse().unwrap();
+        let range = ResolvedRange::last(&span, &to, fixed_now(), &Zone::Utc).unwrap();
+
+        let warning = range.retention_warning("logs", 15, fixed_now()).unwrap();
+        assert_eq!(
+            warning,
+            "Start time 2024-03-11T10:30:45.500Z is beyond the 15-day logs retention; results before 2024-03-16T10:30:45.500Z may be missing"
+        );
+        assert!(range.retention_warning("logs", 30, fixed_now()).is_none());
+    }
+
+    #[test]
+    fn test_resolved_range_parse() {
+        let range = ResolvedRange::parse("2024-01-15T10:00:00Z", "1705316400").unwrap();
+        assert_eq!(range, ResolvedRange::new(1705312800000, 1705316400000));
+        assert!(ResolvedRange::parse("invalid", "now").is_err());
+    }
+
+    fn in_zone(time_str: &str, zone: &str) -> String {
+        time_str
+            .parse::<TimeSpec>()
+            .unwrap()
+            .resolve(fixed_now(), &zone.parse().unwrap())
+            .unwrap()
+            .to_rfc3339_opts(SecondsFormat::Millis, true)
+    }
+
+    #[test]
+    fn test_day_anchors() {
+        assert_eq!(at("today"), "2024-03-31T00:00:00.000Z");
+        assert_eq!(at("yesterday"), "2024-03-30T00:00:00.000Z");
+        assert_eq!(at("yesterday+9h"), "2024-03-30T09:00:00.000Z");
+        assert!("yesterday*2".parse::<TimeSpec>().is_err());
+    }
+
+    #[test]
+    fn test_clock_times_pick_most_recent_occurrence() {
+        assert_eq!(at("09:15"), "2024-03-31T09:15:00.000Z");
+        assert_eq!(at("14:32"), "2024-03-30T14:32:00.000Z");
+        assert_eq!(at("10:30:45"), "2024-03-31T10:30:45.000Z");
+    }
+
+    #[test]
+    fn test_wall_clock_times_use_zone() {
+        assert_eq!(at("2024-06-01 09:00"), "2024-06-01T09:00:00.000Z");
+        assert_eq!(
+            in_zone("2024-06-01 09:00", "Europe/Paris"),
+            "2024-06-01T07:00:00.000Z"
+        );
+        assert_eq!(
+            in_zone("2024-06-01T09:00:00", "-05:00"),
+            "2024-06-01T14:00:00.000Z"
+        );
+        // An explicit offset wins over the zone
+        assert_eq!(
+            in_zone("2024-06-01T09:00:00Z", "Asia/Tokyo"),
+            "2024-06-01T09:00:00.000Z"
+        );
+        // Paris switched to summer time at 02:00 on 2024-03-31
+        assert_eq!(in_zone("today", "Europe/Paris"), "2024-03-30T23:00:00.000Z");
+        assert_eq!(
+            in_zone("yesterday", "Europe/Paris"),
+            "2024-03-29T23:00:00.000Z"
+        );
+        assert_eq!(in_zone("09:00", "Europe/Paris"), "2024-03-31T07:00:00.000Z");
+        assert_eq!(
+            in_zone("2024-03-31 02:30", "Europe/Paris"),
+            "2024-03-31T01:30:00.000Z"
+        );
+    }
+
+    #[test]
+    fn test_zone_parsing() {
+        assert_eq!("utc".parse::<Zone>().unwrap(), Zone::Utc);
+        assert_eq!("local".parse::<Zone>().unwrap(), Zone::Local);
+        assert_eq!("+02:00".parse::<Zone>().unwrap().to_string(), "+02:00");
+        assert_eq!(
+            "America/New_York".parse::<Zone>().unwrap().to_string(),
+            "America/New_York"
+        );
+        assert!("Mars/Olympus".parse::<Zone>().is_err());
+        assert!("+25:00".parse::<Zone>().is_err());
+    }
+
+    #[test]
+    fn test_span_parsing() {
+        assert_eq!("15m".parse::<Span>().unwrap().to_string(), "15m");
+        assert_eq!("3mo".parse::<Span>().unwrap().to_string(), "3mo");
+        assert!("0h".parse::<Span>().is_err());
+        assert!("h".parse::<Span>().is_err());
+        assert!("15".parse::<Span>().is_err());
+    }
+
+    #[test]
+    fn test_range_around_and_last() {
+        let center: TimeSpec = "2024-01-15T10:00:00Z".parse().unwrap();
+        let window: Span = "15m".parse().unwrap();
+        let range = ResolvedRange::around(&center, &window, fixed_now(), &Zone::Utc).unwrap();
+        assert_eq!(
+            range.to_string(),
+            "2024-01-15T09:45:00.000Z to 2024-01-15T10:15:00.000Z"
+        );
+
+        let span: Span = "1mo".parse().unwrap();
+        let to: TimeSpec = "now/d".parse().unwrap();
+        let range = ResolvedRange::last(&span, &to, fixed_now(), &Zone::Utc).unwrap();
+        assert_eq!(
+            range.to_string(),
+            "2024-02-29T00:00:00.000Z to 2024-03-31T00:00:00.000Z"
+        );
+    }
+
+    #[test]
+    fn test_split_range() {
+        let range = ResolvedRange::new(1000, 2000);
+        assert_eq!(
+            range.split(3),
+            vec![
+                ResolvedRange::new(1000, 1333),
+                ResolvedRange::new(1333, 1666),
+                ResolvedRange::new(1666, 2000),
+            ]
+        );
+        assert_eq!(range.split(0), vec![range]);
+        assert_eq!(ResolvedRange::new(0, 2).split(8).len(), 2);
     }
 }
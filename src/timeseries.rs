@@ -0,0 +1,431 @@
+//! Metrics v2 query request specs.
+//!
+//! Used by `metrics timeseries` and `metrics scalar`, which query the v2
+//! formula-and-function API. Named queries are parsed from compact CLI specs
+//! (`NAME=[SOURCE:]QUERY`, e.g. `a=sum:trace.http.request.errors{*}`) and
+//! formulas refer to them by name (e.g. `a / b * 100`).
+//!
+//! Datadog identifies response series by their position in the formulas list,
+//! so [`TimeseriesRequest::labels`] provides the label emitted for each one.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Data sources a named query can read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataSource {
+    Metrics,
+    CloudCost,
+}
+
+impl DataSource {
+    /// Name used as the query prefix and in the API (e.g. "cloud_cost").
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DataSource::Metrics => "metrics",
+            DataSource::CloudCost => "cloud_cost",
+        }
+    }
+}
+
+impl fmt::Display for DataSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A query that formulas can reference by name.
+///
+/// Parsed from `NAME=[SOURCE:]QUERY`, e.g. `a=avg:system.cpu.user{*}` or
+/// `cost=cloud_cost:sum:aws.cost.amortized{*}`. The source defaults to metrics.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NamedQuery {
+    pub name: String,
+    pub data_source: DataSource,
+    pub query: String,
+}
+
+impl FromStr for NamedQuery {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((name, query)) = s.split_once('=') else {
+            return Err(format!(
+                "Query '{}' must be NAME=QUERY, e.g. 'a=avg:system.cpu.user{{*}}'",
+                s
+            ));
+        };
+
+        let name = name.trim();
+        let valid_name = name.starts_with(|c: char| c.is_ascii_alphabetic())
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid_name {
+            return Err(format!(
+                "Invalid query name '{}': use letters, digits and underscores, starting with a letter",
+                name
+            ));
+        }
+
+        let query = query.trim();
+        let (data_source, query) = [DataSource::Metrics, DataSource::CloudCost]
+            .into_iter()
+            .find_map(|source| {
+                query
+                    .strip_prefix(source.as_str())
+                    .and_then(|rest| rest.strip_prefix(':'))
+                    .map(|rest| (source, rest.trim()))
+            })
+            .unwrap_or((DataSource::Metrics, query));
+        if query.is_empty() {
+            return Err(format!("Missing query expression for '{}'", name));
+        }
+
+        Ok(NamedQuery {
+            name: name.to_string(),
+            data_source,
+            query: query.to_string(),
+        })
+    }
+}
+
+/// Parses a rollup interval (e.g. "30s", "5m", "1h", "1d", or plain seconds) into milliseconds.
+pub fn parse_interval(s: &str) -> Result<i64, String> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+
+    let number: i64 = number
+        .parse()
+        .map_err(|_| format!("Invalid interval '{}', expected e.g. 30s, 5m or 1h", s))?;
+    if number == 0 {
+        return Err("Interval must be positive".to_string());
+    }
+
+    let unit_secs = match unit {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 60 * 60 * 24,
+        _ => {
+            return Err(format!(
+                "Invalid interval unit in '{}'. Expected s, m, h or d",
+                s
+            ));
+        }
+    };
+    number
+        .checked_mul(unit_secs * 1000)
+        .ok_or_else(|| format!("Interval '{}' is too long", s))
+}
+
+/// How a scalar query reduces each series to a single number.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ScalarAggregator {
+    #[default]
+    Avg,
+    Sum,
+    Min,
+    Max,
+    Last,
+    Percentile,
+}
+
+impl ScalarAggregator {
+    /// Name used on the command line and in the API (e.g. "avg").
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScalarAggregator::Avg => "avg",
+            ScalarAggregator::Sum => "sum",
+            ScalarAggregator::Min => "min",
+            ScalarAggregator::Max => "max",
+            ScalarAggregator::Last => "last",
+            ScalarAggregator::Percentile => "percentile",
+        }
+    }
+}
+
+impl FromStr for ScalarAggregator {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "avg" => Ok(ScalarAggregator::Avg),
+            "sum" => Ok(ScalarAggregator::Sum),
+            "min" => Ok(ScalarAggregator::Min),
+            "max" => Ok(ScalarAggregator::Max),
+            "last" => Ok(ScalarAggregator::Last),
+            "percentile" => Ok(ScalarAggregator::Percentile),
+            other => Err(format!(
+                "Unknown aggregator '{}'. Expected one of: avg, sum, min, max, last, percentile",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ScalarAggregator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Adds a `by {tags}` clause to a metric query, right after its scope.
+///
+/// `avg:system.cpu.user{env:prod}.rollup(avg, 60)` grouped by `host` becomes
+/// `avg:system.cpu.user{env:prod} by {host}.rollup(avg, 60)`. Tags are merged
+/// into a `by {..}` clause the query already has.
+pub fn with_group_by(query: &str, tags: &[String]) -> String {
+    if tags.is_empty() {
+        return query.to_string();
+    }
+    let Some(end) = query.find('}') else {
+        return format!("{} by {{{}}}", query, tags.join(","));
+    };
+    let (scope, rest) = query.split_at(end + 1);
+
+    let existing = rest
+        .trim_start()
+        .strip_prefix("by")
+        .and_then(|s| s.trim_start().strip_prefix('{'))
+        .and_then(|s| s.split_once('}'));
+    let (mut grouped, rest): (Vec<&str>, &str) = match existing {
+        Some((group, rest)) => (
+            group
+                .split(',')
+                .map(str::trim)
+                .filter(|tag| !tag.is_empty())
+                .collect(),
+            rest,
+        ),
+        None => (Vec::new(), rest),
+    };
+    for tag in tags {
+        if !grouped.contains(&tag.as_str()) {
+            grouped.push(tag);
+        }
+    }
+    format!("{} by {{{}}}{}", scope, grouped.join(","), rest)
+}
+
+/// A complete timeseries request, independent of the backing API.
+#[derive(Debug, Clone)]
+pub struct TimeseriesRequest {
+    pub queries: Vec<NamedQuery>,
+    /// Formulas over the query names; empty returns each query as-is
+    pub formulas: Vec<String>,
+    /// Rollup interval in milliseconds; `None` lets Datadog choose
+    pub interval_ms: Option<i64>,
+}
+
+impl TimeseriesRequest {
+    /// Checks that query names are unique and that formulas only reference known queries.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.queries.is_empty() {
+            return Err("At least one --query is required".to_string());
+        }
+
+        let mut names: Vec<&str> = Vec::new();
+        for query in &self.queries {
+            if names.contains(&query.name.as_str()) {
+                return Err(format!("Duplicate query name '{}'", query.name));
+            }
+            names.push(&query.name);
+        }
+
+        for formula in &self.formulas {
+            for identifier in identifiers(formula) {
+                // Formula functions are followed by '(' and are not query references
+                let is_function = formula[identifier.end..].trim_start().starts_with('(');
+                let name = &formula[identifier];
+                if !is_function && !names.contains(&name) {
+                    return Err(format!(
+                        "Formula '{}' references unknown query '{}'. Defined queries: {}",
+                        formula,
+                        name,
+                        names.join(", ")
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Output label per response series index: the formulas, or the query names without formulas.
+    pub fn labels(&self) -> Vec<String> {
+        if self.formulas.is_empty() {
+            self.queries.iter().map(|q| q.name.clone()).collect()
+        } else {
+            self.formulas.clone()
+        }
+    }
+}
+
+/// Returns the byte ranges of identifiers in a formula, skipping numeric literals.
+fn identifiers(formula: &str) -> Vec<std::ops::Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut start = None;
+    for (i, c) in formula
+        .char_indices()
+        .chain(std::iter::once((formula.len(), ' ')))
+    {
+        let part_of_word = c.is_ascii_alphanumeric() || c == '_' || c == '.';
+        match (start, part_of_word) {
+            (None, true) => start = Some(i),
+            (Some(s), false) => {
+                if formula[s..].starts_with(|c: char| c.is_ascii_alphabetic()) {
+                    ranges.push(s..i);
+                }
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(name: &str) -> NamedQuery {
+        NamedQuery {
+            name: name.to_string(),
+            data_source: DataSource::Metrics,
+            query: "avg:system.cpu.user{*}".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_named_query() {
+        let parsed: NamedQuery = "a=avg:system.cpu.user{env:prod}".parse().unwrap();
+        assert_eq!(parsed.name, "a");
+        assert_eq!(parsed.data_source, DataSource::Metrics);
+        assert_eq!(parsed.query, "avg:system.cpu.user{env:prod}");
+    }
+
+    #[test]
+    fn test_parse_named_query_with_source() {
+        let parsed: NamedQuery = "cost=cloud_cost:sum:aws.cost.amortized{*}".parse().unwrap();
+        assert_eq!(parsed.data_source, DataSource::CloudCost);
+        assert_eq!(parsed.query, "sum:aws.cost.amortized{*}");
+
+        let parsed: NamedQuery = "m=metrics:avg:system.load.1{*}".parse().unwrap();
+        assert_eq!(parsed.data_source, DataSource::Metrics);
+        assert_eq!(parsed.query, "avg:system.load.1{*}");
+    }
+
+    #[test]
+    fn test_parse_named_query_invalid() {
+        assert!("avg:system.cpu.user{*}".parse::<NamedQuery>().is_err());
+        assert!("=avg:system.cpu.user{*}".parse::<NamedQuery>().is_err());
+        assert!("1a=avg:system.cpu.user{*}".parse::<NamedQuery>().is_err());
+        assert!("a=".parse::<NamedQuery>().is_err());
+        assert!("a=cloud_cost:".parse::<NamedQuery>().is_err());
+    }
+
+    #[test]
+    fn test_parse_interval() {
+        assert_eq!(parse_interval("60").unwrap(), 60_000);
+        assert_eq!(parse_interval("30s").unwrap(), 30_000);
+        assert_eq!(parse_interval("5m").unwrap(), 300_000);
+        assert_eq!(parse_interval("1h").unwrap(), 3_600_000);
+        assert_eq!(parse_interval("1d").unwrap(), 86_400_000);
+        assert!(parse_interval("0s").is_err());
+        assert!(parse_interval("5w").is_err());
+        assert!(parse_interval("m").is_err());
+        assert!(parse_interval("999999999999999d").is_err());
+    }
+
+    #[test]
+    fn test_parse_scalar_aggregator() {
+        assert_eq!(
+            "avg".parse::<ScalarAggregator>().unwrap(),
+            ScalarAggregator::Avg
+        );
+        assert_eq!(
+            "LAST".parse::<ScalarAggregator>().unwrap(),
+            ScalarAggregator::Last
+        );
+        assert_eq!(
+            "percentile".parse::<ScalarAggregator>().unwrap(),
+            ScalarAggregator::Percentile
+        );
+        assert!("median".parse::<ScalarAggregator>().is_err());
+    }
+
+    #[test]
+    fn test_with_group_by() {
+        let tags = vec!["host".to_string(), "env".to_string()];
+        assert_eq!(
+            with_group_by("avg:system.cpu.user{*}", &tags),
+            "avg:system.cpu.user{*} by {host,env}"
+        );
+        assert_eq!(
+            with_group_by("sum:trace.http.request.hits{env:prod}.as_count()", &tags),
+            "sum:trace.http.request.hits{env:prod} by {host,env}.as_count()"
+        );
+        assert_eq!(
+            with_group_by("avg:system.cpu.user{*}", &[]),
+            "avg:system.cpu.user{*}"
+        );
+    }
+
+    #[test]
+    fn test_with_group_by_merges_existing_clause() {
+        let tags = vec!["host".to_string(), "env".to_string()];
+        assert_eq!(
+            with_group_by("avg:system.cpu.user{*} by {env}", &tags),
+            "avg:system.cpu.user{*} by {env,host}"
+        );
+        assert_eq!(
+            with_group_by(
+                "sum:trace.http.request.hits{*} by { service }.as_count()",
+                &tags
+            ),
+            "sum:trace.http.request.hits{*} by {service,host,env}.as_count()"
+        );
+    }
+
+    #[test]
+    fn test_validate_formula_references() {
+        let request = TimeseriesRequest {
+            queries: vec![query("errors"), query("hits")],
+            formulas: vec!["errors / hits * 100".into(), "abs(errors - 1.5)".into()],
+            interval_ms: None,
+        };
+        assert!(request.validate().is_ok());
+
+        let request = TimeseriesRequest {
+            formulas: vec!["errors / total".into()],
+            ..request
+        };
+        let error = request.validate().unwrap_err();
+        assert!(error.contains("unknown query 'total'"));
+    }
+
+    #[test]
+    fn test_validate_duplicate_names() {
+        let request = TimeseriesRequest {
+            queries: vec![query("a"), query("a")],
+            formulas: vec![],
+            interval_ms: None,
+        };
+        assert!(request.validate().is_err());
+    }
+
+    #[test]
+    fn test_labels() {
+        let request = TimeseriesRequest {
+            queries: vec![query("a"), query("b")],
+            formulas: vec![],
+            interval_ms: None,
+        };
+        assert_eq!(request.labels(), vec!["a", "b"]);
+
+        let request = TimeseriesRequest {
+            formulas: vec!["a / b".into()],
+            ..request
+        };
+        assert_eq!(request.labels(), vec!["a / b"]);
+    }
+}
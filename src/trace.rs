@@ -0,0 +1,392 @@
+//! Trace reconstruction from flat APM spans.
+//!
+//! The spans search API returns spans as a flat list. This module rebuilds the
+//! parent/child hierarchy from `span_id`/`parent_id`, computes self-time, and
+//! renders the result as an indented waterfall.
+//!
+//! Spans whose parent is not part of the fetched set (e.g. because the parent
+//! fell outside the time range or was not retained) are kept as orphans and
+//! flagged, rather than dropped.
+
+use datadog_api_client::datadogV2::model::Span;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+/// A span reduced to the fields needed to build a trace tree.
+#[derive(Debug, Clone)]
+pub struct TraceSpan {
+    pub span_id: String,
+    /// Parent span ID; `None` for root spans
+    pub parent_id: Option<String>,
+    pub service: String,
+    pub resource: String,
+    /// Start time in Unix nanoseconds
+    pub start_ns: i64,
+    /// Duration in nanoseconds
+    pub duration_ns: i64,
+    /// The original span as JSON, used for NDJSON output
+    pub raw: Value,
+}
+
+impl TraceSpan {
+    /// Extracts trace fields from an API span.
+    ///
+    /// Returns `None` for spans without a span ID, which cannot be placed in a tree.
+    pub fn from_span(span: &Span) -> Option<Self> {
+        let attributes = span.attributes.as_ref()?;
+        let span_id = attributes.span_id.clone()?;
+
+        // Datadog uses "0" (or no value) as the parent ID of root spans
+        let parent_id = attributes
+            .parent_id
+            .clone()
+            .filter(|id| !id.is_empty() && id != "0");
+
+        let start_ns = attributes
+            .start_timestamp
+            .and_then(|t| t.timestamp_nanos_opt())
+            .unwrap_or_default();
+        let end_ns = attributes
+            .end_timestamp
+            .and_then(|t| t.timestamp_nanos_opt())
+            .unwrap_or(start_ns);
+
+        // Prefer the exact @duration measure (nanoseconds) over timestamp arithmetic
+        let duration_ns = attributes
+            .custom
+            .as_ref()
+            .and_then(|custom| custom.get("duration"))
+            .and_then(Value::as_f64)
+            .map(|d| d as i64)
+            .unwrap_or(end_ns - start_ns);
+
+        Some(TraceSpan {
+            span_id,
+            parent_id,
+            service: attributes.service.clone().unwrap_or_default(),
+            resource: attributes.resource_name.clone().unwrap_or_default(),
+            start_ns,
+            duration_ns,
+            raw: serde_json::to_value(span).unwrap_or(Value::Null),
+        })
+    }
+}
+
+/// A span placed in the trace tree.
+#[derive(Debug, Clone)]
+pub struct TraceNode {
+    pub span: TraceSpan,
+    /// Nesting level; roots and orphans are at depth 0
+    pub depth: usize,
+    pub children_count: usize,
+    /// Duration minus the time covered by direct children, in nanoseconds
+    pub self_time_ns: i64,
+    /// Whether the span references a parent that was not found
+    pub orphan: bool,
+    /// Whether this is the last child of its parent (used for tree drawing)
+    pub last_child: bool,
+}
+
+/// A reconstructed trace in depth-first order.
+#[derive(Debug, Clone, Default)]
+pub struct Trace {
+    pub nodes: Vec<TraceNode>,
+}
+
+impl Trace {
+    /// Number of spans whose parent was not found.
+    pub fn orphan_count(&self) -> usize {
+        self.nodes.iter().filter(|n| n.orphan).count()
+    }
+
+    /// Earliest span start in Unix nanoseconds.
+    pub fn start_ns(&self) -> i64 {
+        self.nodes
+            .iter()
+            .map(|n| n.span.start_ns)
+            .min()
+            .unwrap_or_default()
+    }
+}
+
+/// Rebuilds the parent/child hierarchy from a flat list of spans.
+///
+/// Children are ordered by start time. Root spans come first, followed by
+/// orphaned subtrees. Duplicate span IDs are ignored after the first occurrence.
+pub fn build_trace(spans: Vec<TraceSpan>) -> Trace {
+    let mut by_id: HashMap<String, TraceSpan> = HashMap::new();
+    for span in spans {
+        by_id.entry(span.span_id.clone()).or_insert(span);
+    }
+
+    let mut children: HashMap<String, Vec<String>> = HashMap::new();
+    let mut roots = Vec::new();
+    let mut orphans = Vec::new();
+
+    for span in by_id.values() {
+        match &span.parent_id {
+            Some(parent) if by_id.contains_key(parent) => {
+                children
+                    .entry(parent.clone())
+                    .or_default()
+                    .push(span.span_id.clone());
+            }
+            Some(_) => orphans.push(span.span_id.clone()),
+            None => roots.push(span.span_id.clone()),
+        }
+    }
+
+    let sort_by_start = |ids: &mut Vec<String>| {
+        ids.sort_by_key(|id| (by_id[id].start_ns, id.clone()));
+    };
+    sort_by_start(&mut roots);
+    sort_by_start(&mut orphans);
+    for ids in children.values_mut() {
+        sort_by_start(ids);
+    }
+
+    let mut trace = Trace::default();
+    let mut visited = HashSet::new();
+
+    for (top_level, orphan) in [(roots, false), (orphans, true)] {
+        let count = top_level.len();
+        for (index, id) in top_level.into_iter().enumerate() {
+            visit(
+                &id,
+                0,
+                orphan,
+                index + 1 == count,
+                &by_id,
+                &children,
+                &mut visited,
+                &mut trace,
+            );
+        }
+    }
+
+    // Spans that are part of a parent cycle are never reached from a root
+    let mut unreached: Vec<&String> = by_id.keys().filter(|id| !visited.contains(*id)).collect();
+    unreached.sort();
+    let count = unreached.len();
+    for (index, id) in unreached.into_iter().enumerate() {
+        visit(
+            id,
+            0,
+            true,
+            index + 1 == count,
+            &by_id,
+            &children,
+            &mut visited,
+            &mut trace,
+        );
+    }
+
+    trace
+}
+
+#[allow(clippy::too_many_arguments)]
+fn visit(
+    id: &str,
+    depth: usize,
+    orphan: bool,
+    last_child: bool,
+    by_id: &HashMap<String, TraceSpan>,
+    children: &HashMap<String, Vec<String>>,
+    visited: &mut HashSet<String>,
+    trace: &mut Trace,
+) {
+    if !visited.insert(id.to_string()) {
+        return;
+    }
+
+    let span = by_id[id].clone();
+    let child_ids = children.get(id).cloned().unwrap_or_default();
+    let children_ns: i64 = child_ids.iter().map(|c| by_id[c].duration_ns).sum();
+
+    trace.nodes.push(TraceNode {
+        self_time_ns: (span.duration_ns - children_ns).max(0),
+        span,
+        depth,
+        children_count: child_ids.len(),
+        orphan,
+        last_child,
+    });
+
+    let count = child_ids.len();
+    for (index, child) in child_ids.iter().enumerate() {
+        visit(
+            child,
+            depth + 1,
+            false,
+            index + 1 == count,
+            by_id,
+            children,
+            visited,
+            trace,
+        );
+    }
+}
+
+/// Renders the trace as an indented waterfall, one line per span.
+///
+/// Each line shows the offset from the trace start, total duration, self-time,
+/// service and resource. Orphaned subtrees are listed under a separate heading.
+pub fn render_waterfall(trace: &Trace) -> Vec<String> {
+    let trace_start = trace.start_ns();
+    let mut lines = Vec::new();
+    let mut orphan_header_written = false;
+
+    // Tracks, per depth, whether the ancestor at that depth still has siblings below
+    let mut open_levels: Vec<bool> = Vec::new();
+
+    for node in &trace.nodes {
+        if node.orphan && node.depth == 0 && !orphan_header_written {
+            lines.push(String::new());
+            lines.push("Orphaned spans (parent not found):".to_string());
+            orphan_header_written = true;
+        }
+
+        open_levels.truncate(node.depth);
+        let mut prefix = String::new();
+        if node.depth > 0 {
+            for open in &open_levels[1..] {
+                prefix.push_str(if *open { "│  " } else { "   " });
+            }
+            prefix.push_str(if node.last_child {
+                "└─ "
+            } else {
+                "├─ "
+            });
+        }
+        open_levels.push(!node.last_child);
+
+        lines.push(format!(
+            "+{:>10} {:>10}  self {:>10}  {}{}  {}",
+            format_duration(node.span.start_ns - trace_start),
+            format_duration(node.span.duration_ns),
+            format_duration(node.self_time_ns),
+            prefix,
+            node.span.service,
+            node.span.resource,
+        ));
+    }
+
+    lines
+}
+
+/// Formats a nanosecond duration with a unit suited to its magnitude.
+pub fn format_duration(ns: i64) -> String {
+    let abs = ns.unsigned_abs();
+    if abs >= 1_000_000_000 {
+        format!("{:.2}s", ns as f64 / 1e9)
+    } else if abs >= 1_000_000 {
+        format!("{:.2}ms", ns as f64 / 1e6)
+    } else if abs >= 1_000 {
+        format!("{:.2}µs", ns as f64 / 1e3)
+    } else {
+        format!("{}ns", ns)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(id: &str, parent: Option<&str>, start_ns: i64, duration_ns: i64) -> TraceSpan {
+        TraceSpan {
+            span_id: id.to_string(),
+            parent_id: parent.map(str::to_string),
+            service: "svc".to_string(),
+            resource: format!("res-{}", id),
+            start_ns,
+            duration_ns,
+            raw: Value::Null,
+        }
+    }
+
+    #[test]
+    fn test_build_trace_hierarchy() {
+        let trace = build_trace(vec![
+            span("c2", Some("root"), 50, 20),
+            span("root", None, 0, 100),
+            span("c1", Some("root"), 10, 30),
+            span("gc", Some("c1"), 15, 10),
+        ]);
+
+        let order: Vec<&str> = trace
+            .nodes
+            .iter()
+            .map(|n| n.span.span_id.as_str())
+            .collect();
+        assert_eq!(order, vec!["root", "c1", "gc", "c2"]);
+
+        let root = &trace.nodes[0];
+        assert_eq!(root.depth, 0);
+        assert_eq!(root.children_count, 2);
+        assert_eq!(root.self_time_ns, 50);
+
+        assert_eq!(trace.nodes[2].depth, 2);
+        assert_eq!(trace.orphan_count(), 0);
+    }
+
+    #[test]
+    fn test_build_trace_reports_orphans() {
+        let trace = build_trace(vec![
+            span("root", None, 0, 100),
+            span("lost", Some("missing"), 20, 10),
+        ]);
+
+        assert_eq!(trace.nodes.len(), 2);
+        assert_eq!(trace.orphan_count(), 1);
+        let lost = trace
+            .nodes
+            .iter()
+            .find(|n| n.span.span_id == "lost")
+            .unwrap();
+        assert!(lost.orphan);
+        assert_eq!(lost.depth, 0);
+    }
+
+    #[test]
+    fn test_build_trace_survives_parent_cycle() {
+        let trace = build_trace(vec![
+            span("a", Some("b"), 0, 10),
+            span("b", Some("a"), 5, 10),
+        ]);
+        assert_eq!(trace.nodes.len(), 2);
+    }
+
+    #[test]
+    fn test_self_time_never_negative() {
+        // Async children can outlive their parent
+        let trace = build_trace(vec![
+            span("root", None, 0, 10),
+            span("child", Some("root"), 0, 50),
+        ]);
+        assert_eq!(trace.nodes[0].self_time_ns, 0);
+    }
+
+    #[test]
+    fn test_render_waterfall() {
+        let trace = build_trace(vec![
+            span("root", None, 0, 2_000_000),
+            span("child", Some("root"), 500_000, 1_000_000),
+            span("lost", Some("missing"), 0, 1_000),
+        ]);
+
+        let lines = render_waterfall(&trace);
+        assert!(lines[0].contains("res-root"));
+        assert!(lines[1].contains("└─ svc"));
+        assert!(lines[1].contains("+  500.00µs"));
+        assert!(lines.iter().any(|l| l.contains("Orphaned spans")));
+        assert!(lines.last().unwrap().contains("res-lost"));
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(999), "999ns");
+        assert_eq!(format_duration(1_500), "1.50µs");
+        assert_eq!(format_duration(2_500_000), "2.50ms");
+        assert_eq!(format_duration(3_000_000_000), "3.00s");
+    }
+}
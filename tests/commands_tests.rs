@@ -0,0 +1,2916 @@
+//! Offline end-to-end tests for ddog commands.
+//!
+//! Each test starts a local mock of the Datadog API (see `support`) and runs
+//! the `ddog` binary against it via `DDOG_API_URL`, so no credentials or
+//! network access are needed.
+//!
+//! Run with: cargo test --test commands_tests
+
+mod support;
+
+use serde_json::{Value, json};
+use support::{MockResponse, MockServer, events_page, ids, logs_page, run, spans_page};
+
+const LOGS_SEARCH: &str = "/api/v2/logs/events/search";
+const LOGS_AGGREGATE: &str = "/api/v2/logs/analytics/aggregate";
+const SPANS_SEARCH: &str = "/api/v2/spans/events/search";
+const SPANS_AGGREGATE: &str = "/api/v2/spans/analytics/aggregate";
+const METRICS_QUERY: &str = "/api/v1/query";
+const METRICS_LIST: &str = "/api/v1/metrics";
+const METRICS_TIMESERIES: &str = "/api/v2/query/timeseries";
+const METRICS_SCALAR: &str = "/api/v2/query/scalar";
+const EVENTS_SEARCH: &str = "/api/v2/events/search";
+const EVENTS: &str = "/api/v1/events";
+const MONITORS: &str = "/api/v1/monitor";
+const SLO: &str = "/api/v1/slo";
+const SLO_HISTORY: &str = "/api/v1/slo/abc123/history";
+const MONITORS_SEARCH: &str = "/api/v1/monitor/search";
+const DOWNTIMES: &str = "/api/v2/downtime";
+const DASHBOARDS: &str = "/api/v1/dashboard";
+
+/// Parses the single JSON error report written with `--error-format json`.
+fn error_report(stderr: &str) -> Value {
+    serde_json::from_str(stderr.trim()).expect("stderr is not a JSON error report")
+}
+
+#[test]
+fn test_logs_search_streams_ndjson() {
+    let server = MockServer::start();
+    server.respond(
+        "POST",
+        LOGS_SEARCH,
+        MockResponse::json(200, logs_page(&ids(0, 3), None)),
+    );
+
+    let output = run(server.ddog(), &["logs", "search", "status:error"]);
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+
+    let records = output.records();
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[0]["id"], "log-0");
+    assert_eq!(records[2]["attributes"]["message"], "log log-2");
+
+    let requests = server.requests_to(LOGS_SEARCH);
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].header("DD-API-KEY"), Some("test-api-key"));
+    assert_eq!(
+        requests[0].header("DD-APPLICATION-KEY"),
+        Some("test-app-key")
+    );
+
+    let body = requests[0].json();
+    assert_eq!(body["filter"]["query"], "status:error");
+    assert_eq!(body["filter"]["indexes"], json!(["*"]));
+}
+
+#[test]
+fn test_logs_search_follows_cursor_across_pages() {
+    let server = MockServer::start();
+    server
+        .respond(
+            "POST",
+            LOGS_SEARCH,
+            MockResponse::json(200, logs_page(&ids(0, 1000), Some("cursor-1"))),
+        )
+        .respond(
+            "POST",
+            LOGS_SEARCH,
+            MockResponse::json(200, logs_page(&ids(1000, 5), None)),
+        );
+
+    let output = run(server.ddog(), &["logs", "search", "*", "--limit", "0"]);
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+    assert_eq!(output.records().len(), 1005);
+
+    let requests = server.requests_to(LOGS_SEARCH);
+    assert_eq!(requests.len(), 2);
+    assert!(requests[0].json()["page"].get("cursor").is_none());
+    assert_eq!(requests[1].json()["page"]["cursor"], "cursor-1");
+}
+
+#[test]
+fn test_logs_search_stops_at_limit() {
+    let server = MockServer::start();
+    server.respond(
+        "POST",
+        LOGS_SEARCH,
+        MockResponse::json(200, logs_page(&ids(0, 10), None)),
+    );
+
+    let output = run(server.ddog(), &["logs", "search", "*", "--limit", "4"]);
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+    assert_eq!(output.records().len(), 4);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_logs_follow_skips_logs_seen_in_overlapping_polls() {
+    // Logs without an ID are told apart by timestamp and message
+    let timestamp = chrono::Utc::now().to_rfc3339();
+    let page = |ids: &[String]| {
+        let mut page = logs_page(ids, None);
+        let logs = page["data"].as_array_mut().unwrap();
+        logs.push(json!({"type": "log", "attributes": {"message": "no id"}}));
+        for log in logs {
+            log["attributes"]["timestamp"] = json!(timestamp);
+        }
+        page
+    };
+    let server = MockServer::start();
+    server
+        .respond(
+            "POST",
+            LOGS_SEARCH,
+            MockResponse::json(200, page(&ids(0, 2))),
+        )
+        .respond(
+            "POST",
+            LOGS_SEARCH,
+            MockResponse::json(200, page(&ids(1, 2))),
+        );
+
+    let output = support::run_until_interrupted(
+        server.ddog(),
+        &[
+            "logs",
+            "search",
+            "*",
+            "--follow",
+            "--poll-interval",
+            "1",
+            "--follow-lag",
+            "60",
+        ],
+        // The third poll starts once the second one's logs are written
+        || server.requests_to(LOGS_SEARCH).len() >= 3,
+    );
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+
+    let written: Vec<Value> = output
+        .records()
+        .iter()
+        .map(|r| r.get("id").cloned().unwrap_or(Value::Null))
+        .collect();
+    assert_eq!(
+        written,
+        vec![json!("log-0"), json!("log-1"), Value::Null, json!("log-2")]
+    );
+
+    // The second poll reaches back the follow lag before the first one started
+    let requests = server.requests_to(LOGS_SEARCH);
+    let bound = |i: usize, key: &str| -> i64 {
+        requests[i].json()["filter"][key]
+            .as_str()
+            .unwrap()
+            .parse()
+            .unwrap()
+    };
+    let reach_back = bound(0, "to") - bound(1, "from");
+    assert!((60_000..65_000).contains(&reach_back), "{}", reach_back);
+}
+
+#[test]
+fn test_logs_search_uses_requested_indexes() {
+    let server = MockServer::start();
+    server.respond(
+        "POST",
+        LOGS_SEARCH,
+        MockResponse::json(200, logs_page(&[], None)),
+    );
+
+    let output = run(
+        server.ddog(),
+        &["logs", "search", "*", "--indexes", "main,audit"],
+    );
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+    assert!(output.stdout.is_empty());
+
+    let body = server.requests_to(LOGS_SEARCH)[0].json();
+    assert_eq!(body["filter"]["indexes"], json!(["main", "audit"]));
+}
+
+#[test]
+fn test_logs_search_sends_resolved_epoch_millis() {
+    let server = MockServer::start();
+    server.respond(
+        "POST",
+        LOGS_SEARCH,
+        MockResponse::json(200, logs_page(&[], None)),
+    );
+
+    let output = run(
+        server.ddog(),
+        &[
+            "logs",
+            "search",
+            "*",
+            "--from",
+            "2024-01-15",
+            "--to",
+            "now-1h",
+        ],
+    );
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+
+    let body = server.requests_to(LOGS_SEARCH)[0].json();
+    assert_eq!(body["filter"]["from"], "1705276800000");
+    let to = body["filter"]["to"].as_str().unwrap();
+    assert!(to.parse::<i64>().is_ok(), "to was not resolved: {}", to);
+}
+
+#[test]
+fn test_relative_range_resolves_against_one_now() {
+    let server = MockServer::start();
+    server.respond(
+        "POST",
+        LOGS_SEARCH,
+        MockResponse::json(200, logs_page(&[], None)),
+    );
+
+    let output = run(
+        server.ddog(),
+        &["logs", "search", "*", "--from", "now-1h", "--to", "now"],
+    );
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+
+    let body = server.requests_to(LOGS_SEARCH)[0].json();
+    let millis = |field: &str| {
+        body["filter"][field]
+            .as_str()
+            .unwrap()
+            .parse::<i64>()
+            .unwrap()
+    };
+    assert_eq!(millis("to") - millis("from"), 3_600_000);
+}
+
+#[test]
+fn test_inverted_time_range_is_an_invalid_query() {
+    let server = MockServer::start();
+
+    let output = run(
+        server.ddog(),
+        &["spans", "search", "*", "--from", "now", "--to", "now-1h"],
+    );
+    assert_eq!(output.code, 4, "stderr: {}", output.stderr);
+    assert!(output.stderr.contains("End time 'now-1h'"));
+    assert!(server.requests().is_empty());
+}
+
+#[test]
+fn test_future_start_is_rejected_for_every_domain() {
+    let server = MockServer::start();
+
+    for args in [
+        &[
+            "logs",
+            "aggregate",
+            "*",
+            "--from",
+            "now+1h",
+            "--to",
+            "now+2h",
+        ][..],
+        &[
+            "spans", "trace", "abc", "--from", "now+1h", "--to", "now+2h",
+        ],
+        &[
+            "metrics",
+            "scalar",
+            "avg:cpu{*}",
+            "--from",
+            "now+1h",
+            "--to",
+            "now+2h",
+        ],
+        &["metrics", "list", "--from", "now+1d"],
+    ] {
+        let output = run(server.ddog(), args);
+        assert_eq!(output.code, 4, "{:?} stderr: {}", args, output.stderr);
+        assert!(
+            output.stderr.contains("is in the future"),
+            "{}",
+            output.stderr
+        );
+    }
+    assert!(server.requests().is_empty());
+}
+
+#[test]
+fn test_range_beyond_retention_warns_but_runs() {
+    let server = MockServer::start();
+    server.respond(
+        "POST",
+        LOGS_SEARCH,
+        MockResponse::json(200, logs_page(&[], None)),
+    );
+
+    let output = run(server.ddog(), &["logs", "search", "*", "--last", "20d"]);
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+    assert!(
+        output.stderr.contains("beyond the 15-day logs retention"),
+        "stderr: {}",
+        output.stderr
+    );
+    assert_eq!(server.requests_to(LOGS_SEARCH).len(), 1);
+
+    let output = run(server.ddog(), &["logs", "search", "*", "--last", "14d"]);
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+    assert!(
+        !output.stderr.contains("Warning"),
+        "stderr: {}",
+        output.stderr
+    );
+}
+
+/// Runs a logs search with the given time arguments and returns the requested from/to.
+fn logs_search_window(args: &[&str]) -> (i64, i64) {
+    let server = MockServer::start();
+    server.respond(
+        "POST",
+        LOGS_SEARCH,
+        MockResponse::json(200, logs_page(&[], None)),
+    );
+
+    let mut full = vec!["logs", "search", "*"];
+    full.extend_from_slice(args);
+    let output = run(server.ddog(), &full);
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+
+    let body = server.requests_to(LOGS_SEARCH)[0].json();
+    let millis = |field: &str| {
+        body["filter"][field]
+            .as_str()
+            .unwrap()
+            .parse::<i64>()
+            .unwrap()
+    };
+    (millis("from"), millis("to"))
+}
+
+#[test]
+fn test_around_spans_window_on_both_sides() {
+    let window = logs_search_window(&["--around", "2024-01-15T10:00:00Z", "--window", "5m"]);
+    assert_eq!(window, (1705312500000, 1705313100000));
+}
+
+#[test]
+fn test_last_ends_at_to() {
+    let (from, to) = logs_search_window(&["--last", "2h", "--to", "2024-01-15T12:00:00Z"]);
+    assert_eq!((from, to), (1705312800000, 1705320000000));
+
+    let (from, to) = logs_search_window(&["--last", "30m"]);
+    assert_eq!(to - from, 1_800_000);
+}
+
+#[test]
+fn test_since_day_name_starts_at_midnight() {
+    let (from, to) = logs_search_window(&["--since", "yesterday"]);
+    assert_eq!(from % 86_400_000, 0);
+    assert!(to - from >= 86_400_000 && to - from < 2 * 86_400_000);
+}
+
+#[test]
+fn test_tz_reads_wall_clock_times() {
+    let window = logs_search_window(&[
+        "--from",
+        "2024-06-01 09:00",
+        "--to",
+        "2024-06-01 10:00",
+        "--tz",
+        "Europe/Paris",
+    ]);
+    assert_eq!(window, (1717225200000, 1717228800000));
+}
+
+#[test]
+fn test_verbose_logs_resolved_bounds() {
+    let server = MockServer::start();
+    server.respond(
+        "POST",
+        LOGS_SEARCH,
+        MockResponse::json(200, logs_page(&[], None)),
+    );
+
+    let output = run(
+        server.ddog(),
+        &[
+            "logs",
+            "search",
+            "*",
+            "--around",
+            "2024-01-15T10:00:00Z",
+            "--verbose",
+        ],
+    );
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+    assert!(
+        output
+            .stderr
+            .contains("2024-01-15T09:45:00.000Z to 2024-01-15T10:15:00.000Z"),
+        "stderr: {}",
+        output.stderr
+    );
+}
+
+#[test]
+fn test_around_conflicts_with_from() {
+    let server = MockServer::start();
+
+    let output = run(
+        server.ddog(),
+        &[
+            "logs", "search", "*", "--around", "14:32", "--from", "now-1h",
+        ],
+    );
+    assert_ne!(output.code, 0);
+    assert!(output.stderr.contains("cannot be used with"));
+    assert!(server.requests().is_empty());
+}
+
+#[test]
+fn test_invalid_time_is_rejected_before_any_request() {
+    let server = MockServer::start();
+
+    let output = run(
+        server.ddog(),
+        &["logs", "search", "*", "--from", "last-tuesday"],
+    );
+    assert_ne!(output.code, 0);
+    assert!(output.stderr.contains("'last-tuesday' not supported"));
+    assert!(server.requests().is_empty());
+}
+
+#[test]
+fn test_logs_aggregate_flattens_buckets() {
+    let server = MockServer::start();
+    server.respond(
+        "POST",
+        LOGS_AGGREGATE,
+        MockResponse::json(
+            200,
+            json!({
+                "data": {
+                    "buckets": [
+                        { "by": { "service": "api" }, "computes": { "c0": 12 } },
+                        { "by": { "service": "web" }, "computes": { "c0": 3 } }
+                    ]
+                },
+                "meta": { "status": "done" }
+            }),
+        ),
+    );
+
+    let output = run(
+        server.ddog(),
+        &["logs", "aggregate", "status:error", "--group-by", "service"],
+    );
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+
+    let records = output.records();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0]["service"], "api");
+    assert_eq!(records[0]["count"], 12.0);
+    assert_eq!(records[1]["service"], "web");
+
+    let body = server.requests_to(LOGS_AGGREGATE)[0].json();
+    assert_eq!(body["group_by"][0]["facet"], "service");
+    assert_eq!(body["compute"][0]["aggregation"], "count");
+}
+
+#[test]
+fn test_spans_search_streams_ndjson() {
+    let server = MockServer::start();
+    server.respond(
+        "POST",
+        SPANS_SEARCH,
+        MockResponse::json(200, spans_page(&[("s1", "0", "GET /users")], None)),
+    );
+
+    let output = run(server.ddog(), &["spans", "search", "service:api"]);
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+
+    let records = output.records();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0]["attributes"]["span_id"], "s1");
+
+    let body = server.requests_to(SPANS_SEARCH)[0].json();
+    assert_eq!(body["data"]["attributes"]["filter"]["query"], "service:api");
+}
+
+#[test]
+fn test_parallel_search_covers_range_in_contiguous_slices() {
+    let server = MockServer::start();
+    server.respond(
+        "POST",
+        LOGS_SEARCH,
+        MockResponse::json(200, logs_page(&ids(0, 2), None)),
+    );
+
+    let output = run(
+        server.ddog(),
+        &[
+            "logs",
+            "search",
+            "*",
+            "--from",
+            "2024-01-14T00:00:00Z",
+            "--to",
+            "2024-01-15T00:00:00Z",
+            "--parallel",
+            "4",
+            "--limit",
+            "0",
+        ],
+    );
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+
+    let mut windows: Vec<(i64, i64)> = server
+        .requests_to(LOGS_SEARCH)
+        .iter()
+        .map(|request| {
+            let body = request.json();
+            let millis = |field: &str| body["filter"][field].as_str().unwrap().parse().unwrap();
+            (millis("from"), millis("to"))
+        })
+        .collect();
+    windows.sort();
+    assert_eq!(windows.len(), 16);
+    assert_eq!(windows[0].0, 1705190400000);
+    assert_eq!(windows[15].1, 1705276800000);
+    assert!(windows.windows(2).all(|pair| pair[0].1 == pair[1].0));
+
+    // Every slice answered with the same two logs
+    assert_eq!(output.records().len(), 32);
+}
+
+#[test]
+fn test_parallel_search_respects_limit() {
+    let server = MockServer::start();
+    server.respond(
+        "POST",
+        SPANS_SEARCH,
+        MockResponse::json(200, spans_page(&[("s1", "0", "a"), ("s2", "0", "b")], None)),
+    );
+
+    let output = run(
+        server.ddog(),
+        &["spans", "search", "*", "--parallel", "3", "--limit", "5"],
+    );
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+    assert_eq!(output.records().len(), 5);
+}
+
+#[test]
+fn test_parallel_search_emits_boundary_records_once() {
+    let server = MockServer::start();
+    // Both slices return the logs stamped exactly on their shared boundary
+    server.respond(
+        "POST",
+        LOGS_SEARCH,
+        MockResponse::json(200, logs_page(&ids(0, 3), None)),
+    );
+
+    let output = run(
+        server.ddog(),
+        &[
+            "logs",
+            "search",
+            "*",
+            "--from",
+            "2024-01-15T09:59:00Z",
+            "--to",
+            "2024-01-15T10:01:00Z",
+            "--parallel",
+            "2",
+            "--unordered",
+        ],
+    );
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+    assert_eq!(server.requests_to(LOGS_SEARCH).len(), 2);
+    assert_eq!(output.records().len(), 3);
+}
+
+#[test]
+fn test_unordered_requires_parallel() {
+    let server = MockServer::start();
+
+    let output = run(server.ddog(), &["logs", "search", "*", "--unordered"]);
+    assert_ne!(output.code, 0);
+    let output = run(
+        server.ddog(),
+        &["logs", "search", "*", "--follow", "--parallel", "2"],
+    );
+    assert_ne!(output.code, 0);
+    assert!(output.stderr.contains("cannot be used with"));
+    assert!(server.requests().is_empty());
+}
+
+#[test]
+fn test_spans_aggregate_flattens_buckets() {
+    let server = MockServer::start();
+    server.respond(
+        "POST",
+        SPANS_AGGREGATE,
+        MockResponse::json(
+            200,
+            json!({
+                "data": [{
+                    "id": "bucket-1",
+                    "type": "bucket",
+                    "attributes": {
+                        "by": { "resource_name": "GET /users" },
+                        "computes": { "c0": 250000000.0 }
+                    }
+                }],
+                "meta": { "status": "done" }
+            }),
+        ),
+    );
+
+    let output = run(
+        server.ddog(),
+        &[
+            "spans",
+            "aggregate",
+            "service:api",
+            "--group-by",
+            "resource_name",
+            "--compute",
+            "p99:@duration",
+        ],
+    );
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+
+    let records = output.records();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0]["resource_name"], "GET /users");
+    assert_eq!(records[0]["p99_duration"], 250000000.0);
+}
+
+#[test]
+fn test_spans_aggregate_error_rate_divides_error_counts() {
+    let bucket = |resource: &str, computes: Value| {
+        json!({
+            "type": "bucket",
+            "attributes": {"by": {"resource_name": resource}, "computes": computes}
+        })
+    };
+    let server = MockServer::start();
+    server
+        .respond(
+            "POST",
+            SPANS_AGGREGATE,
+            MockResponse::json(
+                200,
+                json!({"data": [
+                    bucket("/users", json!({"c0": 200, "c1": 200})),
+                    bucket("/health", json!({"c0": 50, "c1": 50}))
+                ]}),
+            ),
+        )
+        .respond(
+            "POST",
+            SPANS_AGGREGATE,
+            MockResponse::json(200, json!({"data": [bucket("/users", json!({"c0": 10}))]})),
+        );
+
+    let output = run(
+        server.ddog(),
+        &[
+            "spans",
+            "aggregate",
+            "service:api",
+            "--group-by",
+            "resource_name",
+            "--compute",
+            "count,error_rate",
+        ],
+    );
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+    assert_eq!(
+        output.records(),
+        vec![
+            json!({"resource_name": "/users", "count": 200.0, "error_rate": 0.05}),
+            json!({"resource_name": "/health", "count": 50.0, "error_rate": 0.0}),
+        ]
+    );
+
+    let requests = server.requests_to(SPANS_AGGREGATE);
+    assert_eq!(requests.len(), 2);
+    let errors = requests[1].json();
+    assert_eq!(
+        errors["data"]["attributes"]["filter"]["query"],
+        "(service:api) status:error"
+    );
+    assert_eq!(
+        errors["data"]["attributes"]["compute"],
+        json!([{"aggregation": "count"}])
+    );
+}
+
+#[test]
+fn test_spans_trace_renders_waterfall() {
+    let server = MockServer::start();
+    server.respond(
+        "POST",
+        SPANS_SEARCH,
+        MockResponse::json(
+            200,
+            spans_page(
+                &[
+                    ("root", "0", "GET /checkout"),
+                    ("child", "root", "SELECT orders"),
+                ],
+                None,
+            ),
+        ),
+    );
+
+    let output = run(server.ddog(), &["spans", "trace", "t1"]);
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+    assert!(output.stdout.contains("GET /checkout"));
+    assert!(output.stdout.contains("SELECT orders"));
+
+    let body = server.requests_to(SPANS_SEARCH)[0].json();
+    assert_eq!(body["data"]["attributes"]["filter"]["query"], "trace_id:t1");
+}
+
+#[test]
+fn test_spans_trace_ndjson_annotates_depth() {
+    let server = MockServer::start();
+    server.respond(
+        "POST",
+        SPANS_SEARCH,
+        MockResponse::json(
+            200,
+            spans_page(
+                &[
+                    ("root", "0", "GET /checkout"),
+                    ("child", "root", "SELECT orders"),
+                ],
+                None,
+            ),
+        ),
+    );
+
+    let output = run(server.ddog(), &["spans", "trace", "t1", "--ndjson"]);
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+
+    let records = output.records();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0]["depth"], 0);
+    assert_eq!(records[0]["children_count"], 1);
+    assert_eq!(records[1]["depth"], 1);
+}
+
+#[test]
+fn test_spans_trace_waterfall_refuses_out_file() {
+    let server = MockServer::start();
+    let path = support::temp_path("trace-waterfall.ndjson");
+
+    let output = run(
+        server.ddog(),
+        &["spans", "trace", "t1", "--out", path.to_str().unwrap()],
+    );
+    assert_eq!(output.code, 5, "stderr: {}", output.stderr);
+    assert!(output.stderr.contains("--ndjson"));
+    assert!(server.requests_to(SPANS_SEARCH).is_empty());
+    assert!(!path.exists());
+}
+
+#[test]
+fn test_metrics_query_flattens_points() {
+    let server = MockServer::start();
+    server.respond(
+        "GET",
+        METRICS_QUERY,
+        MockResponse::json(
+            200,
+            json!({
+                "status": "ok",
+                "series": [{
+                    "metric": "system.cpu.user",
+                    "scope": "*",
+                    "tag_set": [],
+                    "pointlist": [[1705315200000.0, 1.5], [1705315260000.0, 2.5]]
+                }]
+            }),
+        ),
+    );
+
+    let output = run(
+        server.ddog(),
+        &[
+            "metrics",
+            "query",
+            "avg:system.cpu.user{*}",
+            "--from",
+            "now-1h",
+        ],
+    );
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+
+    let records = output.records();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0]["metric"], "system.cpu.user");
+    assert_eq!(records[0]["timestamp"], 1705315200);
+    assert_eq!(records[1]["value"], 2.5);
+
+    let request = &server.requests_to(METRICS_QUERY)[0];
+    assert!(request.query.contains("query=avg%3Asystem.cpu.user"));
+}
+
+#[test]
+fn test_metrics_timeseries_labels_points_by_formula() {
+    let server = MockServer::start();
+    server.respond(
+        "POST",
+        METRICS_TIMESERIES,
+        MockResponse::json(
+            200,
+            json!({
+                "data": {
+                    "type": "timeseries_response",
+                    "attributes": {
+                        "series": [
+                            {
+                                "group_tags": ["service:api"],
+                                "query_index": 0,
+                                "unit": [{ "name": "percent", "short_name": "%" }, null]
+                            },
+                            { "group_tags": ["service:web"], "query_index": 0 }
+                        ],
+                        "times": [1705315200000i64, 1705315260000i64],
+                        "values": [[1.5, null], [0.5, 2.0]]
+                    }
+                }
+            }),
+        ),
+    );
+
+    let output = run(
+        server.ddog(),
+        &[
+            "metrics",
+            "timeseries",
+            "--query",
+            "errors=sum:trace.http.request.errors{*} by {service}",
+            "--query",
+            "hits=sum:trace.http.request.hits{*} by {service}",
+            "--formula",
+            "errors / hits * 100",
+            "--interval",
+            "1m",
+        ],
+    );
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+
+    let records = output.records();
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[0]["formula"], "errors / hits * 100");
+    assert_eq!(records[0]["group_tags"], json!(["service:api"]));
+    assert_eq!(records[0]["unit"], "%");
+    assert_eq!(records[0]["timestamp"], 1705315200);
+    assert_eq!(records[0]["value"], 1.5);
+    assert_eq!(records[2]["group_tags"], json!(["service:web"]));
+    assert_eq!(records[2]["value"], 2.0);
+
+    let attributes = &server.requests_to(METRICS_TIMESERIES)[0].json()["data"]["attributes"];
+    assert_eq!(attributes["interval"], 60000);
+    assert_eq!(attributes["queries"][1]["name"], "hits");
+    assert_eq!(attributes["queries"][1]["data_source"], "metrics");
+    assert_eq!(attributes["formulas"][0]["formula"], "errors / hits * 100");
+}
+
+#[test]
+fn test_metrics_timeseries_rejects_unknown_formula_reference() {
+    let server = MockServer::start();
+
+    let output = run(
+        server.ddog(),
+        &[
+            "metrics",
+            "timeseries",
+            "-q",
+            "a=avg:system.cpu.user{*}",
+            "--formula",
+            "a / b",
+        ],
+    );
+    assert_eq!(output.code, 4);
+    assert!(output.stderr.contains("unknown query 'b'"));
+    assert!(server.requests().is_empty());
+}
+
+#[test]
+fn test_metrics_scalar_writes_one_row_per_group() {
+    let server = MockServer::start();
+    server.respond(
+        "POST",
+        METRICS_SCALAR,
+        MockResponse::json(
+            200,
+            json!({
+                "data": {
+                    "type": "scalar_response",
+                    "attributes": {
+                        "columns": [
+                            {
+                                "name": "host",
+                                "type": "group",
+                                "values": [["web-1"], ["web-2"]]
+                            },
+                            {
+                                "name": "a",
+                                "type": "number",
+                                "values": [12.5, 40.0],
+                                "meta": { "unit": [{ "name": "percent", "short_name": "%" }, null] }
+                            }
+                        ]
+                    }
+                }
+            }),
+        ),
+    );
+
+    let output = run(
+        server.ddog(),
+        &[
+            "metrics",
+            "scalar",
+            "avg:system.cpu.user{env:prod}",
+            "--aggregator",
+            "max",
+            "--group-by",
+            "host",
+        ],
+    );
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+    assert_eq!(
+        output.records(),
+        vec![
+            json!({ "host": "web-1", "value": 12.5, "unit": "%" }),
+            json!({ "host": "web-2", "value": 40.0, "unit": "%" })
+        ]
+    );
+
+    let query = &server.requests_to(METRICS_SCALAR)[0].json()["data"]["attributes"]["queries"][0];
+    assert_eq!(query["query"], "avg:system.cpu.user{env:prod} by {host}");
+    assert_eq!(query["aggregator"], "max");
+}
+
+#[test]
+fn test_metrics_query_accepts_iso8601() {
+    let server = MockServer::start();
+    server.respond(
+        "GET",
+        METRICS_QUERY,
+        MockResponse::json(200, json!({ "status": "ok", "series": [] })),
+    );
+
+    let output = run(
+        server.ddog(),
+        &[
+            "metrics",
+            "query",
+            "avg:system.cpu.user{*}",
+            "--from",
+            "2024-01-15T12:00:00+02:00",
+            "--to",
+            "2024-01-15T11:00:00Z",
+        ],
+    );
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+
+    let request = &server.requests_to(METRICS_QUERY)[0];
+    assert!(request.query.contains("from=1705312800"));
+    assert!(request.query.contains("to=1705316400"));
+}
+
+#[test]
+fn test_metrics_list_writes_names() {
+    let server = MockServer::start();
+    server.respond(
+        "GET",
+        METRICS_LIST,
+        MockResponse::json(
+            200,
+            json!({ "from": "1705315200", "metrics": ["system.cpu.user", "system.load.1"] }),
+        ),
+    );
+
+    let output = run(server.ddog(), &["metrics", "list", "--from", "now-1h"]);
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+    assert_eq!(
+        output.records(),
+        vec![
+            json!({ "metric": "system.cpu.user" }),
+            json!({ "metric": "system.load.1" })
+        ]
+    );
+}
+
+#[test]
+fn test_events_search_pages_and_stops_at_limit() {
+    let server = MockServer::start();
+    server
+        .respond(
+            "POST",
+            EVENTS_SEARCH,
+            MockResponse::json(200, events_page(&ids(0, 1000), Some("cursor-1"))),
+        )
+        .respond(
+            "POST",
+            EVENTS_SEARCH,
+            MockResponse::json(200, events_page(&ids(1000, 10), None)),
+        );
+
+    let output = run(
+        server.ddog(),
+        &[
+            "events",
+            "search",
+            "tags:deploy",
+            "--from",
+            "2024-01-15",
+            "--sort",
+            "desc",
+            "--limit",
+            "1005",
+        ],
+    );
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+    let records = output.records();
+    assert_eq!(records.len(), 1005);
+    assert_eq!(records[0]["attributes"]["message"], "event log-0");
+
+    let requests = server.requests_to(EVENTS_SEARCH);
+    assert_eq!(requests.len(), 2);
+    let body = requests[0].json();
+    assert_eq!(body["filter"]["query"], "tags:deploy");
+    assert_eq!(body["filter"]["from"], "1705276800000");
+    assert_eq!(body["sort"], "-timestamp");
+    assert_eq!(requests[1].json()["page"]["cursor"], "cursor-1");
+}
+
+#[test]
+fn test_events_post_sends_title_tags_and_alert_type() {
+    let server = MockServer::start();
+    server.respond(
+        "POST",
+        EVENTS,
+        MockResponse::json(
+            202,
+            json!({
+                "status": "ok",
+                "event": {
+                    "id": 12345,
+                    "title": "Deployed api v1.2.3",
+                    "url": "https://app.datadoghq.com/event/event?id=12345"
+                }
+            }),
+        ),
+    );
+
+    let output = run(
+        server.ddog(),
+        &[
+            "events",
+            "post",
+            "--title",
+            "Deployed api v1.2.3",
+            "--text",
+            "Commit abc123",
+            "--tags",
+            "service:api,deploy",
+            "--alert-type",
+            "success",
+        ],
+    );
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+    let records = output.records();
+    assert_eq!(records[0]["id"], 12345);
+    assert_eq!(
+        records[0]["url"],
+        "https://app.datadoghq.com/event/event?id=12345"
+    );
+
+    let body = server.requests_to(EVENTS)[0].json();
+    assert_eq!(body["title"], "Deployed api v1.2.3");
+    assert_eq!(body["text"], "Commit abc123");
+    assert_eq!(body["tags"], json!(["service:api", "deploy"]));
+    assert_eq!(body["alert_type"], "success");
+}
+
+#[test]
+fn test_events_post_does_not_retry() {
+    let server = MockServer::start();
+    server.respond(
+        "POST",
+        EVENTS,
+        MockResponse::error(500, "Internal Server Error"),
+    );
+
+    let output = run(
+        server.ddog(),
+        &[
+            "--max-retries",
+            "3",
+            "events",
+            "post",
+            "--title",
+            "Deploy",
+            "--text",
+            "",
+        ],
+    );
+    assert_eq!(output.code, 11, "stderr: {}", output.stderr);
+    assert_eq!(server.requests_to(EVENTS).len(), 1);
+}
+
+#[test]
+fn test_events_post_rejects_unknown_alert_type() {
+    let server = MockServer::start();
+
+    let output = run(
+        server.ddog(),
+        &[
+            "events",
+            "post",
+            "--title",
+            "Deploy",
+            "--text",
+            "",
+            "--alert-type",
+            "fatal",
+        ],
+    );
+    assert_eq!(output.code, 5, "stderr: {}", output.stderr);
+    assert!(output.stderr.contains("Unknown alert type 'fatal'"));
+    assert!(server.requests().is_empty());
+}
+
+/// An SLO history response with the overall SLI and optional groups.
+fn slo_history(sli_value: f64, groups: Value) -> Value {
+    json!({
+        "data": {
+            "from_ts": 1705276800,
+            "to_ts": 1705881600,
+            "overall": {"sli_value": sli_value, "error_budget_remaining": {"custom": 40.0}},
+            "groups": groups
+        }
+    })
+}
+
+#[test]
+fn test_slo_list_writes_slos() {
+    let server = MockServer::start();
+    server.respond(
+        "GET",
+        SLO,
+        MockResponse::json(
+            200,
+            json!({"data": [{"id": "abc123", "name": "Checkout", "type": "metric", "thresholds": [{"target": 99.9, "timeframe": "30d"}]}]}),
+        ),
+    );
+
+    let output = run(server.ddog(), &["slo", "list", "--tag", "team:payments"]);
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+    assert_eq!(output.records()[0]["id"], "abc123");
+    assert!(
+        server.requests_to(SLO)[0]
+            .query
+            .contains("tags_query=team%3Apayments")
+    );
+}
+
+#[test]
+fn test_slo_history_writes_overall_and_groups() {
+    let server = MockServer::start();
+    server.respond(
+        "GET",
+        "/api/v1/slo/abc123",
+        MockResponse::json(
+            200,
+            json!({"data": {"id": "abc123", "name": "Checkout", "target_threshold": 99.9}}),
+        ),
+    );
+    server.respond(
+        "GET",
+        SLO_HISTORY,
+        MockResponse::json(
+            200,
+            slo_history(
+                99.94,
+                json!([{"group": "env:prod", "sli_value": 99.8, "error_budget_remaining": {"custom": -100.0}}]),
+            ),
+        ),
+    );
+
+    let output = run(server.ddog(), &["slo", "history", "abc123", "--last", "7d"]);
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+    let records = output.records();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0]["group"], "*");
+    assert_eq!(records[0]["sli_value"], 99.94);
+    assert_eq!(records[0]["error_budget_remaining"], 40.0);
+    assert_eq!(records[1]["group"], "env:prod");
+    assert_eq!(records[1]["target"], 99.9);
+
+    let query = &server.requests_to(SLO_HISTORY)[0].query;
+    assert!(query.contains("target=99.9"), "query: {}", query);
+}
+
+#[test]
+fn test_slo_burn_rate_fails_when_threshold_crossed() {
+    let server = MockServer::start();
+    // 1h burns at 10x, 6h at 2x
+    server
+        .respond(
+            "GET",
+            SLO_HISTORY,
+            MockResponse::json(200, slo_history(99.0, json!([]))),
+        )
+        .respond(
+            "GET",
+            SLO_HISTORY,
+            MockResponse::json(200, slo_history(99.8, json!([]))),
+        );
+
+    let output = run(
+        server.ddog(),
+        &[
+            "slo",
+            "burn-rate",
+            "abc123",
+            "--target",
+            "99.9",
+            "--windows",
+            "1h:14.4,6h",
+            "--threshold",
+            "1.5",
+        ],
+    );
+    assert_eq!(output.code, 12, "stderr: {}", output.stderr);
+    assert!(output.stderr.contains("over 6h"));
+
+    let records = output.records();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0]["window"], "1h");
+    assert_eq!(records[0]["breached"], false);
+    assert_eq!(records[1]["window"], "6h");
+    assert_eq!(records[1]["threshold"], 1.5);
+    assert_eq!(records[1]["breached"], true);
+
+    let requests = server.requests_to(SLO_HISTORY);
+    let window = |query: &str| {
+        let param = |name: &str| -> i64 {
+            let start = query.find(&format!("{}=", name)).unwrap() + name.len() + 1;
+            query[start..].split('&').next().unwrap().parse().unwrap()
+        };
+        param("to_ts") - param("from_ts")
+    };
+    assert_eq!(window(&requests[0].query), 3600);
+    assert_eq!(window(&requests[1].query), 6 * 3600);
+    assert!(server.requests_to("/api/v1/slo/abc123").is_empty());
+}
+
+#[test]
+fn test_slo_burn_rate_passes_under_threshold() {
+    let server = MockServer::start();
+    server.respond(
+        "GET",
+        "/api/v1/slo/abc123",
+        MockResponse::json(
+            200,
+            json!({"data": {"id": "abc123", "target_threshold": 99.0}}),
+        ),
+    );
+    server.respond(
+        "GET",
+        SLO_HISTORY,
+        MockResponse::json(200, slo_history(99.5, json!([]))),
+    );
+
+    let output = run(server.ddog(), &["slo", "burn-rate", "abc123"]);
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+    let records = output.records();
+    assert_eq!(records.len(), 3);
+    assert!(records.iter().all(|r| r["breached"] == false));
+    assert_eq!(records[0]["burn_rate"], 0.5);
+}
+
+/// A monitor as returned by the v1 monitors API.
+fn monitor(id: i64, name: &str, state: &str) -> Value {
+    json!({
+        "id": id,
+        "name": name,
+        "query": "avg(last_5m):avg:system.cpu.user{*} by {host} > 90",
+        "type": "metric alert",
+        "overall_state": state
+    })
+}
+
+#[test]
+fn test_monitors_list_filters_by_status() {
+    let server = MockServer::start();
+    server.respond(
+        "GET",
+        MONITORS,
+        MockResponse::json(
+            200,
+            json!([monitor(1, "High CPU", "Alert"), monitor(2, "Disk", "OK")]),
+        ),
+    );
+
+    let output = run(
+        server.ddog(),
+        &[
+            "monitors",
+            "list",
+            "--monitor-tags",
+            "team:payments",
+            "--status",
+            "alert,no-data",
+        ],
+    );
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+
+    let records = output.records();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0]["name"], "High CPU");
+    assert!(
+        server.requests_to(MONITORS)[0]
+            .query
+            .contains("monitor_tags=team%3Apayments")
+    );
+}
+
+#[test]
+fn test_monitors_search_follows_page_count() {
+    let server = MockServer::start();
+    server.respond(
+        "GET",
+        MONITORS_SEARCH,
+        MockResponse::json(
+            200,
+            json!({
+                "monitors": [{"id": 1, "name": "a"}, {"id": 2, "name": "b"}],
+                "metadata": {"page": 0, "page_count": 2, "per_page": 2, "total_count": 3}
+            }),
+        ),
+    );
+    server.respond(
+        "GET",
+        MONITORS_SEARCH,
+        MockResponse::json(
+            200,
+            json!({
+                "monitors": [{"id": 3, "name": "c"}],
+                "metadata": {"page": 1, "page_count": 2, "per_page": 2, "total_count": 3}
+            }),
+        ),
+    );
+
+    let output = run(server.ddog(), &["monitors", "search", "status:alert"]);
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+    assert_eq!(output.records().len(), 3);
+
+    let requests = server.requests_to(MONITORS_SEARCH);
+    assert_eq!(requests.len(), 2);
+    assert!(requests[0].query.contains("query=status%3Aalert"));
+    assert!(requests[1].query.contains("page=1"));
+}
+
+#[test]
+fn test_monitors_mute_creates_downtime() {
+    let server = MockServer::start();
+    server.respond(
+        "POST",
+        DOWNTIMES,
+        MockResponse::json(
+            200,
+            json!({"data": {
+                "id": "dt-1",
+                "type": "downtime",
+                "attributes": {"scope": "host:web-1"}
+            }}),
+        ),
+    );
+
+    let output = run(
+        server.ddog(),
+        &[
+            "monitors",
+            "mute",
+            "123",
+            "--scope",
+            "host:web-1",
+            "--end",
+            "now+2h",
+            "--message",
+            "Deploying",
+        ],
+    );
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+    assert_eq!(output.records()[0]["id"], "dt-1");
+
+    let body = server.requests_to(DOWNTIMES)[0].json();
+    let attributes = &body["data"]["attributes"];
+    assert_eq!(attributes["monitor_identifier"]["monitor_id"], 123);
+    assert_eq!(attributes["scope"], "host:web-1");
+    assert_eq!(attributes["message"], "Deploying");
+    assert!(attributes["schedule"]["end"].is_string());
+}
+
+#[test]
+fn test_monitors_mute_rejects_past_end() {
+    let server = MockServer::start();
+
+    let output = run(
+        server.ddog(),
+        &["monitors", "mute", "123", "--end", "now-1h"],
+    );
+    assert_eq!(output.code, 4, "stderr: {}", output.stderr);
+    assert!(output.stderr.contains("in the past"));
+    assert!(server.requests().is_empty());
+}
+
+/// A downtime response for the given monitor identifier.
+fn downtime(id: &str, monitor_identifier: Value) -> Value {
+    json!({"data": {
+        "id": id,
+        "type": "downtime",
+        "attributes": {"scope": "*", "monitor_identifier": monitor_identifier}
+    }})
+}
+
+fn no_content() -> MockResponse {
+    MockResponse {
+        status: 204,
+        headers: vec![],
+        body: String::new(),
+    }
+}
+
+#[test]
+fn test_monitors_mute_does_not_retry_create() {
+    let server = MockServer::start();
+    server.respond(
+        "POST",
+        DOWNTIMES,
+        MockResponse::error(500, "Internal Server Error"),
+    );
+
+    let output = run(
+        server.ddog(),
+        &["--max-retries", "3", "monitors", "mute", "123"],
+    );
+    assert_eq!(output.code, 11, "stderr: {}", output.stderr);
+    assert_eq!(server.requests_to(DOWNTIMES).len(), 1);
+}
+
+#[test]
+fn test_monitors_unmute_skips_shared_downtimes() {
+    let server = MockServer::start();
+    server.respond(
+        "GET",
+        "/api/v2/monitor/123/downtime_matches",
+        MockResponse::json(
+            200,
+            json!({"data": [
+                {"id": "dt-own", "type": "downtime_match", "attributes": {"scope": "*"}},
+                {"id": "dt-tags", "type": "downtime_match", "attributes": {"scope": "*"}}
+            ]}),
+        ),
+    );
+    server
+        .respond(
+            "GET",
+            "/api/v2/downtime/dt-own",
+            MockResponse::json(200, downtime("dt-own", json!({"monitor_id": 123}))),
+        )
+        .respond(
+            "GET",
+            "/api/v2/downtime/dt-tags",
+            MockResponse::json(
+                200,
+                downtime("dt-tags", json!({"monitor_tags": ["team:payments"]})),
+            ),
+        )
+        .respond("DELETE", "/api/v2/downtime/dt-own", no_content())
+        .respond("DELETE", "/api/v2/downtime/dt-tags", no_content());
+
+    let output = run(server.ddog(), &["monitors", "unmute", "123"]);
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+    let cancelled: Vec<Value> = output
+        .records()
+        .iter()
+        .map(|r| r["downtime_id"].clone())
+        .collect();
+    assert_eq!(cancelled, vec![json!("dt-own")]);
+    assert!(output.stderr.contains("--include-shared"));
+    let deletes = |server: &MockServer| {
+        server
+            .requests()
+            .iter()
+            .filter(|r| r.method == "DELETE")
+            .count()
+    };
+    assert_eq!(deletes(&server), 1);
+
+    let output = run(
+        server.ddog(),
+        &["monitors", "unmute", "123", "--include-shared"],
+    );
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+    assert_eq!(output.records().len(), 2);
+    assert_eq!(deletes(&server), 3);
+}
+
+#[test]
+fn test_monitors_unmute_cancels_matching_scope() {
+    let server = MockServer::start();
+    server.respond(
+        "GET",
+        "/api/v2/monitor/123/downtime_matches",
+        MockResponse::json(
+            200,
+            json!({"data": [
+                {"id": "dt-1", "type": "downtime_match", "attributes": {"scope": "*"}},
+                {"id": "dt-2", "type": "downtime_match", "attributes": {"scope": "host:web-1"}}
+            ]}),
+        ),
+    );
+    server.respond(
+        "GET",
+        "/api/v2/downtime/dt-2",
+        MockResponse::json(200, downtime("dt-2", json!({"monitor_id": 123}))),
+    );
+    server.respond("DELETE", "/api/v2/downtime/dt-2", no_content());
+
+    let output = run(
+        server.ddog(),
+        &["monitors", "unmute", "123", "--scope", "host:web-1"],
+    );
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+    assert_eq!(
+        output.records(),
+        vec![json!({"downtime_id": "dt-2", "monitor_id": 123, "scope": "host:web-1"})]
+    );
+    assert!(server.requests_to("/api/v2/downtime/dt-1").is_empty());
+}
+
+#[test]
+fn test_monitors_status_writes_group_states() {
+    let server = MockServer::start();
+    let mut body = monitor(42, "High CPU", "Alert");
+    body["state"] = json!({"groups": {
+        "host:a": {"name": "host:a", "status": "Alert", "last_triggered_ts": 1700000000},
+        "host:b": {"name": "host:b", "status": "OK"}
+    }});
+    server.respond("GET", "/api/v1/monitor/42", MockResponse::json(200, body));
+
+    let output = run(
+        server.ddog(),
+        &["monitors", "status", "42", "--status", "alert"],
+    );
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+    assert_eq!(
+        output.records(),
+        vec![json!({
+            "monitor_id": 42,
+            "monitor_name": "High CPU",
+            "group": "host:a",
+            "status": "Alert",
+            "last_triggered_ts": 1700000000
+        })]
+    );
+    assert!(
+        server.requests_to("/api/v1/monitor/42")[0]
+            .query
+            .contains("group_states=all")
+    );
+}
+
+#[test]
+fn test_monitors_export_writes_normalized_files() {
+    let server = MockServer::start();
+    let mut cpu = monitor(1, "[Prod] High CPU", "Alert");
+    cpu["created"] = json!("2024-01-15T10:00:00Z");
+    cpu["tags"] = json!(["team:payments"]);
+    server.respond(
+        "GET",
+        MONITORS,
+        MockResponse::json(200, json!([cpu, monitor(2, "Disk", "OK")])),
+    );
+    let dir = support::temp_path("export");
+
+    let output = run(
+        server.ddog(),
+        &[
+            "monitors",
+            "export",
+            dir.to_str().unwrap(),
+            "--format",
+            "yaml",
+        ],
+    );
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+    assert_eq!(output.records().len(), 2);
+
+    let contents = std::fs::read_to_string(dir.join("prod-high-cpu.yaml")).unwrap();
+    let definition: Value = serde_yaml::from_str(&contents).unwrap();
+    assert_eq!(
+        definition,
+        json!({
+            "name": "[Prod] High CPU",
+            "query": "avg(last_5m):avg:system.cpu.user{*} by {host} > 90",
+            "type": "metric alert",
+            "tags": ["team:payments"]
+        })
+    );
+    assert!(dir.join("disk.yaml").exists());
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+/// Writes monitor definitions as JSON files into a new directory.
+fn definitions_dir(name: &str, definitions: &[Value]) -> std::path::PathBuf {
+    let dir = support::temp_path(name);
+    std::fs::create_dir_all(&dir).unwrap();
+    for (i, definition) in definitions.iter().enumerate() {
+        std::fs::write(dir.join(format!("{}.json", i)), definition.to_string()).unwrap();
+    }
+    dir
+}
+
+#[test]
+fn test_monitors_apply_plans_without_changing() {
+    let server = MockServer::start();
+    server.respond(
+        "GET",
+        MONITORS,
+        MockResponse::json(200, json!([monitor(1, "High CPU", "OK")])),
+    );
+    let dir = definitions_dir(
+        "plan",
+        &[
+            json!({"name": "High CPU", "query": "avg(last_5m):avg:system.cpu.user{*} by {host} > 95", "type": "metric alert"}),
+            json!({"name": "Disk", "query": "max(last_5m):max:system.disk.in_use{*} > 0.9", "type": "metric alert"}),
+        ],
+    );
+
+    let output = run(server.ddog(), &["monitors", "apply", dir.to_str().unwrap()]);
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+    assert!(output.stderr.contains("pass --yes"));
+
+    let records = output.records();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0]["action"], "create");
+    assert_eq!(records[1]["action"], "update");
+    assert_eq!(records[1]["monitor_id"], 1);
+    assert_eq!(records[1]["changes"][0]["path"], "query");
+    assert_eq!(records[1]["applied"], false);
+    assert!(server.requests().iter().all(|r| r.method == "GET"));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_monitors_apply_with_yes_creates_updates_and_prunes() {
+    let server = MockServer::start();
+    server.respond(
+        "GET",
+        MONITORS,
+        MockResponse::json(
+            200,
+            json!([monitor(1, "High CPU", "OK"), monitor(2, "Old", "OK")]),
+        ),
+    );
+    server.respond(
+        "POST",
+        MONITORS,
+        MockResponse::json(200, monitor(3, "Disk", "OK")),
+    );
+    server.respond(
+        "PUT",
+        "/api/v1/monitor/1",
+        MockResponse::json(200, monitor(1, "High CPU", "OK")),
+    );
+    server.respond(
+        "DELETE",
+        "/api/v1/monitor/2",
+        MockResponse::json(200, json!({"deleted_monitor_id": 2})),
+    );
+    let dir = definitions_dir(
+        "apply",
+        &[
+            json!({"name": "High CPU", "query": "avg(last_5m):avg:system.cpu.user{*} by {host} > 95", "type": "metric alert"}),
+            json!({"name": "Disk", "query": "max(last_5m):max:system.disk.in_use{*} > 0.9", "type": "metric alert"}),
+        ],
+    );
+
+    let output = run(
+        server.ddog(),
+        &[
+            "monitors",
+            "apply",
+            dir.to_str().unwrap(),
+            "--prune",
+            "--yes",
+        ],
+    );
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+
+    let records = output.records();
+    let actions: Vec<&str> = records
+        .iter()
+        .map(|r| r["action"].as_str().unwrap())
+        .collect();
+    assert_eq!(actions, vec!["create", "update", "delete"]);
+    assert_eq!(records[0]["monitor_id"], 3);
+    assert!(records.iter().all(|r| r["applied"] == true));
+
+    let update = server.requests_to("/api/v1/monitor/1")[0].json();
+    assert_eq!(
+        update["query"],
+        "avg(last_5m):avg:system.cpu.user{*} by {host} > 95"
+    );
+    assert!(update.get("id").is_none());
+    assert_eq!(server.requests_to("/api/v1/monitor/2").len(), 1);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_monitors_apply_does_not_retry_create() {
+    let server = MockServer::start();
+    server.respond("GET", MONITORS, MockResponse::json(200, json!([])));
+    server.respond(
+        "POST",
+        MONITORS,
+        MockResponse::error(500, "Internal Server Error"),
+    );
+    let dir = definitions_dir(
+        "no-retry",
+        &[
+            json!({"name": "Disk", "query": "max(last_5m):max:system.disk.in_use{*} > 0.9", "type": "metric alert"}),
+        ],
+    );
+
+    let output = run(
+        server.ddog(),
+        &[
+            "--max-retries",
+            "3",
+            "monitors",
+            "apply",
+            dir.to_str().unwrap(),
+            "--yes",
+        ],
+    );
+    assert_eq!(output.code, 11, "stderr: {}", output.stderr);
+    let creates = server
+        .requests()
+        .iter()
+        .filter(|r| r.method == "POST")
+        .count();
+    assert_eq!(creates, 1);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_monitors_apply_rejects_invalid_definition() {
+    let server = MockServer::start();
+    let dir = definitions_dir("invalid", &[json!({"query": "q", "type": "metric alert"})]);
+
+    let output = run(server.ddog(), &["monitors", "apply", dir.to_str().unwrap()]);
+    assert_eq!(output.code, 5, "stderr: {}", output.stderr);
+    assert!(output.stderr.contains("missing field `name`"));
+    assert!(server.requests().is_empty());
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+/// A minimal live dashboard, as returned by the dashboards API.
+fn dashboard(id: &str, widgets: Value) -> Value {
+    json!({
+        "id": id,
+        "title": "Service",
+        "layout_type": "ordered",
+        "author_handle": "someone@example.com",
+        "created_at": "2024-01-15T10:00:00Z",
+        "url": format!("/dashboard/{}/service", id),
+        "widgets": widgets
+    })
+}
+
+/// A timeseries widget with a single query.
+fn timeseries(id: i64, title: &str, query: &str) -> Value {
+    json!({
+        "id": id,
+        "definition": {"type": "timeseries", "title": title, "requests": [{"q": query}]}
+    })
+}
+
+#[test]
+fn test_dashboards_list_writes_summaries() {
+    let server = MockServer::start();
+    server.respond(
+        "GET",
+        DASHBOARDS,
+        MockResponse::json(
+            200,
+            json!({"dashboards": [{"id": "abc-def-ghi", "title": "Service", "layout_type": "ordered"}]}),
+        ),
+    );
+
+    let output = run(server.ddog(), &["dashboards", "list"]);
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+    let records = output.records();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0]["id"], "abc-def-ghi");
+}
+
+#[test]
+fn test_dashboards_export_is_deterministic() {
+    let server = MockServer::start();
+    // Free layout widgets are returned in creation order, not position order
+    let mut live = dashboard(
+        "abc-def-ghi",
+        json!([
+            {"id": 2, "definition": {"type": "note", "content": "below"}, "layout": {"x": 0, "y": 4, "width": 4, "height": 2}},
+            {"id": 1, "definition": {"type": "note", "content": "above"}, "layout": {"x": 0, "y": 0, "width": 4, "height": 2}}
+        ]),
+    );
+    live["layout_type"] = json!("free");
+    server.respond(
+        "GET",
+        DASHBOARDS,
+        MockResponse::json(200, json!({"dashboards": [{"id": "abc-def-ghi"}]})),
+    );
+    server.respond(
+        "GET",
+        "/api/v1/dashboard/abc-def-ghi",
+        MockResponse::json(200, live),
+    );
+    let dir = support::temp_path("dashboards-export");
+
+    let output = run(
+        server.ddog(),
+        &["dashboards", "export", dir.to_str().unwrap()],
+    );
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+    let records = output.records();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0]["title"], "Service");
+
+    let contents = std::fs::read_to_string(dir.join("abc-def-ghi.json")).unwrap();
+    assert!(contents.ends_with("}\n"));
+    let backup: Value = serde_json::from_str(&contents).unwrap();
+    assert!(backup.get("id").is_none());
+    assert!(backup.get("author_handle").is_none());
+    assert!(backup.get("url").is_none());
+    assert_eq!(backup["widgets"][0]["definition"]["content"], "above");
+    assert!(backup["widgets"][0].get("id").is_none());
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+/// Writes a dashboard file and returns its path.
+fn dashboard_file(name: &str, contents: &Value) -> std::path::PathBuf {
+    let path = support::temp_path(name);
+    std::fs::write(&path, contents.to_string()).unwrap();
+    path
+}
+
+#[test]
+fn test_dashboards_import_creates_without_server_fields() {
+    let server = MockServer::start();
+    server.respond(
+        "POST",
+        DASHBOARDS,
+        MockResponse::json(200, dashboard("new-das-hbd", json!([]))),
+    );
+    let file = dashboard_file(
+        "import.json",
+        &dashboard(
+            "abc-def-ghi",
+            json!([timeseries(1, "CPU", "avg:system.cpu.user{*}")]),
+        ),
+    );
+
+    let output = run(
+        server.ddog(),
+        &["dashboards", "import", file.to_str().unwrap()],
+    );
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+    let records = output.records();
+    assert_eq!(records[0]["id"], "new-das-hbd");
+    assert_eq!(records[0]["url"], "/dashboard/new-das-hbd/service");
+
+    let requests = server.requests_to(DASHBOARDS);
+    let body = requests[0].json();
+    assert!(body.get("id").is_none());
+    assert!(body.get("author_handle").is_none());
+    assert!(body["widgets"][0].get("id").is_none());
+    assert_eq!(body["widgets"][0]["definition"]["title"], "CPU");
+    std::fs::remove_file(file).unwrap();
+}
+
+#[test]
+fn test_dashboards_import_does_not_retry_create() {
+    let server = MockServer::start();
+    server.respond(
+        "POST",
+        DASHBOARDS,
+        MockResponse::error(500, "Internal Server Error"),
+    );
+    let file = dashboard_file("no-retry.json", &dashboard("abc-def-ghi", json!([])));
+
+    let output = run(
+        server.ddog(),
+        &[
+            "--max-retries",
+            "3",
+            "dashboards",
+            "import",
+            file.to_str().unwrap(),
+        ],
+    );
+    assert_eq!(output.code, 11, "stderr: {}", output.stderr);
+    assert_eq!(server.requests_to(DASHBOARDS).len(), 1);
+    std::fs::remove_file(file).unwrap();
+}
+
+#[test]
+fn test_dashboards_import_replace_updates_dashboard() {
+    let server = MockServer::start();
+    server.respond(
+        "PUT",
+        "/api/v1/dashboard/abc-def-ghi",
+        MockResponse::json(200, dashboard("abc-def-ghi", json!([]))),
+    );
+    let file = dashboard_file("replace.json", &dashboard("abc-def-ghi", json!([])));
+
+    let output = run(
+        server.ddog(),
+        &[
+            "dashboards",
+            "import",
+            file.to_str().unwrap(),
+            "--replace",
+            "abc-def-ghi",
+        ],
+    );
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+    assert_eq!(server.requests_to("/api/v1/dashboard/abc-def-ghi").len(), 1);
+
+    let output = run(
+        server.ddog(),
+        &[
+            "dashboards",
+            "import",
+            file.to_str().unwrap(),
+            file.to_str().unwrap(),
+            "--replace",
+            "abc-def-ghi",
+        ],
+    );
+    assert_eq!(output.code, 4, "stderr: {}", output.stderr);
+    std::fs::remove_file(file).unwrap();
+}
+
+#[test]
+fn test_dashboards_import_rejects_invalid_file() {
+    let server = MockServer::start();
+    let file = dashboard_file("invalid.json", &json!({"title": "No layout"}));
+
+    let output = run(
+        server.ddog(),
+        &["dashboards", "import", file.to_str().unwrap()],
+    );
+    assert_eq!(output.code, 5, "stderr: {}", output.stderr);
+    assert!(output.stderr.contains("Invalid dashboard"));
+    assert!(server.requests().is_empty());
+    std::fs::remove_file(file).unwrap();
+}
+
+#[test]
+fn test_dashboards_diff_reports_widget_changes() {
+    let server = MockServer::start();
+    server.respond(
+        "GET",
+        "/api/v1/dashboard/abc-def-ghi",
+        MockResponse::json(
+            200,
+            dashboard(
+                "abc-def-ghi",
+                json!([
+                    timeseries(1, "CPU", "avg:system.cpu.user{*}"),
+                    timeseries(2, "Memory", "avg:system.mem.used{*}")
+                ]),
+            ),
+        ),
+    );
+    let file = dashboard_file(
+        "diff.json",
+        &json!({
+            "title": "Service",
+            "layout_type": "ordered",
+            "widgets": [
+                timeseries(1, "CPU", "max:system.cpu.user{*}"),
+                timeseries(3, "Disk", "avg:system.disk.in_use{*}")
+            ]
+        }),
+    );
+
+    let output = run(
+        server.ddog(),
+        &["dashboards", "diff", "abc-def-ghi", file.to_str().unwrap()],
+    );
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+    let records = output.records();
+    let summary: Vec<(&str, &str)> = records
+        .iter()
+        .map(|r| (r["target"].as_str().unwrap(), r["status"].as_str().unwrap()))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("timeseries \"CPU\"", "changed"),
+            ("timeseries \"Disk\"", "added"),
+            ("timeseries \"Memory\"", "removed"),
+        ]
+    );
+    assert_eq!(
+        records[0]["changes"][0]["from"],
+        json!([{"q": "avg:system.cpu.user{*}"}])
+    );
+    std::fs::remove_file(file).unwrap();
+}
+
+#[test]
+fn test_unauthorized_maps_to_auth_error() {
+    let server = MockServer::start();
+    server.respond(
+        "POST",
+        LOGS_SEARCH,
+        MockResponse::error(401, "Unauthorized").header("X-Request-Id", "req-401"),
+    );
+
+    let output = run(
+        server.ddog(),
+        &["--error-format", "json", "logs", "search", "*"],
+    );
+    assert_eq!(output.code, 2);
+
+    let report = error_report(&output.stderr);
+    assert_eq!(report["kind"], "auth");
+    assert_eq!(report["http_status"], 401);
+    assert_eq!(report["command"], "logs search");
+}
+
+#[test]
+fn test_usage_errors_are_reported_as_json() {
+    let server = MockServer::start();
+
+    let output = run(
+        server.ddog(),
+        &[
+            "--error-format",
+            "json",
+            "logs",
+            "search",
+            "*",
+            "--frm",
+            "now-1h",
+        ],
+    );
+    assert_eq!(output.code, 5);
+    let report = error_report(&output.stderr);
+    assert_eq!(report["kind"], "usage");
+    assert_eq!(report["exit_code"], 5);
+    assert_eq!(report["command"], "logs search");
+    assert!(
+        report["message"].as_str().unwrap().contains("'--frm'"),
+        "{}",
+        report
+    );
+
+    // Text mode keeps clap's own message
+    let output = run(server.ddog(), &["logs", "aggregate"]);
+    assert_eq!(output.code, 5);
+    assert!(output.stderr.contains("Usage: ddog logs aggregate"));
+
+    let output = run(server.ddog(), &["--error-format", "json", "--help"]);
+    assert_eq!(output.code, 0);
+    assert!(output.stdout.contains("Usage: ddog"));
+    assert!(server.requests().is_empty());
+}
+
+#[test]
+fn test_forbidden_maps_to_auth_error() {
+    let server = MockServer::start();
+    server.respond("GET", METRICS_LIST, MockResponse::error(403, "Forbidden"));
+
+    let output = run(
+        server.ddog(),
+        &["--error-format", "json", "metrics", "list"],
+    );
+    assert_eq!(output.code, 2);
+
+    let report = error_report(&output.stderr);
+    assert_eq!(report["kind"], "auth");
+    assert_eq!(report["http_status"], 403);
+}
+
+#[test]
+fn test_rate_limit_is_retried() {
+    let server = MockServer::start();
+    server
+        .respond(
+            "POST",
+            LOGS_SEARCH,
+            MockResponse::error(429, "Too many requests").header("X-RateLimit-Reset", "0"),
+        )
+        .respond(
+            "POST",
+            LOGS_SEARCH,
+            MockResponse::json(200, logs_page(&ids(0, 2), None)),
+        );
+
+    let output = run(
+        server.ddog(),
+        &["--max-retries", "1", "logs", "search", "*"],
+    );
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+    assert_eq!(output.records().len(), 2);
+    assert_eq!(server.requests_to(LOGS_SEARCH).len(), 2);
+}
+
+#[test]
+fn test_parallel_search_retries_rate_limited_slice() {
+    let server = MockServer::start();
+    server
+        .respond(
+            "POST",
+            LOGS_SEARCH,
+            MockResponse::error(429, "Too many requests").header("X-RateLimit-Reset", "0"),
+        )
+        .respond(
+            "POST",
+            LOGS_SEARCH,
+            MockResponse::json(200, logs_page(&ids(0, 1), None)),
+        );
+
+    let output = run(
+        server.ddog(),
+        &[
+            "--max-retries",
+            "1",
+            "logs",
+            "search",
+            "*",
+            "--parallel",
+            "2",
+            "--unordered",
+        ],
+    );
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+    // Two slices, one of them retried once
+    assert_eq!(server.requests_to(LOGS_SEARCH).len(), 3);
+    assert_eq!(output.records().len(), 2);
+}
+
+#[test]
+fn test_rate_limit_without_retries_fails() {
+    let server = MockServer::start();
+    server.respond(
+        "POST",
+        SPANS_SEARCH,
+        MockResponse::error(429, "Too many requests"),
+    );
+
+    let output = run(
+        server.ddog(),
+        &[
+            "--error-format",
+            "json",
+            "--max-retries",
+            "0",
+            "spans",
+            "search",
+            "*",
+        ],
+    );
+    assert_eq!(output.code, 9);
+    assert_eq!(error_report(&output.stderr)["kind"], "rate_limited");
+    assert_eq!(server.requests_to(SPANS_SEARCH).len(), 1);
+}
+
+#[test]
+fn test_server_error_maps_to_server_exit_code() {
+    let server = MockServer::start();
+    server.respond(
+        "POST",
+        LOGS_AGGREGATE,
+        MockResponse::error(500, "Internal Server Error"),
+    );
+
+    let output = run(
+        server.ddog(),
+        &["--max-retries", "0", "logs", "aggregate", "*"],
+    );
+    assert_eq!(output.code, 11);
+    assert!(output.stderr.starts_with("Error: "));
+}
+
+#[test]
+fn test_config_show_reports_api_url() {
+    let server = MockServer::start();
+
+    let output = run(server.ddog(), &["config", "show"]);
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+    assert_eq!(output.records()[0]["api_url"], server.url());
+    assert_eq!(output.records()[0]["retention_days"]["logs"], 15);
+    assert!(server.requests().is_empty());
+}
+
+#[test]
+fn test_config_show_without_keys() {
+    let server = MockServer::start();
+    let mut command = server.ddog();
+    command.env_remove("DD_API_KEY");
+
+    let output = run(command, &["config", "show"]);
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+    let record = &output.records()[0];
+    assert_eq!(record["api_key"], "(not set)");
+    assert_eq!(record["api_key_source"], Value::Null);
+    assert_eq!(record["app_key"], "****-key");
+    assert_eq!(record["app_key_source"], "env");
+}
+
+#[test]
+fn test_config_use_keeps_comments_and_order() {
+    let path = support::temp_path("use-config.toml");
+    let contents = "# team config\ndefault_profile = \"us1\"\n\n[profiles.us1]\nsite = \"datadoghq.com\"\n\n[profiles.eu]\nsite = \"datadoghq.eu\"\n";
+    std::fs::write(&path, contents).unwrap();
+    let mut command = support::ddog_command();
+    command.env("DDOG_CONFIG", &path);
+
+    let output = run(command, &["config", "use", "eu"]);
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+    assert_eq!(output.records()[0]["default_profile"], "eu");
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        contents.replace("default_profile = \"us1\"", "default_profile = \"eu\"")
+    );
+    std::fs::remove_file(&path).unwrap();
+}
+
+/// Reads a checkpoint file written by `--checkpoint`.
+fn read_checkpoint(path: &std::path::Path) -> Value {
+    serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+#[test]
+fn test_checkpoint_records_completed_export() {
+    let server = MockServer::start();
+    server
+        .respond(
+            "POST",
+            LOGS_SEARCH,
+            MockResponse::json(200, logs_page(&ids(0, 1000), Some("cursor-1"))),
+        )
+        .respond(
+            "POST",
+            LOGS_SEARCH,
+            MockResponse::json(200, logs_page(&ids(1000, 5), None)),
+        );
+    let path = support::temp_path("complete.ckpt");
+    let path_arg = path.to_str().unwrap();
+
+    let output = run(
+        server.ddog(),
+        &[
+            "logs",
+            "search",
+            "service:api",
+            "--from",
+            "2024-01-15T00:00:00Z",
+            "--to",
+            "2024-01-16T00:00:00Z",
+            "--limit",
+            "0",
+            "--checkpoint",
+            path_arg,
+        ],
+    );
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+    assert_eq!(output.records().len(), 1005);
+    assert_eq!(
+        server.requests_to(LOGS_SEARCH)[1].json()["page"]["cursor"],
+        "cursor-1"
+    );
+
+    let checkpoint = read_checkpoint(&path);
+    assert_eq!(checkpoint["query"], "service:api");
+    assert_eq!(checkpoint["from_ms"], 1705276800000i64);
+    assert_eq!(checkpoint["to_ms"], 1705363200000i64);
+    assert_eq!(checkpoint["records_written"], 1005);
+    assert_eq!(checkpoint["complete"], true);
+
+    // Resuming a finished export fetches nothing
+    let resumed = run(
+        server.ddog(),
+        &[
+            "logs",
+            "search",
+            "service:api",
+            "--checkpoint",
+            path_arg,
+            "--resume",
+        ],
+    );
+    assert_eq!(resumed.code, 0, "stderr: {}", resumed.stderr);
+    assert!(resumed.stdout.is_empty());
+    assert_eq!(server.requests_to(LOGS_SEARCH).len(), 2);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_resume_continues_after_failed_page() {
+    let path = support::temp_path("resume.ckpt");
+    let path_arg = path.to_str().unwrap();
+
+    let failing = MockServer::start();
+    failing
+        .respond(
+            "POST",
+            LOGS_SEARCH,
+            MockResponse::json(200, logs_page(&ids(0, 1000), Some("cursor-1"))),
+        )
+        .respond("POST", LOGS_SEARCH, MockResponse::error(503, "unavailable"));
+    let output = run(
+        failing.ddog(),
+        &[
+            "--max-retries",
+            "0",
+            "logs",
+            "search",
+            "service:api",
+            "--from",
+            "now-1h",
+            "--limit",
+            "0",
+            "--checkpoint",
+            path_arg,
+        ],
+    );
+    assert_eq!(output.code, 11, "stderr: {}", output.stderr);
+    assert_eq!(output.records().len(), 1000);
+    let saved = read_checkpoint(&path);
+    assert_eq!(saved["cursor"], "cursor-1");
+    assert_eq!(saved["records_written"], 1000);
+    assert_eq!(saved["complete"], false);
+
+    let server = MockServer::start();
+    server.respond(
+        "POST",
+        LOGS_SEARCH,
+        MockResponse::json(200, logs_page(&ids(1000, 5), None)),
+    );
+    let resumed = run(
+        server.ddog(),
+        &[
+            "logs",
+            "search",
+            "service:api",
+            "--checkpoint",
+            path_arg,
+            "--resume",
+        ],
+    );
+    assert_eq!(resumed.code, 0, "stderr: {}", resumed.stderr);
+    let records = resumed.records();
+    assert_eq!(records.len(), 5);
+    assert_eq!(records[0]["id"], "log-1000");
+
+    // The resumed request reuses the saved cursor and time bounds
+    let body = server.requests_to(LOGS_SEARCH)[0].json();
+    assert_eq!(body["page"]["cursor"], "cursor-1");
+    assert_eq!(body["filter"]["from"], saved["from_ms"].to_string());
+    assert_eq!(body["filter"]["to"], saved["to_ms"].to_string());
+
+    let done = read_checkpoint(&path);
+    assert_eq!(done["records_written"], 1005);
+    assert_eq!(done["complete"], true);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_checkpoint_limit_carries_over_on_resume() {
+    let path = support::temp_path("limit.ckpt");
+    let path_arg = path.to_str().unwrap();
+    let spans: Vec<(&str, &str, &str)> = vec![("s1", "0", "GET /"); 1000];
+
+    let failing = MockServer::start();
+    failing
+        .respond(
+            "POST",
+            SPANS_SEARCH,
+            MockResponse::json(200, spans_page(&spans, Some("cursor-1"))),
+        )
+        .respond(
+            "POST",
+            SPANS_SEARCH,
+            MockResponse::error(503, "unavailable"),
+        );
+    let output = run(
+        failing.ddog(),
+        &[
+            "--max-retries",
+            "0",
+            "spans",
+            "search",
+            "*",
+            "--limit",
+            "1200",
+            "--checkpoint",
+            path_arg,
+        ],
+    );
+    assert_eq!(output.code, 11, "stderr: {}", output.stderr);
+    assert_eq!(output.records().len(), 1000);
+
+    // --limit defaults to 100, but the saved limit of 1200 applies
+    let server = MockServer::start();
+    server.respond(
+        "POST",
+        SPANS_SEARCH,
+        MockResponse::json(200, spans_page(&spans, Some("cursor-2"))),
+    );
+    let resumed = run(
+        server.ddog(),
+        &["spans", "search", "*", "--checkpoint", path_arg, "--resume"],
+    );
+    assert_eq!(resumed.code, 0, "stderr: {}", resumed.stderr);
+    assert_eq!(resumed.records().len(), 200);
+    assert_eq!(read_checkpoint(&path)["complete"], true);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_checkpoint_resumes_export_killed_mid_page() {
+    use std::io::Read;
+    use std::process::Stdio;
+    use std::time::{Duration, Instant};
+
+    let path = support::temp_path("killed.ckpt");
+    let path_arg = path.to_str().unwrap();
+    let args = [
+        "logs",
+        "search",
+        "*",
+        "--limit",
+        "0",
+        "--checkpoint",
+        path_arg,
+    ];
+
+    // Nothing reads stdout, so writing blocks once the pipe is full, partway
+    // through the first page
+    let server = MockServer::start();
+    server.respond(
+        "POST",
+        LOGS_SEARCH,
+        MockResponse::json(200, logs_page(&ids(0, 1000), Some("cursor-1"))),
+    );
+    let mut child = server
+        .ddog()
+        .args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("run ddog");
+    let started = Instant::now();
+    // Blocked once the saved progress stops moving for a second
+    let (mut written, mut unchanged) = (0, 0);
+    while unchanged < 5 {
+        assert!(
+            started.elapsed() < Duration::from_secs(30),
+            "timed out waiting for ddog to block"
+        );
+        std::thread::sleep(Duration::from_millis(200));
+        if !path.exists() {
+            continue;
+        }
+        let saved = read_checkpoint(&path)["records_written"].as_u64().unwrap();
+        unchanged = if saved > 0 && saved == written {
+            unchanged + 1
+        } else {
+            0
+        };
+        written = saved;
+    }
+    // Reading stdout before ddog has exited would let it write more records
+    child.kill().unwrap();
+    child.wait().unwrap();
+    let mut stdout = String::new();
+    child
+        .stdout
+        .take()
+        .unwrap()
+        .read_to_string(&mut stdout)
+        .unwrap();
+
+    let checkpoint = read_checkpoint(&path);
+    assert_eq!(checkpoint["complete"], false);
+    assert_eq!(checkpoint["records_written"], stdout.lines().count());
+
+    let server = MockServer::start();
+    server
+        .respond(
+            "POST",
+            LOGS_SEARCH,
+            MockResponse::json(200, logs_page(&ids(0, 1000), Some("cursor-1"))),
+        )
+        .respond(
+            "POST",
+            LOGS_SEARCH,
+            MockResponse::json(200, logs_page(&ids(1000, 2), None)),
+        );
+    let mut resume_args = args.to_vec();
+    resume_args.push("--resume");
+    let resumed = run(server.ddog(), &resume_args);
+    assert_eq!(resumed.code, 0, "stderr: {}", resumed.stderr);
+
+    // Together, both runs wrote every log exactly once
+    let ids: Vec<String> = format!("{}{}", stdout, resumed.stdout)
+        .lines()
+        .map(|line| serde_json::from_str::<Value>(line).unwrap()["id"].to_string())
+        .collect();
+    let expected: Vec<String> = (0..1002).map(|i| format!("\"log-{}\"", i)).collect();
+    assert_eq!(ids, expected);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_checkpoint_refuses_to_overwrite_or_mismatch() {
+    let server = MockServer::start();
+    server.respond(
+        "POST",
+        LOGS_SEARCH,
+        MockResponse::json(200, logs_page(&ids(0, 2), None)),
+    );
+    let path = support::temp_path("existing.ckpt");
+    let path_arg = path.to_str().unwrap();
+
+    let first = run(
+        server.ddog(),
+        &["logs", "search", "service:api", "--checkpoint", path_arg],
+    );
+    assert_eq!(first.code, 0, "stderr: {}", first.stderr);
+
+    let again = run(
+        server.ddog(),
+        &["logs", "search", "service:api", "--checkpoint", path_arg],
+    );
+    assert_eq!(again.code, 5);
+    assert!(
+        again.stderr.contains("--resume"),
+        "stderr: {}",
+        again.stderr
+    );
+
+    let other = run(
+        server.ddog(),
+        &[
+            "logs",
+            "search",
+            "service:web",
+            "--checkpoint",
+            path_arg,
+            "--resume",
+        ],
+    );
+    assert_eq!(other.code, 5);
+    assert!(
+        other.stderr.contains("service:api"),
+        "stderr: {}",
+        other.stderr
+    );
+    assert_eq!(server.requests_to(LOGS_SEARCH).len(), 1);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_checkpoint_rejects_buffered_output_and_parallel() {
+    let server = MockServer::start();
+    let path = support::temp_path("rejected.ckpt");
+    let path_arg = path.to_str().unwrap();
+
+    let json = run(
+        server.ddog(),
+        &[
+            "-o",
+            "json",
+            "logs",
+            "search",
+            "*",
+            "--checkpoint",
+            path_arg,
+        ],
+    );
+    assert_eq!(json.code, 5);
+    assert!(!path.exists());
+
+    let parallel = run(
+        server.ddog(),
+        &[
+            "spans",
+            "search",
+            "*",
+            "--checkpoint",
+            path_arg,
+            "--parallel",
+            "4",
+        ],
+    );
+    assert_eq!(parallel.code, 5);
+
+    let resume = run(server.ddog(), &["logs", "search", "*", "--resume"]);
+    assert_eq!(resume.code, 5);
+    assert!(server.requests().is_empty());
+}
+
+#[test]
+fn test_out_writes_rotated_gzip_parts() {
+    use std::io::Read;
+
+    let server = MockServer::start();
+    server.respond(
+        "POST",
+        LOGS_SEARCH,
+        MockResponse::json(200, logs_page(&ids(0, 5), None)),
+    );
+    let path = support::temp_path("logs.ndjson");
+
+    let output = run(
+        server.ddog(),
+        &[
+            "logs",
+            "search",
+            "*",
+            "--out",
+            path.to_str().unwrap(),
+            "--compress",
+            "gzip",
+            "--rotate-every",
+            "2",
+        ],
+    );
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+    assert!(output.stdout.is_empty());
+
+    let name = path.file_name().unwrap().to_str().unwrap();
+    let stem = name.trim_end_matches(".ndjson");
+    let lines: Vec<usize> = (1..=3)
+        .map(|part| {
+            let part_path = path.with_file_name(format!("{}-{:05}.ndjson.gz", stem, part));
+            let mut contents = String::new();
+            flate2::read::GzDecoder::new(std::fs::File::open(&part_path).unwrap())
+                .read_to_string(&mut contents)
+                .unwrap();
+            std::fs::remove_file(&part_path).unwrap();
+            contents.lines().count()
+        })
+        .collect();
+    assert_eq!(lines, vec![2, 2, 1]);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_interrupt_finishes_compressed_out_file() {
+    use std::io::Read;
+
+    let server = MockServer::start();
+    server
+        .respond(
+            "POST",
+            LOGS_SEARCH,
+            MockResponse::json(200, logs_page(&ids(0, 1000), Some("cursor-1"))),
+        )
+        // The next page waits out a long rate limit until interrupted
+        .respond(
+            "POST",
+            LOGS_SEARCH,
+            MockResponse::error(429, "rate limited").header("Retry-After", "300"),
+        );
+    let path = support::temp_path("interrupted.ndjson.gz");
+    let checkpoint = support::temp_path("interrupted.ckpt");
+
+    let output = support::run_until_interrupted(
+        server.ddog(),
+        &[
+            "--retry-timeout",
+            "600",
+            "--out",
+            path.to_str().unwrap(),
+            "--compress",
+            "gzip",
+            "logs",
+            "search",
+            "*",
+            "--limit",
+            "0",
+            "--checkpoint",
+            checkpoint.to_str().unwrap(),
+        ],
+        || server.requests_to(LOGS_SEARCH).len() >= 2,
+    );
+    assert_eq!(output.code, 130, "stderr: {}", output.stderr);
+
+    let mut contents = String::new();
+    flate2::read::GzDecoder::new(std::fs::File::open(&path).unwrap())
+        .read_to_string(&mut contents)
+        .unwrap();
+    assert_eq!(contents.lines().count(), 1000);
+    std::fs::remove_file(&path).unwrap();
+    std::fs::remove_file(&checkpoint).unwrap();
+}
+
+#[test]
+fn test_resume_refuses_compressed_out_file() {
+    let server = MockServer::start();
+    let path = support::temp_path("resume.ndjson.gz");
+    let checkpoint = support::temp_path("resume-gz.ckpt");
+    std::fs::write(
+        &checkpoint,
+        json!({
+            "command": "logs search", "query": "*", "indexes": ["*"], "from_ms": 0, "to_ms": 1,
+            "limit": 0, "cursor": "cursor-1", "page_offset": 0,
+            "records_written": 3, "complete": false
+        })
+        .to_string(),
+    )
+    .unwrap();
+
+    let output = run(
+        server.ddog(),
+        &[
+            "--out",
+            path.to_str().unwrap(),
+            "--compress",
+            "gzip",
+            "logs",
+            "search",
+            "*",
+            "--checkpoint",
+            checkpoint.to_str().unwrap(),
+            "--resume",
+        ],
+    );
+    assert_eq!(output.code, 5, "stderr: {}", output.stderr);
+    assert!(output.stderr.contains("--rotate-every"));
+    assert!(server.requests().is_empty());
+    std::fs::remove_file(&checkpoint).unwrap();
+}
+
+#[test]
+fn test_resume_appends_to_out_file() {
+    let path = support::temp_path("resume-out.csv");
+    let checkpoint = support::temp_path("resume-out.ckpt");
+    let args = |resume: bool| {
+        let mut args = vec![
+            "--max-retries",
+            "0",
+            "-o",
+            "csv",
+            "--fields",
+            "id",
+            "--out",
+            path.to_str().unwrap(),
+            "logs",
+            "search",
+            "*",
+            "--limit",
+            "0",
+            "--checkpoint",
+            checkpoint.to_str().unwrap(),
+        ];
+        if resume {
+            args.push("--resume");
+        }
+        args
+    };
+
+    let failing = MockServer::start();
+    failing
+        .respond(
+            "POST",
+            LOGS_SEARCH,
+            MockResponse::json(200, logs_page(&ids(0, 1000), Some("cursor-1"))),
+        )
+        .respond("POST", LOGS_SEARCH, MockResponse::error(503, "unavailable"));
+    let output = run(failing.ddog(), &args(false));
+    assert_eq!(output.code, 11, "stderr: {}", output.stderr);
+
+    let server = MockServer::start();
+    server.respond(
+        "POST",
+        LOGS_SEARCH,
+        MockResponse::json(200, logs_page(&ids(1000, 2), None)),
+    );
+    let resumed = run(server.ddog(), &args(true));
+    assert_eq!(resumed.code, 0, "stderr: {}", resumed.stderr);
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines.len(), 1003);
+    assert_eq!(lines[0], "id");
+    assert_eq!(lines[1000], "log-999");
+    assert_eq!(lines[1002], "log-1001");
+    std::fs::remove_file(&path).unwrap();
+    std::fs::remove_file(&checkpoint).unwrap();
+}
+
+#[test]
+fn test_search_sort_desc_requests_newest_first() {
+    let server = MockServer::start();
+    server
+        .respond(
+            "POST",
+            LOGS_SEARCH,
+            MockResponse::json(200, logs_page(&ids(0, 2), None)),
+        )
+        .respond(
+            "POST",
+            SPANS_SEARCH,
+            MockResponse::json(200, spans_page(&[("s1", "0", "GET /")], None)),
+        );
+
+    let logs = run(
+        server.ddog(),
+        &[
+            "logs",
+            "search",
+            "status:error",
+            "--sort",
+            "desc",
+            "--limit",
+            "20",
+        ],
+    );
+    assert_eq!(logs.code, 0, "stderr: {}", logs.stderr);
+    let spans = run(server.ddog(), &["spans", "search", "*", "--sort", "desc"]);
+    assert_eq!(spans.code, 0, "stderr: {}", spans.stderr);
+
+    assert_eq!(
+        server.requests_to(LOGS_SEARCH)[0].json()["sort"],
+        "-timestamp"
+    );
+    assert_eq!(
+        server.requests_to(SPANS_SEARCH)[0].json()["data"]["attributes"]["sort"],
+        "-timestamp"
+    );
+
+    // Follow mode only tails forward
+    let follow = run(
+        server.ddog(),
+        &["logs", "search", "*", "--follow", "--sort", "desc"],
+    );
+    assert_eq!(follow.code, 5);
+}
+
+#[test]
+fn test_fields_trim_ndjson_records() {
+    let server = MockServer::start();
+    server.respond(
+        "POST",
+        LOGS_SEARCH,
+        MockResponse::json(200, logs_page(&ids(0, 2), None)),
+    );
+
+    let output = run(
+        server.ddog(),
+        &[
+            "--fields",
+            "id,attributes.timestamp,attributes.message,attributes.missing",
+            "logs",
+            "search",
+            "*",
+        ],
+    );
+    assert_eq!(output.code, 0, "stderr: {}", output.stderr);
+    assert_eq!(
+        output.records()[0],
+        json!({
+            "id": "log-0",
+            "attributes": {
+                "timestamp": "2024-01-15T10:00:00Z",
+                "message": "log log-0"
+            }
+        })
+    );
+}
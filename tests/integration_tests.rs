@@ -9,9 +9,9 @@
 //!
 //! Note: These tests make actual API calls to Datadog and may consume API quota.
 
-use ddog::client::{LogsClient, MetricsClient, SpansClient};
+use ddog::client::{LogsClient, MetricsClient, SortOrder, SpansClient};
 use ddog::config;
-use ddog::time;
+use ddog::time::{self, ResolvedRange};
 use futures_util::StreamExt;
 
 fn has_credentials() -> bool {
@@ -40,7 +40,8 @@ async fn test_logs_search_with_relative_time() {
     assert!(time::is_valid_time_format(to));
     assert!(time::is_valid_time_range(from, to));
 
-    let mut stream = std::pin::pin!(client.search(query, from, to, indexes));
+    let range = ResolvedRange::parse(from, to).expect("Failed to parse time range");
+    let mut stream = std::pin::pin!(client.search(query, &range, indexes, SortOrder::Asc));
     let mut count = 0;
     let max_results = 10; // Limit to avoid consuming too much quota
 
@@ -95,7 +96,8 @@ async fn test_logs_search_with_iso8601_time() {
     let query = "*";
     let indexes = vec!["*".to_string()];
 
-    let mut stream = std::pin::pin!(client.search(query, &from, &to, indexes));
+    let range = ResolvedRange::parse(&from, &to).expect("Failed to parse time range");
+    let mut stream = std::pin::pin!(client.search(query, &range, indexes, SortOrder::Asc));
     let mut count = 0;
     let max_results = 10;
 
@@ -158,7 +160,8 @@ async fn test_logs_search_various_time_ranges() {
         let query = "*";
         let indexes = vec!["*".to_string()];
 
-        let mut stream = std::pin::pin!(client.search(query, from, to, indexes));
+        let range = ResolvedRange::parse(from, to).expect("Failed to parse time range");
+        let mut stream = std::pin::pin!(client.search(query, &range, indexes, SortOrder::Asc));
         let mut has_result = false;
 
         // Just check that the query doesn't error out - check first result
@@ -213,7 +216,8 @@ async fn test_spans_search_with_relative_time() {
     assert!(time::is_valid_time_format(to));
     assert!(time::is_valid_time_range(from, to));
 
-    let mut stream = std::pin::pin!(client.search(query, from, to));
+    let range = ResolvedRange::parse(from, to).expect("Failed to parse time range");
+    let mut stream = std::pin::pin!(client.search(query, &range, SortOrder::Asc));
     let mut count = 0;
     let max_results = 10;
 
@@ -264,7 +268,8 @@ async fn test_spans_search_with_iso8601_time() {
 
     let query = "*";
 
-    let mut stream = std::pin::pin!(client.search(query, &from, &to));
+    let range = ResolvedRange::parse(&from, &to).expect("Failed to parse time range");
+    let mut stream = std::pin::pin!(client.search(query, &range, SortOrder::Asc));
     let mut count = 0;
     let max_results = 10;
 
@@ -323,7 +328,8 @@ async fn test_spans_search_various_time_ranges() {
 
         let query = "*";
 
-        let mut stream = std::pin::pin!(client.search(query, from, to));
+        let range = ResolvedRange::parse(from, to).expect("Failed to parse time range");
+        let mut stream = std::pin::pin!(client.search(query, &range, SortOrder::Asc));
         let mut has_result = false;
 
         // Check first result to verify query format
@@ -393,7 +399,8 @@ async fn test_logs_search_with_unix_timestamp() {
     let query = "*";
     let indexes = vec!["*".to_string()];
 
-    let mut stream = std::pin::pin!(client.search(query, &from, &to, indexes));
+    let range = ResolvedRange::parse(&from, &to).expect("Failed to parse time range");
+    let mut stream = std::pin::pin!(client.search(query, &range, indexes, SortOrder::Asc));
     let mut count = 0;
     let max_results = 10;
 
@@ -450,7 +457,8 @@ async fn test_time_range_edge_cases() {
         let query = "*";
         let indexes = vec!["*".to_string()];
 
-        let mut stream = std::pin::pin!(client.search(query, from, to, indexes));
+        let range = ResolvedRange::parse(from, to).expect("Failed to parse time range");
+        let mut stream = std::pin::pin!(client.search(query, &range, indexes, SortOrder::Asc));
 
         // Just verify it doesn't error out immediately
         let mut error_count = 0;
@@ -503,11 +511,9 @@ async fn test_metrics_query_with_relative_time() {
     let from = "now-1h";
     let to = "now";
 
-    // Convert to Unix seconds for the API
-    let from_secs = time::parse_to_unix_seconds(from).expect("Failed to parse from time");
-    let to_secs = time::parse_to_unix_seconds(to).expect("Failed to parse to time");
+    let range = ResolvedRange::parse(from, to).expect("Failed to parse time range");
 
-    let mut stream = std::pin::pin!(client.query(query, from_secs, to_secs));
+    let mut stream = std::pin::pin!(client.query(query, &range));
     let mut count = 0;
     let max_results = 10;
 
@@ -565,7 +571,9 @@ async fn test_metrics_query_with_unix_timestamp() {
 
     let query = "avg:system.cpu.idle{*}";
 
-    let mut stream = std::pin::pin!(client.query(query, one_hour_ago, now));
+    let range = ResolvedRange::new(one_hour_ago * 1000, now * 1000);
+
+    let mut stream = std::pin::pin!(client.query(query, &range));
     let mut count = 0;
     let max_results = 10;
 
@@ -610,13 +618,13 @@ async fn test_list_metrics() {
 
     // List metrics from the last hour
     use std::time::{SystemTime, UNIX_EPOCH};
-    let one_hour_ago = (SystemTime::now()
+    let now = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap()
-        .as_secs()
-        - 3600) as i64;
+        .as_millis() as i64;
+    let range = ResolvedRange::new(now - 3_600_000, now);
 
-    let mut stream = std::pin::pin!(client.list_active(one_hour_ago));
+    let mut stream = std::pin::pin!(client.list_active(&range));
     let mut count = 0;
     let max_results = 50; // List more metrics to verify the endpoint works
 
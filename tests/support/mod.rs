@@ -0,0 +1,440 @@
+//! Test support: a local stand-in for the Datadog API.
+//!
+//! [`MockServer`] listens on a random localhost port and answers requests with
+//! scripted responses per method and path. The `ddog` binary is pointed at it
+//! through `DDOG_API_URL`, so commands can be tested end to end without
+//! credentials or network access.
+
+// Each test crate uses a different subset of these helpers
+#![allow(dead_code)]
+
+use serde_json::{Value, json};
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// A scripted HTTP response.
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl MockResponse {
+    /// A JSON response with the given status.
+    pub fn json(status: u16, body: Value) -> Self {
+        Self {
+            status,
+            headers: vec![("Content-Type".into(), "application/json".into())],
+            body: body.to_string(),
+        }
+    }
+
+    /// A Datadog-style error response: `{"errors": [message]}`.
+    pub fn error(status: u16, message: &str) -> Self {
+        Self::json(status, json!({ "errors": [message] }))
+    }
+
+    /// Adds a response header.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+}
+
+/// A request received by the mock server.
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    pub query: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl RecordedRequest {
+    /// Returns the value of a header (case-insensitive).
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Parses the request body as JSON.
+    pub fn json(&self) -> Value {
+        serde_json::from_slice(&self.body).expect("request body is not JSON")
+    }
+}
+
+type Routes = HashMap<(String, String), VecDeque<MockResponse>>;
+
+/// A minimal HTTP/1.1 server with scripted responses.
+///
+/// Responses for a method and path are served in the order they were added;
+/// the last one keeps being served once the others are used up. Requests
+/// without a scripted response get a 404.
+pub struct MockServer {
+    addr: SocketAddr,
+    routes: Arc<Mutex<Routes>>,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+    shutdown: Arc<AtomicBool>,
+}
+
+impl MockServer {
+    /// Starts a server on a random localhost port.
+    pub fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind mock server");
+        let addr = listener.local_addr().unwrap();
+        let routes: Arc<Mutex<Routes>> = Arc::default();
+        let requests: Arc<Mutex<Vec<RecordedRequest>>> = Arc::default();
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let (thread_routes, thread_requests, thread_shutdown) =
+            (routes.clone(), requests.clone(), shutdown.clone());
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if thread_shutdown.load(Ordering::SeqCst) {
+                    break;
+                }
+                let Ok(stream) = stream else { continue };
+                let (routes, requests) = (thread_routes.clone(), thread_requests.clone());
+                thread::spawn(move || handle_connection(stream, &routes, &requests));
+            }
+        });
+
+        Self {
+            addr,
+            routes,
+            requests,
+            shutdown,
+        }
+    }
+
+    /// Base URL to use as `DDOG_API_URL`.
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Adds a scripted response for a method and path.
+    pub fn respond(&self, method: &str, path: &str, response: MockResponse) -> &Self {
+        self.routes
+            .lock()
+            .unwrap()
+            .entry((method.to_string(), path.to_string()))
+            .or_default()
+            .push_back(response);
+        self
+    }
+
+    /// Returns all requests received so far.
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Returns the requests received for a path.
+    pub fn requests_to(&self, path: &str) -> Vec<RecordedRequest> {
+        self.requests()
+            .into_iter()
+            .filter(|r| r.path == path)
+            .collect()
+    }
+
+    /// Returns a command running the `ddog` binary against this server.
+    ///
+    /// Credentials are set to dummy values and the config file points to a
+    /// path that does not exist, so the user's environment does not leak in.
+    pub fn ddog(&self) -> Command {
+        let mut command = ddog_command();
+        command.env("DDOG_API_URL", self.url());
+        command
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        // Wake the accept loop so it sees the flag
+        let _ = TcpStream::connect(self.addr);
+    }
+}
+
+fn handle_connection(
+    stream: TcpStream,
+    routes: &Mutex<Routes>,
+    requests: &Mutex<Vec<RecordedRequest>>,
+) {
+    let Some(request) = read_request(&stream) else {
+        return;
+    };
+
+    let response = {
+        let mut routes = routes.lock().unwrap();
+        let key = (request.method.clone(), request.path.clone());
+        match routes.get_mut(&key) {
+            Some(queue) if queue.len() > 1 => queue.pop_front(),
+            Some(queue) => queue.front().cloned(),
+            None => None,
+        }
+    }
+    .unwrap_or_else(|| {
+        MockResponse::error(
+            404,
+            &format!(
+                "no scripted response for {} {}",
+                request.method, request.path
+            ),
+        )
+    });
+
+    requests.lock().unwrap().push(request);
+    let _ = write_response(stream, &response);
+}
+
+fn read_request(stream: &TcpStream) -> Option<RecordedRequest> {
+    let mut reader = BufReader::new(stream);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).ok()?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let target = parts.next()?;
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).ok()?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+
+    let length = headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, v)| v.parse::<usize>().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).ok()?;
+
+    Some(RecordedRequest {
+        method,
+        path: path.to_string(),
+        query: query.to_string(),
+        headers,
+        body,
+    })
+}
+
+fn write_response(mut stream: TcpStream, response: &MockResponse) -> std::io::Result<()> {
+    let mut head = format!("HTTP/1.1 {} Mock\r\n", response.status);
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str(&format!(
+        "Content-Length: {}\r\nConnection: close\r\n\r\n",
+        response.body.len()
+    ));
+    stream.write_all(head.as_bytes())?;
+    stream.write_all(response.body.as_bytes())?;
+    stream.flush()
+}
+
+/// Returns a command running the `ddog` binary with an isolated environment.
+///
+/// Dummy credentials are set, proxies are cleared and `DDOG_CONFIG` points to
+/// a file that does not exist.
+pub fn ddog_command() -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_ddog"));
+    for (name, _) in std::env::vars() {
+        let upper = name.to_ascii_uppercase();
+        if upper.starts_with("DD_") || upper.starts_with("DDOG_") || upper.ends_with("_PROXY") {
+            command.env_remove(&name);
+        }
+    }
+    command
+        .env("DD_API_KEY", "test-api-key")
+        .env("DD_APP_KEY", "test-app-key")
+        .env("DDOG_CONFIG", temp_path("missing-config.toml"));
+    command
+}
+
+/// Returns a unique path in the temp directory; the file is not created.
+pub fn temp_path(name: &str) -> PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let unique = COUNTER.fetch_add(1, Ordering::SeqCst);
+    std::env::temp_dir().join(format!(
+        "ddog-test-{}-{}-{}",
+        std::process::id(),
+        unique,
+        name
+    ))
+}
+
+/// Captured result of running the binary.
+#[derive(Debug)]
+pub struct Output {
+    pub code: i32,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl Output {
+    /// Parses stdout as NDJSON.
+    pub fn records(&self) -> Vec<Value> {
+        self.stdout
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("stdout line is not JSON"))
+            .collect()
+    }
+}
+
+/// Runs a command with the given arguments and captures its output.
+pub fn run(mut command: Command, args: &[&str]) -> Output {
+    let output = command.args(args).output().expect("run ddog");
+    Output {
+        code: output.status.code().unwrap_or(-1),
+        stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+        stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+    }
+}
+
+/// Starts a command, waits until `ready` returns true, then interrupts it
+/// with SIGINT (as Ctrl-C would) and captures its output.
+///
+/// Panics if `ready` is still false after 30 seconds.
+#[cfg(unix)]
+pub fn run_until_interrupted(
+    mut command: Command,
+    args: &[&str],
+    ready: impl Fn() -> bool,
+) -> Output {
+    use std::process::Stdio;
+    use std::time::{Duration, Instant};
+
+    let child = command
+        .args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("run ddog");
+
+    let started = Instant::now();
+    while !ready() {
+        assert!(
+            started.elapsed() < Duration::from_secs(30),
+            "timed out waiting for ddog"
+        );
+        thread::sleep(Duration::from_millis(50));
+    }
+    let status = Command::new("kill")
+        .args(["-INT", &child.id().to_string()])
+        .status()
+        .expect("run kill");
+    assert!(status.success());
+
+    let output = child.wait_with_output().expect("wait for ddog");
+    Output {
+        code: output.status.code().unwrap_or(-1),
+        stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+        stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+    }
+}
+
+/// A logs search response page.
+///
+/// `ids` become log IDs; `after` is the cursor for the next page, if any.
+pub fn logs_page(ids: &[String], after: Option<&str>) -> Value {
+    let data: Vec<Value> = ids
+        .iter()
+        .map(|id| {
+            json!({
+                "id": id,
+                "type": "log",
+                "attributes": {
+                    "timestamp": "2024-01-15T10:00:00Z",
+                    "service": "api",
+                    "status": "error",
+                    "message": format!("log {}", id),
+                    "attributes": {}
+                }
+            })
+        })
+        .collect();
+    page(data, after)
+}
+
+/// A spans search response page.
+///
+/// Each span is `(span_id, parent_id, resource)`; all belong to trace "t1".
+pub fn spans_page(spans: &[(&str, &str, &str)], after: Option<&str>) -> Value {
+    let data: Vec<Value> = spans
+        .iter()
+        .enumerate()
+        .map(|(i, (span_id, parent_id, resource))| {
+            json!({
+                "id": format!("event-{}", span_id),
+                "type": "spans",
+                "attributes": {
+                    "trace_id": "t1",
+                    "span_id": span_id,
+                    "parent_id": parent_id,
+                    "service": "api",
+                    "resource_name": resource,
+                    "start_timestamp": format!("2024-01-15T10:00:00.{:03}Z", i),
+                    "end_timestamp": "2024-01-15T10:00:01Z",
+                    "custom": {}
+                }
+            })
+        })
+        .collect();
+    page(data, after)
+}
+
+/// An events search response page.
+///
+/// `ids` become event IDs; `after` is the cursor for the next page, if any.
+pub fn events_page(ids: &[String], after: Option<&str>) -> Value {
+    let data: Vec<Value> = ids
+        .iter()
+        .map(|id| {
+            json!({
+                "id": id,
+                "type": "event",
+                "attributes": {
+                    "timestamp": "2024-01-15T10:00:00Z",
+                    "message": format!("event {}", id),
+                    "tags": ["deploy"],
+                    "attributes": {}
+                }
+            })
+        })
+        .collect();
+    page(data, after)
+}
+
+fn page(data: Vec<Value>, after: Option<&str>) -> Value {
+    let mut body = json!({ "data": data, "meta": { "status": "done" } });
+    if let Some(after) = after {
+        body["meta"]["page"] = json!({ "after": after });
+    }
+    body
+}
+
+/// Generates `count` log IDs starting at `start`.
+pub fn ids(start: usize, count: usize) -> Vec<String> {
+    (start..start + count)
+        .map(|i| format!("log-{}", i))
+        .collect()
+}